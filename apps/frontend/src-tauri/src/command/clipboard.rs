@@ -21,6 +21,13 @@ pub fn read_clipboard_html() -> Result<Option<String>, String> {
     }
 }
 
+/// 读取剪贴板纯文本，供笔记模板 `{{clipboard}}` 变量使用；无文本或不可用时返回 None
+pub(crate) fn read_clipboard_text() -> Option<String> {
+    let _guard = CLIPBOARD_LOCK.lock().ok()?;
+    let mut clipboard = arboard::Clipboard::new().ok()?;
+    clipboard.get_text().ok().filter(|t| !t.is_empty())
+}
+
 /// 读取剪贴板图片位图，编码为 PNG 并返回 base64 字符串（含 data URL 前缀）。
 /// 用于单独复制图片/截图场景：arboard 读 ImageData → png crate 编码 → base64。
 /// 剪贴板无图片时 readImage 抛错，返回 None。
@@ -69,7 +76,7 @@ fn is_image_ext(path: &Path) -> bool {
 }
 
 /// 根据扩展名猜测 MIME 类型，用于 data URL 前缀。
-fn mime_of(path: &Path) -> &'static str {
+pub(crate) fn mime_of(path: &Path) -> &'static str {
     let ext = path
         .extension()
         .and_then(|e| e.to_str())
@@ -83,6 +90,8 @@ fn mime_of(path: &Path) -> &'static str {
         "webp" => "image/webp",
         "ico" => "image/x-icon",
         "tiff" | "tif" => "image/tiff",
+        "svg" => "image/svg+xml",
+        "avif" => "image/avif",
         _ => "application/octet-stream",
     }
 }
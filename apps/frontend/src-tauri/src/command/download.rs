@@ -13,7 +13,7 @@ use crate::types::common::{
 };
 use crate::utils::common::{
     default_save_base_dir, determine_save_path_for_blob, get_extension_from_content_type,
-    get_remote_file_info,
+    get_remote_file_info, http_client,
 };
 
 #[tauri::command]
@@ -359,6 +359,44 @@ pub async fn download_files(
     Ok(results)
 }
 
+/// 下载到内存（不落盘），返回（字节, Content-Type）。超过 `max_size` 时中止并报错
+pub async fn fetch_remote_bytes(
+    url: &str,
+    max_size: u64,
+) -> Result<(Vec<u8>, Option<String>), String> {
+    let response = http_client()
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("下载请求失败: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("下载失败: HTTP状态码为 {}", response.status()));
+    }
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|s| s.to_string());
+
+    if response.content_length().unwrap_or(0) > max_size {
+        return Err(format!("文件过大 (> {} KB)", max_size / 1024));
+    }
+
+    let mut stream = response.bytes_stream();
+    let mut bytes: Vec<u8> = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| format!("读取数据块失败: {}", e))?;
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() as u64 > max_size {
+            return Err(format!("文件过大 (> {} KB)", max_size / 1024));
+        }
+    }
+
+    Ok((bytes, content_type))
+}
+
 #[tauri::command]
 pub async fn get_file_info(url: String) -> Result<FileInfo, String> {
     get_remote_file_info(&url).await
@@ -1,17 +1,29 @@
 use std::env;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::process::Command;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use rfd::FileDialog;
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 
+use crate::command::knowledge_chunk::update_chunk_cache;
+use crate::command::knowledge_conflict::{
+	KnowledgeSaveError, check_external_modification, content_hash, file_mtime_ms,
+};
+use crate::command::knowledge_git::{auto_commit_notes, note_commit_message};
+use crate::command::knowledge_history::{
+	load_history_retention, move_history, snapshot_before_write,
+};
+use crate::command::knowledge_link::plan_inbound_links;
+use crate::command::knowledge_listing::{list_all_markdown, listing_persists};
+use crate::command::knowledge_trash::{empty_expired_trash, move_to_trash};
+use crate::command::knowledge_vault::{encode_note, read_note_text};
 use crate::types::common::SaveFileResult;
 use crate::utils::common::default_save_base_dir;
 
-fn sanitize_filename(title: &str) -> String {
+pub(crate) fn sanitize_filename(title: &str) -> String {
 	let base = if title.trim().is_empty() {
 		let ms = SystemTime::now()
 			.duration_since(UNIX_EPOCH)
@@ -33,13 +45,37 @@ fn sanitize_filename(title: &str) -> String {
 	format!("{}.md", trimmed)
 }
 
-fn is_md_file_path(p: &Path) -> bool {
+pub(crate) fn is_md_file_path(p: &Path) -> bool {
 	p.extension()
 		.and_then(|s| s.to_str())
 		.map(|e| e.eq_ignore_ascii_case("md"))
 		.unwrap_or(false)
 }
 
+/// 原子写入：先写同目录临时文件并落盘，再 `rename` 覆盖目标，避免写到一半崩溃留下残缺文件
+pub(crate) fn write_file_atomic(path: &Path, bytes: &[u8]) -> Result<(), String> {
+	let dir = path.parent().ok_or("无法解析文件所在目录")?;
+	let name = path
+		.file_name()
+		.map(|s| s.to_string_lossy().to_string())
+		.unwrap_or_default();
+	let ms = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_millis())
+		.unwrap_or(0);
+	let tmp = dir.join(format!(".{}.{}.{}.tmp", name, std::process::id(), ms));
+	let write = || -> std::io::Result<()> {
+		let mut f = fs::File::create(&tmp)?;
+		f.write_all(bytes)?;
+		f.sync_all()?;
+		fs::rename(&tmp, path)
+	};
+	write().map_err(|e| {
+		let _ = fs::remove_file(&tmp);
+		e.to_string()
+	})
+}
+
 /// `file_path` 可为「完整 .md 文件路径」或「目录」（与前端语义一致：传文件夹则在其下生成标题.md）
 fn resolve_write_path_from_file_path_arg(raw: &str, title: &str) -> Result<PathBuf, String> {
 	let trimmed = raw.trim();
@@ -84,6 +120,18 @@ pub struct SaveKnowledgeMarkdownInput {
 	/// 编辑已有条目且标题已改时传入：与 `title` 对应的原磁盘文件名，用于重命名旧 .md，避免产生重复文件
 	#[serde(default)]
 	pub previous_title: Option<String>,
+	/// 与 `previous_title` 配合：重命名成功后同步改写知识库中指向旧文件名的 `[[wiki]]` / 相对 Markdown 链接
+	#[serde(default)]
+	pub update_links: bool,
+	/// 编辑器加载时文件的修改时间（毫秒）；与磁盘不一致视为外部修改
+	#[serde(default)]
+	pub expected_mtime_ms: Option<u64>,
+	/// 编辑器加载时内容的 SHA-256；传入时优先于 `expected_mtime_ms` 判断
+	#[serde(default)]
+	pub expected_hash: Option<String>,
+	/// 编辑器加载时的原文，冲突时作为三方合并的 base
+	#[serde(default)]
+	pub base_content: Option<String>,
 }
 
 /// 删除知识 Markdown：与保存相同的 `filePath`/`dirPath` + `title` 解析目标文件
@@ -98,7 +146,7 @@ pub struct DeleteKnowledgeMarkdownInput {
 }
 
 /// 解析后的保存目标路径（`content` / `overwrite` 不参与计算）
-async fn compute_save_target_path(
+pub(crate) async fn compute_save_target_path(
 	app: &AppHandle,
 	input: &SaveKnowledgeMarkdownInput,
 ) -> Result<PathBuf, String> {
@@ -118,7 +166,7 @@ async fn compute_save_target_path(
 }
 
 /// 未传 `file_path`/`dir_path` 时的目录：`KNOWLEDGE_DIR` 环境变量，否则为「savePath + knowledge 子目录」
-async fn resolve_knowledge_dir(app: &AppHandle) -> Result<PathBuf, String> {
+pub(crate) async fn resolve_knowledge_dir(app: &AppHandle) -> Result<PathBuf, String> {
 	if let Ok(dir) = env::var("KNOWLEDGE_DIR") {
 		let p = PathBuf::from(dir.trim());
 		if !p.as_os_str().is_empty() {
@@ -129,12 +177,35 @@ async fn resolve_knowledge_dir(app: &AppHandle) -> Result<PathBuf, String> {
 	Ok(base.join("knowledge"))
 }
 
+/// 某篇笔记所属的知识库根目录：优先 `dir_path`，否则默认知识库目录；笔记不在其下时退化为笔记所在目录
+pub(crate) async fn knowledge_root_for(
+	app: &AppHandle,
+	dir_path: Option<&String>,
+	note_path: &Path,
+) -> Result<PathBuf, String> {
+	let root = match dir_path.map(|s| s.trim()).filter(|s| !s.is_empty()) {
+		Some(d) => PathBuf::from(d),
+		None => resolve_knowledge_dir(app).await?,
+	};
+	if note_path.starts_with(&root) {
+		return Ok(root);
+	}
+	Ok(note_path
+		.parent()
+		.map(Path::to_path_buf)
+		.unwrap_or(root))
+}
+
 /// 查询即将写入的完整路径及是否已存在同名文件（供前端二次确认）
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct KnowledgeMarkdownTarget {
 	pub path: String,
 	pub exists: bool,
+	/// 已存在时的修改时间（毫秒），可作为保存时的 `expectedMtimeMs`
+	pub updated_at_ms: Option<u64>,
+	/// 已存在时的内容 SHA-256，可作为保存时的 `expectedHash`
+	pub content_hash: Option<String>,
 }
 
 #[tauri::command]
@@ -147,21 +218,33 @@ pub async fn resolve_knowledge_markdown_target(
 		&& fs::metadata(&path)
 			.map(|m| m.is_file())
 			.unwrap_or(false);
+	let (updated_at_ms, hash) = if exists {
+		(
+			file_mtime_ms(&path),
+			read_note_text(&path).ok().map(|c| content_hash(c.as_bytes())),
+		)
+	} else {
+		(None, None)
+	};
 	Ok(KnowledgeMarkdownTarget {
 		path: path.to_string_lossy().to_string(),
 		exists,
+		updated_at_ms,
+		content_hash: hash,
 	})
 }
 
-/// 将 Markdown 写入本地。覆盖已存在文件须 `overwrite: true`
+/// 将 Markdown 写入本地。覆盖已存在文件须 `overwrite: true`；传入 `expectedMtimeMs` / `expectedHash` 时检测外部修改，冲突返回结构化错误
 #[tauri::command]
 pub async fn save_knowledge_markdown(
 	app: AppHandle,
 	input: SaveKnowledgeMarkdownInput,
-) -> Result<SaveFileResult, String> {
+) -> Result<SaveFileResult, KnowledgeSaveError> {
 	let path = compute_save_target_path(&app, &input).await?;
 
 	let mut renamed_from_previous = false;
+	let mut rewritten_links = 0usize;
+	let mut renamed_from: Option<PathBuf> = None;
 	if let Some(ref prev_raw) = input.previous_title {
 		let prev = prev_raw.trim();
 		let cur = input.title.trim();
@@ -173,29 +256,58 @@ pub async fn save_knowledge_markdown(
 				dir_path: input.dir_path.clone(),
 				overwrite: false,
 				previous_title: None,
+				update_links: false,
+				expected_mtime_ms: None,
+				expected_hash: None,
+				base_content: None,
 			};
 			let old_path = compute_save_target_path(&app, &old_input).await?;
 			if old_path != path && old_path.exists() {
 				let meta = fs::metadata(&old_path).map_err(|e| e.to_string())?;
 				if !meta.is_file() {
-					return Err("原知识文件路径不是普通文件".to_string());
+					return Err("原知识文件路径不是普通文件".into());
 				}
 				if path.exists() {
 					let pmeta = fs::metadata(&path).map_err(|e| e.to_string())?;
 					if !pmeta.is_file() {
-						return Err("目标路径已存在且非文件".to_string());
+						return Err("目标路径已存在且非文件".into());
 					}
 					if !input.overwrite {
 						return Err(format!(
 							"文件已存在：{}",
 							path.to_string_lossy()
-						));
+						)
+						.into());
 					}
-					fs::remove_file(&old_path).map_err(|e| e.to_string())?;
+				}
+				// 编辑器持有的是旧文件的版本，须在改写链接 / 重命名之前比对，避免覆盖外部修改
+				check_external_modification(
+					&old_path,
+					input.expected_mtime_ms,
+					input.expected_hash.as_deref(),
+					input.base_content.as_deref(),
+					&input.content,
+				)?;
+				// 旧文件仍在磁盘上时解析入链，重命名成功后再写回，失败时其他笔记保持原样
+				let links = if input.update_links {
+					let root = knowledge_root_for(&app, input.dir_path.as_ref(), &old_path).await?;
+					Some(plan_inbound_links(&root, &old_path, &path)?)
 				} else {
-					fs::rename(&old_path, &path).map_err(|e| e.to_string())?;
-					renamed_from_previous = true;
+					None
+				};
+				if path.exists() {
+					// 被覆盖的同名笔记先移入回收站，可恢复
+					move_to_trash(&app, &path)?;
+				}
+				fs::rename(&old_path, &path).map_err(|e| e.to_string())?;
+				move_history(&old_path, &path)?;
+				if let Some(mut links) = links {
+					// 被覆盖的旧笔记已在回收站，不再写回
+					links.skip(&path);
+					rewritten_links = links.apply()?;
 				}
+				renamed_from_previous = true;
+				renamed_from = Some(old_path);
 			}
 		}
 	}
@@ -206,22 +318,57 @@ pub async fn save_knowledge_markdown(
 			return Err(format!(
 				"文件已存在：{}",
 				path.to_string_lossy()
-			));
+			)
+			.into());
+		}
+		if !renamed_from_previous {
+			check_external_modification(
+				&path,
+				input.expected_mtime_ms,
+				input.expected_hash.as_deref(),
+				input.base_content.as_deref(),
+				&input.content,
+			)?;
 		}
 	}
 
+	let existed = path.exists();
 	if let Some(parent) = path.parent() {
 		fs::create_dir_all(parent).map_err(|e| e.to_string())?;
 	}
-	fs::write(&path, input.content.as_bytes()).map_err(|e| e.to_string())?;
+	// 覆盖前为旧内容生成历史快照，误覆盖 / AI 改写后可恢复
+	// 位于已解锁的加密知识库中时落盘为密文
+	let bytes = encode_note(&path, &input.content)?;
+	let retention = load_history_retention(&app).await;
+	snapshot_before_write(&path, &input.content, retention)?;
+	write_file_atomic(&path, &bytes)?;
+	let mut message = if rewritten_links > 0 {
+		format!("已保存至 {}，已更新 {} 处链接", path.display(), rewritten_links)
+	} else {
+		format!("已保存至 {}", path.display())
+	};
+
+	// 知识库位于 Git 仓库且开启自动提交时，提交本次改动（链接改写涉及的其他笔记留待手动提交）
+	let root = knowledge_root_for(&app, input.dir_path.as_ref(), &path).await?;
+	let (action, files): (&str, Vec<&Path>) = match renamed_from.as_deref() {
+		Some(old) => ("重命名", vec![old, path.as_path()]),
+		None if existed => ("更新", vec![path.as_path()]),
+		None => ("新增", vec![path.as_path()]),
+	};
+	let commit_message = note_commit_message(action, &root, &files);
+	if let Some(err) = auto_commit_notes(&app, &root, &files, &commit_message).await {
+		message.push_str(&format!("（Git 自动提交失败：{}）", err));
+	}
+	// 分块缓存仅用于检索加速，更新失败不影响保存结果
+	let _ = update_chunk_cache(&root, &files);
 	Ok(SaveFileResult {
 		success: "success".to_string(),
 		file_path: Some(path.to_string_lossy().to_string()),
-		message: format!("已保存至 {}", path.display()),
+		message,
 	})
 }
 
-/// 按与保存一致的路径规则删除本地 Markdown 文件
+/// 按与保存一致的路径规则删除本地 Markdown 文件（移入回收站）
 #[tauri::command]
 pub async fn delete_knowledge_markdown(
 	app: AppHandle,
@@ -234,6 +381,10 @@ pub async fn delete_knowledge_markdown(
 		dir_path: input.dir_path,
 		overwrite: false,
 		previous_title: None,
+		update_links: false,
+		expected_mtime_ms: None,
+		expected_hash: None,
+		base_content: None,
 	};
 	let path = compute_save_target_path(&app, &save_like).await?;
 
@@ -248,16 +399,26 @@ pub async fn delete_knowledge_markdown(
 		return Err("仅允许删除 .md 文件".to_string());
 	}
 
-	fs::remove_file(&path).map_err(|e| e.to_string())?;
+	// 移入回收站而非直接删除，可在回收站中恢复
+	move_to_trash(&app, &path)?;
+	let _ = empty_expired_trash(&app, None).await;
+	let mut message = format!("已移至回收站 {}", path.display());
+	let root = knowledge_root_for(&app, save_like.dir_path.as_ref(), &path).await?;
+	let files = [path.as_path()];
+	let commit_message = note_commit_message("删除", &root, &files);
+	if let Some(err) = auto_commit_notes(&app, &root, &files, &commit_message).await {
+		message.push_str(&format!("（Git 自动提交失败：{}）", err));
+	}
+	let _ = update_chunk_cache(&root, &files);
 	Ok(SaveFileResult {
 		success: "success".to_string(),
 		file_path: Some(path.to_string_lossy().to_string()),
-		message: format!("已删除 {}", path.display()),
+		message,
 	})
 }
 
 /// 递归收集目录下（含子目录）的 `.md` 文件路径
-fn collect_md_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<(), String> {
+pub(crate) fn collect_md_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<(), String> {
 	let rd = fs::read_dir(dir).map_err(|e| e.to_string())?;
 	for ent in rd {
 		let ent = ent.map_err(|e| e.to_string())?;
@@ -292,14 +453,14 @@ pub struct KnowledgeMarkdownFileEntry {
 	pub updated_at_ms: u64,
 }
 
-#[tauri::command]
-pub async fn list_knowledge_markdown_files(
-	app: AppHandle,
-	input: ListKnowledgeMarkdownInput,
-) -> Result<Vec<KnowledgeMarkdownFileEntry>, String> {
-	let dir = match input.dir_path.as_ref().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+/// 知识库根目录：传入 `dir_path` 时用之，否则为 [`resolve_knowledge_dir`]；要求目录已存在
+pub(crate) async fn resolve_knowledge_root(
+	app: &AppHandle,
+	dir_path: Option<&String>,
+) -> Result<PathBuf, String> {
+	let dir = match dir_path.map(|s| s.trim()).filter(|s| !s.is_empty()) {
 		Some(d) => PathBuf::from(d),
-		None => resolve_knowledge_dir(&app).await?,
+		None => resolve_knowledge_dir(app).await?,
 	};
 	if !dir.exists() {
 		return Err(format!("目录不存在：{}", dir.display()));
@@ -308,33 +469,20 @@ pub async fn list_knowledge_markdown_files(
 	if !meta.is_dir() {
 		return Err("路径不是目录".to_string());
 	}
-	let mut paths: Vec<PathBuf> = Vec::new();
-	collect_md_files(&dir, &mut paths)?;
-	paths.sort_by(|a, b| {
-		let ta = fs::metadata(a).and_then(|m| m.modified()).ok();
-		let tb = fs::metadata(b).and_then(|m| m.modified()).ok();
-		tb.cmp(&ta)
-	});
-	let mut out = Vec::with_capacity(paths.len());
-	for p in paths {
-		let title = p
-			.file_stem()
-			.and_then(|s| s.to_str())
-			.unwrap_or("未命名")
-			.to_string();
-		let updated_at_ms = fs::metadata(&p)
-			.ok()
-			.and_then(|m| m.modified().ok())
-			.and_then(|t| t.duration_since(UNIX_EPOCH).ok())
-			.map(|d| d.as_millis() as u64)
-			.unwrap_or(0);
-		out.push(KnowledgeMarkdownFileEntry {
-			path: p.to_string_lossy().to_string(),
-			title,
-			updated_at_ms,
-		});
-	}
-	Ok(out)
+	Ok(dir)
+}
+
+/// 按修改时间新→旧列出全部笔记（基于元数据缓存，分页查询见 `list_knowledge_markdown_page`）
+#[tauri::command]
+pub async fn list_knowledge_markdown_files(
+	app: AppHandle,
+	input: ListKnowledgeMarkdownInput,
+) -> Result<Vec<KnowledgeMarkdownFileEntry>, String> {
+	let dir = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let persist = listing_persists(&app, &dir).await;
+	tauri::async_runtime::spawn_blocking(move || list_all_markdown(&dir, persist))
+		.await
+		.map_err(|e| e.to_string())?
 }
 
 #[derive(Debug, Deserialize)]
@@ -347,6 +495,10 @@ pub struct ReadKnowledgeMarkdownFileInput {
 #[serde(rename_all = "camelCase")]
 pub struct ReadKnowledgeMarkdownFileResult {
 	pub content: String,
+	/// 读取时的修改时间（毫秒），保存时回传为 `expectedMtimeMs`
+	pub updated_at_ms: Option<u64>,
+	/// 读取时内容的 SHA-256，保存时回传为 `expectedHash`
+	pub content_hash: String,
 }
 
 /// 知识库导入：仅允许选择 `.md` 文件（系统文件对话框过滤器）
@@ -358,7 +510,7 @@ pub fn select_knowledge_import_md_file() -> Result<String, String> {
 	}
 }
 
-/// 读取单个 `.md` 文件正文（UTF-8）
+/// 读取单个 `.md` 文件正文（UTF-8）；加密知识库中的笔记需先解锁
 #[tauri::command]
 pub fn read_knowledge_markdown_file(
 	input: ReadKnowledgeMarkdownFileInput,
@@ -374,340 +526,10 @@ pub fn read_knowledge_markdown_file(
 	if !is_md_file_path(&p) {
 		return Err("仅允许读取 .md 文件".to_string());
 	}
-	let content = fs::read_to_string(&p).map_err(|e| e.to_string())?;
-	Ok(ReadKnowledgeMarkdownFileResult { content })
-}
-
-// —— 本地 .md 用 Cursor / Trae（用户所称 tare）打开 ——
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum DetectedMarkdownEditor {
-	Cursor,
-	Trae,
-}
-
-#[cfg(target_os = "macos")]
-fn frontmost_application_name() -> Option<String> {
-	let script =
-		r#"tell application "System Events" to get name of first application process whose frontmost is true"#;
-	let output = Command::new("osascript").args(["-e", script]).output().ok()?;
-	if !output.status.success() {
-		return None;
-	}
-	let s = String::from_utf8(output.stdout).ok()?;
-	let t = s.trim();
-	if t.is_empty() {
-		None
-	} else {
-		Some(t.to_string())
-	}
-}
-
-/// Windows：前台窗口所属进程名（小写），失败则 None
-#[cfg(target_os = "windows")]
-fn frontmost_application_name() -> Option<String> {
-	let ps = concat!(
-		"$pid = [uint32]0; ",
-		"Add-Type -MemberDefinition '[DllImport(\"user32.dll\")]public static extern System.IntPtr GetForegroundWindow();",
-		"[DllImport(\"user32.dll\")]public static extern uint GetWindowThreadProcessId(System.IntPtr h,out uint p);' ",
-		"-Name U -Namespace W; ",
-		"[void][W.U]::GetWindowThreadProcessId([W.U]::GetForegroundWindow(),[ref]$pid); ",
-		"(Get-Process -Id $pid -ErrorAction SilentlyContinue).ProcessName",
-	);
-	let output = Command::new("powershell")
-		.args(["-NoProfile", "-STA", "-Command", ps])
-		.output()
-		.ok()?;
-	if !output.status.success() {
-		return None;
-	}
-	let s = String::from_utf8(output.stdout).ok()?;
-	let t = s.trim().to_lowercase();
-	if t.is_empty() {
-		None
-	} else {
-		Some(t)
-	}
-}
-
-#[cfg(all(unix, not(target_os = "macos")))]
-fn frontmost_application_name() -> Option<String> {
-	None
-}
-
-/// 子进程退出码 0 视为成功（如 pgrep 找到进程）
-fn command_exit_zero(program: &str, args: &[&str]) -> bool {
-	Command::new(program)
-		.args(args)
-		.status()
-		.ok()
-		.is_some_and(|s| s.success())
-}
-
-/// macOS：用「系统事件」进程名 + `ps` 命令行（含 Cursor.app）+ `pgrep` 多重判定；Electron 常只有 Helper 进程带完整路径，`pgrep -x Cursor` 易漏检
-#[cfg(target_os = "macos")]
-fn is_cursor_running_applescript() -> bool {
-	let Some(out) = Command::new("/usr/bin/osascript")
-		.args([
-			"-e",
-			r#"tell application "System Events""#,
-			"-e",
-			r#"repeat with procName in (name of every process)"#,
-			"-e",
-			r#"set t to procName as string"#,
-			"-e",
-			r#"if t contains "Cursor" then return true"#,
-			"-e",
-			r#"end repeat"#,
-			"-e",
-			r#"end tell"#,
-			"-e",
-			r#"return false"#,
-		])
-		.output()
-		.ok()
-	else {
-		return false;
-	};
-	if !out.status.success() {
-		return false;
-	}
-	String::from_utf8_lossy(&out.stdout)
-		.trim()
-		.eq_ignore_ascii_case("true")
-}
-
-#[cfg(target_os = "macos")]
-fn is_cursor_running_ps() -> bool {
-	let Ok(output) = Command::new("/bin/ps")
-		.args(["-ax", "-o", "command="])
-		.output()
-	else {
-		return false;
-	};
-	String::from_utf8_lossy(&output.stdout).lines().any(|line| {
-		// 典型：.../Cursor.app/...；无路径时仍有 Electron Helper 进程名
-		line.contains("Cursor.app/")
-			|| line.contains("Cursor Helper")
-			|| line.contains("MacOS/Cursor")
-	})
-}
-
-#[cfg(target_os = "macos")]
-fn is_cursor_running() -> bool {
-	is_cursor_running_applescript()
-		|| is_cursor_running_ps()
-		|| command_exit_zero("/usr/bin/pgrep", &["-x", "Cursor"])
-		|| command_exit_zero("/usr/bin/pgrep", &["-f", "Cursor.app"])
-		|| command_exit_zero("/usr/bin/pgrep", &["-x", "cursor"])
-}
-
-/// Cursor 稳定版常见 Bundle ID（Todesktop 分发）；若变更可再补 `open -a Cursor`
-#[cfg(target_os = "macos")]
-const CURSOR_MACOS_BUNDLE_ID: &str = "com.todesktop.230313mzl4w4u92";
-
-/// macOS：优先按 Bundle ID 打开，再 `open -a Cursor`，再尝试 PATH 中的 `cursor` CLI
-#[cfg(target_os = "macos")]
-fn spawn_open_cursor_macos(path: &Path) -> Result<(), String> {
-	let path_str = path.to_str().ok_or("路径包含无效字符")?;
-	if Command::new("/usr/bin/open")
-		.args(["-b", CURSOR_MACOS_BUNDLE_ID, "--", path_str])
-		.status()
-		.map(|s| s.success())
-		.unwrap_or(false)
-	{
-		return Ok(());
-	}
-	if spawn_open_editor("Cursor", path).is_ok() {
-		return Ok(());
-	}
-	Command::new("cursor")
-		.arg(path_str)
-		.spawn()
-		.map_err(|e| format!("无法用 Cursor 打开文件: {e}"))?;
-	Ok(())
-}
-
-#[cfg(target_os = "windows")]
-fn is_cursor_running() -> bool {
-	let Ok(output) = Command::new("tasklist").args(["/FO", "CSV", "/NH"]).output() else {
-		return false;
-	};
-	String::from_utf8_lossy(&output.stdout)
-		.to_lowercase()
-		.contains("cursor.exe")
-}
-
-#[cfg(all(unix, not(target_os = "macos")))]
-fn is_cursor_running() -> bool {
-	command_exit_zero("pgrep", &["-x", "cursor"])
-		|| command_exit_zero("pgrep", &["-f", "/Cursor.app/"])
-		|| command_exit_zero("pgrep", &["-f", "cursor"])
-}
-
-#[cfg(target_os = "macos")]
-fn is_trae_running() -> bool {
-	for name in ["Trae", "Trae CN"] {
-		if command_exit_zero("/usr/bin/pgrep", &["-x", name]) {
-			return true;
-		}
-	}
-	command_exit_zero("/usr/bin/pgrep", &["-f", "Trae.app"])
-}
-
-#[cfg(target_os = "windows")]
-fn is_trae_running() -> bool {
-	let Ok(output) = Command::new("tasklist").args(["/FO", "CSV", "/NH"]).output() else {
-		return false;
-	};
-	let lower = String::from_utf8_lossy(&output.stdout).to_lowercase();
-	lower.contains("trae.exe")
-}
-
-#[cfg(all(unix, not(target_os = "macos")))]
-fn is_trae_running() -> bool {
-	command_exit_zero("pgrep", &["-x", "trae"]) || command_exit_zero("pgrep", &["-f", "trae"])
-}
-
-/// 1) 前台为 Cursor → Cursor；2) 前台为 Trae → Trae；3) 否则看谁已在运行（**优先 Cursor**，解决本应用前台时误走 Trae）；4) 都未运行则默认 Trae
-fn detect_markdown_editor() -> DetectedMarkdownEditor {
-	if let Some(name) = frontmost_application_name() {
-		let lower = name.to_lowercase();
-		if lower.contains("cursor") {
-			return DetectedMarkdownEditor::Cursor;
-		}
-		if lower.contains("trae") {
-			return DetectedMarkdownEditor::Trae;
-		}
-	}
-	if is_cursor_running() {
-		return DetectedMarkdownEditor::Cursor;
-	}
-	if is_trae_running() {
-		return DetectedMarkdownEditor::Trae;
-	}
-	// 进程检测失败时（如未授权自动化 System Events）：按是否安装 Cursor.app / Trae.app 兜底
-	#[cfg(target_os = "macos")]
-	if let Some(kind) = detect_editor_by_cursor_trae_installed() {
-		return kind;
-	}
-	DetectedMarkdownEditor::Trae
-}
-
-#[cfg(target_os = "macos")]
-fn macos_app_bundle_present(name: &str) -> bool {
-	let home = env::var("HOME").unwrap_or_default();
-	[
-		format!("/Applications/{name}.app"),
-		format!("{home}/Applications/{name}.app"),
-	]
-	.iter()
-	.any(|p| Path::new(p).is_dir())
-}
-
-/// 仅当能明确判断「只装了其一」或「两者都装了」时用；双装优先 Cursor
-#[cfg(target_os = "macos")]
-fn detect_editor_by_cursor_trae_installed() -> Option<DetectedMarkdownEditor> {
-	let cursor = macos_app_bundle_present("Cursor");
-	let trae = macos_app_bundle_present("Trae") || macos_app_bundle_present("Trae CN");
-	match (cursor, trae) {
-		(true, false) => Some(DetectedMarkdownEditor::Cursor),
-		(false, true) => Some(DetectedMarkdownEditor::Trae),
-		(true, true) => Some(DetectedMarkdownEditor::Cursor),
-		_ => None,
-	}
-}
-
-#[cfg(target_os = "macos")]
-fn spawn_open_editor(app_bundle_name: &str, path: &Path) -> Result<(), String> {
-	let path_str = path.to_str().ok_or("路径包含无效字符")?;
-	let status = Command::new("/usr/bin/open")
-		.args(["-a", app_bundle_name, "--", path_str])
-		.status()
-		.map_err(|e| format!("无法启动 {app_bundle_name}: {e}"))?;
-	if status.success() {
-		Ok(())
-	} else {
-		Err(format!("open -a {app_bundle_name} 退出码非 0"))
-	}
-}
-
-#[cfg(not(target_os = "macos"))]
-fn spawn_open_editor(cli_name: &str, path: &Path) -> Result<(), String> {
-	let path_str = path.to_str().ok_or("路径包含无效字符")?;
-	Command::new(cli_name)
-		.arg(path_str)
-		.spawn()
-		.map_err(|e| format!("无法启动 {cli_name}: {e}"))?;
-	Ok(())
-}
-
-fn open_markdown_with_detected_editor(path: &Path) -> Result<DetectedMarkdownEditor, String> {
-	let kind = detect_markdown_editor();
-	match kind {
-		DetectedMarkdownEditor::Cursor => {
-			#[cfg(target_os = "macos")]
-			{
-				spawn_open_cursor_macos(path)?;
-			}
-			#[cfg(not(target_os = "macos"))]
-			{
-				spawn_open_editor("cursor", path)?;
-			}
-			Ok(DetectedMarkdownEditor::Cursor)
-		}
-		DetectedMarkdownEditor::Trae => {
-			#[cfg(target_os = "macos")]
-			{
-				if spawn_open_editor("Trae", path).is_err() {
-					// 部分安装为「Trae CN」等显示名
-					spawn_open_editor("Trae CN", path)?;
-				}
-			}
-			#[cfg(not(target_os = "macos"))]
-			{
-				spawn_open_editor("trae", path)?;
-			}
-			Ok(DetectedMarkdownEditor::Trae)
-		}
-	}
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct OpenKnowledgeMarkdownInEditorInput {
-	pub file_path: String,
-}
-
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct OpenKnowledgeMarkdownInEditorResult {
-	/// 实际用于打开的编辑器：`Cursor` 或 `Trae`
-	pub opened_with: String,
-}
-
-/// 在检测到的编辑器中打开本地 `.md`：前台名 → 运行中进程（优先 Cursor）→ macOS 再按已安装 .app 兜底；打开时 macOS Cursor 用 Bundle ID / open -a / CLI，Trae 支持 Trae CN
-#[tauri::command]
-pub fn open_knowledge_markdown_in_editor(
-	input: OpenKnowledgeMarkdownInEditorInput,
-) -> Result<OpenKnowledgeMarkdownInEditorResult, String> {
-	let trimmed = input.file_path.trim();
-	if trimmed.is_empty() {
-		return Err("filePath 不能为空".to_string());
-	}
-	let p = PathBuf::from(trimmed);
-	if !p.exists() || !p.is_file() {
-		return Err("文件不存在或不是普通文件".to_string());
-	}
-	if !is_md_file_path(&p) {
-		return Err("仅允许打开 .md 文件".to_string());
-	}
-	let used = open_markdown_with_detected_editor(&p)?;
-	let opened_with = match used {
-		DetectedMarkdownEditor::Cursor => "Cursor",
-		DetectedMarkdownEditor::Trae => "Trae",
-	};
-	Ok(OpenKnowledgeMarkdownInEditorResult {
-		opened_with: opened_with.to_string(),
+	let content = read_note_text(&p)?;
+	Ok(ReadKnowledgeMarkdownFileResult {
+		content_hash: content_hash(content.as_bytes()),
+		updated_at_ms: file_mtime_ms(&p),
+		content,
 	})
 }
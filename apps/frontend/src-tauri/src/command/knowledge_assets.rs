@@ -0,0 +1,631 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::command::download::fetch_remote_bytes;
+use crate::command::knowledge::{is_md_file_path, knowledge_root_for, resolve_knowledge_root, write_file_atomic};
+use crate::command::knowledge_conflict::content_hash;
+use crate::command::knowledge_history::{load_history_retention, snapshot_before_write};
+use crate::command::knowledge_trash::trashed_note_files;
+use crate::command::knowledge_vault::{decode_note, encode_note, read_note_text};
+
+// —— 知识库附件：按内容哈希存入根目录 `assets/`，笔记中以相对路径引用，避免 base64 撑大 Markdown ——
+
+/// 附件目录名（位于知识库根目录下，文件夹树中不展示）
+pub(crate) const ASSETS_DIR_NAME: &str = "assets";
+/// 文件名取内容哈希前缀的长度（十六进制字符数）
+const HASH_NAME_LEN: usize = 16;
+
+pub(crate) fn assets_dir(root: &Path) -> PathBuf {
+	root.join(ASSETS_DIR_NAME)
+}
+
+pub(crate) fn ext_of_mime(mime: &str) -> &'static str {
+	let mime = mime.split(';').next().unwrap_or_default();
+	match mime.trim().to_ascii_lowercase().as_str() {
+		"image/png" => "png",
+		"image/jpeg" | "image/jpg" => "jpg",
+		"image/gif" => "gif",
+		"image/bmp" => "bmp",
+		"image/webp" => "webp",
+		"image/svg+xml" => "svg",
+		"image/x-icon" | "image/vnd.microsoft.icon" => "ico",
+		"image/tiff" => "tiff",
+		"image/avif" => "avif",
+		"application/pdf" => "pdf",
+		_ => "bin",
+	}
+}
+
+/// 解析 `data:<mime>;base64,<data>`，返回（字节, 扩展名）
+pub(crate) fn decode_data_url(data_url: &str) -> Result<(Vec<u8>, &'static str), String> {
+	let rest = data_url
+		.trim()
+		.strip_prefix("data:")
+		.ok_or("不是有效的 data URL")?;
+	let (header, data) = rest.split_once(',').ok_or("不是有效的 data URL")?;
+	let Some(mime) = header.strip_suffix(";base64") else {
+		return Err("仅支持 base64 编码的 data URL".to_string());
+	};
+	let bytes = base64::engine::general_purpose::STANDARD
+		.decode(data.trim())
+		.map_err(|e| format!("base64 解码失败: {}", e))?;
+	Ok((bytes, ext_of_mime(mime)))
+}
+
+fn is_safe_ext(ext: &str) -> bool {
+	!ext.is_empty() && ext.len() <= 8 && ext.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// 按内容写入附件目录；相同内容复用已有文件。返回（附件路径, 是否复用）
+pub(crate) fn store_asset(root: &Path, bytes: &[u8], ext: &str) -> Result<(PathBuf, bool), String> {
+	if bytes.is_empty() {
+		return Err("附件内容为空".to_string());
+	}
+	let ext = ext.trim_start_matches('.').to_ascii_lowercase();
+	let ext = if is_safe_ext(&ext) { ext } else { "bin".to_string() };
+	let hash = content_hash(bytes);
+	let dir = assets_dir(root);
+	fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+	let path = dir.join(format!("{}.{}", &hash[..HASH_NAME_LEN], ext));
+	if path.is_file() {
+		return Ok((path, true));
+	}
+	write_file_atomic(&path, bytes)?;
+	Ok((path, false))
+}
+
+/// `to` 相对目录 `from_dir` 的 `/` 分隔路径（用于 Markdown 链接）
+pub(crate) fn relative_link(from_dir: &Path, to: &Path) -> String {
+	let from: Vec<Component> = from_dir.components().collect();
+	let target: Vec<Component> = to.components().collect();
+	let common = from
+		.iter()
+		.zip(target.iter())
+		.take_while(|(a, b)| a == b)
+		.count();
+	let mut parts: Vec<String> = vec!["..".to_string(); from.len() - common];
+	parts.extend(
+		target[common..]
+			.iter()
+			.map(|c| c.as_os_str().to_string_lossy().to_string()),
+	);
+	parts.join("/")
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveKnowledgeAssetInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	/// 引用该附件的笔记路径（用于计算相对链接）
+	pub note_path: String,
+	/// 粘贴的图片（`read_clipboard_image_base64` 等返回的 data URL）
+	#[serde(default)]
+	pub data_url: Option<String>,
+	/// 拖入的本地文件路径
+	#[serde(default)]
+	pub source_path: Option<String>,
+	/// 图片替代文本，缺省取源文件名
+	#[serde(default)]
+	pub alt: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeAssetSaved {
+	pub path: String,
+	/// 相对笔记所在目录的链接路径
+	pub relative_path: String,
+	/// 可直接插入笔记的 Markdown 片段
+	pub markdown: String,
+	/// 附件目录中已有相同内容，未重复写入
+	pub deduplicated: bool,
+}
+
+fn is_image_ext(ext: &str) -> bool {
+	matches!(
+		ext,
+		"png" | "jpg" | "jpeg" | "gif" | "bmp" | "webp" | "svg" | "ico" | "tiff" | "tif" | "avif"
+	)
+}
+
+/// 保存粘贴 / 拖入的附件，返回相对链接与 Markdown 片段
+#[tauri::command]
+pub async fn save_knowledge_asset(
+	app: AppHandle,
+	input: SaveKnowledgeAssetInput,
+) -> Result<KnowledgeAssetSaved, String> {
+	let note = PathBuf::from(input.note_path.trim());
+	if !is_md_file_path(&note) {
+		return Err("notePath 须为 .md 文件".to_string());
+	}
+	let root = knowledge_root_for(&app, input.dir_path.as_ref(), &note).await?;
+	let (bytes, ext, default_alt) = match (input.data_url.as_deref(), input.source_path.as_deref()) {
+		(Some(url), _) if !url.trim().is_empty() => {
+			let (bytes, ext) = decode_data_url(url)?;
+			(bytes, ext.to_string(), "image".to_string())
+		}
+		(_, Some(src)) if !src.trim().is_empty() => {
+			let src = Path::new(src.trim());
+			let bytes = fs::read(src).map_err(|e| format!("读取文件失败: {}", e))?;
+			let ext = src
+				.extension()
+				.and_then(|e| e.to_str())
+				.unwrap_or("bin")
+				.to_ascii_lowercase();
+			let stem = src
+				.file_stem()
+				.and_then(|s| s.to_str())
+				.unwrap_or("file")
+				.to_string();
+			(bytes, ext, stem)
+		}
+		_ => return Err("dataUrl 与 sourcePath 至少提供一个".to_string()),
+	};
+	let (path, deduplicated) = store_asset(&root, &bytes, &ext)?;
+	let note_dir = note.parent().unwrap_or(&root);
+	let relative_path = relative_link(note_dir, &path);
+	let alt = input
+		.alt
+		.map(|s| s.trim().to_string())
+		.filter(|s| !s.is_empty())
+		.unwrap_or(default_alt)
+		.replace(['[', ']'], "");
+	let ext = path
+		.extension()
+		.and_then(|e| e.to_str())
+		.unwrap_or_default();
+	let markdown = if is_image_ext(ext) {
+		format!("![{}]({})", alt, relative_path)
+	} else {
+		format!("[{}]({})", alt, relative_path)
+	};
+	Ok(KnowledgeAssetSaved {
+		path: path.to_string_lossy().to_string(),
+		relative_path,
+		markdown,
+		deduplicated,
+	})
+}
+
+/// 收集知识库中所有 Markdown（含 `.history` 快照），附件被历史版本引用时同样视为在用
+fn collect_referencing_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<(), String> {
+	for ent in fs::read_dir(dir).map_err(|e| e.to_string())? {
+		let ent = ent.map_err(|e| e.to_string())?;
+		let p = ent.path();
+		let ft = ent.file_type().map_err(|e| e.to_string())?;
+		if ft.is_dir() {
+			collect_referencing_files(&p, out)?;
+		} else if ft.is_file() && is_md_file_path(&p) {
+			out.push(p);
+		}
+	}
+	Ok(())
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeAssetEntry {
+	pub path: String,
+	pub name: String,
+	pub size: u64,
+}
+
+/// 未被任何笔记、历史快照或回收站中的笔记引用的附件。
+/// 附件文件名为内容哈希，直接按文件名在文本中查找即可覆盖 Markdown 图片、链接与 `<img>` 等写法
+fn find_unreferenced(app: &AppHandle, root: &Path) -> Result<Vec<KnowledgeAssetEntry>, String> {
+	let dir = assets_dir(root);
+	if !dir.is_dir() {
+		return Ok(Vec::new());
+	}
+	let mut assets = Vec::new();
+	for ent in fs::read_dir(&dir).map_err(|e| e.to_string())? {
+		let ent = ent.map_err(|e| e.to_string())?;
+		if !ent.file_type().map_err(|e| e.to_string())?.is_file() {
+			continue;
+		}
+		let name = ent.file_name().to_string_lossy().to_string();
+		if name.starts_with('.') {
+			continue;
+		}
+		let size = ent.metadata().map(|m| m.len()).unwrap_or(0);
+		assets.push(KnowledgeAssetEntry {
+			path: ent.path().to_string_lossy().to_string(),
+			name,
+			size,
+		});
+	}
+	if assets.is_empty() {
+		return Ok(assets);
+	}
+
+	let mut files = Vec::new();
+	for ent in fs::read_dir(root).map_err(|e| e.to_string())? {
+		let ent = ent.map_err(|e| e.to_string())?;
+		let p = ent.path();
+		if p == dir {
+			continue;
+		}
+		let ft = ent.file_type().map_err(|e| e.to_string())?;
+		if ft.is_dir() {
+			collect_referencing_files(&p, &mut files)?;
+		} else if ft.is_file() && is_md_file_path(&p) {
+			files.push(p);
+		}
+	}
+	let mut files: Vec<(PathBuf, PathBuf)> = files.into_iter().map(|f| (f.clone(), f)).collect();
+	files.extend(trashed_note_files(app, root)?);
+
+	let mut referenced: HashSet<String> = HashSet::new();
+	for (f, note_path) in files {
+		let Ok(bytes) = fs::read(&f) else {
+			continue;
+		};
+		// 加密笔记解密后再比对；知识库未解锁时无法判断引用，报错而不是把附件当作未引用
+		let content = String::from_utf8_lossy(&decode_note(&note_path, bytes)?).into_owned();
+		for a in &assets {
+			if !referenced.contains(&a.name) && content.contains(&a.name) {
+				referenced.insert(a.name.clone());
+			}
+		}
+		if referenced.len() == assets.len() {
+			break;
+		}
+	}
+	assets.retain(|a| !referenced.contains(&a.name));
+	assets.sort_by(|a, b| a.name.cmp(&b.name));
+	Ok(assets)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeAssetsInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+}
+
+/// 列出未被引用的附件
+#[tauri::command]
+pub async fn list_unreferenced_knowledge_assets(
+	app: AppHandle,
+	input: KnowledgeAssetsInput,
+) -> Result<Vec<KnowledgeAssetEntry>, String> {
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	tauri::async_runtime::spawn_blocking(move || find_unreferenced(&app, &root))
+		.await
+		.map_err(|e| e.to_string())?
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GcKnowledgeAssetsInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	/// 为 true 时只返回将被删除的附件，不实际删除
+	#[serde(default)]
+	pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeAssetsGcResult {
+	pub removed: Vec<KnowledgeAssetEntry>,
+	pub freed_bytes: u64,
+	pub dry_run: bool,
+}
+
+/// 清理未被引用的附件
+#[tauri::command]
+pub async fn gc_knowledge_assets(
+	app: AppHandle,
+	input: GcKnowledgeAssetsInput,
+) -> Result<KnowledgeAssetsGcResult, String> {
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let dry_run = input.dry_run;
+	tauri::async_runtime::spawn_blocking(move || {
+		let mut removed = find_unreferenced(&app, &root)?;
+		if !dry_run {
+			removed.retain(|a| fs::remove_file(&a.path).is_ok());
+		}
+		let freed_bytes = removed.iter().map(|a| a.size).sum();
+		Ok(KnowledgeAssetsGcResult {
+			removed,
+			freed_bytes,
+			dry_run,
+		})
+	})
+	.await
+	.map_err(|e| e.to_string())?
+}
+
+/// 单张远程图片的大小上限
+const MAX_REMOTE_IMAGE_SIZE: u64 = 20 * 1024 * 1024;
+
+fn is_remote_url(url: &str) -> bool {
+	let lower = url.to_ascii_lowercase();
+	lower.starts_with("http://") || lower.starts_with("https://") || lower.starts_with("//")
+}
+
+/// 按文件头识别常见图片格式（Content-Type 缺失或为 octet-stream 时兜底）
+fn sniff_image_ext(bytes: &[u8]) -> Option<&'static str> {
+	if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+		Some("png")
+	} else if bytes.starts_with(b"\xff\xd8\xff") {
+		Some("jpg")
+	} else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+		Some("gif")
+	} else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+		Some("webp")
+	} else if bytes.starts_with(b"BM") {
+		Some("bmp")
+	} else if bytes.len() >= 12 && &bytes[4..12] == b"ftypavif" {
+		Some("avif")
+	} else {
+		let head = String::from_utf8_lossy(&bytes[..bytes.len().min(512)]).to_ascii_lowercase();
+		head.contains("<svg").then_some("svg")
+	}
+}
+
+/// `![alt](url "title")` 中 url 的区间；`open` 为 `](` 之后的位置
+fn markdown_image_url(content: &str, open: usize) -> Option<Range<usize>> {
+	let rest = &content[open..];
+	let lead = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+	let start = open + lead;
+	let rest = &content[start..];
+	if let Some(inner) = rest.strip_prefix('<') {
+		let end = inner.find('>')?;
+		return Some(start + 1..start + 1 + end);
+	}
+	let end = rest.find(|c: char| c.is_whitespace() || c == ')')?;
+	(end > 0).then_some(start..start + end)
+}
+
+/// `<img ... src="url">` 中 url 的区间；`tag_start` 为 `<img` 的位置
+fn html_img_src(content: &str, tag_start: usize) -> Option<Range<usize>> {
+	let tag_end = tag_start + content[tag_start..].find('>')?;
+	let tag = &content[tag_start..tag_end];
+	let lower = tag.to_ascii_lowercase();
+	let mut from = 0;
+	while let Some(i) = lower[from..].find("src") {
+		let at = from + i;
+		from = at + 3;
+		// 排除 data-src、srcset 等属性
+		let prev_ok = lower[..at]
+			.chars()
+			.last()
+			.is_some_and(|c| c.is_whitespace());
+		let after = lower[at + 3..].trim_start();
+		if !prev_ok || !after.starts_with('=') {
+			continue;
+		}
+		let value_pos = tag.len() - tag[at + 3..].trim_start()[1..].trim_start().len();
+		let value = &tag[value_pos..];
+		let (start, len) = match value.chars().next() {
+			Some(q @ ('"' | '\'')) => (value_pos + 1, value[1..].find(q)?),
+			// 无引号的值到空白为止（标签已截止于 `>`）；位于标签末尾时去掉自闭合的 `/`
+			_ => {
+				let len = match value.find(char::is_whitespace) {
+					Some(len) => len,
+					None => value.strip_suffix('/').unwrap_or(value).len(),
+				};
+				(value_pos, len)
+			}
+		};
+		return Some(tag_start + start..tag_start + start + len);
+	}
+	None
+}
+
+/// 扫描笔记中的远程图片地址（跳过围栏代码块），返回（url 区间, url）
+pub(crate) fn find_remote_images(content: &str) -> Vec<(Range<usize>, String)> {
+	let mut out = Vec::new();
+	let mut offset = 0usize;
+	let mut in_fence = false;
+	for line in content.split_inclusive('\n') {
+		let line_start = offset;
+		offset += line.len();
+		let trimmed = line.trim_start();
+		if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+			in_fence = !in_fence;
+			continue;
+		}
+		if in_fence {
+			continue;
+		}
+		let mut i = 0usize;
+		while i < line.len() {
+			let rest = &line[i..];
+			let found = if rest.starts_with("![") {
+				rest.find("](")
+					.and_then(|close| markdown_image_url(content, line_start + i + close + 2))
+			} else if rest.get(..4).is_some_and(|s| s.eq_ignore_ascii_case("<img")) {
+				html_img_src(content, line_start + i)
+			} else {
+				None
+			};
+			match found {
+				Some(range) => {
+					let url = content[range.clone()].to_string();
+					i = range.end - line_start;
+					if is_remote_url(&url) {
+						out.push((range, url));
+					}
+				}
+				None => i += rest.chars().next().map_or(1, char::len_utf8),
+			}
+		}
+	}
+	out
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalizeKnowledgeImagesInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	pub file_path: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeImageLocalizeItem {
+	pub url: String,
+	/// `localized` 新下载 / `reused` 附件目录已有相同内容 / `failed` 下载失败（原链接保留）
+	pub status: String,
+	/// 在笔记中出现的次数
+	pub occurrences: usize,
+	pub local_path: Option<String>,
+	pub relative_path: Option<String>,
+	pub size: Option<u64>,
+	pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeImageLocalizeResult {
+	pub file_path: String,
+	/// 笔记内容是否被改写
+	pub updated: bool,
+	pub items: Vec<KnowledgeImageLocalizeItem>,
+}
+
+/// 下载正文中的远程图片到 `root/assets/` 并改写为相对 `note_dir` 的链接；返回（新内容, 每个地址的结果）。
+/// 下载失败的地址保留原链接
+pub(crate) async fn localize_remote_images_in(
+	root: &Path,
+	note_dir: &Path,
+	content: &str,
+) -> (String, Vec<KnowledgeImageLocalizeItem>) {
+	let found = find_remote_images(content);
+
+	// 同一地址只下载一次
+	let mut order: Vec<String> = Vec::new();
+	let mut counts: HashMap<String, usize> = HashMap::new();
+	for (_, url) in &found {
+		let n = counts.entry(url.clone()).or_insert(0);
+		if *n == 0 {
+			order.push(url.clone());
+		}
+		*n += 1;
+	}
+
+	let mut replacements: HashMap<String, String> = HashMap::new();
+	let mut items = Vec::with_capacity(order.len());
+	for url in order {
+		let occurrences = counts[&url];
+		let request_url = if url.starts_with("//") {
+			format!("https:{}", url)
+		} else {
+			url.clone()
+		};
+		let stored = match fetch_remote_bytes(&request_url, MAX_REMOTE_IMAGE_SIZE).await {
+			Ok((bytes, content_type)) => {
+				let by_mime = content_type
+					.as_deref()
+					.map(ext_of_mime)
+					.filter(|e| *e != "bin" && *e != "pdf");
+				match by_mime.or_else(|| sniff_image_ext(&bytes)) {
+					Some(ext) => store_asset(root, &bytes, ext).map(|(p, reused)| (p, reused, bytes.len() as u64)),
+					None => Err("响应内容不是图片".to_string()),
+				}
+			}
+			Err(e) => Err(e),
+		};
+		items.push(match stored {
+			Ok((path, reused, size)) => {
+				let rel = relative_link(note_dir, &path);
+				replacements.insert(url.clone(), rel.clone());
+				KnowledgeImageLocalizeItem {
+					url,
+					status: if reused { "reused" } else { "localized" }.to_string(),
+					occurrences,
+					local_path: Some(path.to_string_lossy().to_string()),
+					relative_path: Some(rel),
+					size: Some(size),
+					error: None,
+				}
+			}
+			Err(e) => KnowledgeImageLocalizeItem {
+				url,
+				status: "failed".to_string(),
+				occurrences,
+				local_path: None,
+				relative_path: None,
+				size: None,
+				error: Some(e),
+			},
+		});
+	}
+
+	if replacements.is_empty() {
+		return (content.to_string(), items);
+	}
+	let mut next = String::with_capacity(content.len());
+	let mut cursor = 0usize;
+	for (range, url) in &found {
+		if let Some(rel) = replacements.get(url) {
+			next.push_str(&content[cursor..range.start]);
+			next.push_str(rel);
+			cursor = range.end;
+		}
+	}
+	next.push_str(&content[cursor..]);
+	(next, items)
+}
+
+/// 下载笔记中的远程图片到 `assets/`（按内容去重），并把链接改写为本地相对路径
+#[tauri::command]
+pub async fn localize_knowledge_remote_images(
+	app: AppHandle,
+	input: LocalizeKnowledgeImagesInput,
+) -> Result<KnowledgeImageLocalizeResult, String> {
+	let note = PathBuf::from(input.file_path.trim());
+	if !is_md_file_path(&note) || !note.is_file() {
+		return Err("文件不存在或不是 .md 文件".to_string());
+	}
+	let root = knowledge_root_for(&app, input.dir_path.as_ref(), &note).await?;
+	let content = read_note_text(&note)?;
+	let note_dir = note.parent().unwrap_or(&root).to_path_buf();
+	let (next, items) = localize_remote_images_in(&root, &note_dir, &content).await;
+	let updated = next != content;
+	if updated {
+		let bytes = encode_note(&note, &next)?;
+		snapshot_before_write(&note, &next, load_history_retention(&app).await)?;
+		write_file_atomic(&note, &bytes)?;
+	}
+	Ok(KnowledgeImageLocalizeResult {
+		file_path: note.to_string_lossy().to_string(),
+		updated,
+		items,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn src_of(tag: &str) -> Option<&str> {
+		html_img_src(tag, 0).map(|r| &tag[r])
+	}
+
+	#[test]
+	fn html_img_src_reads_quoted_and_unquoted_values() {
+		assert_eq!(src_of(r#"<img alt="x" src="a b.png">"#), Some("a b.png"));
+		assert_eq!(src_of("<img src='https://x.com/a.png' />"), Some("https://x.com/a.png"));
+		assert_eq!(src_of("<img src=https://x.com/a.png>"), Some("https://x.com/a.png"));
+		assert_eq!(src_of("<img src=https://x.com/a.png width=10>"), Some("https://x.com/a.png"));
+		assert_eq!(src_of("<img src=assets/a.png/>"), Some("assets/a.png"));
+	}
+
+	#[test]
+	fn html_img_src_ignores_similar_attributes() {
+		assert_eq!(src_of(r#"<img data-src="lazy.png" srcset="a.png 2x" src="real.png">"#), Some("real.png"));
+		assert_eq!(src_of(r#"<img data-src="lazy.png">"#), None);
+	}
+}
@@ -0,0 +1,717 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::command::knowledge::{
+	collect_md_files, resolve_knowledge_root, sanitize_filename, write_file_atomic,
+};
+use crate::command::knowledge_conflict::content_hash;
+use crate::command::knowledge_history::{HistoryRetention, load_history_retention, snapshot_before_write};
+use crate::command::knowledge_link::split_front_matter;
+use crate::command::knowledge_sync::{
+	conflict_copy_rel, emit_progress, load_manifest, save_manifest, try_begin_sync,
+};
+use crate::command::knowledge_trash::move_to_trash;
+use crate::command::knowledge_vault::is_vault_dir;
+use crate::utils::common::http_client;
+
+// —— 与服务端知识库双向同步：笔记 front matter 的 `knowledgeId` 对应后端文档 id，
+// 本地清单记录上次同步时的内容哈希与服务端 `updatedAt`，据此增量推送 / 拉取 ——
+
+/// 同步清单文件（位于知识库根目录，以 `.` 开头不参与文件树）
+const MANIFEST_FILE: &str = ".knowledge-backend.json";
+const PROGRESS_EVENT: &str = "knowledge://backend-sync-progress";
+/// front matter 中记录后端文档 id 的键
+const ID_KEY: &str = "knowledgeId";
+const PAGE_SIZE: usize = 100;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ManifestEntry {
+	/// 相对知识库根目录的路径（`/` 分隔）
+	path: String,
+	hash: String,
+	/// 服务端 `updatedAt`；新建后尚未取得时为 None，下次同步补齐
+	updated_at: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+	/// 服务地址或账号变更后清单作废，按首次同步处理
+	base_url: String,
+	author_id: i64,
+	notes: BTreeMap<String, ManifestEntry>,
+	/// 本地已删除、等待删除服务端文档的 id → 删除时间（毫秒）；服务端删除成功后移除
+	tombstones: BTreeMap<String, i64>,
+	last_sync_ms: Option<i64>,
+}
+
+fn load_backend_manifest(root: &Path, base_url: &str, author_id: i64) -> Manifest {
+	load_manifest::<Manifest>(root, MANIFEST_FILE)
+		.filter(|m| m.base_url == base_url && m.author_id == author_id)
+		.unwrap_or_else(|| Manifest {
+			base_url: base_url.to_string(),
+			author_id,
+			..Default::default()
+		})
+}
+
+// —— front matter 中的 knowledgeId ——
+
+fn id_line_value(line: &str) -> Option<&str> {
+	let rest = line.trim().strip_prefix(ID_KEY)?.trim_start().strip_prefix(':')?;
+	Some(rest.trim().trim_matches(|c| c == '"' || c == '\''))
+}
+
+/// 读取笔记 front matter 中的 `knowledgeId`
+fn note_id(content: &str) -> Option<String> {
+	let (_, block) = split_front_matter(content)?;
+	block
+		.lines()
+		.find_map(id_line_value)
+		.filter(|v| !v.is_empty())
+		.map(str::to_string)
+}
+
+/// 去掉 `knowledgeId` 行后的正文（推送到服务端的内容）；front matter 因此为空时整段移除
+fn strip_note_id(content: &str) -> String {
+	let Some((len, block)) = split_front_matter(content) else {
+		return content.to_string();
+	};
+	let kept: Vec<&str> = block.lines().filter(|l| id_line_value(l).is_none()).collect();
+	let body = &content[len..];
+	if kept.iter().all(|l| l.trim().is_empty()) {
+		return body.trim_start_matches(['\r', '\n']).to_string();
+	}
+	format!("---\n{}\n---\n{}", kept.join("\n"), body)
+}
+
+/// 写入（或替换）`knowledgeId`
+fn with_note_id(content: &str, id: &str) -> String {
+	let line = format!("{}: {}", ID_KEY, serde_json::to_string(id).unwrap_or_default());
+	let stripped = strip_note_id(content);
+	match split_front_matter(&stripped) {
+		Some((len, block)) => {
+			let block = block.trim_end_matches(['\r', '\n']);
+			format!("---\n{}\n{}\n---\n{}", line, block, &stripped[len..])
+		}
+		None => format!("---\n{}\n---\n\n{}", line, stripped),
+	}
+}
+
+// —— 服务端接口（knowledge.controller，统一包裹为 `{ data, success, message }`）——
+
+#[derive(Debug, Deserialize)]
+struct Envelope<T> {
+	data: Option<T>,
+	success: Option<bool>,
+	message: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteItem {
+	id: String,
+	updated_at: Option<String>,
+	#[serde(default)]
+	is_owned: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemotePage {
+	list: Vec<RemoteItem>,
+	total: usize,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteDetail {
+	title: Option<String>,
+	content: Option<String>,
+	updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoteCreated {
+	id: String,
+}
+
+struct Backend<'a> {
+	base_url: &'a str,
+	token: &'a str,
+	author: Option<&'a str>,
+	author_id: i64,
+}
+
+impl Backend<'_> {
+	fn request(&self, method: Method, path: &str) -> RequestBuilder {
+		http_client()
+			.request(method, format!("{}{}", self.base_url, path))
+			.bearer_auth(self.token)
+	}
+
+	/// 发送请求并解出 `data`；`Ok(None)` 表示 404
+	async fn call<T: DeserializeOwned>(&self, req: RequestBuilder) -> Result<Option<T>, String> {
+		let resp = req.send().await.map_err(|e| format!("请求服务端失败: {}", e))?;
+		let status = resp.status();
+		if status == StatusCode::NOT_FOUND {
+			return Ok(None);
+		}
+		if status == StatusCode::UNAUTHORIZED {
+			return Err("登录已失效，请重新登录".to_string());
+		}
+		let text = resp.text().await.map_err(|e| format!("读取响应失败: {}", e))?;
+		let envelope: Envelope<T> = serde_json::from_str(&text).map_err(|e| {
+			if status.is_success() {
+				format!("解析响应失败: {}", e)
+			} else {
+				format!("服务端返回 {}", status)
+			}
+		})?;
+		if !status.is_success() || envelope.success == Some(false) {
+			let message = match envelope.message {
+				Some(serde_json::Value::String(s)) => s,
+				Some(v) => v.to_string(),
+				None => status.to_string(),
+			};
+			return Err(format!("服务端错误: {}", message));
+		}
+		envelope.data.map(Some).ok_or_else(|| "服务端响应缺少 data".to_string())
+	}
+
+	/// 当前用户拥有的全部文档（列表不含正文）
+	async fn list_owned(&self) -> Result<HashMap<String, RemoteItem>, String> {
+		let mut out = HashMap::new();
+		let mut page_no = 1;
+		let mut seen = 0usize;
+		loop {
+			let req = self.request(
+				Method::GET,
+				&format!("/knowledge/list?pageNo={}&pageSize={}", page_no, PAGE_SIZE),
+			);
+			let page: RemotePage = self.call(req).await?.ok_or("知识库列表接口不存在")?;
+			let count = page.list.len();
+			seen += count;
+			out.extend(page.list.into_iter().filter(|i| i.is_owned).map(|i| (i.id.clone(), i)));
+			if count == 0 || seen >= page.total {
+				return Ok(out);
+			}
+			page_no += 1;
+		}
+	}
+
+	/// 文档详情；已不存在（404）时为 `Ok(None)`
+	async fn find(&self, id: &str) -> Result<Option<RemoteDetail>, String> {
+		self.call(self.request(Method::GET, &format!("/knowledge/detail/{}", id))).await
+	}
+
+	async fn detail(&self, id: &str) -> Result<RemoteDetail, String> {
+		self.find(id).await?.ok_or_else(|| "服务端文档不存在".to_string())
+	}
+
+	fn body(&self, title: &str, content: &str) -> serde_json::Value {
+		serde_json::json!({
+			"title": title,
+			"content": content,
+			"author": self.author,
+			"authorId": self.author_id,
+		})
+	}
+
+	async fn create(&self, title: &str, content: &str) -> Result<String, String> {
+		let req = self.request(Method::POST, "/knowledge/save").json(&self.body(title, content));
+		let created: RemoteCreated = self.call(req).await?.ok_or("新建接口不存在")?;
+		Ok(created.id)
+	}
+
+	/// 更新已有文档，返回新的 `updatedAt`；文档已不存在时为 `Ok(None)`
+	async fn update(&self, id: &str, title: &str, content: &str) -> Result<Option<Option<String>>, String> {
+		let mut body = self.body(title, content);
+		body["id"] = serde_json::Value::String(id.to_string());
+		let req = self.request(Method::PUT, &format!("/knowledge/update/{}", id)).json(&body);
+		Ok(self.call::<RemoteDetail>(req).await?.map(|d| d.updated_at))
+	}
+
+	/// 删除文档（进入服务端回收站）；已不存在视为成功
+	async fn delete(&self, id: &str) -> Result<(), String> {
+		self.call::<serde_json::Value>(self.request(Method::DELETE, &format!("/knowledge/delete/{}", id)))
+			.await
+			.map(|_| ())
+	}
+}
+
+// —— 本地笔记 ——
+
+struct LocalNote {
+	rel: String,
+	hash: String,
+	content: String,
+}
+
+impl LocalNote {
+	fn title(&self) -> String {
+		Path::new(&self.rel)
+			.file_stem()
+			.map(|s| s.to_string_lossy().to_string())
+			.unwrap_or_default()
+	}
+}
+
+/// 扫描本地笔记：带 id 的按 id 归档；无 id 或 id 重复（复制出来的笔记）的归入待新建
+fn scan_local(root: &Path) -> Result<(HashMap<String, LocalNote>, Vec<LocalNote>), String> {
+	let mut files = Vec::new();
+	collect_md_files(root, &mut files)?;
+	files.sort();
+	let mut by_id = HashMap::new();
+	let mut fresh = Vec::new();
+	for p in files {
+		let bytes = fs::read(&p).map_err(|e| format!("读取 {} 失败: {}", p.display(), e))?;
+		let note = LocalNote {
+			rel: rel_path(root, &p)?,
+			hash: content_hash(&bytes),
+			content: String::from_utf8_lossy(&bytes).into_owned(),
+		};
+		match note_id(&note.content) {
+			Some(id) if !by_id.contains_key(&id) => {
+				by_id.insert(id, note);
+			}
+			_ => fresh.push(note),
+		}
+	}
+	Ok((by_id, fresh))
+}
+
+fn rel_path(root: &Path, p: &Path) -> Result<String, String> {
+	Ok(p.strip_prefix(root)
+		.map_err(|e| e.to_string())?
+		.to_string_lossy()
+		.replace('\\', "/"))
+}
+
+/// 为拉取的新文档选择不冲突的文件名
+fn unique_note_path(root: &Path, title: &str) -> PathBuf {
+	let name = sanitize_filename(title);
+	let stem = name.trim_end_matches(".md").to_string();
+	let mut candidate = root.join(&name);
+	let mut n = 2;
+	while candidate.exists() {
+		candidate = root.join(format!("{}-{}.md", stem, n));
+		n += 1;
+	}
+	candidate
+}
+
+/// 两侧都修改过的笔记：服务端版本另存为 `copy_path`，本地版本已推送
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeBackendConflict {
+	pub id: String,
+	pub path: String,
+	pub copy_path: String,
+	pub remote_title: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeBackendSyncError {
+	/// 笔记相对路径；仅存在于服务端时为文档 id
+	pub path: String,
+	pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeBackendSyncResult {
+	/// 新建到服务端的笔记（相对路径）
+	pub created: Vec<String>,
+	pub pushed: Vec<String>,
+	pub pulled: Vec<String>,
+	pub deleted_local: Vec<String>,
+	/// 已删除的服务端文档 id
+	pub deleted_remote: Vec<String>,
+	pub conflicts: Vec<KnowledgeBackendConflict>,
+	pub errors: Vec<KnowledgeBackendSyncError>,
+	pub message: String,
+}
+
+/// 单篇笔记的同步动作
+enum Action {
+	/// 服务端无此 id（已删除或属于其他账号）且本地有修改：作为新文档创建并改写 id
+	Create,
+	Push,
+	Pull,
+	DeleteLocal,
+	DeleteRemote,
+	/// 两侧都改过：内容一致时只记录，否则服务端版本另存为冲突副本、本地版本推送
+	Conflict,
+	/// 两侧均未变化，仅补齐清单中的 `updatedAt`
+	Record,
+	Forget,
+}
+
+fn plan(
+	local: Option<&LocalNote>,
+	remote: Option<&RemoteItem>,
+	known: Option<&ManifestEntry>,
+	tombstoned: bool,
+) -> Option<Action> {
+	let local_changed = |l: &LocalNote| known.is_none_or(|k| k.hash != l.hash || k.path != l.rel);
+	let remote_changed = |r: &RemoteItem| {
+		known.is_none_or(|k| k.updated_at.is_some() && k.updated_at != r.updated_at)
+	};
+	match (local, remote, known) {
+		(None, None, _) => (known.is_some() || tombstoned).then_some(Action::Forget),
+		(Some(_), None, None) => Some(Action::Create),
+		(Some(l), None, Some(_)) => Some(if local_changed(l) { Action::Create } else { Action::DeleteLocal }),
+		(None, Some(_), None) => Some(if tombstoned { Action::DeleteRemote } else { Action::Pull }),
+		(None, Some(r), Some(_)) => Some(if remote_changed(r) { Action::Pull } else { Action::DeleteRemote }),
+		(Some(l), Some(r), _) => match (local_changed(l), remote_changed(r)) {
+			(false, false) => known.is_some_and(|k| k.updated_at.is_none()).then_some(Action::Record),
+			(true, false) => Some(Action::Push),
+			(false, true) => Some(Action::Pull),
+			(true, true) => Some(Action::Conflict),
+		},
+	}
+}
+
+/// 写回本地笔记：覆盖已有笔记前先留历史快照
+fn write_note(path: &Path, content: &str, retention: HistoryRetention) -> Result<String, String> {
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+	}
+	snapshot_before_write(path, content, retention)?;
+	write_file_atomic(path, content.as_bytes())?;
+	Ok(content_hash(content.as_bytes()))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncKnowledgeBackendInput {
+	/// 服务端地址（含全局前缀 `/api`），与前端 `BASE_URL` 一致
+	pub base_url: String,
+	/// 登录 token（前端 localStorage 中的 JWT）
+	pub token: String,
+	/// 当前登录用户 id，新建文档时写入 `authorId`
+	pub author_id: i64,
+	#[serde(default)]
+	pub author: Option<String>,
+	#[serde(default)]
+	pub dir_path: Option<String>,
+}
+
+/// 与服务端知识库双向同步；进度通过 `knowledge://backend-sync-progress` 事件推送。
+/// 无 `knowledgeId` 的本地笔记会新建到服务端，并把返回的 id 写入其 front matter
+#[tauri::command]
+pub async fn sync_knowledge_backend(
+	app: AppHandle,
+	input: SyncKnowledgeBackendInput,
+) -> Result<KnowledgeBackendSyncResult, String> {
+	let _running = try_begin_sync()?;
+	let base_url = input.base_url.trim().trim_end_matches('/');
+	if !(base_url.starts_with("http://") || base_url.starts_with("https://")) {
+		return Err("服务端地址须为 http / https".to_string());
+	}
+	let token = input.token.trim().trim_start_matches("Bearer ").trim();
+	if token.is_empty() {
+		return Err("未登录".to_string());
+	}
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	// 加密笔记若以明文推送到服务端即失去端到端加密的意义
+	if is_vault_dir(&root) {
+		return Err("已加密的知识库不支持与服务端同步".to_string());
+	}
+	let backend = Backend {
+		base_url,
+		token,
+		author: input.author.as_deref().filter(|a| !a.trim().is_empty()),
+		author_id: input.author_id,
+	};
+	let emit = |phase: &str, processed: usize, total: usize, current: Option<&str>| {
+		emit_progress(&app, PROGRESS_EVENT, phase, processed, total, current);
+	};
+
+	emit("scanning", 0, 0, None);
+	let mut manifest = load_backend_manifest(&root, base_url, input.author_id);
+	let (local, fresh) = scan_local(&root)?;
+	let remote = backend.list_owned().await?;
+
+	let ids: BTreeSet<String> = local
+		.keys()
+		.chain(remote.keys())
+		.chain(manifest.notes.keys())
+		.chain(manifest.tombstones.keys())
+		.cloned()
+		.collect();
+	let mut planned: Vec<(Option<String>, Option<&LocalNote>, Action)> = ids
+		.into_iter()
+		.filter_map(|id| {
+			let action = plan(
+				local.get(&id),
+				remote.get(&id),
+				manifest.notes.get(&id),
+				manifest.tombstones.contains_key(&id),
+			)?;
+			Some((Some(id.clone()), local.get(&id), action))
+		})
+		.collect();
+	planned.extend(fresh.iter().map(|n| (None, Some(n), Action::Create)));
+
+	let retention = load_history_retention(&app).await;
+	let mut result = KnowledgeBackendSyncResult::default();
+	let total = planned.len();
+	for (i, (id, note, action)) in planned.into_iter().enumerate() {
+		let label = note.map(|n| n.rel.clone()).or_else(|| id.clone()).unwrap_or_default();
+		emit("syncing", i, total, Some(&label));
+		let outcome: Result<(), String> = async {
+			match action {
+				Action::Forget => {
+					if let Some(id) = &id {
+						manifest.notes.remove(id);
+						manifest.tombstones.remove(id);
+					}
+				}
+				Action::Record => {
+					let (Some(id), Some(note)) = (&id, note) else { return Ok(()) };
+					let updated_at = remote.get(id).and_then(|r| r.updated_at.clone());
+					manifest.notes.insert(
+						id.clone(),
+						ManifestEntry { path: note.rel.clone(), hash: note.hash.clone(), updated_at },
+					);
+				}
+				Action::Create => {
+					let note = note.ok_or("缺少本地笔记")?;
+					let new_id = backend.create(&note.title(), &strip_note_id(&note.content)).await?;
+					// 旧 id 在服务端已不存在，清单中的记录随之作废
+					if let Some(old) = &id {
+						manifest.notes.remove(old);
+					}
+					let content = with_note_id(&note.content, &new_id);
+					write_file_atomic(&root.join(&note.rel), content.as_bytes())?;
+					manifest.notes.insert(
+						new_id,
+						ManifestEntry {
+							path: note.rel.clone(),
+							hash: content_hash(content.as_bytes()),
+							updated_at: None,
+						},
+					);
+					result.created.push(note.rel.clone());
+				}
+				Action::Push => {
+					let (Some(id), Some(note)) = (&id, note) else { return Ok(()) };
+					let updated_at = backend
+						.update(id, &note.title(), &strip_note_id(&note.content))
+						.await?
+						.ok_or("服务端文档不存在")?;
+					manifest.notes.insert(
+						id.clone(),
+						ManifestEntry { path: note.rel.clone(), hash: note.hash.clone(), updated_at },
+					);
+					result.pushed.push(note.rel.clone());
+				}
+				Action::Pull => {
+					let id = id.as_deref().ok_or("缺少文档 id")?;
+					let detail = backend.detail(id).await?;
+					let path = match note {
+						Some(n) => root.join(&n.rel),
+						None => unique_note_path(&root, detail.title.as_deref().unwrap_or_default()),
+					};
+					let content = with_note_id(detail.content.as_deref().unwrap_or_default(), id);
+					let hash = write_note(&path, &content, retention)?;
+					let rel = rel_path(&root, &path)?;
+					manifest.notes.insert(
+						id.to_string(),
+						ManifestEntry {
+							path: rel.clone(),
+							hash,
+							updated_at: detail.updated_at.or_else(|| remote.get(id).and_then(|r| r.updated_at.clone())),
+						},
+					);
+					manifest.tombstones.remove(id);
+					result.pulled.push(rel);
+				}
+				Action::DeleteLocal => {
+					let (Some(id), Some(note)) = (&id, note) else { return Ok(()) };
+					// 列表按页偏移读取，翻页期间服务端有增删时可能漏掉文档；确认已删除（404）再删本地
+					if let Some(detail) = backend.find(id).await? {
+						let known = manifest.notes.get(id).and_then(|k| k.updated_at.clone());
+						if detail.updated_at.is_some() && detail.updated_at != known {
+							let content = with_note_id(detail.content.as_deref().unwrap_or_default(), id);
+							let hash = write_note(&root.join(&note.rel), &content, retention)?;
+							manifest.notes.insert(
+								id.clone(),
+								ManifestEntry { path: note.rel.clone(), hash, updated_at: detail.updated_at },
+							);
+							result.pulled.push(note.rel.clone());
+						}
+						return Ok(());
+					}
+					move_to_trash(&app, &root.join(&note.rel))?;
+					manifest.notes.remove(id);
+					result.deleted_local.push(note.rel.clone());
+				}
+				Action::DeleteRemote => {
+					let id = id.as_deref().ok_or("缺少文档 id")?;
+					// 先记墓碑：删除失败时下次同步重试，而不是把文档重新拉回本地
+					manifest.notes.remove(id);
+					manifest
+						.tombstones
+						.entry(id.to_string())
+						.or_insert_with(|| Local::now().timestamp_millis());
+					backend.delete(id).await?;
+					manifest.tombstones.remove(id);
+					result.deleted_remote.push(id.to_string());
+				}
+				Action::Conflict => {
+					let (Some(id), Some(note)) = (&id, note) else { return Ok(()) };
+					let detail = backend.detail(id).await?;
+					let remote_content = detail.content.unwrap_or_default();
+					let local_content = strip_note_id(&note.content);
+					let updated_at = if remote_content == local_content {
+						detail.updated_at
+					} else {
+						// 冲突副本不带 id，下次同步作为新文档上传
+						let copy = root.join(conflict_copy_rel(&note.rel, &|c| root.join(c).exists()));
+						write_note(&copy, &strip_note_id(&remote_content), retention)?;
+						result.conflicts.push(KnowledgeBackendConflict {
+							id: id.clone(),
+							path: note.rel.clone(),
+							copy_path: rel_path(&root, &copy)?,
+							remote_title: detail.title,
+						});
+						let updated_at = backend
+							.update(id, &note.title(), &local_content)
+							.await?
+							.ok_or("服务端文档不存在")?;
+						result.pushed.push(note.rel.clone());
+						updated_at
+					};
+					manifest.notes.insert(
+						id.clone(),
+						ManifestEntry { path: note.rel.clone(), hash: note.hash.clone(), updated_at },
+					);
+				}
+			}
+			Ok(())
+		}
+		.await;
+		if let Err(error) = outcome {
+			result.errors.push(KnowledgeBackendSyncError { path: label, error });
+		}
+	}
+
+	manifest.last_sync_ms = Some(Local::now().timestamp_millis());
+	save_manifest(&root, MANIFEST_FILE, &manifest)?;
+	emit("done", total, total, None);
+	result.message = format!(
+		"新建 {}，推送 {}，拉取 {}，本地删除 {}，服务端删除 {}，冲突 {}，失败 {}",
+		result.created.len(),
+		result.pushed.len(),
+		result.pulled.len(),
+		result.deleted_local.len(),
+		result.deleted_remote.len(),
+		result.conflicts.len(),
+		result.errors.len()
+	);
+	Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn local(rel: &str, hash: &str) -> LocalNote {
+		LocalNote { rel: rel.to_string(), hash: hash.to_string(), content: String::new() }
+	}
+
+	fn remote(updated_at: &str) -> RemoteItem {
+		RemoteItem { id: "1".to_string(), updated_at: Some(updated_at.to_string()), is_owned: true }
+	}
+
+	fn known(rel: &str, hash: &str, updated_at: Option<&str>) -> ManifestEntry {
+		ManifestEntry { path: rel.to_string(), hash: hash.to_string(), updated_at: updated_at.map(str::to_string) }
+	}
+
+	fn name(action: Option<Action>) -> &'static str {
+		match action {
+			None => "none",
+			Some(Action::Create) => "create",
+			Some(Action::Push) => "push",
+			Some(Action::Pull) => "pull",
+			Some(Action::DeleteLocal) => "delete-local",
+			Some(Action::DeleteRemote) => "delete-remote",
+			Some(Action::Conflict) => "conflict",
+			Some(Action::Record) => "record",
+			Some(Action::Forget) => "forget",
+		}
+	}
+
+	#[test]
+	fn note_id_reads_front_matter_only() {
+		assert_eq!(note_id("---\ntitle: a\nknowledgeId: \"42\"\n---\nbody").as_deref(), Some("42"));
+		assert_eq!(note_id("---\nknowledgeId: abc\n---\n").as_deref(), Some("abc"));
+		assert_eq!(note_id("---\nknowledgeId: \"\"\n---\n"), None);
+		assert_eq!(note_id("knowledgeId: 42\n"), None);
+	}
+
+	#[test]
+	fn strip_note_id_keeps_other_fields_and_drops_empty_front_matter() {
+		assert_eq!(strip_note_id("---\ntitle: a\nknowledgeId: 42\n---\nbody"), "---\ntitle: a\n---\nbody");
+		assert_eq!(strip_note_id("---\nknowledgeId: 42\n---\n\nbody"), "body");
+		assert_eq!(strip_note_id("plain body"), "plain body");
+	}
+
+	#[test]
+	fn with_note_id_inserts_or_replaces_the_id() {
+		assert_eq!(with_note_id("body", "7"), "---\nknowledgeId: \"7\"\n---\n\nbody");
+		let replaced = with_note_id("---\ntitle: a\nknowledgeId: 1\n---\nbody", "7");
+		assert_eq!(replaced, "---\nknowledgeId: \"7\"\ntitle: a\n---\nbody");
+		assert_eq!(note_id(&replaced).as_deref(), Some("7"));
+		assert_eq!(strip_note_id(&with_note_id("body", "7")), "body");
+	}
+
+	#[test]
+	fn plan_single_side_changes() {
+		let l = local("a.md", "h1");
+		let r = remote("t1");
+		let k = known("a.md", "h1", Some("t1"));
+		assert_eq!(name(plan(Some(&l), None, None, false)), "create");
+		assert_eq!(name(plan(None, Some(&r), None, false)), "pull");
+		assert_eq!(name(plan(None, Some(&r), None, true)), "delete-remote");
+		assert_eq!(name(plan(Some(&l), Some(&r), Some(&k), false)), "none");
+		assert_eq!(name(plan(Some(&local("a.md", "h2")), Some(&r), Some(&k), false)), "push");
+		assert_eq!(name(plan(Some(&local("b.md", "h1")), Some(&r), Some(&k), false)), "push");
+		assert_eq!(name(plan(Some(&l), Some(&remote("t2")), Some(&k), false)), "pull");
+		assert_eq!(name(plan(Some(&local("a.md", "h2")), Some(&remote("t2")), Some(&k), false)), "conflict");
+	}
+
+	#[test]
+	fn plan_deletions() {
+		let l = local("a.md", "h1");
+		let r = remote("t1");
+		let k = known("a.md", "h1", Some("t1"));
+		// 服务端已删除：本地未改则删本地，改过则重新创建
+		assert_eq!(name(plan(Some(&l), None, Some(&k), false)), "delete-local");
+		assert_eq!(name(plan(Some(&local("a.md", "h2")), None, Some(&k), false)), "create");
+		// 本地已删除：服务端未改则删服务端，改过则拉回
+		assert_eq!(name(plan(None, Some(&r), Some(&k), false)), "delete-remote");
+		assert_eq!(name(plan(None, Some(&remote("t2")), Some(&k), false)), "pull");
+		assert_eq!(name(plan(None, None, Some(&k), false)), "forget");
+		assert_eq!(name(plan(None, None, None, true)), "forget");
+		assert_eq!(name(plan(None, None, None, false)), "none");
+	}
+
+	#[test]
+	fn plan_records_missing_updated_at() {
+		let l = local("a.md", "h1");
+		let k = known("a.md", "h1", None);
+		assert_eq!(name(plan(Some(&l), Some(&remote("t1")), Some(&k), false)), "record");
+	}
+}
@@ -0,0 +1,579 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::command::knowledge::{collect_md_files, resolve_knowledge_root, write_file_atomic};
+use crate::command::knowledge_conflict::content_hash;
+use crate::command::knowledge_link::split_front_matter;
+use crate::command::knowledge_vault::is_vault_dir;
+
+// —— RAG 分块：按标题层级切分笔记，块 id 为「标题路径 + 正文」的内容哈希，内容不变则 id 不变；
+// 分块结果缓存在 `<知识库>/.index/chunks.json`，只重算内容有变化的笔记，并给出新增 / 移除的块 id ——
+
+pub(crate) const INDEX_DIR_NAME: &str = ".index";
+const CHUNK_CACHE_FILE: &str = "chunks.json";
+const CACHE_VERSION: u32 = 1;
+const DEFAULT_MAX_TOKENS: usize = 400;
+const MIN_MAX_TOKENS: usize = 64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeChunk {
+	pub id: String,
+	/// 所属标题路径（由外到内），位于首个标题之前的内容为空
+	pub heading_path: Vec<String>,
+	/// 1 起始、闭区间的源文件行号
+	pub start_line: usize,
+	pub end_line: usize,
+	pub token_estimate: usize,
+	pub text: String,
+}
+
+/// 粗略 token 估算：CJK 字符各计 1，其余非空白字符约 4 个计 1
+pub(crate) fn estimate_tokens(text: &str) -> usize {
+	let mut cjk = 0usize;
+	let mut other = 0usize;
+	for c in text.chars() {
+		if is_cjk(c) {
+			cjk += 1;
+		} else if !c.is_whitespace() {
+			other += 1;
+		}
+	}
+	cjk + other.div_ceil(4)
+}
+
+pub(crate) fn is_cjk(c: char) -> bool {
+	matches!(c as u32,
+		0x3040..=0x30FF // 日文假名
+		| 0x3400..=0x4DBF
+		| 0x4E00..=0x9FFF
+		| 0xAC00..=0xD7AF // 韩文
+		| 0xF900..=0xFAFF
+		| 0x20000..=0x2FA1F)
+}
+
+/// ATX 标题 `## 标题 ##` → (级别, 文本)
+pub(crate) fn atx_heading(line: &str) -> Option<(usize, String)> {
+	let indent = line.len() - line.trim_start_matches(' ').len();
+	if indent > 3 {
+		return None;
+	}
+	let rest = &line[indent..];
+	let level = rest.chars().take_while(|c| *c == '#').count();
+	if !(1..=6).contains(&level) {
+		return None;
+	}
+	let text = &rest[level..];
+	if !text.is_empty() && !text.starts_with([' ', '\t']) {
+		return None;
+	}
+	let text = text.trim().trim_end_matches('#').trim_end();
+	Some((level, text.to_string()))
+}
+
+struct Line<'a> {
+	/// 1 起始行号
+	no: usize,
+	text: &'a str,
+	/// 代码块内（含围栏行）的行不识别标题、不作为段落分隔
+	in_fence: bool,
+}
+
+struct Section<'a> {
+	heading_path: Vec<String>,
+	lines: Vec<Line<'a>>,
+	has_heading: bool,
+}
+
+fn sections(content: &str) -> Vec<Section<'_>> {
+	let skip_bytes = split_front_matter(content).map(|(len, _)| len).unwrap_or(0);
+	let mut out = vec![Section {
+		heading_path: Vec::new(),
+		lines: Vec::new(),
+		has_heading: false,
+	}];
+	let mut stack: Vec<(usize, String)> = Vec::new();
+	let mut fence: Option<String> = None;
+	let mut offset = 0usize;
+	for (idx, raw) in content.split_inclusive('\n').enumerate() {
+		let start = offset;
+		offset += raw.len();
+		if start < skip_bytes {
+			continue;
+		}
+		let text = raw.trim_end_matches(['\r', '\n']);
+		let trimmed = text.trim_start();
+		let marker = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m));
+		let in_fence = match (&fence, marker) {
+			(Some(open), Some(m)) if m == open => {
+				fence = None;
+				true
+			}
+			(Some(_), _) => true,
+			(None, Some(m)) => {
+				fence = Some(m.to_string());
+				true
+			}
+			(None, None) => false,
+		};
+		if !in_fence && let Some((level, title)) = atx_heading(text) {
+			while stack.last().is_some_and(|(l, _)| *l >= level) {
+				stack.pop();
+			}
+			stack.push((level, title));
+			out.push(Section {
+				heading_path: stack.iter().map(|(_, t)| t.clone()).collect(),
+				lines: Vec::new(),
+				has_heading: true,
+			});
+		}
+		if let Some(section) = out.last_mut() {
+			section.lines.push(Line {
+				no: idx + 1,
+				text,
+				in_fence,
+			});
+		}
+	}
+	out
+}
+
+/// 把段落（空行分隔，代码块整体算一段）贪心装入不超过 `max_tokens` 的块；超长段落按行再切
+fn pack(lines: &[Line], max_tokens: usize) -> Vec<(usize, usize)> {
+	let mut blocks: Vec<(usize, usize)> = Vec::new();
+	let mut cur: Option<(usize, usize)> = None;
+	for (i, l) in lines.iter().enumerate() {
+		if !l.in_fence && l.text.trim().is_empty() {
+			blocks.extend(cur.take());
+			continue;
+		}
+		cur = Some(match cur {
+			Some((s, _)) => (s, i),
+			None => (i, i),
+		});
+	}
+	blocks.extend(cur);
+
+	let tokens = |s: usize, e: usize| -> usize { lines[s..=e].iter().map(|l| estimate_tokens(l.text)).sum() };
+	let mut ranges: Vec<(usize, usize)> = Vec::new();
+	let mut acc: Option<(usize, usize, usize)> = None;
+	let flush = |acc: &mut Option<(usize, usize, usize)>, ranges: &mut Vec<(usize, usize)>| {
+		if let Some((s, e, _)) = acc.take() {
+			ranges.push((s, e));
+		}
+	};
+	for (s, e) in blocks {
+		let t = tokens(s, e);
+		if t > max_tokens {
+			flush(&mut acc, &mut ranges);
+			let mut line_acc: Option<(usize, usize, usize)> = None;
+			for (i, line) in lines.iter().enumerate().take(e + 1).skip(s) {
+				let lt = estimate_tokens(line.text);
+				match line_acc {
+					Some((ls, _, n)) if n + lt <= max_tokens => line_acc = Some((ls, i, n + lt)),
+					_ => {
+						flush(&mut line_acc, &mut ranges);
+						line_acc = Some((i, i, lt));
+					}
+				}
+			}
+			flush(&mut line_acc, &mut ranges);
+			continue;
+		}
+		match acc {
+			Some((as_, _, n)) if n + t <= max_tokens => acc = Some((as_, e, n + t)),
+			_ => {
+				flush(&mut acc, &mut ranges);
+				acc = Some((s, e, t));
+			}
+		}
+	}
+	flush(&mut acc, &mut ranges);
+	ranges
+}
+
+/// 切分整篇笔记；同一笔记内内容完全相同的块追加 `-2`、`-3` 区分
+pub(crate) fn chunk_markdown(content: &str, max_tokens: usize) -> Vec<KnowledgeChunk> {
+	let mut out = Vec::new();
+	let mut seen: HashMap<String, usize> = HashMap::new();
+	for section in sections(content) {
+		// 标题行单独成块没有检索价值：只有标题、没有正文的小节跳过
+		let body = if section.has_heading { &section.lines[1..] } else { &section.lines[..] };
+		if body.iter().all(|l| l.text.trim().is_empty()) {
+			continue;
+		}
+		let mut ranges = pack(&section.lines, max_tokens);
+		// 标题行后紧跟空行时会单独成段：并入下一块，使首块带上标题
+		if section.has_heading && ranges.len() > 1 && ranges[0] == (0, 0) {
+			ranges.remove(0);
+			ranges[0].0 = 0;
+		}
+		for (s, e) in ranges {
+			let text = section.lines[s..=e]
+				.iter()
+				.map(|l| l.text)
+				.collect::<Vec<_>>()
+				.join("\n");
+			let hash = content_hash(format!("{}\n{}", section.heading_path.join(" > "), text).as_bytes());
+			let base = hash[..16].to_string();
+			let n = seen.entry(base.clone()).or_insert(0);
+			*n += 1;
+			let id = if *n == 1 { base } else { format!("{}-{}", base, n) };
+			out.push(KnowledgeChunk {
+				id,
+				heading_path: section.heading_path.clone(),
+				start_line: section.lines[s].no,
+				end_line: section.lines[e].no,
+				token_estimate: estimate_tokens(&text),
+				text,
+			});
+		}
+	}
+	out
+}
+
+// —— 分块缓存 ——
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CachedNote {
+	hash: String,
+	chunks: Vec<KnowledgeChunk>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChunkCache {
+	version: u32,
+	max_tokens: usize,
+	/// 相对知识库根目录的路径（`/` 分隔）→ 分块
+	notes: BTreeMap<String, CachedNote>,
+	/// 尚未被 [`refresh_knowledge_chunks`] 取走的差异
+	#[serde(default)]
+	pending: KnowledgeChunkDiff,
+}
+
+fn cache_path(root: &Path) -> PathBuf {
+	root.join(INDEX_DIR_NAME).join(CHUNK_CACHE_FILE)
+}
+
+fn load_cache(root: &Path, max_tokens: usize) -> ChunkCache {
+	fs::read_to_string(cache_path(root))
+		.ok()
+		.and_then(|s| serde_json::from_str::<ChunkCache>(&s).ok())
+		.filter(|c| c.version == CACHE_VERSION && c.max_tokens == max_tokens)
+		.unwrap_or(ChunkCache {
+			version: CACHE_VERSION,
+			max_tokens,
+			..Default::default()
+		})
+}
+
+/// 未指定时沿用缓存中的块大小，避免不同调用方互相使缓存失效
+fn effective_max_tokens(root: &Path, requested: Option<usize>) -> usize {
+	requested
+		.or_else(|| {
+			let raw = fs::read_to_string(cache_path(root)).ok()?;
+			let cache = serde_json::from_str::<serde_json::Value>(&raw).ok()?;
+			cache.get("maxTokens")?.as_u64().map(|n| n as usize)
+		})
+		.unwrap_or(DEFAULT_MAX_TOKENS)
+		.max(MIN_MAX_TOKENS)
+}
+
+fn save_cache(root: &Path, cache: &ChunkCache) -> Result<(), String> {
+	let dir = root.join(INDEX_DIR_NAME);
+	fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+	let json = serde_json::to_string(cache).map_err(|e| e.to_string())?;
+	write_file_atomic(&cache_path(root), json.as_bytes())
+}
+
+pub(crate) fn rel_of(root: &Path, p: &Path) -> Option<String> {
+	Some(p.strip_prefix(root).ok()?.to_string_lossy().replace('\\', "/"))
+}
+
+/// 自上次 [`refresh_knowledge_chunks`] 以来累计的块级差异（保存、检索等途径更新缓存时同样记入）
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeChunkDiff {
+	/// 重新切分的笔记（相对路径）
+	pub changed_notes: BTreeSet<String>,
+	pub removed_notes: BTreeSet<String>,
+	pub added: BTreeSet<String>,
+	pub removed: BTreeSet<String>,
+}
+
+impl KnowledgeChunkDiff {
+	/// 记入一次变更；先增后删（或先删后增）的 id 相互抵消
+	fn record(&mut self, added: impl IntoIterator<Item = String>, removed: impl IntoIterator<Item = String>) {
+		for id in added {
+			if !self.removed.remove(&id) {
+				self.added.insert(id);
+			}
+		}
+		for id in removed {
+			if !self.added.remove(&id) {
+				self.removed.insert(id);
+			}
+		}
+	}
+
+	fn is_empty(&self) -> bool {
+		self.changed_notes.is_empty() && self.removed_notes.is_empty() && self.added.is_empty() && self.removed.is_empty()
+	}
+}
+
+/// 重新切分一篇笔记并把块 id 差异记入缓存；`note` 不存在时从缓存中移除。返回缓存是否有变化
+fn refresh_note(root: &Path, cache: &mut ChunkCache, note: &Path) -> Result<bool, String> {
+	let Some(rel) = rel_of(root, note) else {
+		return Ok(false);
+	};
+	let old_ids: HashSet<String> = cache
+		.notes
+		.get(&rel)
+		.map(|n| n.chunks.iter().map(|c| c.id.clone()).collect())
+		.unwrap_or_default();
+	if !note.is_file() {
+		if cache.notes.remove(&rel).is_none() {
+			return Ok(false);
+		}
+		cache.pending.changed_notes.remove(&rel);
+		cache.pending.removed_notes.insert(rel);
+		cache.pending.record([], old_ids);
+		return Ok(true);
+	}
+	let bytes = fs::read(note).map_err(|e| format!("读取 {} 失败: {}", note.display(), e))?;
+	let hash = content_hash(&bytes);
+	if cache.notes.get(&rel).is_some_and(|n| n.hash == hash) {
+		return Ok(false);
+	}
+	let chunks = chunk_markdown(&String::from_utf8_lossy(&bytes), cache.max_tokens);
+	let new_ids: HashSet<String> = chunks.iter().map(|c| c.id.clone()).collect();
+	cache.pending.record(
+		new_ids.difference(&old_ids).cloned().collect::<Vec<_>>(),
+		old_ids.difference(&new_ids).cloned().collect::<Vec<_>>(),
+	);
+	cache.pending.removed_notes.remove(&rel);
+	cache.pending.changed_notes.insert(rel.clone());
+	cache.notes.insert(rel, CachedNote { hash, chunks });
+	Ok(true)
+}
+
+/// 增量刷新整个知识库的分块缓存
+fn refresh_cache(root: &Path, max_tokens: usize) -> Result<ChunkCache, String> {
+	if is_vault_dir(root) {
+		return Err("加密知识库不建立分块索引".to_string());
+	}
+	let mut cache = load_cache(root, max_tokens);
+	let mut notes = Vec::new();
+	collect_md_files(root, &mut notes)?;
+	let live: HashSet<String> = notes.iter().filter_map(|p| rel_of(root, p)).collect();
+	let gone: Vec<PathBuf> = cache
+		.notes
+		.keys()
+		.filter(|rel| !live.contains(*rel))
+		.map(|rel| root.join(rel))
+		.collect();
+	let mut dirty = !cache_path(root).is_file();
+	for p in notes.iter().chain(gone.iter()) {
+		dirty |= refresh_note(root, &mut cache, p)?;
+	}
+	if dirty {
+		save_cache(root, &cache)?;
+	}
+	Ok(cache)
+}
+
+/// 笔记保存 / 删除后更新分块缓存；知识库尚未建立缓存时不做任何事
+pub(crate) fn update_chunk_cache(root: &Path, notes: &[&Path]) -> Result<(), String> {
+	let path = cache_path(root);
+	if !path.is_file() || is_vault_dir(root) {
+		return Ok(());
+	}
+	let Some(mut cache) = fs::read_to_string(&path)
+		.ok()
+		.and_then(|s| serde_json::from_str::<ChunkCache>(&s).ok())
+		.filter(|c| c.version == CACHE_VERSION)
+	else {
+		return Ok(());
+	};
+	let mut dirty = false;
+	for note in notes {
+		dirty |= refresh_note(root, &mut cache, note)?;
+	}
+	if dirty {
+		save_cache(root, &cache)?;
+	}
+	Ok(())
+}
+
+/// 带路径的块（检索等功能使用）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeNoteChunk {
+	/// 笔记绝对路径
+	pub path: String,
+	#[serde(flatten)]
+	pub chunk: KnowledgeChunk,
+}
+
+/// 刷新后返回知识库全部块
+pub(crate) fn load_all_chunks(root: &Path, max_tokens: Option<usize>) -> Result<Vec<KnowledgeNoteChunk>, String> {
+	let cache = refresh_cache(root, effective_max_tokens(root, max_tokens))?;
+	Ok(cache
+		.notes
+		.into_iter()
+		.flat_map(|(rel, note)| {
+			let path = root.join(&rel).to_string_lossy().to_string();
+			note.chunks.into_iter().map(move |chunk| KnowledgeNoteChunk {
+				path: path.clone(),
+				chunk,
+			})
+		})
+		.collect())
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshKnowledgeChunksInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	/// 单块 token 上限；未传时沿用上次的设置（首次为 400），变更后全部重算
+	#[serde(default)]
+	pub max_tokens: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshKnowledgeChunksResult {
+	pub notes: usize,
+	pub chunks: usize,
+	#[serde(flatten)]
+	pub diff: KnowledgeChunkDiff,
+}
+
+/// 增量刷新分块缓存，并取走自上次调用以来新增 / 移除的块 id，供向量库增量更新
+#[tauri::command]
+pub async fn refresh_knowledge_chunks(
+	app: AppHandle,
+	input: Option<RefreshKnowledgeChunksInput>,
+) -> Result<RefreshKnowledgeChunksResult, String> {
+	let input = input.unwrap_or_default();
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let mut cache = refresh_cache(&root, effective_max_tokens(&root, input.max_tokens))?;
+	let diff = std::mem::take(&mut cache.pending);
+	if !diff.is_empty() {
+		save_cache(&root, &cache)?;
+	}
+	Ok(RefreshKnowledgeChunksResult {
+		notes: cache.notes.len(),
+		chunks: cache.notes.values().map(|n| n.chunks.len()).sum(),
+		diff,
+	})
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListKnowledgeChunksInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	/// 只返回该笔记的块
+	#[serde(default)]
+	pub file_path: Option<String>,
+	#[serde(default)]
+	pub max_tokens: Option<usize>,
+}
+
+/// 列出分块（先增量刷新缓存）
+#[tauri::command]
+pub async fn list_knowledge_chunks(
+	app: AppHandle,
+	input: Option<ListKnowledgeChunksInput>,
+) -> Result<Vec<KnowledgeNoteChunk>, String> {
+	let input = input.unwrap_or_default();
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let mut chunks = load_all_chunks(&root, input.max_tokens)?;
+	if let Some(fp) = input.file_path.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+		let target = PathBuf::from(fp);
+		chunks.retain(|c| Path::new(&c.path) == target);
+	}
+	Ok(chunks)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const NOTE: &str = "# 笔记\n\n开头段落。\n\n## 第一节\n\n第一节正文。\n\n## 第二节\n\n第二节正文。\n\n## 第三节\n\n第三节正文。\n";
+
+	fn ids(content: &str) -> Vec<String> {
+		chunk_markdown(content, DEFAULT_MAX_TOKENS).into_iter().map(|c| c.id).collect()
+	}
+
+	#[test]
+	fn chunk_ids_survive_unrelated_edits() {
+		let before = ids(NOTE);
+		assert_eq!(before.len(), 4);
+		// 修改第二节：只有该块 id 改变
+		let edited = ids(&NOTE.replace("第二节正文。", "第二节正文（已修改）。"));
+		assert_eq!(edited.len(), 4);
+		assert_eq!([&edited[0], &edited[1], &edited[3]], [&before[0], &before[1], &before[3]]);
+		assert_ne!(edited[2], before[2]);
+		// 前面插入内容使行号整体后移，其余块 id 不变
+		let shifted = chunk_markdown(&NOTE.replace("开头段落。", "开头段落。\n\n新增一段。"), DEFAULT_MAX_TOKENS);
+		let shifted_ids: Vec<&str> = shifted.iter().map(|c| c.id.as_str()).collect();
+		assert_eq!(&shifted_ids[1..], &before[1..].iter().map(String::as_str).collect::<Vec<_>>()[..]);
+		assert_eq!(shifted[1].start_line, chunk_markdown(NOTE, DEFAULT_MAX_TOKENS)[1].start_line + 2);
+	}
+
+	#[test]
+	fn identical_chunks_get_numbered_ids() {
+		let chunks = chunk_markdown("## 小节\n\n同样的段落。\n\n## 小节\n\n同样的段落。\n", DEFAULT_MAX_TOKENS);
+		assert_eq!(chunks.len(), 2);
+		assert_eq!(chunks[1].id, format!("{}-2", chunks[0].id));
+	}
+
+	#[test]
+	fn diff_reports_exactly_the_changed_chunks() {
+		let dir = tempfile::tempdir().unwrap();
+		let root = dir.path();
+		fs::write(root.join("a.md"), NOTE).unwrap();
+		fs::write(root.join("b.md"), "# B\n\nB 的正文。\n").unwrap();
+		let first = refresh_cache(root, DEFAULT_MAX_TOKENS).unwrap();
+		let a_before: Vec<String> = first.notes["a.md"].chunks.iter().map(|c| c.id.clone()).collect();
+		let b_before: BTreeSet<String> = first.notes["b.md"].chunks.iter().map(|c| c.id.clone()).collect();
+		assert_eq!(first.pending.added.len(), a_before.len() + b_before.len());
+		assert!(first.pending.removed.is_empty());
+		let mut cleared = first;
+		cleared.pending = KnowledgeChunkDiff::default();
+		save_cache(root, &cleared).unwrap();
+
+		// 未改动时不产生差异
+		assert!(refresh_cache(root, DEFAULT_MAX_TOKENS).unwrap().pending.is_empty());
+
+		fs::write(root.join("a.md"), NOTE.replace("第二节正文。", "第二节正文（已修改）。")).unwrap();
+		fs::remove_file(root.join("b.md")).unwrap();
+		let cache = refresh_cache(root, DEFAULT_MAX_TOKENS).unwrap();
+		let a_after: Vec<String> = cache.notes["a.md"].chunks.iter().map(|c| c.id.clone()).collect();
+		let diff = cache.pending;
+		assert_eq!(diff.changed_notes, BTreeSet::from(["a.md".to_string()]));
+		assert_eq!(diff.removed_notes, BTreeSet::from(["b.md".to_string()]));
+		assert_eq!(diff.added, BTreeSet::from([a_after[2].clone()]));
+		let mut removed = b_before;
+		removed.insert(a_before[2].clone());
+		assert_eq!(diff.removed, removed);
+	}
+
+	#[test]
+	fn diff_record_cancels_add_then_remove() {
+		let mut diff = KnowledgeChunkDiff::default();
+		diff.record(["x".to_string(), "y".to_string()], []);
+		diff.record([], ["x".to_string(), "z".to_string()]);
+		assert_eq!(diff.added, BTreeSet::from(["y".to_string()]));
+		assert_eq!(diff.removed, BTreeSet::from(["z".to_string()]));
+	}
+}
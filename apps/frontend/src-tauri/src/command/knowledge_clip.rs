@@ -0,0 +1,314 @@
+use std::fs;
+use std::path::Path;
+
+use chrono::{SecondsFormat, Utc};
+use encoding_rs::{Encoding, UTF_8};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::command::knowledge::{
+	SaveKnowledgeMarkdownInput, compute_save_target_path, knowledge_root_for, write_file_atomic,
+};
+use crate::command::knowledge_assets::{KnowledgeImageLocalizeItem, localize_remote_images_in};
+use crate::command::knowledge_history::{load_history_retention, snapshot_before_write};
+use crate::command::knowledge_import::front_matter;
+use crate::command::knowledge_vault::encode_note;
+use crate::utils::common::http_client;
+use crate::utils::html_to_md::html_to_markdown_with_base;
+use crate::utils::readability::{Article, extract_article};
+
+// —— 网页剪藏：抓取网页 → 提取正文 → 转 Markdown → 图片本地化 → 带 front matter 保存为笔记 ——
+
+/// 网页 HTML 大小上限
+const MAX_PAGE_SIZE: u64 = 10 * 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipUrlToKnowledgeInput {
+	pub url: String,
+	/// 笔记标题；缺省取页面标题
+	#[serde(default)]
+	pub title: Option<String>,
+	#[serde(default)]
+	pub file_path: Option<String>,
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	#[serde(default)]
+	pub overwrite: bool,
+	/// 是否下载正文图片到 `assets/`，默认开启
+	#[serde(default = "default_true")]
+	pub localize_images: bool,
+}
+
+fn default_true() -> bool {
+	true
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipUrlToKnowledgeResult {
+	pub file_path: String,
+	pub title: String,
+	/// 重定向后的最终地址
+	pub source_url: String,
+	pub author: Option<String>,
+	pub images: Vec<KnowledgeImageLocalizeItem>,
+	pub message: String,
+}
+
+/// 抓取网页 HTML，返回（最终地址, HTML）；边读边计数，超过 `MAX_PAGE_SIZE` 即中止（不依赖 Content-Length）
+async fn fetch_page(url: &str) -> Result<(reqwest::Url, String), String> {
+	let response = http_client()
+		.get(url)
+		.header(reqwest::header::ACCEPT, "text/html,application/xhtml+xml")
+		.send()
+		.await
+		.map_err(|e| format!("请求网页失败: {}", e))?;
+	if !response.status().is_success() {
+		return Err(format!("请求网页失败: HTTP状态码为 {}", response.status()));
+	}
+	let content_type = response
+		.headers()
+		.get(reqwest::header::CONTENT_TYPE)
+		.and_then(|v| v.to_str().ok())
+		.unwrap_or_default()
+		.to_ascii_lowercase();
+	if !content_type.is_empty() && !content_type.contains("html") {
+		return Err(format!("不是网页内容：{}", content_type));
+	}
+	let too_large = || format!("网页过大 (> {} KB)", MAX_PAGE_SIZE / 1024);
+	if response.content_length().unwrap_or(0) > MAX_PAGE_SIZE {
+		return Err(too_large());
+	}
+	let final_url = response.url().clone();
+	let mut stream = response.bytes_stream();
+	let mut bytes: Vec<u8> = Vec::new();
+	while let Some(chunk) = stream.next().await {
+		let chunk = chunk.map_err(|e| format!("读取网页内容失败: {}", e))?;
+		bytes.extend_from_slice(&chunk);
+		if bytes.len() as u64 > MAX_PAGE_SIZE {
+			return Err(too_large());
+		}
+	}
+	// 按响应头声明的字符集解码，缺省 UTF-8
+	let encoding = content_type
+		.split(';')
+		.find_map(|p| p.trim().strip_prefix("charset="))
+		.and_then(|c| Encoding::for_label(c.trim_matches('"').as_bytes()))
+		.unwrap_or(UTF_8);
+	Ok((final_url, encoding.decode(&bytes).0.into_owned()))
+}
+
+/// 提取正文并转为 Markdown，返回（标题, 正文, 文章信息）；正文缺少一级标题时补上
+fn clip_markdown(
+	final_url: &reqwest::Url,
+	html: &str,
+	title: Option<&str>,
+) -> Result<(String, String, Article), String> {
+	let article = extract_article(html);
+	let title = title
+		.map(str::trim)
+		.filter(|s| !s.is_empty())
+		.map(str::to_string)
+		.or_else(|| article.title.clone())
+		.unwrap_or_else(|| final_url.host_str().unwrap_or("网页剪藏").to_string());
+	let mut body = html_to_markdown_with_base(&article.content_html, Some(final_url), &mut |_| None);
+	if body.trim().is_empty() {
+		return Err("未能从网页中提取到正文".to_string());
+	}
+	if !body.starts_with("# ") {
+		body = format!("# {}\n\n{}", title, body);
+	}
+	Ok((title, body, article))
+}
+
+/// 带 front matter（来源地址、抓取时间、作者等）的笔记内容
+fn clip_note_content(
+	title: &str,
+	final_url: &reqwest::Url,
+	fetched_at: String,
+	article: &Article,
+	body: &str,
+) -> String {
+	format!(
+		"{}{}\n",
+		front_matter(
+			&[
+				("title", Some(title.to_string())),
+				("source", Some(final_url.to_string())),
+				("fetched_at", Some(fetched_at)),
+				("author", article.author.clone()),
+				("site", article.site_name.clone()),
+				("published", article.published.clone()),
+				("excerpt", article.excerpt.clone()),
+			],
+			&[],
+		),
+		body
+	)
+}
+
+/// 抓取网页正文并保存为知识笔记（front matter 记录来源地址、抓取时间与作者）
+#[tauri::command]
+pub async fn clip_url_to_knowledge(
+	app: AppHandle,
+	input: ClipUrlToKnowledgeInput,
+) -> Result<ClipUrlToKnowledgeResult, String> {
+	let url = input.url.trim();
+	let parsed = reqwest::Url::parse(url).map_err(|e| format!("网址无效: {}", e))?;
+	if !matches!(parsed.scheme(), "http" | "https") {
+		return Err("仅支持 http / https 网址".to_string());
+	}
+	let (final_url, html) = fetch_page(url).await?;
+	let fetched_at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+
+	let (title, body, article) = clip_markdown(&final_url, &html, input.title.as_deref())?;
+
+	let save_input = SaveKnowledgeMarkdownInput {
+		title: title.clone(),
+		content: String::new(),
+		file_path: input.file_path.clone(),
+		dir_path: input.dir_path.clone(),
+		overwrite: input.overwrite,
+		previous_title: None,
+		update_links: false,
+		expected_mtime_ms: None,
+		expected_hash: None,
+		base_content: None,
+	};
+	let path = compute_save_target_path(&app, &save_input).await?;
+	if path.is_dir() {
+		return Err("目标路径已存在且非文件".to_string());
+	}
+	if path.exists() && !input.overwrite {
+		return Err(format!("文件已存在：{}", path.to_string_lossy()));
+	}
+
+	let (body, images) = if input.localize_images {
+		let root = knowledge_root_for(&app, input.dir_path.as_ref(), &path).await?;
+		let note_dir = path.parent().map(Path::to_path_buf).unwrap_or_else(|| root.clone());
+		localize_remote_images_in(&root, &note_dir, &body).await
+	} else {
+		(body, Vec::new())
+	};
+
+	let content = clip_note_content(&title, &final_url, fetched_at, &article, &body);
+
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+	}
+	// 剪藏到已解锁的加密知识库时落盘为密文
+	let bytes = encode_note(&path, &content)?;
+	let retention = load_history_retention(&app).await;
+	snapshot_before_write(&path, &content, retention)?;
+	write_file_atomic(&path, &bytes)?;
+
+	let failed = images.iter().filter(|i| i.status == "failed").count();
+	let message = if failed > 0 {
+		format!("已剪藏至 {}，{} 张图片下载失败（保留原链接）", path.display(), failed)
+	} else {
+		format!("已剪藏至 {}", path.display())
+	};
+	Ok(ClipUrlToKnowledgeResult {
+		file_path: path.to_string_lossy().to_string(),
+		title,
+		source_url: final_url.to_string(),
+		author: article.author,
+		images,
+		message,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::io::{BufRead, BufReader, Write};
+	use std::net::TcpListener;
+
+	const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\0\x01\0\0\0\x01\x08\x06\0\0\0\x1f\x15\xc4\x89";
+
+	/// 本机测试站点：`/article` 返回文章页，`/img.png` 返回图片，`/huge` 不带 Content-Length 返回超限正文
+	fn serve() -> String {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let base = format!("http://{}", listener.local_addr().unwrap());
+		std::thread::spawn(move || {
+			for stream in listener.incoming() {
+				let Ok(mut stream) = stream else { continue };
+				let mut reader = BufReader::new(stream.try_clone().unwrap());
+				let mut request_line = String::new();
+				reader.read_line(&mut request_line).unwrap();
+				let mut line = String::new();
+				while reader.read_line(&mut line).unwrap() > 2 {
+					line.clear();
+				}
+				let path = request_line.split_whitespace().nth(1).unwrap_or("/").to_string();
+				let (content_type, body): (&str, Vec<u8>) = match path.as_str() {
+					"/article" => (
+						"text/html; charset=utf-8",
+						br#"<html><head><title>Fixture Article</title><meta name="author" content="Ada"></head>
+<body><nav><a href="/">Home</a></nav><article><h1>Fixture Article</h1>
+<p>The first paragraph of the article body is long enough to be picked as the main content of this page.</p>
+<p><img src="/img.png" alt="pic"></p>
+<p>A second paragraph keeps the readability heuristics happy with a little more prose to score.</p>
+</article></body></html>"#
+							.to_vec(),
+					),
+					"/img.png" => ("image/png", PNG.to_vec()),
+					"/huge" => {
+						let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nConnection: close\r\n\r\n");
+						let block = vec![b'a'; 1024 * 1024];
+						for _ in 0..=MAX_PAGE_SIZE / block.len() as u64 {
+							if stream.write_all(&block).is_err() {
+								break;
+							}
+						}
+						continue;
+					}
+					_ => ("text/plain", b"not found".to_vec()),
+				};
+				let head = format!(
+					"HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+					content_type,
+					body.len()
+				);
+				let _ = stream.write_all(head.as_bytes());
+				let _ = stream.write_all(&body);
+			}
+		});
+		base
+	}
+
+	#[tokio::test]
+	async fn clips_fixture_article_with_front_matter_and_local_images() {
+		let base = serve();
+		let (final_url, html) = fetch_page(&format!("{}/article", base)).await.unwrap();
+		let (title, body, article) = clip_markdown(&final_url, &html, None).unwrap();
+		assert_eq!(title, "Fixture Article");
+		assert!(body.contains("first paragraph of the article body"));
+		assert!(!body.contains("Home"));
+		assert!(body.contains(&format!("{}/img.png", base)));
+
+		let dir = tempfile::tempdir().unwrap();
+		let (body, images) = localize_remote_images_in(dir.path(), dir.path(), &body).await;
+		assert_eq!(images.len(), 1);
+		assert_eq!(images[0].status, "localized");
+		let local = images[0].local_path.as_deref().unwrap();
+		assert_eq!(fs::read(local).unwrap(), PNG);
+		assert!(body.contains(images[0].relative_path.as_deref().unwrap()));
+		assert!(!body.contains(&base));
+
+		let content = clip_note_content(&title, &final_url, "2026-01-02T03:04:05Z".to_string(), &article, &body);
+		assert!(content.starts_with("---\ntitle: \"Fixture Article\"\n"));
+		assert!(content.contains(&format!("source: \"{}/article\"\n", base)));
+		assert!(content.contains("fetched_at: \"2026-01-02T03:04:05Z\"\n"));
+		assert!(content.contains("author: \"Ada\"\n"));
+	}
+
+	#[tokio::test]
+	async fn rejects_pages_over_the_size_cap_without_content_length() {
+		let base = serve();
+		let err = fetch_page(&format!("{}/huge", base)).await.unwrap_err();
+		assert!(err.contains("网页过大"), "{}", err);
+	}
+}
@@ -0,0 +1,304 @@
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use similar::{DiffOp, TextDiff};
+
+use crate::command::knowledge_vault::decode_note;
+
+// —— 知识 Markdown 保存冲突：外部修改检测（mtime / 内容哈希）与三方合并建议 ——
+
+const MARKER_OURS: &str = "<<<<<<< 当前编辑";
+const MARKER_SEP: &str = "=======";
+const MARKER_THEIRS: &str = ">>>>>>> 磁盘版本";
+
+/// 内容 SHA-256（小写十六进制）
+pub(crate) fn content_hash(bytes: &[u8]) -> String {
+	let digest = Sha256::digest(bytes);
+	digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 文件修改时间（毫秒）；读取失败为 None
+pub(crate) fn file_mtime_ms(path: &Path) -> Option<u64> {
+	fs::metadata(path)
+		.ok()
+		.and_then(|m| m.modified().ok())
+		.and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+		.map(|d| d.as_millis() as u64)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeMergeProposal {
+	/// 合并结果；存在冲突时含 `<<<<<<<` / `=======` / `>>>>>>>` 标记
+	pub content: String,
+	pub conflict_count: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeSaveConflict {
+	/// 固定为 `conflict`，前端据此区分普通错误字符串
+	pub kind: &'static str,
+	pub message: String,
+	pub path: String,
+	/// 磁盘上（被外部修改后）的内容
+	pub disk_content: String,
+	pub disk_mtime_ms: Option<u64>,
+	pub disk_hash: String,
+	/// 本次尝试保存的内容
+	pub your_content: String,
+	/// 传入 `baseContent` 时给出三方合并建议
+	pub merged: Option<KnowledgeMergeProposal>,
+}
+
+/// 知识保存错误：普通错误序列化为字符串（与其他命令一致），冲突序列化为结构体
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum KnowledgeSaveError {
+	Message(String),
+	Conflict(Box<KnowledgeSaveConflict>),
+}
+
+impl From<String> for KnowledgeSaveError {
+	fn from(s: String) -> Self {
+		KnowledgeSaveError::Message(s)
+	}
+}
+
+impl From<&str> for KnowledgeSaveError {
+	fn from(s: &str) -> Self {
+		KnowledgeSaveError::Message(s.to_string())
+	}
+}
+
+/// 校验磁盘文件是否仍是编辑器加载时的版本：传了 `expected_hash` 时只比哈希（同步工具可能只改 mtime），否则比 mtime
+pub(crate) fn check_external_modification(
+	path: &Path,
+	expected_mtime_ms: Option<u64>,
+	expected_hash: Option<&str>,
+	base_content: Option<&str>,
+	your_content: &str,
+) -> Result<(), KnowledgeSaveError> {
+	if expected_mtime_ms.is_none() && expected_hash.is_none() {
+		return Ok(());
+	}
+	if !path.is_file() {
+		return Ok(());
+	}
+	// 加密笔记按明文比对，与读取命令返回的哈希一致
+	let disk = decode_note(path, fs::read(path).map_err(|e| e.to_string())?)?;
+	let disk_hash = content_hash(&disk);
+	let disk_mtime_ms = file_mtime_ms(path);
+	let changed = match expected_hash.map(str::trim).filter(|h| !h.is_empty()) {
+		Some(h) => !h.eq_ignore_ascii_case(&disk_hash),
+		None => disk_mtime_ms != expected_mtime_ms,
+	};
+	if !changed {
+		return Ok(());
+	}
+	let disk_content = String::from_utf8_lossy(&disk).to_string();
+	let merged = base_content.map(|base| {
+		let (content, conflict_count) = merge3(base, your_content, &disk_content);
+		KnowledgeMergeProposal {
+			content,
+			conflict_count,
+		}
+	});
+	Err(KnowledgeSaveError::Conflict(Box::new(KnowledgeSaveConflict {
+		kind: "conflict",
+		message: format!("文件已被外部修改：{}", path.display()),
+		path: path.to_string_lossy().to_string(),
+		disk_content,
+		disk_mtime_ms,
+		disk_hash,
+		your_content: your_content.to_string(),
+		merged,
+	})))
+}
+
+/// 相对 base 的一处改动：替换 base 行区间 `start..end` 为 `lines`
+struct Change<'a> {
+	start: usize,
+	end: usize,
+	lines: Vec<&'a str>,
+}
+
+fn changes<'a>(base: &[&str], other: &'a [&'a str]) -> Vec<Change<'a>> {
+	let diff = TextDiff::from_slices(base, other);
+	diff.ops()
+		.iter()
+		.filter(|op| !matches!(op, DiffOp::Equal { .. }))
+		.map(|op| {
+			let old = op.old_range();
+			let new = op.new_range();
+			Change {
+				start: old.start,
+				end: old.end,
+				lines: other[new].to_vec(),
+			}
+		})
+		.collect()
+}
+
+/// 将同一组内某一侧的改动应用到 base 的 `start..end` 区间
+fn apply_side<'a>(base: &[&'a str], start: usize, end: usize, side: &[&Change<'a>]) -> Vec<&'a str> {
+	let mut out = Vec::new();
+	let mut cursor = start;
+	for c in side {
+		out.extend_from_slice(&base[cursor..c.start]);
+		out.extend(c.lines.iter().copied());
+		cursor = c.end;
+	}
+	out.extend_from_slice(&base[cursor..end]);
+	out
+}
+
+/// 行级三方合并：双方改动不重叠时自动合并，重叠且结果不同时输出冲突标记。返回（合并文本, 冲突数）
+pub(crate) fn merge3(base: &str, ours: &str, theirs: &str) -> (String, usize) {
+	let base_lines: Vec<&str> = base.split_inclusive('\n').collect();
+	let our_lines: Vec<&str> = ours.split_inclusive('\n').collect();
+	let their_lines: Vec<&str> = theirs.split_inclusive('\n').collect();
+	let ours_changes = changes(&base_lines, &our_lines);
+	let theirs_changes = changes(&base_lines, &their_lines);
+
+	let mut out = String::with_capacity(ours.len().max(theirs.len()));
+	let mut conflicts = 0usize;
+	let mut cursor = 0usize;
+	let (mut i, mut j) = (0usize, 0usize);
+	while i < ours_changes.len() || j < theirs_changes.len() {
+		// 取起点更靠前的一侧开组，再不断吸收与当前组区间重叠（或同点插入）的改动
+		let take_ours = match (ours_changes.get(i), theirs_changes.get(j)) {
+			(Some(a), Some(b)) => a.start <= b.start,
+			(Some(_), None) => true,
+			_ => false,
+		};
+		let first = if take_ours { &ours_changes[i] } else { &theirs_changes[j] };
+		let (start, mut end) = (first.start, first.end);
+		let mut group_ours: Vec<&Change> = Vec::new();
+		let mut group_theirs: Vec<&Change> = Vec::new();
+		loop {
+			let mut grew = false;
+			while let Some(c) = ours_changes.get(i) {
+				if c.start < end || c.start == start || (c.start == end && c.start == c.end) {
+					end = end.max(c.end);
+					group_ours.push(c);
+					i += 1;
+					grew = true;
+				} else {
+					break;
+				}
+			}
+			while let Some(c) = theirs_changes.get(j) {
+				if c.start < end || c.start == start || (c.start == end && c.start == c.end) {
+					end = end.max(c.end);
+					group_theirs.push(c);
+					j += 1;
+					grew = true;
+				} else {
+					break;
+				}
+			}
+			if !grew {
+				break;
+			}
+		}
+
+		for line in &base_lines[cursor..start] {
+			out.push_str(line);
+		}
+		let our_text = apply_side(&base_lines, start, end, &group_ours);
+		let their_text = apply_side(&base_lines, start, end, &group_theirs);
+		if group_theirs.is_empty() || our_text == their_text {
+			out.extend(our_text);
+		} else if group_ours.is_empty() {
+			out.extend(their_text);
+		} else {
+			conflicts += 1;
+			push_marker_line(&mut out, MARKER_OURS);
+			push_block(&mut out, &our_text);
+			push_marker_line(&mut out, MARKER_SEP);
+			push_block(&mut out, &their_text);
+			push_marker_line(&mut out, MARKER_THEIRS);
+		}
+		cursor = end;
+	}
+	for line in &base_lines[cursor..] {
+		out.push_str(line);
+	}
+	(out, conflicts)
+}
+
+fn push_marker_line(out: &mut String, marker: &str) {
+	if !out.is_empty() && !out.ends_with('\n') {
+		out.push('\n');
+	}
+	out.push_str(marker);
+	out.push('\n');
+}
+
+fn push_block(out: &mut String, lines: &[&str]) {
+	for line in lines {
+		out.push_str(line);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn merge3_combines_non_overlapping_edits() {
+		let base = "a\nb\nc\nd\n";
+		let ours = "A\nb\nc\nd\n";
+		let theirs = "a\nb\nc\nD\n";
+		assert_eq!(merge3(base, ours, theirs), ("A\nb\nc\nD\n".to_string(), 0));
+	}
+
+	#[test]
+	fn merge3_takes_one_side_when_other_unchanged() {
+		let base = "a\nb\n";
+		assert_eq!(merge3(base, base, "a\nb\nc\n"), ("a\nb\nc\n".to_string(), 0));
+		assert_eq!(merge3(base, "b\n", base), ("b\n".to_string(), 0));
+	}
+
+	#[test]
+	fn merge3_identical_edits_do_not_conflict() {
+		let base = "a\nb\n";
+		let edited = "a\nB\n";
+		assert_eq!(merge3(base, edited, edited), (edited.to_string(), 0));
+	}
+
+	#[test]
+	fn merge3_marks_overlapping_edits() {
+		let base = "a\nb\nc\n";
+		let (merged, conflicts) = merge3(base, "a\nours\nc\n", "a\ntheirs\nc\n");
+		assert_eq!(conflicts, 1);
+		assert_eq!(
+			merged,
+			format!("a\n{}\nours\n{}\ntheirs\n{}\nc\n", MARKER_OURS, MARKER_SEP, MARKER_THEIRS)
+		);
+	}
+
+	#[test]
+	fn merge3_conflict_markers_start_on_new_line() {
+		// 末行无换行时冲突标记仍须独占一行
+		let (merged, conflicts) = merge3("x", "ours", "theirs");
+		assert_eq!(conflicts, 1);
+		assert_eq!(
+			merged,
+			format!("{}\nours\n{}\ntheirs\n{}\n", MARKER_OURS, MARKER_SEP, MARKER_THEIRS)
+		);
+	}
+
+	#[test]
+	fn merge3_same_point_insertions_conflict() {
+		let base = "a\nc\n";
+		let (merged, conflicts) = merge3(base, "a\nb1\nc\n", "a\nb2\nc\n");
+		assert_eq!(conflicts, 1);
+		assert!(merged.starts_with("a\n") && merged.ends_with("c\n"));
+		assert!(merged.contains("b1\n") && merged.contains("b2\n"));
+	}
+}
@@ -0,0 +1,424 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::command::knowledge::{collect_md_files, is_md_file_path, knowledge_root_for, resolve_knowledge_root};
+use crate::command::knowledge_chunk::update_chunk_cache;
+use crate::command::knowledge_conflict::{content_hash, file_mtime_ms};
+use crate::command::knowledge_export::note_body;
+use crate::command::knowledge_git::auto_commit_notes;
+use crate::command::knowledge_link::rewrite_inbound_links;
+use crate::command::knowledge_trash::{empty_expired_trash, move_to_trash};
+use crate::command::knowledge_vault::read_note_text;
+
+// —— 重复笔记检测：内容哈希相同为完全重复；正文按字符 shingle 做 MinHash + LSH 分桶找候选，
+// 再以 shingle 集合的 Jaccard 相似度确认近似重复；合并时保留一篇，其余移入回收站 ——
+
+/// 字符 shingle 长度（中英文统一按字符切分）
+const SHINGLE_CHARS: usize = 5;
+/// MinHash 签名长度 = 分桶数 × 每桶行数；32×4 时相似度约 0.42 以上的笔记对即有较大概率成为候选
+const LSH_BANDS: usize = 32;
+const LSH_ROWS: usize = 4;
+const DEFAULT_THRESHOLD: f64 = 0.8;
+/// 规范化后正文少于该字符数的笔记不参与近似重复判断，避免短笔记互相误报
+const MIN_NEAR_CHARS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KnowledgeDuplicateKind {
+	/// 内容完全相同
+	Exact,
+	/// 相似度不低于阈值
+	Near,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeDuplicateNote {
+	pub path: String,
+	pub title: String,
+	pub size: usize,
+	pub updated_at_ms: Option<u64>,
+	pub content_hash: String,
+	/// 与建议保留笔记的 Jaccard 相似度（0～1），保留笔记自身为 1
+	pub similarity: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeDuplicateCluster {
+	pub kind: KnowledgeDuplicateKind,
+	/// 组内与保留笔记的最低相似度
+	pub similarity: f64,
+	/// 建议保留的笔记：优先不带 `_2`、`副本` 等后缀，其次内容最长、最近修改
+	pub keep_path: String,
+	/// 首项为建议保留的笔记
+	pub notes: Vec<KnowledgeDuplicateNote>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeDuplicateReport {
+	pub scanned: usize,
+	pub threshold: f64,
+	/// 完全重复在前，其余按相似度降序
+	pub clusters: Vec<KnowledgeDuplicateCluster>,
+}
+
+struct Candidate {
+	path: PathBuf,
+	title: String,
+	size: usize,
+	updated_at_ms: Option<u64>,
+	hash: String,
+	/// 已排序去重的 shingle 哈希
+	shingles: Vec<u64>,
+}
+
+/// FNV-1a：跨版本、跨平台稳定的 64 位哈希
+fn fnv1a(bytes: &[u8]) -> u64 {
+	let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+	for b in bytes {
+		h ^= *b as u64;
+		h = h.wrapping_mul(0x0100_0000_01b3);
+	}
+	h
+}
+
+fn splitmix64(mut x: u64) -> u64 {
+	x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+	x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+	x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+	x ^ (x >> 31)
+}
+
+/// 去掉 front matter，小写并把连续空白压成一个空格
+fn normalize(content: &str) -> Vec<char> {
+	let mut out = Vec::new();
+	for word in note_body(content).split_whitespace() {
+		if !out.is_empty() {
+			out.push(' ');
+		}
+		out.extend(word.chars().flat_map(char::to_lowercase));
+	}
+	out
+}
+
+fn shingles(chars: &[char]) -> Vec<u64> {
+	let mut buf = [0u8; 4];
+	let mut out: Vec<u64> = chars
+		.windows(SHINGLE_CHARS)
+		.map(|w| {
+			let mut bytes = Vec::with_capacity(w.len() * 3);
+			for c in w {
+				bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+			}
+			fnv1a(&bytes)
+		})
+		.collect();
+	out.sort_unstable();
+	out.dedup();
+	out
+}
+
+fn minhash(shingles: &[u64]) -> Vec<u64> {
+	(0..LSH_BANDS * LSH_ROWS)
+		.map(|i| {
+			let seed = splitmix64(i as u64);
+			shingles.iter().map(|s| splitmix64(s ^ seed)).min().unwrap_or(u64::MAX)
+		})
+		.collect()
+}
+
+/// 两个已排序集合的 Jaccard 相似度
+fn jaccard(a: &[u64], b: &[u64]) -> f64 {
+	if a.is_empty() && b.is_empty() {
+		return 1.0;
+	}
+	let (mut i, mut j, mut inter) = (0usize, 0usize, 0usize);
+	while i < a.len() && j < b.len() {
+		match a[i].cmp(&b[j]) {
+			std::cmp::Ordering::Less => i += 1,
+			std::cmp::Ordering::Greater => j += 1,
+			std::cmp::Ordering::Equal => {
+				inter += 1;
+				i += 1;
+				j += 1;
+			}
+		}
+	}
+	inter as f64 / (a.len() + b.len() - inter) as f64
+}
+
+/// 文件名像重复保存产生的副本：`xxx_2`、`xxx (2)`、`xxx 副本`、`xxx copy`
+fn looks_like_copy(title: &str) -> bool {
+	let t = title.trim();
+	let lower = t.to_lowercase();
+	if lower.ends_with("副本") || lower.ends_with(" copy") {
+		return true;
+	}
+	if let Some(inner) = t.strip_suffix(')')
+		&& let Some((_, n)) = inner.rsplit_once(" (")
+	{
+		return !n.is_empty() && n.chars().all(|c| c.is_ascii_digit());
+	}
+	t.rsplit_once('_')
+		.is_some_and(|(head, n)| !head.is_empty() && !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn find_parent(parent: &mut [usize], mut x: usize) -> usize {
+	while parent[x] != x {
+		parent[x] = parent[parent[x]];
+		x = parent[x];
+	}
+	x
+}
+
+fn union(parent: &mut [usize], a: usize, b: usize) {
+	let (ra, rb) = (find_parent(parent, a), find_parent(parent, b));
+	if ra != rb {
+		parent[ra.max(rb)] = ra.min(rb);
+	}
+}
+
+fn find_duplicates(root: &Path, threshold: f64) -> Result<KnowledgeDuplicateReport, String> {
+	let mut paths = Vec::new();
+	collect_md_files(root, &mut paths)?;
+	paths.sort();
+	let scanned = paths.len();
+	let mut notes: Vec<Candidate> = Vec::new();
+	let mut near_chars: Vec<bool> = Vec::new();
+	for path in paths {
+		// 读取失败（加密知识库未解锁等）或正文为空的笔记不参与比较
+		let Ok(content) = read_note_text(&path) else {
+			continue;
+		};
+		if note_body(&content).trim().is_empty() {
+			continue;
+		}
+		let chars = normalize(&content);
+		near_chars.push(chars.len() >= MIN_NEAR_CHARS);
+		notes.push(Candidate {
+			title: path
+				.file_stem()
+				.map(|s| s.to_string_lossy().to_string())
+				.unwrap_or_default(),
+			size: content.len(),
+			updated_at_ms: file_mtime_ms(&path),
+			hash: content_hash(content.as_bytes()),
+			shingles: shingles(&chars),
+			path,
+		});
+	}
+
+	let mut parent: Vec<usize> = (0..notes.len()).collect();
+	let mut by_hash: HashMap<&str, usize> = HashMap::new();
+	for (i, n) in notes.iter().enumerate() {
+		match by_hash.get(n.hash.as_str()) {
+			Some(&first) => union(&mut parent, first, i),
+			None => {
+				by_hash.insert(&n.hash, i);
+			}
+		}
+	}
+
+	// LSH：签名按带切分，同一带内完全相同的笔记成为候选对；完全重复只取代表参与
+	let mut buckets: HashMap<(usize, u64), Vec<usize>> = HashMap::new();
+	for (i, n) in notes.iter().enumerate() {
+		if !near_chars[i] || by_hash.get(n.hash.as_str()) != Some(&i) {
+			continue;
+		}
+		let sig = minhash(&n.shingles);
+		for (band, rows) in sig.chunks(LSH_ROWS).enumerate() {
+			let mut bytes = Vec::with_capacity(rows.len() * 8);
+			for r in rows {
+				bytes.extend_from_slice(&r.to_le_bytes());
+			}
+			buckets.entry((band, fnv1a(&bytes))).or_default().push(i);
+		}
+	}
+	let mut checked: HashSet<(usize, usize)> = HashSet::new();
+	for members in buckets.values().filter(|m| m.len() > 1) {
+		for (x, &a) in members.iter().enumerate() {
+			for &b in &members[x + 1..] {
+				if checked.insert((a, b)) && jaccard(&notes[a].shingles, &notes[b].shingles) >= threshold {
+					union(&mut parent, a, b);
+				}
+			}
+		}
+	}
+
+	let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+	for i in 0..notes.len() {
+		let r = find_parent(&mut parent, i);
+		groups.entry(r).or_default().push(i);
+	}
+	let mut clusters: Vec<KnowledgeDuplicateCluster> = groups
+		.into_values()
+		.filter(|g| g.len() > 1)
+		.map(|mut group| {
+			group.sort_by_key(|&i| {
+				let n = &notes[i];
+				(looks_like_copy(&n.title), Reverse(n.size), Reverse(n.updated_at_ms))
+			});
+			let keep = &notes[group[0]];
+			let exact = group.iter().all(|&i| notes[i].hash == keep.hash);
+			let members: Vec<KnowledgeDuplicateNote> = group
+				.iter()
+				.map(|&i| {
+					let n = &notes[i];
+					KnowledgeDuplicateNote {
+						path: n.path.to_string_lossy().to_string(),
+						title: n.title.clone(),
+						size: n.size,
+						updated_at_ms: n.updated_at_ms,
+						content_hash: n.hash.clone(),
+						similarity: if n.hash == keep.hash {
+							1.0
+						} else {
+							jaccard(&keep.shingles, &n.shingles)
+						},
+					}
+				})
+				.collect();
+			KnowledgeDuplicateCluster {
+				kind: if exact {
+					KnowledgeDuplicateKind::Exact
+				} else {
+					KnowledgeDuplicateKind::Near
+				},
+				similarity: members.iter().map(|m| m.similarity).fold(1.0, f64::min),
+				keep_path: members[0].path.clone(),
+				notes: members,
+			}
+		})
+		.collect();
+	clusters.sort_by(|a, b| {
+		(a.kind != KnowledgeDuplicateKind::Exact)
+			.cmp(&(b.kind != KnowledgeDuplicateKind::Exact))
+			.then_with(|| b.similarity.total_cmp(&a.similarity))
+			.then_with(|| a.keep_path.cmp(&b.keep_path))
+	});
+	Ok(KnowledgeDuplicateReport {
+		scanned,
+		threshold,
+		clusters,
+	})
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindKnowledgeDuplicatesInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	/// 近似重复的相似度阈值（0～1），默认 0.8
+	#[serde(default)]
+	pub threshold: Option<f64>,
+}
+
+/// 查找完全重复与近似重复的笔记，按组返回相似度与建议保留的笔记
+#[tauri::command]
+pub async fn find_knowledge_duplicates(
+	app: AppHandle,
+	input: Option<FindKnowledgeDuplicatesInput>,
+) -> Result<KnowledgeDuplicateReport, String> {
+	let input = input.unwrap_or_default();
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let threshold = input
+		.threshold
+		.filter(|t| t.is_finite())
+		.unwrap_or(DEFAULT_THRESHOLD)
+		.clamp(0.1, 1.0);
+	tauri::async_runtime::spawn_blocking(move || find_duplicates(&root, threshold))
+		.await
+		.map_err(|e| e.to_string())?
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeKnowledgeDuplicatesInput {
+	pub keep_path: String,
+	/// 移入回收站的笔记
+	pub remove_paths: Vec<String>,
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	/// 为 true 时把其他笔记中指向被移除笔记的链接改为指向保留的笔记
+	#[serde(default)]
+	pub update_links: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeMergeDuplicatesResult {
+	pub keep_path: String,
+	pub trashed: Vec<String>,
+	pub rewritten_links: usize,
+	pub message: String,
+}
+
+fn ensure_note_file(path: &Path) -> Result<(), String> {
+	if !path.is_file() {
+		return Err(format!("文件不存在：{}", path.display()));
+	}
+	if !is_md_file_path(path) {
+		return Err(format!("仅支持 .md 文件：{}", path.display()));
+	}
+	Ok(())
+}
+
+/// 合并重复笔记：保留 `keepPath`，其余移入回收站（可恢复）
+#[tauri::command]
+pub async fn merge_knowledge_duplicates(
+	app: AppHandle,
+	input: MergeKnowledgeDuplicatesInput,
+) -> Result<KnowledgeMergeDuplicatesResult, String> {
+	let keep = PathBuf::from(input.keep_path.trim());
+	ensure_note_file(&keep)?;
+	let mut remove: Vec<PathBuf> = Vec::new();
+	for raw in &input.remove_paths {
+		let p = PathBuf::from(raw.trim());
+		if p == keep || remove.contains(&p) {
+			continue;
+		}
+		ensure_note_file(&p)?;
+		remove.push(p);
+	}
+	if remove.is_empty() {
+		return Err("没有需要移除的笔记".to_string());
+	}
+	let root = knowledge_root_for(&app, input.dir_path.as_ref(), &keep).await?;
+
+	let mut rewritten_links = 0usize;
+	let mut trashed: Vec<PathBuf> = Vec::new();
+	for p in &remove {
+		// 入链须在移入回收站前改写，此时被移除的笔记仍能参与链接解析
+		if input.update_links {
+			rewritten_links += rewrite_inbound_links(&root, p, &keep)?;
+		}
+		move_to_trash(&app, p)?;
+		trashed.push(p.clone());
+	}
+	let _ = empty_expired_trash(&app, None).await;
+
+	let mut message = if rewritten_links > 0 {
+		format!("已保留 {}，{} 篇移至回收站，已更新 {} 处链接", keep.display(), trashed.len(), rewritten_links)
+	} else {
+		format!("已保留 {}，{} 篇移至回收站", keep.display(), trashed.len())
+	};
+	let files: Vec<&Path> = trashed.iter().map(PathBuf::as_path).collect();
+	let keep_rel = keep.strip_prefix(&root).unwrap_or(&keep).to_string_lossy().replace('\\', "/");
+	let commit_message = format!("docs(knowledge): 合并重复笔记至 {}", keep_rel);
+	if let Some(err) = auto_commit_notes(&app, &root, &files, &commit_message).await {
+		message.push_str(&format!("（Git 自动提交失败：{}）", err));
+	}
+	let _ = update_chunk_cache(&root, &files);
+	Ok(KnowledgeMergeDuplicatesResult {
+		keep_path: keep.to_string_lossy().to_string(),
+		trashed: trashed.iter().map(|p| p.to_string_lossy().to_string()).collect(),
+		rewritten_links,
+		message,
+	})
+}
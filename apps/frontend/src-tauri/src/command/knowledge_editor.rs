@@ -0,0 +1,874 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::AppHandle;
+
+use crate::command::knowledge::is_md_file_path;
+use crate::utils::common::get_store_json;
+use crate::utils::percent::encode_segment;
+
+// —— 本地 .md 用外部编辑器打开：内置 Cursor / Trae（用户所称 tare）/ VS Code / Zed / Sublime / Typora / Obsidian / 终端 vim，另可配置自定义命令 ——
+// 设置（store）：`knowledgeEditorOrder` 检测顺序（id 数组或逗号分隔），`knowledgeEditorCustomCommand` 自定义命令模板（`{path}` / `{line}`），`knowledgeEditorCustomName` 其显示名
+// 未设置检测顺序时保持原行为：只在 Cursor / Trae 间检测，优先 Cursor，都未检测到则用 Trae
+
+const CUSTOM_EDITOR_ID: &str = "custom";
+const DEFAULT_ORDER: [&str; 2] = ["cursor", "trae"];
+const FALLBACK_EDITOR_ID: &str = "trae";
+
+/// 打开方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Launch {
+	/// VS Code 系 CLI：`cli -g 路径:行`
+	Goto,
+	/// `cli 路径:行`
+	PathColonLine,
+	/// 不支持定位到行
+	Plain,
+	/// `obsidian://open?path=`，不支持定位到行
+	ObsidianUri,
+	/// 在系统终端中运行 `vim +行 路径`
+	TerminalVim,
+}
+
+struct BuiltinEditor {
+	id: &'static str,
+	name: &'static str,
+	/// 前台应用名（小写，完整匹配；Windows 为进程名）
+	frontmost: &'static [&'static str],
+	/// `pgrep -x` 进程名
+	#[cfg_attr(not(unix), allow(dead_code))]
+	processes: &'static [&'static str],
+	/// Windows `tasklist` 中的映像名（小写）
+	#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+	windows_exe: &'static str,
+	/// macOS .app 名称，依次尝试
+	#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+	mac_apps: &'static [&'static str],
+	/// 命令行工具名：先查 PATH，macOS 再查 .app 内 `mac_cli_dir` 目录
+	clis: &'static [&'static str],
+	#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+	mac_cli_dir: &'static str,
+	launch: Launch,
+}
+
+const BUILTIN_EDITORS: &[BuiltinEditor] = &[
+	BuiltinEditor {
+		id: "cursor",
+		name: "Cursor",
+		frontmost: &["cursor"],
+		processes: &["Cursor", "cursor"],
+		windows_exe: "cursor.exe",
+		mac_apps: &["Cursor"],
+		clis: &["cursor"],
+		mac_cli_dir: "Contents/Resources/app/bin",
+		launch: Launch::Goto,
+	},
+	BuiltinEditor {
+		id: "trae",
+		name: "Trae",
+		frontmost: &["trae", "trae cn"],
+		processes: &["Trae", "Trae CN", "trae"],
+		windows_exe: "trae.exe",
+		mac_apps: &["Trae", "Trae CN"],
+		clis: &["trae"],
+		mac_cli_dir: "Contents/Resources/app/bin",
+		launch: Launch::Goto,
+	},
+	BuiltinEditor {
+		id: "vscode",
+		name: "VS Code",
+		frontmost: &["code", "visual studio code"],
+		processes: &["Code", "code"],
+		windows_exe: "code.exe",
+		mac_apps: &["Visual Studio Code"],
+		clis: &["code"],
+		mac_cli_dir: "Contents/Resources/app/bin",
+		launch: Launch::Goto,
+	},
+	BuiltinEditor {
+		id: "zed",
+		name: "Zed",
+		frontmost: &["zed"],
+		processes: &["zed", "Zed", "zed-editor"],
+		windows_exe: "zed.exe",
+		mac_apps: &["Zed"],
+		clis: &["zed", "zeditor"],
+		mac_cli_dir: "",
+		launch: Launch::PathColonLine,
+	},
+	BuiltinEditor {
+		id: "sublime",
+		name: "Sublime Text",
+		frontmost: &["sublime text", "sublime_text"],
+		processes: &["sublime_text", "Sublime Text"],
+		windows_exe: "sublime_text.exe",
+		mac_apps: &["Sublime Text"],
+		clis: &["subl"],
+		mac_cli_dir: "Contents/SharedSupport/bin",
+		launch: Launch::PathColonLine,
+	},
+	BuiltinEditor {
+		id: "typora",
+		name: "Typora",
+		frontmost: &["typora"],
+		processes: &["Typora", "typora"],
+		windows_exe: "typora.exe",
+		mac_apps: &["Typora"],
+		clis: &["typora"],
+		mac_cli_dir: "",
+		launch: Launch::Plain,
+	},
+	BuiltinEditor {
+		id: "obsidian",
+		name: "Obsidian",
+		frontmost: &["obsidian"],
+		processes: &["Obsidian", "obsidian"],
+		windows_exe: "obsidian.exe",
+		mac_apps: &["Obsidian"],
+		clis: &[],
+		mac_cli_dir: "",
+		launch: Launch::ObsidianUri,
+	},
+	BuiltinEditor {
+		id: "vim",
+		name: "Vim（终端）",
+		frontmost: &[],
+		processes: &[],
+		windows_exe: "",
+		mac_apps: &[],
+		clis: &["vim", "nvim"],
+		mac_cli_dir: "",
+		launch: Launch::TerminalVim,
+	},
+];
+
+fn builtin_editor(id: &str) -> Option<&'static BuiltinEditor> {
+	BUILTIN_EDITORS.iter().find(|e| e.id == id)
+}
+
+// —— 设置 ——
+
+struct EditorSettings {
+	order: Vec<String>,
+	/// 用户是否设置过检测顺序；未设置时兜底为 Trae（原行为）
+	order_configured: bool,
+	custom_command: Option<String>,
+	custom_name: String,
+}
+
+/// store 中的字符串值；取原始 JSON，避免 `get_store_value` 去引号时破坏命令模板中的引号
+async fn store_text(app: &AppHandle, key: &str) -> Option<String> {
+	let text = match get_store_json(app, key).await.ok()? {
+		Value::String(s) => s,
+		Value::Null => return None,
+		other => other.to_string(),
+	};
+	let text = text.trim().to_string();
+	(!text.is_empty()).then_some(text)
+}
+
+/// `knowledgeEditorOrder`：id 数组或逗号分隔的字符串；未知 id 与重复项被忽略
+fn parse_order(value: &Value) -> Vec<String> {
+	let items: Vec<String> = match value {
+		Value::Array(items) => items
+			.iter()
+			.filter_map(Value::as_str)
+			.map(str::to_string)
+			.collect(),
+		Value::String(s) => s.split(',').map(str::to_string).collect(),
+		_ => Vec::new(),
+	};
+	let mut out: Vec<String> = Vec::new();
+	for id in items {
+		let id = id.trim().to_lowercase();
+		let known = id == CUSTOM_EDITOR_ID || builtin_editor(&id).is_some();
+		if known && !out.contains(&id) {
+			out.push(id);
+		}
+	}
+	out
+}
+
+async fn load_editor_settings(app: &AppHandle) -> EditorSettings {
+	let order = get_store_json(app, "knowledgeEditorOrder")
+		.await
+		.map(|v| parse_order(&v))
+		.unwrap_or_default();
+	let order_configured = !order.is_empty();
+	EditorSettings {
+		order: if order_configured {
+			order
+		} else {
+			DEFAULT_ORDER.iter().map(|s| s.to_string()).collect()
+		},
+		order_configured,
+		custom_command: store_text(app, "knowledgeEditorCustomCommand").await,
+		custom_name: store_text(app, "knowledgeEditorCustomName")
+			.await
+			.unwrap_or_else(|| "自定义命令".to_string()),
+	}
+}
+
+// —— 检测 ——
+
+#[cfg(target_os = "macos")]
+fn frontmost_application_name() -> Option<String> {
+	let script =
+		r#"tell application "System Events" to get name of first application process whose frontmost is true"#;
+	let output = Command::new("osascript").args(["-e", script]).output().ok()?;
+	if !output.status.success() {
+		return None;
+	}
+	let s = String::from_utf8(output.stdout).ok()?;
+	let t = s.trim();
+	if t.is_empty() {
+		None
+	} else {
+		Some(t.to_string())
+	}
+}
+
+/// Windows：前台窗口所属进程名（小写），失败则 None
+#[cfg(target_os = "windows")]
+fn frontmost_application_name() -> Option<String> {
+	let ps = concat!(
+		"$pid = [uint32]0; ",
+		"Add-Type -MemberDefinition '[DllImport(\"user32.dll\")]public static extern System.IntPtr GetForegroundWindow();",
+		"[DllImport(\"user32.dll\")]public static extern uint GetWindowThreadProcessId(System.IntPtr h,out uint p);' ",
+		"-Name U -Namespace W; ",
+		"[void][W.U]::GetWindowThreadProcessId([W.U]::GetForegroundWindow(),[ref]$pid); ",
+		"(Get-Process -Id $pid -ErrorAction SilentlyContinue).ProcessName",
+	);
+	let output = Command::new("powershell")
+		.args(["-NoProfile", "-STA", "-Command", ps])
+		.output()
+		.ok()?;
+	if !output.status.success() {
+		return None;
+	}
+	let s = String::from_utf8(output.stdout).ok()?;
+	let t = s.trim().to_lowercase();
+	if t.is_empty() {
+		None
+	} else {
+		Some(t)
+	}
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn frontmost_application_name() -> Option<String> {
+	None
+}
+
+/// 子进程退出码 0 视为成功（如 pgrep 找到进程）
+#[cfg(unix)]
+fn command_exit_zero(program: &str, args: &[&str]) -> bool {
+	Command::new(program)
+		.args(args)
+		.stdout(std::process::Stdio::null())
+		.stderr(std::process::Stdio::null())
+		.status()
+		.ok()
+		.is_some_and(|s| s.success())
+}
+
+/// macOS：用「系统事件」进程名 + `ps` 命令行（含 Cursor.app）+ `pgrep` 多重判定；Electron 常只有 Helper 进程带完整路径，`pgrep -x Cursor` 易漏检
+#[cfg(target_os = "macos")]
+fn is_cursor_running_applescript() -> bool {
+	let Some(out) = Command::new("/usr/bin/osascript")
+		.args([
+			"-e",
+			r#"tell application "System Events""#,
+			"-e",
+			r#"repeat with procName in (name of every process)"#,
+			"-e",
+			r#"set t to procName as string"#,
+			"-e",
+			r#"if t contains "Cursor" then return true"#,
+			"-e",
+			r#"end repeat"#,
+			"-e",
+			r#"end tell"#,
+			"-e",
+			r#"return false"#,
+		])
+		.output()
+		.ok()
+	else {
+		return false;
+	};
+	if !out.status.success() {
+		return false;
+	}
+	String::from_utf8_lossy(&out.stdout)
+		.trim()
+		.eq_ignore_ascii_case("true")
+}
+
+#[cfg(target_os = "macos")]
+fn is_cursor_running_ps() -> bool {
+	let Ok(output) = Command::new("/bin/ps")
+		.args(["-ax", "-o", "command="])
+		.output()
+	else {
+		return false;
+	};
+	String::from_utf8_lossy(&output.stdout).lines().any(|line| {
+		// 典型：.../Cursor.app/...；无路径时仍有 Electron Helper 进程名
+		line.contains("Cursor.app/")
+			|| line.contains("Cursor Helper")
+			|| line.contains("MacOS/Cursor")
+	})
+}
+
+#[cfg(target_os = "macos")]
+fn is_cursor_running() -> bool {
+	is_cursor_running_applescript()
+		|| is_cursor_running_ps()
+		|| command_exit_zero("/usr/bin/pgrep", &["-x", "Cursor"])
+		|| command_exit_zero("/usr/bin/pgrep", &["-f", "Cursor.app"])
+		|| command_exit_zero("/usr/bin/pgrep", &["-x", "cursor"])
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn is_cursor_running() -> bool {
+	command_exit_zero("pgrep", &["-x", "cursor"])
+		|| command_exit_zero("pgrep", &["-f", "/Cursor.app/"])
+		|| command_exit_zero("pgrep", &["-f", "cursor"])
+}
+
+#[cfg(target_os = "macos")]
+fn is_trae_running() -> bool {
+	for name in ["Trae", "Trae CN"] {
+		if command_exit_zero("/usr/bin/pgrep", &["-x", name]) {
+			return true;
+		}
+	}
+	command_exit_zero("/usr/bin/pgrep", &["-f", "Trae.app"])
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn is_trae_running() -> bool {
+	command_exit_zero("pgrep", &["-x", "trae"]) || command_exit_zero("pgrep", &["-f", "trae"])
+}
+
+#[cfg(target_os = "windows")]
+fn is_editor_running(editor: &BuiltinEditor) -> bool {
+	if editor.windows_exe.is_empty() {
+		return false;
+	}
+	let Ok(output) = Command::new("tasklist").args(["/FO", "CSV", "/NH"]).output() else {
+		return false;
+	};
+	String::from_utf8_lossy(&output.stdout)
+		.to_lowercase()
+		.contains(editor.windows_exe)
+}
+
+/// Cursor / Trae 沿用多重判定，其余按进程名 `pgrep -x`
+#[cfg(unix)]
+fn is_editor_running(editor: &BuiltinEditor) -> bool {
+	match editor.id {
+		"cursor" => is_cursor_running(),
+		"trae" => is_trae_running(),
+		_ => {
+			let pgrep = if cfg!(target_os = "macos") {
+				"/usr/bin/pgrep"
+			} else {
+				"pgrep"
+			};
+			editor
+				.processes
+				.iter()
+				.any(|name| command_exit_zero(pgrep, &["-x", name]))
+		}
+	}
+}
+
+#[cfg(target_os = "macos")]
+fn macos_app_bundle(name: &str) -> Option<PathBuf> {
+	let home = env::var("HOME").unwrap_or_default();
+	[
+		format!("/Applications/{name}.app"),
+		format!("{home}/Applications/{name}.app"),
+	]
+	.into_iter()
+	.map(PathBuf::from)
+	.find(|p| p.is_dir())
+}
+
+/// 在 PATH 中查找命令；Windows 依次补 `.exe` / `.cmd` / `.bat`
+fn find_in_path(name: &str) -> Option<PathBuf> {
+	let exts: &[&str] = if cfg!(target_os = "windows") {
+		&[".exe", ".cmd", ".bat"]
+	} else {
+		&[""]
+	};
+	let paths = env::var_os("PATH")?;
+	env::split_paths(&paths).find_map(|dir| {
+		exts.iter()
+			.map(|ext| dir.join(format!("{name}{ext}")))
+			.find(|p| p.is_file())
+	})
+}
+
+fn editor_cli(editor: &BuiltinEditor) -> Option<PathBuf> {
+	if let Some(p) = editor.clis.iter().find_map(|c| find_in_path(c)) {
+		return Some(p);
+	}
+	#[cfg(target_os = "macos")]
+	if !editor.mac_cli_dir.is_empty() {
+		for app in editor.mac_apps {
+			if let Some(bundle) = macos_app_bundle(app) {
+				for cli in editor.clis {
+					let p = bundle.join(editor.mac_cli_dir).join(cli);
+					if p.is_file() {
+						return Some(p);
+					}
+				}
+			}
+		}
+	}
+	None
+}
+
+fn is_editor_installed(editor: &BuiltinEditor) -> bool {
+	#[cfg(target_os = "macos")]
+	if editor.mac_apps.iter().any(|a| macos_app_bundle(a).is_some()) {
+		return true;
+	}
+	editor_cli(editor).is_some()
+}
+
+/// 按检测顺序：1) 前台应用 2) 运行中进程 3) 已安装（自定义命令已配置即视为可用）；都未命中时，设置过顺序则用第一项，否则为 Trae
+fn detect_editor(settings: &EditorSettings) -> String {
+	let builtins: Vec<&BuiltinEditor> = settings
+		.order
+		.iter()
+		.filter_map(|id| builtin_editor(id))
+		.collect();
+	if let Some(name) = frontmost_application_name() {
+		let lower = name.to_lowercase();
+		if let Some(e) = builtins.iter().find(|e| e.frontmost.contains(&lower.as_str())) {
+			return e.id.to_string();
+		}
+	}
+	if let Some(e) = builtins.iter().find(|e| is_editor_running(e)) {
+		return e.id.to_string();
+	}
+	let available = settings.order.iter().find(|id| match builtin_editor(id) {
+		Some(e) => is_editor_installed(e),
+		None => settings.custom_command.is_some(),
+	});
+	if let Some(id) = available {
+		return id.clone();
+	}
+	if settings.order_configured {
+		settings.order[0].clone()
+	} else {
+		FALLBACK_EDITOR_ID.to_string()
+	}
+}
+
+// —— 打开 ——
+
+fn spawn_detached(program: &Path, args: &[String]) -> Result<(), String> {
+	Command::new(program)
+		.args(args)
+		.spawn()
+		.map(|_| ())
+		.map_err(|e| format!("无法启动 {}: {e}", program.display()))
+}
+
+/// Cursor 稳定版常见 Bundle ID（Todesktop 分发）；若变更可再补 `open -a Cursor`
+#[cfg(target_os = "macos")]
+const CURSOR_MACOS_BUNDLE_ID: &str = "com.todesktop.230313mzl4w4u92";
+
+/// macOS：优先按 Bundle ID 打开，再 `open -a Cursor`，再尝试 PATH 中的 `cursor` CLI
+#[cfg(target_os = "macos")]
+fn spawn_open_cursor_macos(path: &Path) -> Result<(), String> {
+	let path_str = path.to_str().ok_or("路径包含无效字符")?;
+	if Command::new("/usr/bin/open")
+		.args(["-b", CURSOR_MACOS_BUNDLE_ID, "--", path_str])
+		.status()
+		.map(|s| s.success())
+		.unwrap_or(false)
+	{
+		return Ok(());
+	}
+	if spawn_open_editor("Cursor", path).is_ok() {
+		return Ok(());
+	}
+	Command::new("cursor")
+		.arg(path_str)
+		.spawn()
+		.map_err(|e| format!("无法用 Cursor 打开文件: {e}"))?;
+	Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn spawn_open_editor(app_bundle_name: &str, path: &Path) -> Result<(), String> {
+	let path_str = path.to_str().ok_or("路径包含无效字符")?;
+	let status = Command::new("/usr/bin/open")
+		.args(["-a", app_bundle_name, "--", path_str])
+		.status()
+		.map_err(|e| format!("无法启动 {app_bundle_name}: {e}"))?;
+	if status.success() {
+		Ok(())
+	} else {
+		Err(format!("open -a {app_bundle_name} 退出码非 0"))
+	}
+}
+
+#[cfg(not(target_os = "macos"))]
+fn spawn_open_editor(cli_name: &str, path: &Path) -> Result<(), String> {
+	let path_str = path.to_str().ok_or("路径包含无效字符")?;
+	Command::new(cli_name)
+		.arg(path_str)
+		.spawn()
+		.map_err(|e| format!("无法启动 {cli_name}: {e}"))?;
+	Ok(())
+}
+
+/// 不定位行号地打开：macOS 走 .app（Cursor 用 Bundle ID，Trae 兼容 Trae CN），其余平台走 CLI
+fn open_without_line(editor: &BuiltinEditor, path: &Path) -> Result<(), String> {
+	#[cfg(target_os = "macos")]
+	{
+		if editor.id == "cursor" {
+			return spawn_open_cursor_macos(path);
+		}
+		let mut last_err = None;
+		for app in editor.mac_apps {
+			match spawn_open_editor(app, path) {
+				Ok(()) => return Ok(()),
+				Err(e) => last_err = Some(e),
+			}
+		}
+		if let Some(cli) = editor_cli(editor) {
+			return spawn_detached(&cli, &[path.to_string_lossy().to_string()]);
+		}
+		Err(last_err.unwrap_or_else(|| format!("未找到 {}", editor.name)))
+	}
+	#[cfg(not(target_os = "macos"))]
+	{
+		match editor_cli(editor) {
+			Some(cli) => spawn_detached(&cli, &[path.to_string_lossy().to_string()]),
+			None => {
+				let cli = editor.clis.first().ok_or_else(|| format!("未找到 {}", editor.name))?;
+				spawn_open_editor(cli, path)
+			}
+		}
+	}
+}
+
+/// 用系统 URL 处理程序打开 `scheme://` 链接
+fn open_uri(uri: &str) -> Result<(), String> {
+	#[cfg(target_os = "macos")]
+	let mut cmd = {
+		let mut c = Command::new("/usr/bin/open");
+		c.arg(uri);
+		c
+	};
+	#[cfg(target_os = "windows")]
+	let mut cmd = {
+		let mut c = Command::new("rundll32");
+		c.args(["url.dll,FileProtocolHandler", uri]);
+		c
+	};
+	#[cfg(all(unix, not(target_os = "macos")))]
+	let mut cmd = {
+		let mut c = Command::new("xdg-open");
+		c.arg(uri);
+		c
+	};
+	cmd.spawn().map(|_| ()).map_err(|e| format!("无法打开链接 {uri}: {e}"))
+}
+
+#[cfg(target_os = "macos")]
+fn shell_quote(s: &str) -> String {
+	format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// 在新终端窗口中运行 vim（macOS 用 Terminal.app，Windows 新开控制台，Linux 依次尝试 `$TERMINAL` 与常见终端）
+fn open_in_terminal_vim(editor: &BuiltinEditor, path: &Path, line: Option<u32>) -> Result<(), String> {
+	let vim = editor_cli(editor)
+		.map(|p| p.to_string_lossy().to_string())
+		.unwrap_or_else(|| "vim".to_string());
+	let mut args = Vec::new();
+	if let Some(line) = line {
+		args.push(format!("+{line}"));
+	}
+	args.push(path.to_string_lossy().to_string());
+
+	#[cfg(target_os = "macos")]
+	{
+		let shell_cmd = std::iter::once(vim.as_str())
+			.chain(args.iter().map(String::as_str))
+			.map(shell_quote)
+			.collect::<Vec<_>>()
+			.join(" ");
+		let escaped = shell_cmd.replace('\\', "\\\\").replace('"', "\\\"");
+		let status = Command::new("/usr/bin/osascript")
+			.args([
+				"-e",
+				&format!(r#"tell application "Terminal" to do script "{escaped}""#),
+				"-e",
+				r#"tell application "Terminal" to activate"#,
+			])
+			.status()
+			.map_err(|e| format!("无法启动终端: {e}"))?;
+		if status.success() {
+			Ok(())
+		} else {
+			Err("无法在 Terminal 中运行 vim".to_string())
+		}
+	}
+	#[cfg(target_os = "windows")]
+	{
+		// 直接启动 vim 并为其新建控制台；经 `cmd /C start` 转发时路径中的 `&`、`^` 会被 cmd 重新解析
+		use std::os::windows::process::CommandExt;
+		const CREATE_NEW_CONSOLE: u32 = 0x0000_0010;
+		Command::new(&vim)
+			.args(&args)
+			.creation_flags(CREATE_NEW_CONSOLE)
+			.spawn()
+			.map(|_| ())
+			.map_err(|e| format!("无法启动 {vim}: {e}"))
+	}
+	#[cfg(all(unix, not(target_os = "macos")))]
+	{
+		let mut terminals: Vec<(String, &str)> = Vec::new();
+		if let Ok(t) = env::var("TERMINAL")
+			&& !t.trim().is_empty()
+		{
+			terminals.push((t.trim().to_string(), "-e"));
+		}
+		for (t, flag) in [
+			("x-terminal-emulator", "-e"),
+			("gnome-terminal", "--"),
+			("konsole", "-e"),
+			("xterm", "-e"),
+		] {
+			terminals.push((t.to_string(), flag));
+		}
+		for (term, flag) in terminals {
+			let Some(program) = find_in_path(&term) else {
+				continue;
+			};
+			let mut all = vec![flag.to_string(), vim.clone()];
+			all.extend(args.iter().cloned());
+			if spawn_detached(&program, &all).is_ok() {
+				return Ok(());
+			}
+		}
+		Err("未找到可用的终端程序，可设置 TERMINAL 环境变量".to_string())
+	}
+}
+
+/// 按空白切分命令模板，支持单 / 双引号包裹含空格的参数
+fn split_command_template(template: &str) -> Vec<String> {
+	let mut args = Vec::new();
+	let mut cur = String::new();
+	let mut quote: Option<char> = None;
+	let mut in_arg = false;
+	for c in template.chars() {
+		match quote {
+			Some(q) if c == q => quote = None,
+			Some(_) => cur.push(c),
+			None if c == '"' || c == '\'' => {
+				quote = Some(c);
+				in_arg = true;
+			}
+			None if c.is_whitespace() => {
+				if in_arg {
+					args.push(std::mem::take(&mut cur));
+					in_arg = false;
+				}
+			}
+			None => {
+				cur.push(c);
+				in_arg = true;
+			}
+		}
+	}
+	if in_arg {
+		args.push(cur);
+	}
+	args
+}
+
+/// 自定义命令：逐个参数替换 `{path}` / `{line}`（未传行号时为 1）；模板中没有 `{path}` 时把路径追加为最后一个参数
+fn open_with_custom_command(template: &str, path: &Path, line: Option<u32>) -> Result<(), String> {
+	let path_str = path.to_string_lossy();
+	let line_str = line.unwrap_or(1).to_string();
+	let mut args: Vec<String> = split_command_template(template)
+		.into_iter()
+		.map(|a| a.replace("{path}", &path_str).replace("{line}", &line_str))
+		.collect();
+	if !template.contains("{path}") {
+		args.push(path_str.to_string());
+	}
+	if args.is_empty() {
+		return Err("自定义编辑器命令为空".to_string());
+	}
+	let program = args.remove(0);
+	let program = find_in_path(&program).unwrap_or_else(|| PathBuf::from(&program));
+	spawn_detached(&program, &args)
+}
+
+fn open_with_editor(
+	settings: &EditorSettings,
+	id: &str,
+	path: &Path,
+	line: Option<u32>,
+) -> Result<String, String> {
+	if id == CUSTOM_EDITOR_ID {
+		let template = settings
+			.custom_command
+			.as_deref()
+			.ok_or("未设置自定义编辑器命令（knowledgeEditorCustomCommand）")?;
+		open_with_custom_command(template, path, line)?;
+		return Ok(settings.custom_name.clone());
+	}
+	let editor = builtin_editor(id).ok_or_else(|| format!("未知编辑器：{id}"))?;
+	let path_str = path.to_string_lossy().to_string();
+	match (editor.launch, line) {
+		// 定位到行需要 CLI；找不到或启动失败时退化为仅打开文件
+		(Launch::Goto, Some(line)) => match editor_cli(editor) {
+			Some(cli) if spawn_detached(&cli, &["-g".to_string(), format!("{path_str}:{line}")]).is_ok() => {}
+			_ => open_without_line(editor, path)?,
+		},
+		(Launch::PathColonLine, Some(line)) => match editor_cli(editor) {
+			Some(cli) if spawn_detached(&cli, &[format!("{path_str}:{line}")]).is_ok() => {}
+			_ => open_without_line(editor, path)?,
+		},
+		(Launch::ObsidianUri, _) => {
+			open_uri(&format!("obsidian://open?path={}", encode_segment(&path_str)))?;
+		}
+		(Launch::TerminalVim, _) => open_in_terminal_vim(editor, path, line)?,
+		_ => open_without_line(editor, path)?,
+	}
+	Ok(editor.name.to_string())
+}
+
+// —— 命令 ——
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeEditorInfo {
+	pub id: String,
+	pub name: String,
+	/// 是否支持打开并定位到行
+	pub supports_line: bool,
+	/// 已安装（自定义命令为已配置）
+	pub installed: bool,
+	pub custom: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeEditorList {
+	/// 当前生效的检测顺序
+	pub order: Vec<String>,
+	pub order_configured: bool,
+	/// 不指定编辑器时将使用的编辑器 id
+	pub detected: String,
+	pub editors: Vec<KnowledgeEditorInfo>,
+}
+
+/// 列出可用编辑器（内置 + 自定义）、检测顺序及当前会选中的编辑器，供设置页与「打开方式」菜单使用
+#[tauri::command]
+pub async fn list_knowledge_editors(app: AppHandle) -> Result<KnowledgeEditorList, String> {
+	let settings = load_editor_settings(&app).await;
+	tauri::async_runtime::spawn_blocking(move || {
+		let mut editors: Vec<KnowledgeEditorInfo> = BUILTIN_EDITORS
+			.iter()
+			.map(|e| KnowledgeEditorInfo {
+				id: e.id.to_string(),
+				name: e.name.to_string(),
+				supports_line: matches!(
+					e.launch,
+					Launch::Goto | Launch::PathColonLine | Launch::TerminalVim
+				),
+				installed: is_editor_installed(e),
+				custom: false,
+			})
+			.collect();
+		editors.push(KnowledgeEditorInfo {
+			id: CUSTOM_EDITOR_ID.to_string(),
+			name: settings.custom_name.clone(),
+			supports_line: settings
+				.custom_command
+				.as_deref()
+				.is_some_and(|c| c.contains("{line}")),
+			installed: settings.custom_command.is_some(),
+			custom: true,
+		});
+		KnowledgeEditorList {
+			detected: detect_editor(&settings),
+			order: settings.order,
+			order_configured: settings.order_configured,
+			editors,
+		}
+	})
+	.await
+	.map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenKnowledgeMarkdownInEditorInput {
+	pub file_path: String,
+	/// 打开后定位到的行（从 1 开始）；编辑器不支持时忽略
+	#[serde(default)]
+	pub line: Option<u32>,
+	/// 指定编辑器 id（见 `list_knowledge_editors`），不传时按检测顺序自动选择
+	#[serde(default)]
+	pub editor_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenKnowledgeMarkdownInEditorResult {
+	/// 实际用于打开的编辑器显示名，如 `Cursor` / `Trae`
+	pub opened_with: String,
+	pub editor_id: String,
+}
+
+/// 在外部编辑器中打开本地 `.md`：指定 `editorId` 时直接使用，否则按检测顺序（前台 → 运行中 → 已安装）选择；未设置顺序时与原先一致，在 Cursor / Trae 间选择
+#[tauri::command]
+pub async fn open_knowledge_markdown_in_editor(
+	app: AppHandle,
+	input: OpenKnowledgeMarkdownInEditorInput,
+) -> Result<OpenKnowledgeMarkdownInEditorResult, String> {
+	let trimmed = input.file_path.trim();
+	if trimmed.is_empty() {
+		return Err("filePath 不能为空".to_string());
+	}
+	let p = PathBuf::from(trimmed);
+	if !p.exists() || !p.is_file() {
+		return Err("文件不存在或不是普通文件".to_string());
+	}
+	if !is_md_file_path(&p) {
+		return Err("仅允许打开 .md 文件".to_string());
+	}
+	let settings = load_editor_settings(&app).await;
+	let requested = input
+		.editor_id
+		.map(|s| s.trim().to_lowercase())
+		.filter(|s| !s.is_empty());
+	let line = input.line.filter(|l| *l > 0);
+	tauri::async_runtime::spawn_blocking(move || {
+		let id = requested.unwrap_or_else(|| detect_editor(&settings));
+		let opened_with = open_with_editor(&settings, &id, &p, line)?;
+		Ok(OpenKnowledgeMarkdownInEditorResult {
+			opened_with,
+			editor_id: id,
+		})
+	})
+	.await
+	.map_err(|e| e.to_string())?
+}
@@ -0,0 +1,918 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::Engine as _;
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, LinkType, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use syntect::highlighting::ThemeSet;
+use syntect::html::highlighted_html_for_string;
+use syntect::parsing::SyntaxSet;
+use tauri::AppHandle;
+
+use crate::command::clipboard::mime_of;
+use crate::command::knowledge::{collect_md_files, is_md_file_path};
+use crate::command::knowledge_export_docx::render_docx;
+use crate::command::knowledge_assets::relative_link;
+use crate::command::knowledge_link::{is_external_href, normalize_components, split_front_matter};
+use crate::command::knowledge_lint::heading_slug;
+use crate::command::knowledge_vault::read_note_text;
+use crate::utils::common::{default_save_base_dir, get_store_value};
+use crate::utils::percent::{encode_link_path, percent_decode};
+
+// —— 知识导出：单篇或整个文件夹渲染为独立 HTML（内嵌本地图片），再由 HTML 打印 PDF，或直接生成 DOCX ——
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KnowledgeExportFormat {
+	Html,
+	Pdf,
+	Docx,
+}
+
+impl KnowledgeExportFormat {
+	fn ext(self) -> &'static str {
+		match self {
+			KnowledgeExportFormat::Html => "html",
+			KnowledgeExportFormat::Pdf => "pdf",
+			KnowledgeExportFormat::Docx => "docx",
+		}
+	}
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KnowledgeExportTheme {
+	#[default]
+	Light,
+	Dark,
+	Sepia,
+}
+
+/// DOCX 使用的主题参数（颜色为不带 `#` 的十六进制）
+pub(crate) struct DocxTheme {
+	pub font: &'static str,
+	pub text_color: &'static str,
+	pub heading_color: &'static str,
+	pub link_color: &'static str,
+	pub code_bg: &'static str,
+	/// 页面背景色；None 为默认白色
+	pub page_bg: Option<&'static str>,
+}
+
+impl KnowledgeExportTheme {
+	pub(crate) fn syntect_theme(self) -> &'static str {
+		match self {
+			KnowledgeExportTheme::Light => "InspiredGitHub",
+			KnowledgeExportTheme::Dark => "base16-ocean.dark",
+			KnowledgeExportTheme::Sepia => "Solarized (light)",
+		}
+	}
+
+	pub(crate) fn css_vars(self) -> &'static str {
+		match self {
+			KnowledgeExportTheme::Light => {
+				"--bg:#ffffff;--fg:#1f2328;--muted:#59636e;--border:#d1d9e0;--link:#0969da;--code-bg:#f6f8fa;--quote:#59636e;"
+			}
+			KnowledgeExportTheme::Dark => {
+				"--bg:#0d1117;--fg:#e6edf3;--muted:#9198a1;--border:#3d444d;--link:#4493f8;--code-bg:#151b23;--quote:#9198a1;"
+			}
+			KnowledgeExportTheme::Sepia => {
+				"--bg:#fbf6ec;--fg:#433422;--muted:#7a6a53;--border:#e0d4bd;--link:#8a4b08;--code-bg:#f3ead8;--quote:#7a6a53;"
+			}
+		}
+	}
+
+	pub(crate) fn docx(self) -> DocxTheme {
+		match self {
+			KnowledgeExportTheme::Light => DocxTheme {
+				font: "Microsoft YaHei",
+				text_color: "1F2328",
+				heading_color: "1F2328",
+				link_color: "0969DA",
+				code_bg: "F6F8FA",
+				page_bg: None,
+			},
+			KnowledgeExportTheme::Dark => DocxTheme {
+				font: "Microsoft YaHei",
+				text_color: "E6EDF3",
+				heading_color: "FFFFFF",
+				link_color: "4493F8",
+				code_bg: "151B23",
+				page_bg: Some("0D1117"),
+			},
+			KnowledgeExportTheme::Sepia => DocxTheme {
+				font: "SimSun",
+				text_color: "433422",
+				heading_color: "5B4120",
+				link_color: "8A4B08",
+				code_bg: "F3EAD8",
+				page_bg: Some("FBF6EC"),
+			},
+		}
+	}
+}
+
+pub(crate) const BASE_CSS: &str = r#"
+*{box-sizing:border-box}
+html{-webkit-print-color-adjust:exact;print-color-adjust:exact}
+body{margin:0;background:var(--bg);color:var(--fg);font:16px/1.7 -apple-system,BlinkMacSystemFont,"Segoe UI","PingFang SC","Microsoft YaHei",sans-serif}
+.markdown-body{max-width:860px;margin:0 auto;padding:40px 32px}
+h1,h2,h3,h4,h5,h6{margin:1.4em 0 .6em;line-height:1.3}
+h1,h2{padding-bottom:.3em;border-bottom:1px solid var(--border)}
+a{color:var(--link);text-decoration:none}
+img{max-width:100%}
+blockquote{margin:1em 0;padding:0 1em;color:var(--quote);border-left:4px solid var(--border)}
+code{font-family:ui-monospace,SFMono-Regular,Menlo,Consolas,monospace;font-size:.9em;background:var(--code-bg);padding:.15em .4em;border-radius:4px}
+pre{padding:14px 16px;overflow:auto;border-radius:6px;background:var(--code-bg);line-height:1.5}
+pre code{padding:0;background:none}
+table{border-collapse:collapse;margin:1em 0;display:block;overflow:auto}
+th,td{border:1px solid var(--border);padding:6px 13px}
+th{font-weight:600}
+hr{border:0;border-top:1px solid var(--border);margin:2em 0}
+li:has(>input[type=checkbox]){list-style:none}
+li>input[type=checkbox]{margin:0 .4em 0 -1.4em}
+.math-display{display:block;overflow:auto;margin:1em 0;text-align:center}
+.footnote-definition{font-size:.9em;color:var(--muted)}
+.export-index li{margin:.3em 0}
+@media print{.markdown-body{max-width:none;padding:0}pre{white-space:pre-wrap}}
+"#;
+
+/// KaTeX 0.16.4（随应用打包）；单篇导出内联到页面，站点只写一份 `katex.min.js` 供各页引用
+pub(crate) const KATEX_JS: &str = include_str!("../../vendor/katex/katex.min.js");
+/// 页面加载后把 `\(...\)` / `\[...\]` 渲染为 MathML，无需联网与字体文件
+pub(crate) const KATEX_RENDER: &str = r#"<script>document.addEventListener('DOMContentLoaded',function(){document.querySelectorAll('.math').forEach(function(e){katex.render(e.textContent.slice(2,-2),e,{displayMode:e.classList.contains('math-display'),output:'mathml',throwOnError:false})})})</script>"#;
+
+fn syntax_set() -> &'static SyntaxSet {
+	static SET: OnceLock<SyntaxSet> = OnceLock::new();
+	SET.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn theme_set() -> &'static ThemeSet {
+	static SET: OnceLock<ThemeSet> = OnceLock::new();
+	SET.get_or_init(ThemeSet::load_defaults)
+}
+
+pub(crate) fn escape_html(s: &str) -> String {
+	let mut out = String::with_capacity(s.len());
+	for c in s.chars() {
+		match c {
+			'&' => out.push_str("&amp;"),
+			'<' => out.push_str("&lt;"),
+			'>' => out.push_str("&gt;"),
+			'"' => out.push_str("&quot;"),
+			'\'' => out.push_str("&#39;"),
+			_ => out.push(c),
+		}
+	}
+	out
+}
+
+pub(crate) fn markdown_options() -> Options {
+	Options::ENABLE_TABLES
+		| Options::ENABLE_FOOTNOTES
+		| Options::ENABLE_STRIKETHROUGH
+		| Options::ENABLE_TASKLISTS
+		| Options::ENABLE_MATH
+		| Options::ENABLE_GFM
+}
+
+/// 去掉 front matter 后的正文
+pub(crate) fn note_body(content: &str) -> &str {
+	match split_front_matter(content) {
+		Some((end, _)) => &content[end..],
+		None => content,
+	}
+}
+
+/// 本地图片引用解析为文件路径；远程地址、data URL 返回 None
+pub(crate) fn resolve_local_image(note_dir: &Path, src: &str) -> Option<PathBuf> {
+	let src = src.trim();
+	let lower = src.to_ascii_lowercase();
+	if lower.starts_with("http://")
+		|| lower.starts_with("https://")
+		|| lower.starts_with("//")
+		|| lower.starts_with("data:")
+		|| src.is_empty()
+	{
+		return None;
+	}
+	let raw = src.strip_prefix("file://").unwrap_or(src);
+	let decoded = percent_decode(raw.split(['?', '#']).next().unwrap_or(raw));
+	let p = Path::new(&decoded);
+	let full = if p.is_absolute() {
+		p.to_path_buf()
+	} else {
+		note_dir.join(p)
+	};
+	full.is_file().then_some(full)
+}
+
+fn image_data_url(path: &Path) -> Option<String> {
+	let bytes = fs::read(path).ok()?;
+	let b64 = base64::engine::general_purpose::STANDARD.encode(&bytes);
+	Some(format!("data:{};base64,{}", mime_of(path), b64))
+}
+
+/// 渲染时改写图片与链接地址；返回 None 时保持原样
+pub(crate) trait RenderHooks {
+	/// `wiki` 为 `![[图片]]` 形式
+	fn image(&mut self, src: &str, wiki: bool) -> Option<String>;
+
+	/// `wiki` 为 `[[笔记]]` 形式（需在解析选项中启用 wiki 链接）
+	fn link(&mut self, _href: &str, _wiki: bool) -> Option<String> {
+		None
+	}
+}
+
+/// 导出单篇：本地图片内嵌为 data URL
+struct EmbedImages<'a>(&'a Path);
+
+impl RenderHooks for EmbedImages<'_> {
+	fn image(&mut self, src: &str, _wiki: bool) -> Option<String> {
+		resolve_local_image(self.0, src).and_then(|p| image_data_url(&p))
+	}
+}
+
+/// 文件夹导出为 HTML：本地图片内嵌，指向同批导出笔记的链接改写为对应页面
+struct FolderPages<'a> {
+	note_dir: &'a Path,
+	page_dir: &'a Path,
+	/// 笔记路径 → 输出页面路径
+	pages: &'a HashMap<PathBuf, PathBuf>,
+}
+
+impl RenderHooks for FolderPages<'_> {
+	fn image(&mut self, src: &str, wiki: bool) -> Option<String> {
+		EmbedImages(self.note_dir).image(src, wiki)
+	}
+
+	fn link(&mut self, href: &str, _wiki: bool) -> Option<String> {
+		let href = href.trim();
+		if is_external_href(href) {
+			return None;
+		}
+		let (path, anchor) = match href.split_once('#') {
+			Some((p, a)) => (p, Some(a)),
+			None => (href, None),
+		};
+		let target = percent_decode(path.split('?').next().unwrap_or(path));
+		if target.is_empty() {
+			return None;
+		}
+		let page = self.pages.get(&normalize_components(&self.note_dir.join(&target)))?;
+		let mut out = encode_link_path(&relative_link(self.page_dir, page));
+		if let Some(anchor) = anchor {
+			out.push('#');
+			out.push_str(anchor);
+		}
+		Some(out)
+	}
+}
+
+/// HTML 片段中 `<img src="...">` 按钩子改写
+fn rewrite_html_images(html: &str, hooks: &mut dyn RenderHooks) -> String {
+	let lower = html.to_ascii_lowercase();
+	if !lower.contains("<img") {
+		return html.to_string();
+	}
+	let mut out = String::with_capacity(html.len());
+	let mut cursor = 0usize;
+	let mut search = 0usize;
+	while let Some(i) = lower[search..].find("<img") {
+		let tag_start = search + i;
+		let Some(tag_len) = html[tag_start..].find('>') else {
+			break;
+		};
+		let tag_end = tag_start + tag_len;
+		search = tag_end;
+		let tag_lower = &lower[tag_start..tag_end];
+		let Some(src_at) = tag_lower.find(" src=").map(|j| tag_start + j + 5) else {
+			continue;
+		};
+		let quote = html[src_at..].chars().next();
+		let (value_start, value_end) = match quote {
+			Some(q @ ('"' | '\'')) => match html[src_at + 1..tag_end].find(q) {
+				Some(len) => (src_at + 1, src_at + 1 + len),
+				None => continue,
+			},
+			_ => continue,
+		};
+		let Some(url) = hooks.image(&html[value_start..value_end], false) else {
+			continue;
+		};
+		out.push_str(&html[cursor..value_start]);
+		out.push_str(&escape_html(&url));
+		cursor = value_end;
+	}
+	out.push_str(&html[cursor..]);
+	out
+}
+
+fn highlight_code(code: &str, lang: &str, theme: KnowledgeExportTheme) -> String {
+	let ss = syntax_set();
+	let syntax = ss
+		.find_syntax_by_token(lang.trim())
+		.unwrap_or_else(|| ss.find_syntax_plain_text());
+	let themes = theme_set();
+	match themes.themes.get(theme.syntect_theme()) {
+		Some(t) => highlighted_html_for_string(code, ss, syntax, t)
+			.unwrap_or_else(|_| format!("<pre><code>{}</code></pre>", escape_html(code))),
+		None => format!("<pre><code>{}</code></pre>", escape_html(code)),
+	}
+}
+
+/// 渲染正文为 HTML 片段（本地图片内嵌），返回（HTML, 是否含公式）
+pub(crate) fn render_note_html(
+	content: &str,
+	note_dir: &Path,
+	theme: KnowledgeExportTheme,
+) -> (String, bool) {
+	render_note_html_with(content, theme, markdown_options(), &mut EmbedImages(note_dir))
+}
+
+/// 按给定解析选项渲染正文，图片与链接地址交由 `hooks` 改写；标题带 GitHub 风格锚点 id，重名依次追加 `-1`、`-2`
+pub(crate) fn render_note_html_with(
+	content: &str,
+	theme: KnowledgeExportTheme,
+	options: Options,
+	hooks: &mut dyn RenderHooks,
+) -> (String, bool) {
+	let parser = Parser::new_ext(note_body(content), options);
+	let mut events: Vec<Event> = Vec::new();
+	let mut code: Option<(String, String)> = None;
+	// (标题起始事件下标, 标题文本)
+	let mut heading: Option<(usize, String)> = None;
+	let mut slugs: HashMap<String, usize> = HashMap::new();
+	let mut has_math = false;
+	for event in parser {
+		if let Some((lang, buf)) = code.as_mut() {
+			match event {
+				Event::Text(t) => buf.push_str(&t),
+				Event::End(TagEnd::CodeBlock) => {
+					let html = highlight_code(buf, lang, theme);
+					events.push(Event::Html(CowStr::from(html)));
+					code = None;
+				}
+				_ => {}
+			}
+			continue;
+		}
+		if let Some((_, text)) = heading.as_mut()
+			&& let Event::Text(t) | Event::Code(t) = &event
+		{
+			text.push_str(t);
+		}
+		match event {
+			Event::Start(Tag::CodeBlock(kind)) => {
+				let lang = match kind {
+					CodeBlockKind::Fenced(info) => {
+						info.split_whitespace().next().unwrap_or_default().to_string()
+					}
+					CodeBlockKind::Indented => String::new(),
+				};
+				code = Some((lang, String::new()));
+			}
+			Event::Start(Tag::Heading { .. }) => {
+				heading = Some((events.len(), String::new()));
+				events.push(event);
+			}
+			Event::End(TagEnd::Heading(_)) => {
+				if let Some((at, text)) = heading.take()
+					&& let Event::Start(Tag::Heading { id, .. }) = &mut events[at]
+					&& id.is_none()
+				{
+					let base = heading_slug(&text);
+					let n = slugs.entry(base.clone()).or_insert(0);
+					let slug = if *n == 0 { base } else { format!("{}-{}", base, n) };
+					*n += 1;
+					*id = Some(CowStr::from(slug));
+				}
+				events.push(event);
+			}
+			Event::Start(Tag::Image {
+				link_type,
+				dest_url,
+				title,
+				id,
+			}) => {
+				let wiki = matches!(link_type, LinkType::WikiLink { .. });
+				let dest_url = match hooks.image(&dest_url, wiki) {
+					Some(url) => CowStr::from(url),
+					None => dest_url,
+				};
+				events.push(Event::Start(Tag::Image {
+					link_type,
+					dest_url,
+					title,
+					id,
+				}));
+			}
+			Event::Start(Tag::Link {
+				link_type,
+				dest_url,
+				title,
+				id,
+			}) => {
+				let wiki = matches!(link_type, LinkType::WikiLink { .. });
+				let dest_url = match hooks.link(&dest_url, wiki) {
+					Some(url) => CowStr::from(url),
+					None => dest_url,
+				};
+				events.push(Event::Start(Tag::Link {
+					link_type,
+					dest_url,
+					title,
+					id,
+				}));
+			}
+			Event::Html(h) => events.push(Event::Html(CowStr::from(rewrite_html_images(&h, hooks)))),
+			Event::InlineHtml(h) => {
+				events.push(Event::InlineHtml(CowStr::from(rewrite_html_images(&h, hooks))))
+			}
+			Event::InlineMath(m) => {
+				has_math = true;
+				events.push(Event::InlineHtml(CowStr::from(format!(
+					"<span class=\"math math-inline\">\\({}\\)</span>",
+					escape_html(&m)
+				))));
+			}
+			Event::DisplayMath(m) => {
+				has_math = true;
+				// 行间公式位于段落内，用块级 span 避免生成 `<p><div>` 嵌套
+				events.push(Event::InlineHtml(CowStr::from(format!(
+					"<span class=\"math math-display\">\\[{}\\]</span>",
+					escape_html(&m)
+				))));
+			}
+			e => events.push(e),
+		}
+	}
+	let mut html = String::new();
+	pulldown_cmark::html::push_html(&mut html, events.into_iter());
+	(html, has_math)
+}
+
+/// 组装为可独立打开的完整 HTML 文档
+pub(crate) fn wrap_html_document(
+	title: &str,
+	body: &str,
+	has_math: bool,
+	theme: KnowledgeExportTheme,
+) -> String {
+	let katex = if has_math {
+		format!("<script>{}</script>\n{}", KATEX_JS, KATEX_RENDER)
+	} else {
+		String::new()
+	};
+	format!(
+		"<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width,initial-scale=1\">\n<title>{}</title>\n<style>:root{{{}}}{}</style>\n{}\n</head>\n<body>\n<article class=\"markdown-body\">\n{}</article>\n</body>\n</html>\n",
+		escape_html(title),
+		theme.css_vars(),
+		BASE_CSS,
+		katex,
+		body
+	)
+}
+
+fn note_title(p: &Path) -> String {
+	p.file_stem()
+		.and_then(|s| s.to_str())
+		.unwrap_or("未命名")
+		.to_string()
+}
+
+fn export_html_string(note: &Path, theme: KnowledgeExportTheme) -> Result<String, String> {
+	let content = read_note_text(note)?;
+	let note_dir = note.parent().unwrap_or(Path::new("."));
+	let (body, has_math) = render_note_html(&content, note_dir, theme);
+	Ok(wrap_html_document(&note_title(note), &body, has_math, theme))
+}
+
+fn folder_page_html(
+	note: &Path,
+	page: &Path,
+	pages: &HashMap<PathBuf, PathBuf>,
+	theme: KnowledgeExportTheme,
+) -> Result<String, String> {
+	let content = read_note_text(note)?;
+	let mut hooks = FolderPages {
+		note_dir: note.parent().unwrap_or(Path::new(".")),
+		page_dir: page.parent().unwrap_or(Path::new(".")),
+		pages,
+	};
+	let (body, has_math) = render_note_html_with(&content, theme, markdown_options(), &mut hooks);
+	Ok(wrap_html_document(&note_title(note), &body, has_math, theme))
+}
+
+// —— PDF：调用本机 Chrome / Edge / Chromium 的无头打印 ——
+
+fn chrome_candidates() -> Vec<PathBuf> {
+	let mut out: Vec<PathBuf> = Vec::new();
+	#[cfg(target_os = "macos")]
+	{
+		for app in [
+			"Google Chrome",
+			"Microsoft Edge",
+			"Chromium",
+			"Brave Browser",
+		] {
+			out.push(PathBuf::from(format!("/Applications/{app}.app/Contents/MacOS/{app}")));
+		}
+	}
+	#[cfg(target_os = "windows")]
+	{
+		let roots = ["ProgramFiles", "ProgramFiles(x86)", "LocalAppData"]
+			.iter()
+			.filter_map(|k| std::env::var(k).ok())
+			.collect::<Vec<_>>();
+		for root in &roots {
+			out.push(Path::new(root).join(r"Google\Chrome\Application\chrome.exe"));
+			out.push(Path::new(root).join(r"Microsoft\Edge\Application\msedge.exe"));
+			out.push(Path::new(root).join(r"Chromium\Application\chrome.exe"));
+		}
+	}
+	#[cfg(target_os = "linux")]
+	{
+		for name in [
+			"google-chrome",
+			"google-chrome-stable",
+			"chromium",
+			"chromium-browser",
+			"microsoft-edge",
+			"microsoft-edge-stable",
+		] {
+			if let Ok(o) = Command::new("which").arg(name).output()
+				&& o.status.success()
+			{
+				let p = String::from_utf8_lossy(&o.stdout).trim().to_string();
+				if !p.is_empty() {
+					out.push(PathBuf::from(p));
+				}
+			}
+		}
+	}
+	out
+}
+
+/// 设置中 `knowledgeExportBrowserPath` 优先，否则自动探测
+async fn find_pdf_browser(app: &AppHandle) -> Result<PathBuf, String> {
+	if let Ok(v) = get_store_value(app, "knowledgeExportBrowserPath").await {
+		let p = PathBuf::from(v.trim());
+		if !v.trim().is_empty() && p.is_file() {
+			return Ok(p);
+		}
+	}
+	chrome_candidates()
+		.into_iter()
+		.find(|p| p.is_file())
+		.ok_or_else(|| "未找到 Chrome / Edge / Chromium，无法导出 PDF；可在设置中指定浏览器路径".to_string())
+}
+
+fn file_url(p: &Path) -> String {
+	let s = p.to_string_lossy().replace('\\', "/");
+	let mut out = String::from(if s.starts_with('/') { "file://" } else { "file:///" });
+	for c in s.chars() {
+		match c {
+			' ' => out.push_str("%20"),
+			'#' => out.push_str("%23"),
+			'%' => out.push_str("%25"),
+			'?' => out.push_str("%3F"),
+			_ => out.push(c),
+		}
+	}
+	out
+}
+
+fn print_pdf(browser: &Path, html: &str, out: &Path) -> Result<(), String> {
+	let ms = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_millis())
+		.unwrap_or(0);
+	let tmp = std::env::temp_dir().join(format!("dnhyxc-export-{}-{}.html", std::process::id(), ms));
+	fs::write(&tmp, html).map_err(|e| e.to_string())?;
+	let result = Command::new(browser)
+		.args([
+			"--headless=new",
+			"--disable-gpu",
+			"--no-first-run",
+			"--no-pdf-header-footer",
+			"--virtual-time-budget=5000",
+		])
+		.arg(format!("--print-to-pdf={}", out.display()))
+		.arg(file_url(&tmp))
+		.output();
+	let _ = fs::remove_file(&tmp);
+	let output = result.map_err(|e| format!("启动浏览器失败: {}", e))?;
+	if !out.is_file() {
+		return Err(format!(
+			"PDF 生成失败: {}",
+			String::from_utf8_lossy(&output.stderr).trim()
+		));
+	}
+	Ok(())
+}
+
+/// `pages` 为文件夹导出时同批笔记的输出页面，单篇导出传空表
+fn export_one(
+	note: &Path,
+	out: &Path,
+	format: KnowledgeExportFormat,
+	theme: KnowledgeExportTheme,
+	browser: Option<&Path>,
+	pages: &HashMap<PathBuf, PathBuf>,
+) -> Result<(), String> {
+	if let Some(parent) = out.parent() {
+		fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+	}
+	match format {
+		KnowledgeExportFormat::Html if !pages.is_empty() => {
+			fs::write(out, folder_page_html(note, out, pages, theme)?).map_err(|e| e.to_string())
+		}
+		KnowledgeExportFormat::Html => {
+			fs::write(out, export_html_string(note, theme)?).map_err(|e| e.to_string())
+		}
+		KnowledgeExportFormat::Pdf => {
+			let browser = browser.ok_or("未找到可用于导出 PDF 的浏览器")?;
+			print_pdf(browser, &export_html_string(note, theme)?, out)
+		}
+		KnowledgeExportFormat::Docx => {
+			let content = read_note_text(note)?;
+			let note_dir = note.parent().unwrap_or(Path::new("."));
+			let bytes = render_docx(&content, note_dir, &note_title(note), &theme.docx())?;
+			fs::write(out, bytes).map_err(|e| e.to_string())
+		}
+	}
+}
+
+/// 目标已存在且不覆盖时顺延为 `name_1.ext`、`name_2.ext`…
+pub(crate) fn free_path(p: PathBuf, overwrite: bool) -> PathBuf {
+	if overwrite || !p.exists() {
+		return p;
+	}
+	let parent = p.parent().map(Path::to_path_buf).unwrap_or_default();
+	let stem = note_title(&p);
+	let ext = p
+		.extension()
+		.and_then(|e| e.to_str())
+		.unwrap_or_default()
+		.to_string();
+	let mut n = 1;
+	loop {
+		let candidate = parent.join(format!("{}_{}.{}", stem, n, ext));
+		if !candidate.exists() {
+			return candidate;
+		}
+		n += 1;
+	}
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportKnowledgeInput {
+	/// 导出单篇笔记
+	#[serde(default)]
+	pub file_path: Option<String>,
+	/// 导出整个文件夹（含子目录），与 `filePath` 二选一
+	#[serde(default)]
+	pub folder_path: Option<String>,
+	pub format: KnowledgeExportFormat,
+	#[serde(default)]
+	pub theme: KnowledgeExportTheme,
+	/// 输出目录；不传时弹出系统对话框（单篇为另存为，文件夹为选择目录）
+	#[serde(default)]
+	pub target_dir: Option<String>,
+	#[serde(default)]
+	pub overwrite: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeExportFailure {
+	pub path: String,
+	pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeExportResult {
+	pub success: String,
+	/// 用户在对话框中取消
+	pub cancelled: bool,
+	pub files: Vec<String>,
+	pub failed: Vec<KnowledgeExportFailure>,
+	pub message: String,
+}
+
+impl KnowledgeExportResult {
+	fn cancelled() -> Self {
+		KnowledgeExportResult {
+			success: "success".to_string(),
+			cancelled: true,
+			files: Vec::new(),
+			failed: Vec::new(),
+			message: "用户取消了导出".to_string(),
+		}
+	}
+}
+
+/// 文件夹导出为 HTML 时额外生成的目录页
+fn write_folder_index(out_dir: &Path, title: &str, files: &[String], theme: KnowledgeExportTheme) -> Result<(), String> {
+	let mut items = String::new();
+	for f in files {
+		let p = Path::new(f);
+		let Ok(rel) = p.strip_prefix(out_dir) else {
+			continue;
+		};
+		let href = rel.to_string_lossy().replace('\\', "/");
+		items.push_str(&format!(
+			"<li><a href=\"{}\">{}</a></li>\n",
+			escape_html(&encode_link_path(&href)),
+			escape_html(&href.trim_end_matches(".html").replace('/', " / "))
+		));
+	}
+	let body = format!(
+		"<h1>{}</h1>\n<ul class=\"export-index\">\n{}</ul>\n",
+		escape_html(title),
+		items
+	);
+	fs::write(
+		out_dir.join("index.html"),
+		wrap_html_document(title, &body, false, theme),
+	)
+	.map_err(|e| e.to_string())
+}
+
+/// 导出笔记为 HTML / PDF / DOCX
+#[tauri::command]
+pub async fn export_knowledge_markdown(
+	app: AppHandle,
+	input: ExportKnowledgeInput,
+) -> Result<KnowledgeExportResult, String> {
+	let format = input.format;
+	let theme = input.theme;
+	let ext = format.ext();
+	let browser = match format {
+		KnowledgeExportFormat::Pdf => Some(find_pdf_browser(&app).await?),
+		_ => None,
+	};
+	let target_dir = input
+		.target_dir
+		.as_deref()
+		.map(str::trim)
+		.filter(|s| !s.is_empty())
+		.map(PathBuf::from);
+
+	// (源笔记, 输出路径)
+	let mut jobs: Vec<(PathBuf, PathBuf)> = Vec::new();
+	let mut folder_out: Option<(PathBuf, String)> = None;
+	match (
+		input.file_path.as_deref().map(str::trim).filter(|s| !s.is_empty()),
+		input.folder_path.as_deref().map(str::trim).filter(|s| !s.is_empty()),
+	) {
+		(Some(file), _) => {
+			let note = PathBuf::from(file);
+			if !note.is_file() || !is_md_file_path(&note) {
+				return Err("文件不存在或不是 .md 文件".to_string());
+			}
+			let name = format!("{}.{}", note_title(&note), ext);
+			let out = match target_dir {
+				Some(dir) => free_path(dir.join(&name), input.overwrite),
+				None => {
+					let dialog = rfd::AsyncFileDialog::new()
+						.set_title("导出笔记")
+						.set_directory(default_save_base_dir(&app).await)
+						.set_file_name(&name)
+						.add_filter(ext.to_uppercase(), &[ext]);
+					match dialog.save_file().await {
+						Some(file) => file.path().to_path_buf(),
+						None => return Ok(KnowledgeExportResult::cancelled()),
+					}
+				}
+			};
+			jobs.push((note, out));
+		}
+		(None, Some(folder)) => {
+			let folder = PathBuf::from(folder);
+			if !folder.is_dir() {
+				return Err(format!("目录不存在：{}", folder.display()));
+			}
+			let base = match target_dir {
+				Some(dir) => dir,
+				None => {
+					let dialog = rfd::AsyncFileDialog::new()
+						.set_title("选择导出目录")
+						.set_directory(default_save_base_dir(&app).await);
+					match dialog.pick_folder().await {
+						Some(dir) => dir.path().to_path_buf(),
+						None => return Ok(KnowledgeExportResult::cancelled()),
+					}
+				}
+			};
+			let folder_name = folder
+				.file_name()
+				.map(|s| s.to_string_lossy().to_string())
+				.unwrap_or_else(|| "knowledge".to_string());
+			let out_dir = base.join(&folder_name);
+			let mut notes = Vec::new();
+			collect_md_files(&folder, &mut notes)?;
+			notes.sort();
+			for note in notes {
+				let mut rel = note.strip_prefix(&folder).unwrap_or(&note).with_extension(ext);
+				// 根目录的 index.md 让位给目录页
+				if format == KnowledgeExportFormat::Html && rel.as_os_str().eq_ignore_ascii_case("index.html") {
+					rel = PathBuf::from(format!("{}-note.html", note_title(&note)));
+				}
+				jobs.push((note, free_path(out_dir.join(rel), input.overwrite)));
+			}
+			folder_out = Some((out_dir, folder_name));
+		}
+		(None, None) => return Err("filePath 与 folderPath 至少提供一个".to_string()),
+	}
+
+	let pages: HashMap<PathBuf, PathBuf> = match &folder_out {
+		Some(_) if format == KnowledgeExportFormat::Html => jobs
+			.iter()
+			.map(|(note, out)| (normalize_components(note), out.clone()))
+			.collect(),
+		_ => HashMap::new(),
+	};
+	let (files, failed) = tauri::async_runtime::spawn_blocking(move || {
+		let mut files = Vec::new();
+		let mut failed = Vec::new();
+		for (note, out) in &jobs {
+			match export_one(note, out, format, theme, browser.as_deref(), &pages) {
+				Ok(()) => files.push(out.to_string_lossy().to_string()),
+				Err(error) => failed.push(KnowledgeExportFailure {
+					path: note.to_string_lossy().to_string(),
+					error,
+				}),
+			}
+		}
+		if format == KnowledgeExportFormat::Html
+			&& let Some((out_dir, title)) = &folder_out
+			&& !files.is_empty()
+			&& let Err(error) = write_folder_index(out_dir, title, &files, theme)
+		{
+			failed.push(KnowledgeExportFailure {
+				path: out_dir.join("index.html").to_string_lossy().to_string(),
+				error,
+			});
+		}
+		(files, failed)
+	})
+	.await
+	.map_err(|e| e.to_string())?;
+
+	let message = if failed.is_empty() {
+		format!("已导出 {} 个文件", files.len())
+	} else {
+		format!("已导出 {} 个文件，{} 个失败", files.len(), failed.len())
+	};
+	Ok(KnowledgeExportResult {
+		success: if files.is_empty() && !failed.is_empty() {
+			"error".to_string()
+		} else {
+			"success".to_string()
+		},
+		cancelled: false,
+		files,
+		failed,
+		message,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn folder_pages_rewrite_note_links_to_html() {
+		let root = Path::new("/kb");
+		let mut pages = HashMap::new();
+		pages.insert(PathBuf::from("/kb/index.md"), PathBuf::from("/out/kb/index-note.html"));
+		pages.insert(PathBuf::from("/kb/sub/a b.md"), PathBuf::from("/out/kb/sub/a b.html"));
+		let mut hooks = FolderPages {
+			note_dir: &root.join("sub"),
+			page_dir: Path::new("/out/kb/sub"),
+			pages: &pages,
+		};
+		assert_eq!(hooks.link("../index.md#intro", false).as_deref(), Some("../index-note.html#intro"));
+		assert_eq!(hooks.link("a%20b.md", false).as_deref(), Some("a%20b.html"));
+		assert_eq!(hooks.link("missing.md", false), None);
+		assert_eq!(hooks.link("https://example.com/x.md", false), None);
+	}
+
+	#[test]
+	fn math_pages_do_not_load_remote_scripts() {
+		let (body, has_math) = render_note_html("$a^2$", Path::new("."), KnowledgeExportTheme::default());
+		assert!(has_math);
+		assert!(body.contains("\\(a^2\\)"));
+		let html = wrap_html_document("t", &body, has_math, KnowledgeExportTheme::default());
+		assert!(html.contains(KATEX_JS));
+		assert!(!html.contains("<script src=") && !html.contains("<link"));
+	}
+}
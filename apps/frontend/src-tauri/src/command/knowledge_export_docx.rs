@@ -0,0 +1,715 @@
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::Path;
+
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::command::knowledge_export::{markdown_options, note_body, resolve_local_image, DocxTheme};
+
+// —— 知识导出 DOCX：把 Markdown 事件流直接写成 WordprocessingML，不依赖 Office / pandoc ——
+
+/// 图片最大宽度（EMU，约 6 英寸，A4 去掉页边距后的正文宽度）
+const MAX_IMAGE_WIDTH_EMU: u64 = 5_486_400;
+/// 按 96 DPI 将像素换算为 EMU
+const EMU_PER_PX: u64 = 9_525;
+/// 每级列表 / 引用的缩进（twip）
+const INDENT_STEP: u32 = 420;
+const MATH_FONTS: &str = "<w:rFonts w:ascii=\"Cambria Math\" w:hAnsi=\"Cambria Math\"/>";
+
+fn escape_xml(s: &str) -> String {
+	let mut out = String::with_capacity(s.len());
+	for c in s.chars() {
+		match c {
+			'&' => out.push_str("&amp;"),
+			'<' => out.push_str("&lt;"),
+			'>' => out.push_str("&gt;"),
+			'"' => out.push_str("&quot;"),
+			// XML 1.0 不允许的控制字符直接丢弃
+			c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => {}
+			_ => out.push(c),
+		}
+	}
+	out
+}
+
+/// 读取常见位图的像素尺寸；不支持的格式返回 None（导出为替代文本）
+fn image_size(bytes: &[u8], ext: &str) -> Option<(u32, u32)> {
+	match ext {
+		"png" if bytes.len() >= 24 => Some((
+			u32::from_be_bytes(bytes[16..20].try_into().ok()?),
+			u32::from_be_bytes(bytes[20..24].try_into().ok()?),
+		)),
+		"gif" if bytes.len() >= 10 => Some((
+			u16::from_le_bytes([bytes[6], bytes[7]]) as u32,
+			u16::from_le_bytes([bytes[8], bytes[9]]) as u32,
+		)),
+		"bmp" if bytes.len() >= 26 => Some((
+			i32::from_le_bytes(bytes[18..22].try_into().ok()?).unsigned_abs(),
+			i32::from_le_bytes(bytes[22..26].try_into().ok()?).unsigned_abs(),
+		)),
+		"jpg" | "jpeg" => jpeg_size(bytes),
+		_ => None,
+	}
+}
+
+fn jpeg_size(bytes: &[u8]) -> Option<(u32, u32)> {
+	let mut i = 2usize;
+	while i + 9 < bytes.len() {
+		if bytes[i] != 0xFF {
+			i += 1;
+			continue;
+		}
+		let marker = bytes[i + 1];
+		let len = u16::from_be_bytes([bytes[i + 2], bytes[i + 3]]) as usize;
+		// SOF0–SOF15（排除 DHT / JPG / DAC）
+		if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+			let h = u16::from_be_bytes([bytes[i + 5], bytes[i + 6]]) as u32;
+			let w = u16::from_be_bytes([bytes[i + 7], bytes[i + 8]]) as u32;
+			return Some((w, h));
+		}
+		i += 2 + len;
+	}
+	None
+}
+
+/// 已读取的本地图片：（字节, 扩展名, 宽, 高）
+type LoadedImage = (Vec<u8>, String, u32, u32);
+
+struct Media {
+	rel_id: String,
+	name: String,
+	bytes: Vec<u8>,
+}
+
+#[derive(Default)]
+struct InlineStyle {
+	bold: u32,
+	italic: u32,
+	strike: u32,
+}
+
+struct ListState {
+	/// 有序列表的下一个序号；无序列表为 None
+	next: Option<u64>,
+}
+
+struct DocxWriter<'a> {
+	theme: &'a DocxTheme,
+	note_dir: &'a Path,
+	body: String,
+	/// 当前段落已累积的 run
+	runs: String,
+	/// 段落首个 run 之前要插入的前缀（列表符号、任务框）
+	pending_prefix: Option<String>,
+	style: InlineStyle,
+	heading: Option<u8>,
+	quote_depth: u32,
+	lists: Vec<ListState>,
+	/// 当前超链接（关系 id，外部链接才有）与其中累积的 run
+	link: Option<(Option<String>, String)>,
+	/// 图片替代文本收集中：图片数据与尺寸待 End(Image) 时输出
+	image: Option<(String, Option<LoadedImage>)>,
+	code_block: Option<String>,
+	table_rows: Vec<String>,
+	table_cells: Vec<String>,
+	in_table_head: bool,
+	in_cell: bool,
+	links: Vec<(String, String)>,
+	media: Vec<Media>,
+	next_rel: u32,
+	drawing_id: u32,
+}
+
+impl<'a> DocxWriter<'a> {
+	fn new(theme: &'a DocxTheme, note_dir: &'a Path) -> Self {
+		DocxWriter {
+			theme,
+			note_dir,
+			body: String::new(),
+			runs: String::new(),
+			pending_prefix: None,
+			style: InlineStyle::default(),
+			heading: None,
+			quote_depth: 0,
+			lists: Vec::new(),
+			link: None,
+			image: None,
+			code_block: None,
+			table_rows: Vec::new(),
+			table_cells: Vec::new(),
+			in_table_head: false,
+			in_cell: false,
+			links: Vec::new(),
+			media: Vec::new(),
+			// rId1 固定给 styles.xml，rId2 给 settings.xml
+			next_rel: 3,
+			drawing_id: 1,
+		}
+	}
+
+	fn alloc_rel(&mut self) -> String {
+		let id = format!("rId{}", self.next_rel);
+		self.next_rel += 1;
+		id
+	}
+
+	/// 组装 `w:rPr`；OOXML 要求子元素按 rStyle → rFonts → b → i → strike → 其余 的顺序出现
+	fn run_props(&self, style: Option<&str>, fonts: &str, tail: &str) -> String {
+		let mut p = String::new();
+		// 外部链接内统一用超链接样式（同一 run 只能有一个 rStyle）
+		let style = if self.link.as_ref().is_some_and(|(rel, _)| rel.is_some()) {
+			Some("Hyperlink")
+		} else {
+			style
+		};
+		if let Some(s) = style {
+			p.push_str(&format!("<w:rStyle w:val=\"{}\"/>", s));
+		}
+		p.push_str(fonts);
+		if self.style.bold > 0 || self.in_table_head {
+			p.push_str("<w:b/>");
+		}
+		if self.style.italic > 0 {
+			p.push_str("<w:i/>");
+		}
+		if self.style.strike > 0 {
+			p.push_str("<w:strike/>");
+		}
+		p.push_str(tail);
+		if p.is_empty() {
+			String::new()
+		} else {
+			format!("<w:rPr>{}</w:rPr>", p)
+		}
+	}
+
+	fn push_raw_run(&mut self, run: String) {
+		if let Some(prefix) = self.pending_prefix.take() {
+			self.runs.push_str(&format!(
+				"<w:r><w:t xml:space=\"preserve\">{}</w:t></w:r>",
+				escape_xml(&prefix)
+			));
+		}
+		match self.link.as_mut() {
+			Some((_, buf)) => buf.push_str(&run),
+			None => self.runs.push_str(&run),
+		}
+	}
+
+	fn push_styled_text(&mut self, text: &str, style: Option<&str>, fonts: &str, tail: &str) {
+		if text.is_empty() {
+			return;
+		}
+		if let Some((alt, _)) = self.image.as_mut() {
+			alt.push_str(text);
+			return;
+		}
+		let run = format!(
+			"<w:r>{}<w:t xml:space=\"preserve\">{}</w:t></w:r>",
+			self.run_props(style, fonts, tail),
+			escape_xml(text)
+		);
+		self.push_raw_run(run);
+	}
+
+	fn push_text(&mut self, text: &str) {
+		self.push_styled_text(text, None, "", "");
+	}
+
+	/// 公式按斜体 Cambria Math 输出 TeX 源码
+	fn push_math(&mut self, text: &str) {
+		self.style.italic += 1;
+		self.push_styled_text(text, None, MATH_FONTS, "");
+		self.style.italic -= 1;
+	}
+
+	fn paragraph_props(&self) -> String {
+		let mut p = String::new();
+		if let Some(level) = self.heading {
+			p.push_str(&format!("<w:pStyle w:val=\"Heading{}\"/>", level));
+		} else if self.quote_depth > 0 {
+			p.push_str("<w:pStyle w:val=\"Quote\"/>");
+		}
+		let indent = INDENT_STEP * (self.lists.len() as u32 + self.quote_depth);
+		if indent > 0 {
+			p.push_str(&format!("<w:ind w:left=\"{}\"/>", indent));
+		}
+		if p.is_empty() {
+			String::new()
+		} else {
+			format!("<w:pPr>{}</w:pPr>", p)
+		}
+	}
+
+	/// 结束当前段落；表格单元格内的段落暂存到单元格
+	fn flush(&mut self) {
+		if self.runs.is_empty() && self.pending_prefix.is_none() {
+			return;
+		}
+		if let Some(prefix) = self.pending_prefix.take() {
+			self.runs.insert_str(
+				0,
+				&format!("<w:r><w:t xml:space=\"preserve\">{}</w:t></w:r>", escape_xml(&prefix)),
+			);
+		}
+		let runs = std::mem::take(&mut self.runs);
+		if self.in_cell {
+			self.runs = runs;
+			return;
+		}
+		self.body
+			.push_str(&format!("<w:p>{}{}</w:p>", self.paragraph_props(), runs));
+	}
+
+	fn push_image(&mut self, alt: String, data: Option<LoadedImage>) {
+		let Some((bytes, ext, w, h)) = data else {
+			if !alt.is_empty() {
+				self.push_text(&format!("[{}]", alt));
+			}
+			return;
+		};
+		let mut cx = w as u64 * EMU_PER_PX;
+		let mut cy = h as u64 * EMU_PER_PX;
+		if cx > MAX_IMAGE_WIDTH_EMU {
+			cy = cy * MAX_IMAGE_WIDTH_EMU / cx;
+			cx = MAX_IMAGE_WIDTH_EMU;
+		}
+		let rel_id = self.alloc_rel();
+		let name = format!("image{}.{}", self.media.len() + 1, ext);
+		let id = self.drawing_id;
+		self.drawing_id += 1;
+		let alt = escape_xml(&alt);
+		let run = format!(
+			concat!(
+				"<w:r><w:drawing><wp:inline distT=\"0\" distB=\"0\" distL=\"0\" distR=\"0\">",
+				"<wp:extent cx=\"{cx}\" cy=\"{cy}\"/><wp:docPr id=\"{id}\" name=\"Picture {id}\" descr=\"{alt}\"/>",
+				"<a:graphic xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\">",
+				"<a:graphicData uri=\"http://schemas.openxmlformats.org/drawingml/2006/picture\">",
+				"<pic:pic xmlns:pic=\"http://schemas.openxmlformats.org/drawingml/2006/picture\">",
+				"<pic:nvPicPr><pic:cNvPr id=\"{id}\" name=\"{name}\"/><pic:cNvPicPr/></pic:nvPicPr>",
+				"<pic:blipFill><a:blip r:embed=\"{rel}\"/><a:stretch><a:fillRect/></a:stretch></pic:blipFill>",
+				"<pic:spPr><a:xfrm><a:off x=\"0\" y=\"0\"/><a:ext cx=\"{cx}\" cy=\"{cy}\"/></a:xfrm>",
+				"<a:prstGeom prst=\"rect\"><a:avLst/></a:prstGeom></pic:spPr>",
+				"</pic:pic></a:graphicData></a:graphic></wp:inline></w:drawing></w:r>"
+			),
+			cx = cx,
+			cy = cy,
+			id = id,
+			alt = alt,
+			name = name,
+			rel = rel_id
+		);
+		self.media.push(Media {
+			rel_id,
+			name,
+			bytes,
+		});
+		self.push_raw_run(run);
+	}
+
+	fn load_image(&self, src: &str) -> Option<LoadedImage> {
+		let path = resolve_local_image(self.note_dir, src)?;
+		let ext = path
+			.extension()
+			.and_then(|e| e.to_str())
+			.map(|e| e.to_ascii_lowercase())?;
+		let ext = if ext == "jpeg" { "jpg".to_string() } else { ext };
+		let bytes = fs::read(&path).ok()?;
+		let (w, h) = image_size(&bytes, &ext)?;
+		(w > 0 && h > 0).then_some((bytes, ext, w, h))
+	}
+
+	fn push_code_block(&mut self, code: &str) {
+		for line in code.trim_end_matches('\n').split('\n') {
+			self.body.push_str(&format!(
+				"<w:p><w:pPr><w:pStyle w:val=\"CodeBlock\"/>{}</w:pPr><w:r><w:t xml:space=\"preserve\">{}</w:t></w:r></w:p>",
+				if self.lists.is_empty() && self.quote_depth == 0 {
+					String::new()
+				} else {
+					format!(
+						"<w:ind w:left=\"{}\"/>",
+						INDENT_STEP * (self.lists.len() as u32 + self.quote_depth)
+					)
+				},
+				escape_xml(line)
+			));
+		}
+	}
+
+	fn handle(&mut self, event: Event) {
+		if let Some(buf) = self.code_block.as_mut() {
+			match event {
+				Event::Text(t) => buf.push_str(&t),
+				Event::End(TagEnd::CodeBlock) => {
+					let code = self.code_block.take().unwrap_or_default();
+					self.push_code_block(&code);
+				}
+				_ => {}
+			}
+			return;
+		}
+		match event {
+			Event::Start(tag) => self.start(tag),
+			Event::End(tag) => self.end(tag),
+			Event::Text(t) => self.push_text(&t),
+			Event::Code(t) => {
+				let shading = format!(
+					"<w:shd w:val=\"clear\" w:color=\"auto\" w:fill=\"{}\"/>",
+					self.theme.code_bg
+				);
+				self.push_styled_text(&t, Some("CodeChar"), "", &shading);
+			}
+			Event::InlineMath(t) => self.push_math(&t),
+			Event::DisplayMath(t) => {
+				self.flush();
+				self.push_math(&t);
+				self.flush();
+			}
+			Event::SoftBreak => self.push_text(" "),
+			Event::HardBreak => self.push_raw_run("<w:r><w:br/></w:r>".to_string()),
+			Event::Rule => {
+				self.flush();
+				self.body.push_str(
+					"<w:p><w:pPr><w:pBdr><w:bottom w:val=\"single\" w:sz=\"6\" w:space=\"1\" w:color=\"auto\"/></w:pBdr></w:pPr></w:p>",
+				);
+			}
+			Event::TaskListMarker(done) => {
+				let mark = if done { "☑ " } else { "☐ " };
+				match self.pending_prefix.as_mut() {
+					// 任务列表不显示项目符号，只显示勾选框
+					Some(p) => *p = mark.to_string(),
+					None => self.push_text(mark),
+				}
+			}
+			Event::FootnoteReference(label) => {
+				self.push_styled_text(&format!("[{}]", label), None, "", "<w:vertAlign w:val=\"superscript\"/>")
+			}
+			// 原始 HTML 无法可靠映射到 Word，忽略
+			Event::Html(_) | Event::InlineHtml(_) => {}
+		}
+	}
+
+	fn start(&mut self, tag: Tag) {
+		match tag {
+			// 松散列表中段落紧跟列表项开始：仅有待插入的列表符号时不断段
+			Tag::Paragraph if !self.runs.is_empty() => self.flush(),
+			Tag::Heading { level, .. } => {
+				self.flush();
+				self.heading = Some(match level {
+					HeadingLevel::H1 => 1,
+					HeadingLevel::H2 => 2,
+					HeadingLevel::H3 => 3,
+					HeadingLevel::H4 => 4,
+					HeadingLevel::H5 => 5,
+					HeadingLevel::H6 => 6,
+				});
+			}
+			Tag::BlockQuote(_) => {
+				self.flush();
+				self.quote_depth += 1;
+			}
+			Tag::CodeBlock(_) => {
+				self.flush();
+				self.code_block = Some(String::new());
+			}
+			Tag::List(start) => {
+				self.flush();
+				self.lists.push(ListState { next: start });
+			}
+			Tag::Item => {
+				self.flush();
+				let prefix = match self.lists.last_mut().and_then(|l| l.next.as_mut()) {
+					Some(n) => {
+						let p = format!("{}. ", n);
+						*n += 1;
+						p
+					}
+					None => "• ".to_string(),
+				};
+				self.pending_prefix = Some(prefix);
+			}
+			Tag::FootnoteDefinition(label) => {
+				self.flush();
+				self.pending_prefix = Some(format!("[{}] ", label));
+			}
+			Tag::Table(_) => {
+				self.flush();
+				self.table_rows.clear();
+			}
+			Tag::TableHead => {
+				self.in_table_head = true;
+				self.table_cells.clear();
+			}
+			Tag::TableRow => self.table_cells.clear(),
+			Tag::TableCell => {
+				self.in_cell = true;
+				self.runs.clear();
+			}
+			Tag::Emphasis => self.style.italic += 1,
+			Tag::Strong => self.style.bold += 1,
+			Tag::Strikethrough => self.style.strike += 1,
+			Tag::Link { dest_url, .. } => {
+				let lower = dest_url.to_ascii_lowercase();
+				let rel = if lower.starts_with("http://")
+					|| lower.starts_with("https://")
+					|| lower.starts_with("mailto:")
+				{
+					let id = self.alloc_rel();
+					self.links.push((id.clone(), dest_url.to_string()));
+					Some(id)
+				} else {
+					None
+				};
+				self.link = Some((rel, String::new()));
+			}
+			Tag::Image { dest_url, .. } => {
+				let data = self.load_image(&dest_url);
+				self.image = Some((String::new(), data));
+			}
+			_ => {}
+		}
+	}
+
+	fn end(&mut self, tag: TagEnd) {
+		match tag {
+			TagEnd::Paragraph => self.flush(),
+			TagEnd::Heading(_) => {
+				self.flush();
+				self.heading = None;
+			}
+			TagEnd::BlockQuote(_) => {
+				self.flush();
+				self.quote_depth = self.quote_depth.saturating_sub(1);
+			}
+			TagEnd::List(_) => {
+				self.flush();
+				self.lists.pop();
+			}
+			TagEnd::Item | TagEnd::FootnoteDefinition => self.flush(),
+			TagEnd::TableCell => {
+				let runs = std::mem::take(&mut self.runs);
+				self.in_cell = false;
+				self.table_cells.push(format!(
+					"<w:tc><w:tcPr><w:tcW w:w=\"0\" w:type=\"auto\"/></w:tcPr><w:p>{}</w:p></w:tc>",
+					runs
+				));
+			}
+			TagEnd::TableHead | TagEnd::TableRow => {
+				let cells = std::mem::take(&mut self.table_cells).concat();
+				let header = if self.in_table_head {
+					"<w:trPr><w:tblHeader/></w:trPr>"
+				} else {
+					""
+				};
+				self.table_rows
+					.push(format!("<w:tr>{}{}</w:tr>", header, cells));
+				self.in_table_head = false;
+			}
+			TagEnd::Table => {
+				let rows = std::mem::take(&mut self.table_rows).concat();
+				self.body.push_str(&format!(
+					"<w:tbl><w:tblPr><w:tblStyle w:val=\"TableGrid\"/><w:tblW w:w=\"0\" w:type=\"auto\"/></w:tblPr>{}</w:tbl><w:p/>",
+					rows
+				));
+			}
+			TagEnd::Emphasis => self.style.italic = self.style.italic.saturating_sub(1),
+			TagEnd::Strong => self.style.bold = self.style.bold.saturating_sub(1),
+			TagEnd::Strikethrough => self.style.strike = self.style.strike.saturating_sub(1),
+			TagEnd::Link => {
+				if let Some((rel, buf)) = self.link.take() {
+					match rel {
+						Some(id) => self
+							.runs
+							.push_str(&format!("<w:hyperlink r:id=\"{}\">{}</w:hyperlink>", id, buf)),
+						None => self.runs.push_str(&buf),
+					}
+				}
+			}
+			TagEnd::Image => {
+				if let Some((alt, data)) = self.image.take() {
+					self.push_image(alt, data);
+				}
+			}
+			_ => {}
+		}
+	}
+
+	fn document_xml(&self) -> String {
+		let background = self
+			.theme
+			.page_bg
+			.map(|c| format!("<w:background w:color=\"{}\"/>", c))
+			.unwrap_or_default();
+		format!(
+			concat!(
+				"<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>",
+				"<w:document xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\" ",
+				"xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\" ",
+				"xmlns:wp=\"http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing\">",
+				"{}<w:body>{}<w:sectPr><w:pgSz w:w=\"11906\" w:h=\"16838\"/>",
+				"<w:pgMar w:top=\"1440\" w:right=\"1440\" w:bottom=\"1440\" w:left=\"1440\" w:header=\"708\" w:footer=\"708\" w:gutter=\"0\"/>",
+				"</w:sectPr></w:body></w:document>"
+			),
+			background, self.body
+		)
+	}
+
+	fn document_rels(&self) -> String {
+		let mut rels = String::from(concat!(
+			"<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>",
+			"<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">",
+			"<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles\" Target=\"styles.xml\"/>",
+			"<Relationship Id=\"rId2\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/settings\" Target=\"settings.xml\"/>"
+		));
+		for (id, url) in &self.links {
+			rels.push_str(&format!(
+				"<Relationship Id=\"{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink\" Target=\"{}\" TargetMode=\"External\"/>",
+				id,
+				escape_xml(url)
+			));
+		}
+		for m in &self.media {
+			rels.push_str(&format!(
+				"<Relationship Id=\"{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/image\" Target=\"media/{}\"/>",
+				m.rel_id, m.name
+			));
+		}
+		rels.push_str("</Relationships>");
+		rels
+	}
+
+	fn styles_xml(&self) -> String {
+		let t = self.theme;
+		let heading = |level: u8, size: u32| {
+			format!(
+				concat!(
+					"<w:style w:type=\"paragraph\" w:styleId=\"Heading{l}\"><w:name w:val=\"heading {l}\"/>",
+					"<w:basedOn w:val=\"Normal\"/><w:next w:val=\"Normal\"/><w:qFormat/>",
+					"<w:pPr><w:keepNext/><w:spacing w:before=\"240\" w:after=\"120\"/><w:outlineLvl w:val=\"{o}\"/></w:pPr>",
+					"<w:rPr><w:b/><w:color w:val=\"{c}\"/><w:sz w:val=\"{s}\"/><w:szCs w:val=\"{s}\"/></w:rPr></w:style>"
+				),
+				l = level,
+				o = level - 1,
+				c = t.heading_color,
+				s = size
+			)
+		};
+		let headings: String = [(1, 36), (2, 30), (3, 26), (4, 24), (5, 22), (6, 22)]
+			.iter()
+			.map(|(l, s)| heading(*l, *s))
+			.collect();
+		format!(
+			concat!(
+				"<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>",
+				"<w:styles xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\">",
+				"<w:docDefaults><w:rPrDefault><w:rPr>",
+				"<w:rFonts w:ascii=\"{font}\" w:hAnsi=\"{font}\" w:eastAsia=\"{font}\" w:cs=\"{font}\"/>",
+				"<w:color w:val=\"{text}\"/><w:sz w:val=\"22\"/><w:szCs w:val=\"22\"/><w:lang w:val=\"zh-CN\" w:eastAsia=\"zh-CN\"/>",
+				"</w:rPr></w:rPrDefault><w:pPrDefault><w:pPr><w:spacing w:after=\"120\" w:line=\"300\" w:lineRule=\"auto\"/></w:pPr></w:pPrDefault></w:docDefaults>",
+				"<w:style w:type=\"paragraph\" w:default=\"1\" w:styleId=\"Normal\"><w:name w:val=\"Normal\"/><w:qFormat/></w:style>",
+				"{headings}",
+				"<w:style w:type=\"paragraph\" w:styleId=\"Quote\"><w:name w:val=\"Quote\"/><w:basedOn w:val=\"Normal\"/>",
+				"<w:pPr><w:pBdr><w:left w:val=\"single\" w:sz=\"18\" w:space=\"8\" w:color=\"A0A0A0\"/></w:pBdr></w:pPr>",
+				"<w:rPr><w:i/><w:color w:val=\"808080\"/></w:rPr></w:style>",
+				"<w:style w:type=\"paragraph\" w:styleId=\"CodeBlock\"><w:name w:val=\"Code Block\"/><w:basedOn w:val=\"Normal\"/>",
+				"<w:pPr><w:shd w:val=\"clear\" w:color=\"auto\" w:fill=\"{code_bg}\"/><w:spacing w:after=\"0\" w:line=\"240\" w:lineRule=\"auto\"/></w:pPr>",
+				"<w:rPr><w:rFonts w:ascii=\"Consolas\" w:hAnsi=\"Consolas\"/><w:sz w:val=\"19\"/><w:szCs w:val=\"19\"/></w:rPr></w:style>",
+				"<w:style w:type=\"character\" w:styleId=\"CodeChar\"><w:name w:val=\"Code Char\"/>",
+				"<w:rPr><w:rFonts w:ascii=\"Consolas\" w:hAnsi=\"Consolas\"/><w:sz w:val=\"20\"/></w:rPr></w:style>",
+				"<w:style w:type=\"character\" w:styleId=\"Hyperlink\"><w:name w:val=\"Hyperlink\"/>",
+				"<w:rPr><w:color w:val=\"{link}\"/><w:u w:val=\"single\"/></w:rPr></w:style>",
+				"<w:style w:type=\"table\" w:styleId=\"TableGrid\"><w:name w:val=\"Table Grid\"/><w:tblPr>",
+				"<w:tblBorders><w:top w:val=\"single\" w:sz=\"4\" w:color=\"A0A0A0\"/><w:left w:val=\"single\" w:sz=\"4\" w:color=\"A0A0A0\"/>",
+				"<w:bottom w:val=\"single\" w:sz=\"4\" w:color=\"A0A0A0\"/><w:right w:val=\"single\" w:sz=\"4\" w:color=\"A0A0A0\"/>",
+				"<w:insideH w:val=\"single\" w:sz=\"4\" w:color=\"A0A0A0\"/><w:insideV w:val=\"single\" w:sz=\"4\" w:color=\"A0A0A0\"/></w:tblBorders>",
+				"<w:tblCellMar><w:left w:w=\"108\" w:type=\"dxa\"/><w:right w:w=\"108\" w:type=\"dxa\"/></w:tblCellMar>",
+				"</w:tblPr></w:style>",
+				"</w:styles>"
+			),
+			font = t.font,
+			text = t.text_color,
+			headings = headings,
+			code_bg = t.code_bg,
+			link = t.link_color
+		)
+	}
+}
+
+const CONTENT_TYPES_HEAD: &str = concat!(
+	"<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>",
+	"<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">",
+	"<Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>",
+	"<Default Extension=\"xml\" ContentType=\"application/xml\"/>",
+	"<Default Extension=\"png\" ContentType=\"image/png\"/>",
+	"<Default Extension=\"jpg\" ContentType=\"image/jpeg\"/>",
+	"<Default Extension=\"gif\" ContentType=\"image/gif\"/>",
+	"<Default Extension=\"bmp\" ContentType=\"image/bmp\"/>",
+	"<Override PartName=\"/word/document.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml\"/>",
+	"<Override PartName=\"/word/styles.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml\"/>",
+	"<Override PartName=\"/word/settings.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.settings+xml\"/>",
+	"<Override PartName=\"/docProps/core.xml\" ContentType=\"application/vnd.openxmlformats-package.core-properties+xml\"/>",
+	"</Types>"
+);
+
+const ROOT_RELS: &str = concat!(
+	"<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>",
+	"<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">",
+	"<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"word/document.xml\"/>",
+	"<Relationship Id=\"rId2\" Type=\"http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties\" Target=\"docProps/core.xml\"/>",
+	"</Relationships>"
+);
+
+/// `displayBackgroundShape` 让 Word 显示页面背景色（深色 / 护眼主题）
+const SETTINGS_XML: &str = concat!(
+	"<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>",
+	"<w:settings xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\">",
+	"<w:displayBackgroundShape/><w:defaultTabStop w:val=\"420\"/><w:compat/>",
+	"</w:settings>"
+);
+
+fn core_xml(title: &str) -> String {
+	format!(
+		concat!(
+			"<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>",
+			"<cp:coreProperties xmlns:cp=\"http://schemas.openxmlformats.org/package/2006/metadata/core-properties\" ",
+			"xmlns:dc=\"http://purl.org/dc/elements/1.1/\">",
+			"<dc:title>{}</dc:title><dc:creator>dnhyxc-ai</dc:creator></cp:coreProperties>"
+		),
+		escape_xml(title)
+	)
+}
+
+/// 渲染 Markdown 为 DOCX 字节
+pub(crate) fn render_docx(
+	content: &str,
+	note_dir: &Path,
+	title: &str,
+	theme: &DocxTheme,
+) -> Result<Vec<u8>, String> {
+	let mut writer = DocxWriter::new(theme, note_dir);
+	for event in Parser::new_ext(note_body(content), markdown_options()) {
+		writer.handle(event);
+	}
+	writer.flush();
+
+	let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+	let opts = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+	let mut add = |name: &str, bytes: &[u8]| -> Result<(), String> {
+		zip.start_file(name, opts).map_err(|e| e.to_string())?;
+		zip.write_all(bytes).map_err(|e| e.to_string())
+	};
+	add("[Content_Types].xml", CONTENT_TYPES_HEAD.as_bytes())?;
+	add("_rels/.rels", ROOT_RELS.as_bytes())?;
+	add("docProps/core.xml", core_xml(title).as_bytes())?;
+	add("word/document.xml", writer.document_xml().as_bytes())?;
+	add("word/styles.xml", writer.styles_xml().as_bytes())?;
+	add("word/settings.xml", SETTINGS_XML.as_bytes())?;
+	add("word/_rels/document.xml.rels", writer.document_rels().as_bytes())?;
+	for m in &writer.media {
+		add(&format!("word/media/{}", m.name), &m.bytes)?;
+	}
+	let cursor = zip.finish().map_err(|e| e.to_string())?;
+	Ok(cursor.into_inner())
+}
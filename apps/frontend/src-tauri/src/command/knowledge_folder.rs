@@ -0,0 +1,589 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::command::knowledge::{is_md_file_path, resolve_knowledge_root, write_file_atomic};
+use crate::command::knowledge_assets::ASSETS_DIR_NAME;
+use crate::command::knowledge_conflict::file_mtime_ms;
+use crate::command::knowledge_history::{HISTORY_DIR_NAME, move_history};
+use crate::command::knowledge_trash::move_to_trash;
+use crate::types::common::SaveFileResult;
+
+// —— 知识库文件夹：树形结构、增删改移，以及排序 / 置顶（持久化在根目录 `.knowledge-meta.json`） ——
+
+const META_FILE_NAME: &str = ".knowledge-meta.json";
+
+/// 元数据文件读改写串行化
+static META_LOCK: Mutex<()> = Mutex::new(());
+
+/// 排序与置顶；键均为相对知识库根目录、以 `/` 分隔的路径，根目录为空串
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KnowledgeMeta {
+	/// 文件夹相对路径 → 子项（文件夹名 / 笔记文件名）的手动排序
+	#[serde(default)]
+	pub order: BTreeMap<String, Vec<String>>,
+	/// 置顶的文件夹或笔记相对路径
+	#[serde(default)]
+	pub pinned: Vec<String>,
+}
+
+fn read_meta(root: &Path) -> KnowledgeMeta {
+	fs::read_to_string(root.join(META_FILE_NAME))
+		.ok()
+		.and_then(|s| serde_json::from_str(&s).ok())
+		.unwrap_or_default()
+}
+
+fn write_meta(root: &Path, meta: &KnowledgeMeta) -> Result<(), String> {
+	let json = serde_json::to_string_pretty(meta).map_err(|e| e.to_string())?;
+	write_file_atomic(&root.join(META_FILE_NAME), json.as_bytes())
+}
+
+fn update_meta(root: &Path, f: impl FnOnce(&mut KnowledgeMeta)) -> Result<(), String> {
+	let _guard = META_LOCK.lock().map_err(|e| e.to_string())?;
+	let mut meta = read_meta(root);
+	f(&mut meta);
+	write_meta(root, &meta)
+}
+
+/// 路径相对根目录的 `/` 形式；不在根目录下返回 None
+pub(crate) fn relative_key(root: &Path, p: &Path) -> Option<String> {
+	let rel = p.strip_prefix(root).ok()?;
+	Some(
+		rel.components()
+			.map(|c| c.as_os_str().to_string_lossy().to_string())
+			.collect::<Vec<_>>()
+			.join("/"),
+	)
+}
+
+/// 重命名 / 移动后，元数据中以 `from` 为前缀的键与置顶路径同步替换为 `to`
+fn rename_meta_prefix(meta: &mut KnowledgeMeta, from: &str, to: &str) {
+	let remap = |key: &str| -> Option<String> {
+		if key == from {
+			Some(to.to_string())
+		} else {
+			key.strip_prefix(&format!("{}/", from))
+				.map(|rest| format!("{}/{}", to, rest))
+		}
+	};
+	let order = std::mem::take(&mut meta.order);
+	meta.order = order
+		.into_iter()
+		.map(|(k, v)| (remap(&k).unwrap_or(k), v))
+		.collect();
+	for p in meta.pinned.iter_mut() {
+		if let Some(n) = remap(p) {
+			*p = n;
+		}
+	}
+}
+
+/// 父目录排序列表中的旧名替换为新名（同目录改名时保持位置）
+fn rename_in_order(meta: &mut KnowledgeMeta, parent_key: &str, old_name: &str, new_name: &str) {
+	if let Some(list) = meta.order.get_mut(parent_key) {
+		for n in list.iter_mut() {
+			if n == old_name {
+				*n = new_name.to_string();
+			}
+		}
+	}
+}
+
+fn remove_meta_prefix(meta: &mut KnowledgeMeta, key: &str) {
+	let prefix = format!("{}/", key);
+	meta.order.retain(|k, _| k != key && !k.starts_with(&prefix));
+	meta.pinned.retain(|p| p != key && !p.starts_with(&prefix));
+}
+
+/// 文件夹 / 笔记路径：可为绝对路径或相对根目录的路径；不允许 `..` 跳出根目录
+fn resolve_in_root(root: &Path, raw: &str) -> Result<PathBuf, String> {
+	let trimmed = raw.trim().trim_end_matches(['/', '\\']);
+	if trimmed.is_empty() {
+		return Ok(root.to_path_buf());
+	}
+	let p = Path::new(trimmed);
+	let rel = if p.is_absolute() {
+		p.strip_prefix(root)
+			.map_err(|_| format!("路径不在知识库目录中：{}", trimmed))?
+			.to_path_buf()
+	} else {
+		p.to_path_buf()
+	};
+	if rel
+		.components()
+		.any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+	{
+		return Err(format!("非法路径：{}", trimmed));
+	}
+	Ok(root.join(rel))
+}
+
+fn validate_folder_name(name: &str) -> Result<String, String> {
+	let n = name.trim();
+	if n.is_empty() {
+		return Err("文件夹名不能为空".to_string());
+	}
+	if n.starts_with('.') {
+		return Err("文件夹名不能以 . 开头".to_string());
+	}
+	if n.chars().any(|c| matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|')) {
+		return Err("文件夹名包含非法字符".to_string());
+	}
+	Ok(n.to_string())
+}
+
+fn parent_key_of(root: &Path, p: &Path) -> String {
+	p.parent()
+		.and_then(|parent| relative_key(root, parent))
+		.unwrap_or_default()
+}
+
+fn file_name_of(p: &Path) -> String {
+	p.file_name()
+		.map(|s| s.to_string_lossy().to_string())
+		.unwrap_or_default()
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeTreeNote {
+	pub path: String,
+	pub title: String,
+	pub updated_at_ms: u64,
+	pub pinned: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeFolderNode {
+	pub name: String,
+	pub path: String,
+	/// 相对根目录的 `/` 路径，根节点为空串
+	pub relative_path: String,
+	/// 本目录直接包含的笔记数
+	pub note_count: usize,
+	/// 含所有子目录的笔记总数
+	pub total_note_count: usize,
+	pub pinned: bool,
+	pub children: Vec<KnowledgeFolderNode>,
+	/// `includeNotes` 为 true 时返回本目录下的笔记
+	pub notes: Vec<KnowledgeTreeNote>,
+}
+
+/// 置顶在前，其次按手动排序，最后未排序的按 `fallback` 规则
+fn sort_by_meta<T>(
+	items: &mut [T],
+	order: Option<&Vec<String>>,
+	name_of: impl Fn(&T) -> String,
+	pinned_of: impl Fn(&T) -> bool,
+	fallback: impl Fn(&T, &T) -> std::cmp::Ordering,
+) {
+	let rank: HashMap<&str, usize> = order
+		.map(|list| {
+			list.iter()
+				.enumerate()
+				.map(|(i, n)| (n.as_str(), i))
+				.collect()
+		})
+		.unwrap_or_default();
+	items.sort_by(|a, b| {
+		let (pa, pb) = (pinned_of(a), pinned_of(b));
+		let (na, nb) = (name_of(a), name_of(b));
+		let (ra, rb) = (rank.get(na.as_str()), rank.get(nb.as_str()));
+		pb.cmp(&pa)
+			.then_with(|| match (ra, rb) {
+				(Some(x), Some(y)) => x.cmp(y),
+				(Some(_), None) => std::cmp::Ordering::Less,
+				(None, Some(_)) => std::cmp::Ordering::Greater,
+				(None, None) => fallback(a, b),
+			})
+	});
+}
+
+/// 以 `.` 开头的隐藏项（如 `.history`、`.knowledge-meta.json`）不在文件夹树中展示
+fn is_hidden_tree_entry(name: &str) -> bool {
+	name.starts_with('.')
+}
+
+fn build_node(
+	root: &Path,
+	dir: &Path,
+	meta: &KnowledgeMeta,
+	include_notes: bool,
+) -> Result<KnowledgeFolderNode, String> {
+	let key = relative_key(root, dir).unwrap_or_default();
+	let mut children = Vec::new();
+	let mut notes = Vec::new();
+	for ent in fs::read_dir(dir).map_err(|e| e.to_string())? {
+		let ent = ent.map_err(|e| e.to_string())?;
+		let name = ent.file_name().to_string_lossy().to_string();
+		if is_hidden_tree_entry(&name) || (dir == root && name == ASSETS_DIR_NAME) {
+			continue;
+		}
+		let p = ent.path();
+		let ft = ent.file_type().map_err(|e| e.to_string())?;
+		if ft.is_dir() {
+			children.push(build_node(root, &p, meta, include_notes)?);
+		} else if ft.is_file() && is_md_file_path(&p) {
+			let rel = relative_key(root, &p).unwrap_or_default();
+			notes.push(KnowledgeTreeNote {
+				title: p
+					.file_stem()
+					.and_then(|s| s.to_str())
+					.unwrap_or("未命名")
+					.to_string(),
+				updated_at_ms: file_mtime_ms(&p).unwrap_or(0),
+				pinned: meta.pinned.contains(&rel),
+				path: p.to_string_lossy().to_string(),
+			});
+		}
+	}
+	let order = meta.order.get(&key);
+	sort_by_meta(
+		&mut children,
+		order,
+		|c| c.name.clone(),
+		|c| c.pinned,
+		|a, b| a.name.cmp(&b.name),
+	);
+	sort_by_meta(
+		&mut notes,
+		order,
+		|n| file_name_of(Path::new(&n.path)),
+		|n| n.pinned,
+		|a, b| b.updated_at_ms.cmp(&a.updated_at_ms),
+	);
+	let note_count = notes.len();
+	let total_note_count = note_count + children.iter().map(|c| c.total_note_count).sum::<usize>();
+	Ok(KnowledgeFolderNode {
+		name: file_name_of(dir),
+		path: dir.to_string_lossy().to_string(),
+		pinned: !key.is_empty() && meta.pinned.contains(&key),
+		relative_path: key,
+		note_count,
+		total_note_count,
+		children,
+		notes: if include_notes { notes } else { Vec::new() },
+	})
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeFolderTreeInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	#[serde(default)]
+	pub include_notes: bool,
+}
+
+/// 知识库文件夹树（含各目录笔记数；可选带出笔记列表）
+#[tauri::command]
+pub async fn get_knowledge_folder_tree(
+	app: AppHandle,
+	input: KnowledgeFolderTreeInput,
+) -> Result<KnowledgeFolderNode, String> {
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	tauri::async_runtime::spawn_blocking(move || {
+		let meta = read_meta(&root);
+		build_node(&root, &root, &meta, input.include_notes)
+	})
+	.await
+	.map_err(|e| e.to_string())?
+}
+
+fn ok_result(path: &Path, message: String) -> SaveFileResult {
+	SaveFileResult {
+		success: "success".to_string(),
+		file_path: Some(path.to_string_lossy().to_string()),
+		message,
+	}
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateKnowledgeFolderInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	/// 父文件夹（绝对路径或相对根目录），缺省为根目录
+	#[serde(default)]
+	pub parent: Option<String>,
+	pub name: String,
+}
+
+#[tauri::command]
+pub async fn create_knowledge_folder(
+	app: AppHandle,
+	input: CreateKnowledgeFolderInput,
+) -> Result<SaveFileResult, String> {
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let parent = resolve_in_root(&root, input.parent.as_deref().unwrap_or(""))?;
+	let name = validate_folder_name(&input.name)?;
+	let target = parent.join(&name);
+	if target.exists() {
+		return Err(format!("已存在同名文件或文件夹：{}", target.display()));
+	}
+	fs::create_dir_all(&target).map_err(|e| e.to_string())?;
+	Ok(ok_result(&target, format!("已创建文件夹 {}", name)))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameKnowledgeFolderInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	pub folder_path: String,
+	pub new_name: String,
+}
+
+#[tauri::command]
+pub async fn rename_knowledge_folder(
+	app: AppHandle,
+	input: RenameKnowledgeFolderInput,
+) -> Result<SaveFileResult, String> {
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let from = resolve_in_root(&root, &input.folder_path)?;
+	if from == root || !from.is_dir() {
+		return Err("只能重命名知识库中的子文件夹".to_string());
+	}
+	let name = validate_folder_name(&input.new_name)?;
+	let to = from.with_file_name(&name);
+	if to == from {
+		return Ok(ok_result(&to, "名称未变化".to_string()));
+	}
+	if to.exists() {
+		return Err(format!("已存在同名文件或文件夹：{}", to.display()));
+	}
+	fs::rename(&from, &to).map_err(|e| e.to_string())?;
+	let (from_key, to_key) = (
+		relative_key(&root, &from).unwrap_or_default(),
+		relative_key(&root, &to).unwrap_or_default(),
+	);
+	let parent_key = parent_key_of(&root, &from);
+	let old_name = file_name_of(&from);
+	update_meta(&root, |m| {
+		rename_meta_prefix(m, &from_key, &to_key);
+		rename_in_order(m, &parent_key, &old_name, &name);
+	})?;
+	Ok(ok_result(&to, format!("已重命名为 {}", name)))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveKnowledgeFolderInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	pub folder_path: String,
+	/// 目标父文件夹，空串为根目录
+	pub target_parent: String,
+}
+
+#[tauri::command]
+pub async fn move_knowledge_folder(
+	app: AppHandle,
+	input: MoveKnowledgeFolderInput,
+) -> Result<SaveFileResult, String> {
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let from = resolve_in_root(&root, &input.folder_path)?;
+	if from == root || !from.is_dir() {
+		return Err("只能移动知识库中的子文件夹".to_string());
+	}
+	let parent = resolve_in_root(&root, &input.target_parent)?;
+	if !parent.is_dir() {
+		return Err(format!("目标文件夹不存在：{}", parent.display()));
+	}
+	if parent.starts_with(&from) {
+		return Err("不能移动到自身或其子文件夹中".to_string());
+	}
+	let to = parent.join(file_name_of(&from));
+	if to == from {
+		return Ok(ok_result(&to, "位置未变化".to_string()));
+	}
+	if to.exists() {
+		return Err(format!("目标位置已存在同名文件夹：{}", to.display()));
+	}
+	fs::rename(&from, &to).map_err(|e| e.to_string())?;
+	let (from_key, to_key) = (
+		relative_key(&root, &from).unwrap_or_default(),
+		relative_key(&root, &to).unwrap_or_default(),
+	);
+	update_meta(&root, |m| rename_meta_prefix(m, &from_key, &to_key))?;
+	Ok(ok_result(&to, format!("已移动至 {}", parent.display())))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteKnowledgeFolderInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	pub folder_path: String,
+	/// 非空文件夹须为 true：其中的文件逐个移入回收站后删除目录
+	#[serde(default)]
+	pub recursive: bool,
+}
+
+/// 将目录下所有文件逐个移入回收站，再自底向上删除已清空的目录；
+/// 仅笔记快照目录 `.history` 随文件夹直接删除
+fn trash_folder_tree(app: &AppHandle, dir: &Path, trashed: &mut usize) -> Result<(), String> {
+	for ent in fs::read_dir(dir).map_err(|e| e.to_string())? {
+		let ent = ent.map_err(|e| e.to_string())?;
+		let p = ent.path();
+		let ft = ent.file_type().map_err(|e| e.to_string())?;
+		if ft.is_dir() {
+			if ent.file_name() == HISTORY_DIR_NAME {
+				fs::remove_dir_all(&p).map_err(|e| e.to_string())?;
+			} else {
+				trash_folder_tree(app, &p, trashed)?;
+			}
+		} else {
+			move_to_trash(app, &p)?;
+			*trashed += 1;
+		}
+	}
+	fs::remove_dir(dir).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_knowledge_folder(
+	app: AppHandle,
+	input: DeleteKnowledgeFolderInput,
+) -> Result<SaveFileResult, String> {
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let dir = resolve_in_root(&root, &input.folder_path)?;
+	if dir == root || !dir.is_dir() {
+		return Err("只能删除知识库中的子文件夹".to_string());
+	}
+	let is_empty = fs::read_dir(&dir)
+		.map_err(|e| e.to_string())?
+		.next()
+		.is_none();
+	if !is_empty && !input.recursive {
+		return Err("文件夹非空，请确认后再删除".to_string());
+	}
+	let mut trashed = 0usize;
+	trash_folder_tree(&app, &dir, &mut trashed)?;
+	let key = relative_key(&root, &dir).unwrap_or_default();
+	update_meta(&root, |m| remove_meta_prefix(m, &key))?;
+	let message = if trashed > 0 {
+		format!("已删除文件夹，{} 个文件已移至回收站", trashed)
+	} else {
+		"已删除文件夹".to_string()
+	};
+	Ok(ok_result(&dir, message))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveKnowledgeMarkdownToFolderInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	pub file_path: String,
+	/// 目标文件夹，空串为根目录
+	pub target_folder: String,
+	#[serde(default)]
+	pub overwrite: bool,
+}
+
+/// 将笔记移动到另一文件夹（历史快照随之迁移）
+#[tauri::command]
+pub async fn move_knowledge_markdown_to_folder(
+	app: AppHandle,
+	input: MoveKnowledgeMarkdownToFolderInput,
+) -> Result<SaveFileResult, String> {
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let from = resolve_in_root(&root, &input.file_path)?;
+	if !from.is_file() || !is_md_file_path(&from) {
+		return Err("文件不存在或不是 .md 文件".to_string());
+	}
+	let folder = resolve_in_root(&root, &input.target_folder)?;
+	if !folder.is_dir() {
+		return Err(format!("目标文件夹不存在：{}", folder.display()));
+	}
+	let to = folder.join(file_name_of(&from));
+	if to == from {
+		return Ok(ok_result(&to, "位置未变化".to_string()));
+	}
+	if to.exists() {
+		if !input.overwrite {
+			return Err(format!("文件已存在：{}", to.display()));
+		}
+		// 被覆盖的同名笔记先移入回收站，可恢复
+		move_to_trash(&app, &to)?;
+	}
+	fs::rename(&from, &to).map_err(|e| e.to_string())?;
+	move_history(&from, &to)?;
+	let (from_key, to_key) = (
+		relative_key(&root, &from).unwrap_or_default(),
+		relative_key(&root, &to).unwrap_or_default(),
+	);
+	update_meta(&root, |m| rename_meta_prefix(m, &from_key, &to_key))?;
+	Ok(ok_result(&to, format!("已移动至 {}", folder.display())))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetKnowledgeItemOrderInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	/// 所属文件夹，空串为根目录
+	#[serde(default)]
+	pub folder: String,
+	/// 子项名称（文件夹名或笔记文件名，含 `.md`）的新顺序
+	pub order: Vec<String>,
+}
+
+/// 持久化某文件夹下子项的手动排序
+#[tauri::command]
+pub async fn set_knowledge_item_order(
+	app: AppHandle,
+	input: SetKnowledgeItemOrderInput,
+) -> Result<(), String> {
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let folder = resolve_in_root(&root, &input.folder)?;
+	let key = relative_key(&root, &folder).unwrap_or_default();
+	update_meta(&root, |m| {
+		if input.order.is_empty() {
+			m.order.remove(&key);
+		} else {
+			m.order.insert(key, input.order);
+		}
+	})
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetKnowledgeItemPinnedInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	/// 文件夹或笔记路径
+	pub path: String,
+	pub pinned: bool,
+}
+
+/// 置顶 / 取消置顶文件夹或笔记
+#[tauri::command]
+pub async fn set_knowledge_item_pinned(
+	app: AppHandle,
+	input: SetKnowledgeItemPinnedInput,
+) -> Result<(), String> {
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let target = resolve_in_root(&root, &input.path)?;
+	if target == root {
+		return Err("根目录不能置顶".to_string());
+	}
+	if !target.exists() {
+		return Err(format!("路径不存在：{}", target.display()));
+	}
+	let key = relative_key(&root, &target).unwrap_or_default();
+	update_meta(&root, |m| {
+		m.pinned.retain(|p| p != &key);
+		if input.pinned {
+			m.pinned.push(key);
+		}
+	})
+}
@@ -0,0 +1,444 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::command::knowledge::{
+	collect_md_files, is_md_file_path, knowledge_root_for, resolve_knowledge_root, write_file_atomic,
+};
+use crate::command::knowledge_chunk::{atx_heading, is_cjk, update_chunk_cache};
+use crate::command::knowledge_git::{auto_commit_notes, note_commit_message};
+use crate::command::knowledge_history::{
+	HistoryRetention, KnowledgeMarkdownDiff, line_diff, load_history_retention, snapshot_before_write,
+};
+use crate::command::knowledge_link::split_front_matter;
+use crate::command::knowledge_vault::{encode_note, read_note_text};
+
+// —— Markdown 格式化：无序列表标记统一为 `-`，标题 `#` 后单空格、去掉结尾 `#` 并与上下文空一行，
+// 管道表格按列对齐，连续空行合并为一行；front matter、代码块与 `$$` 公式块原样保留 ——
+
+#[derive(Debug, Clone, Copy)]
+enum Align {
+	None,
+	Left,
+	Center,
+	Right,
+}
+
+/// 显示宽度：CJK 与全角字符计 2
+fn display_width(s: &str) -> usize {
+	s.chars()
+		.map(|c| {
+			if is_cjk(c) || matches!(c as u32, 0x3000..=0x303F | 0xFF01..=0xFF60 | 0xFFE0..=0xFFE6) {
+				2
+			} else {
+				1
+			}
+		})
+		.sum()
+}
+
+/// 分隔线 `***`、`* * *`、`---`
+fn is_thematic_break(trimmed: &str) -> bool {
+	let marks: Vec<char> = trimmed.chars().filter(|c| !c.is_whitespace()).collect();
+	marks.len() >= 3 && matches!(marks[0], '*' | '-' | '_') && marks.iter().all(|c| *c == marks[0])
+}
+
+/// `*` / `+` 无序列表标记改为 `-`，保留缩进、任务框与行尾空格
+fn normalize_list_marker(line: &str) -> Option<String> {
+	let rest = line.trim_start_matches([' ', '\t']);
+	let indent = &line[..line.len() - rest.len()];
+	let mut chars = rest.chars();
+	if !matches!(chars.next(), Some('*' | '+')) || !matches!(chars.next(), Some(' ' | '\t')) {
+		return None;
+	}
+	if is_thematic_break(rest) {
+		return None;
+	}
+	Some(format!("{}-{}", indent, &rest[1..]))
+}
+
+/// ATX 标题去掉缩进，`#` 后单个空格，去掉结尾的 `#` 序列（须与正文以空白隔开，`C#` 保留）
+fn normalize_heading(line: &str) -> Option<String> {
+	let (level, _) = atx_heading(line)?;
+	let marks = "#".repeat(level);
+	let mut text = line.trim_start()[level..].trim();
+	let without_closing = text.trim_end_matches('#');
+	if without_closing.is_empty() || without_closing.ends_with([' ', '\t']) {
+		text = without_closing.trim_end();
+	}
+	Some(if text.is_empty() {
+		marks
+	} else {
+		format!("{} {}", marks, text)
+	})
+}
+
+/// 按未转义的 `|` 切分表格行，去掉首尾可选的 `|`
+fn split_row(line: &str) -> Vec<String> {
+	let mut t = line.trim();
+	t = t.strip_prefix('|').unwrap_or(t);
+	if t.ends_with('|') && !t.ends_with("\\|") {
+		t = &t[..t.len() - 1];
+	}
+	let mut cells = Vec::new();
+	let mut cur = String::new();
+	let mut escaped = false;
+	for c in t.chars() {
+		if c == '|' && !escaped {
+			cells.push(cur.trim().to_string());
+			cur.clear();
+		} else {
+			cur.push(c);
+		}
+		escaped = c == '\\' && !escaped;
+	}
+	cells.push(cur.trim().to_string());
+	cells
+}
+
+/// 表格分隔行 `| --- | :-: | --: |` → 各列对齐方式
+fn delimiter_row(line: &str) -> Option<Vec<Align>> {
+	if !line.contains('|') {
+		return None;
+	}
+	split_row(line)
+		.iter()
+		.map(|cell| {
+			let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+			if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+				return None;
+			}
+			Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+				(true, true) => Align::Center,
+				(true, false) => Align::Left,
+				(false, true) => Align::Right,
+				(false, false) => Align::None,
+			})
+		})
+		.collect()
+}
+
+fn pad_cell(text: &str, width: usize, align: Align) -> String {
+	let gap = width.saturating_sub(display_width(text));
+	match align {
+		Align::Right => format!("{}{}", " ".repeat(gap), text),
+		Align::Center => format!("{}{}{}", " ".repeat(gap / 2), text, " ".repeat(gap - gap / 2)),
+		Align::None | Align::Left => format!("{}{}", text, " ".repeat(gap)),
+	}
+}
+
+/// 表头、分隔行与数据行重排为等宽列；列数取各行最大值，缺失的单元格补空
+fn format_table(header: &str, aligns: &[Align], rows: &[&str]) -> Vec<String> {
+	let indent = &header[..header.len() - header.trim_start().len()];
+	let mut cells: Vec<Vec<String>> = std::iter::once(header).chain(rows.iter().copied()).map(split_row).collect();
+	let cols = cells.iter().map(Vec::len).max().unwrap_or(0).max(aligns.len());
+	for row in &mut cells {
+		row.resize(cols, String::new());
+	}
+	let align_of = |c: usize| aligns.get(c).copied().unwrap_or(Align::None);
+	let widths: Vec<usize> = (0..cols)
+		.map(|c| cells.iter().map(|r| display_width(&r[c])).max().unwrap_or(0).max(3))
+		.collect();
+	let render = |row: &[String]| {
+		let inner: Vec<String> = row
+			.iter()
+			.enumerate()
+			.map(|(c, text)| pad_cell(text, widths[c], align_of(c)))
+			.collect();
+		format!("{}| {} |", indent, inner.join(" | "))
+	};
+	let delimiter: Vec<String> = widths
+		.iter()
+		.enumerate()
+		.map(|(c, w)| match align_of(c) {
+			Align::None => "-".repeat(*w),
+			Align::Left => format!(":{}", "-".repeat(w - 1)),
+			Align::Right => format!("{}:", "-".repeat(w - 1)),
+			Align::Center => format!(":{}:", "-".repeat(w - 2)),
+		})
+		.collect();
+	let mut out = vec![render(&cells[0]), format!("{}| {} |", indent, delimiter.join(" | "))];
+	out.extend(cells[1..].iter().map(|r| render(r)));
+	out
+}
+
+/// 代码块 / 公式块起始标记：``` ``` ```、`~~~`（记录实际长度）或单独一行的 `$$`
+fn block_fence(trimmed: &str) -> Option<String> {
+	if trimmed.trim_end() == "$$" {
+		return Some("$$".to_string());
+	}
+	let ch = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+	let run: String = trimmed.chars().take_while(|c| *c == ch).collect();
+	(run.len() >= 3).then_some(run)
+}
+
+fn closes_fence(trimmed: &str, fence: &str) -> bool {
+	if fence == "$$" {
+		return trimmed.trim_end() == "$$";
+	}
+	let ch = fence.chars().next().unwrap_or('`');
+	let t = trimmed.trim_end();
+	t.len() >= fence.len() && t.chars().all(|c| c == ch)
+}
+
+/// 格式化整篇 Markdown；保持原有换行风格（CRLF / LF），结果以单个换行结尾
+pub(crate) fn format_markdown(content: &str) -> String {
+	let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+	let (front, body) = match split_front_matter(content) {
+		Some((len, _)) => content.split_at(len),
+		None => ("", content),
+	};
+	let lines: Vec<&str> = body.lines().collect();
+	let mut out: Vec<String> = Vec::with_capacity(lines.len());
+	// front matter 之后允许保留一个空行，无 front matter 时去掉开头空行
+	let push_blank = |out: &mut Vec<String>| {
+		if out.last().map_or(!front.is_empty(), |l| !l.is_empty()) {
+			out.push(String::new());
+		}
+	};
+	let mut fence: Option<String> = None;
+	let mut i = 0;
+	while i < lines.len() {
+		let line = lines[i];
+		let trimmed = line.trim_start();
+		i += 1;
+		if let Some(f) = &fence {
+			if closes_fence(trimmed, f) {
+				fence = None;
+			}
+			out.push(line.to_string());
+			continue;
+		}
+		if let Some(f) = block_fence(trimmed) {
+			fence = Some(f);
+			out.push(line.to_string());
+			continue;
+		}
+		if trimmed.is_empty() {
+			push_blank(&mut out);
+			continue;
+		}
+		if let Some(heading) = normalize_heading(line) {
+			if out.last().is_some_and(|l| !l.is_empty()) {
+				out.push(String::new());
+			}
+			out.push(heading);
+			out.push(String::new());
+			continue;
+		}
+		if line.contains('|')
+			&& let Some(aligns) = lines.get(i).and_then(|l| delimiter_row(l))
+			&& aligns.len() == split_row(line).len()
+		{
+			let start = i + 1;
+			let mut end = start;
+			while end < lines.len()
+				&& lines[end].contains('|')
+				&& block_fence(lines[end].trim_start()).is_none()
+			{
+				end += 1;
+			}
+			out.extend(format_table(line, &aligns, &lines[start..end]));
+			i = end;
+			continue;
+		}
+		out.push(normalize_list_marker(line).unwrap_or_else(|| line.to_string()));
+	}
+	while out.last().is_some_and(|l| l.is_empty()) {
+		out.pop();
+	}
+	if out.is_empty() {
+		return front.to_string();
+	}
+	let mut result = String::with_capacity(content.len() + 16);
+	result.push_str(front);
+	result.push_str(&out.join(newline));
+	result.push_str(newline);
+	result
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatKnowledgeMarkdownInput {
+	/// 只格式化这一篇；不传时格式化整个知识库
+	#[serde(default)]
+	pub file_path: Option<String>,
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	/// 为 true 时只返回差异，不写入
+	#[serde(default)]
+	pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeFormatChange {
+	pub path: String,
+	pub diff: KnowledgeMarkdownDiff,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeFormatResult {
+	pub scanned: usize,
+	/// 内容有变化的笔记及其差异
+	pub changed: Vec<KnowledgeFormatChange>,
+	/// 读取失败（加密知识库未解锁等）而跳过的笔记
+	pub skipped: Vec<String>,
+	pub dry_run: bool,
+	pub message: String,
+}
+
+struct FormatOutcome {
+	changed: Vec<KnowledgeFormatChange>,
+	/// 实际写入的笔记（dry-run 时为空）
+	written: Vec<PathBuf>,
+	skipped: Vec<String>,
+}
+
+/// 逐篇格式化；非 dry-run 时先生成历史快照再原子写入
+fn format_files(files: &[PathBuf], dry_run: bool, retention: HistoryRetention) -> Result<FormatOutcome, String> {
+	let mut outcome = FormatOutcome {
+		changed: Vec::new(),
+		written: Vec::new(),
+		skipped: Vec::new(),
+	};
+	for path in files {
+		let Ok(old) = read_note_text(path) else {
+			outcome.skipped.push(path.to_string_lossy().to_string());
+			continue;
+		};
+		let new = format_markdown(&old);
+		if new == old {
+			continue;
+		}
+		if !dry_run {
+			let bytes = encode_note(path, &new)?;
+			snapshot_before_write(path, &new, retention)?;
+			write_file_atomic(path, &bytes)?;
+			outcome.written.push(path.clone());
+		}
+		outcome.changed.push(KnowledgeFormatChange {
+			path: path.to_string_lossy().to_string(),
+			diff: line_diff(&old, &new, 3),
+		});
+	}
+	Ok(outcome)
+}
+
+/// 格式化单篇或整个知识库的 Markdown；`dryRun` 时只返回差异
+#[tauri::command]
+pub async fn format_knowledge_markdown(
+	app: AppHandle,
+	input: FormatKnowledgeMarkdownInput,
+) -> Result<KnowledgeFormatResult, String> {
+	let (root, files) = match input.file_path.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+		Some(fp) => {
+			let path = PathBuf::from(fp);
+			if !path.is_file() || !is_md_file_path(&path) {
+				return Err(format!("不是 Markdown 文件：{}", path.display()));
+			}
+			let root = knowledge_root_for(&app, input.dir_path.as_ref(), &path).await?;
+			(root, vec![path])
+		}
+		None => {
+			let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+			let mut files = Vec::new();
+			collect_md_files(&root, &mut files)?;
+			files.sort();
+			(root, files)
+		}
+	};
+	let scanned = files.len();
+	let dry_run = input.dry_run;
+	let retention = load_history_retention(&app).await;
+	let FormatOutcome {
+		changed,
+		written,
+		skipped,
+	} = tauri::async_runtime::spawn_blocking(move || format_files(&files, dry_run, retention))
+		.await
+		.map_err(|e| e.to_string())??;
+
+	let mut message = if dry_run {
+		format!("共 {} 篇笔记需要格式化", changed.len())
+	} else {
+		format!("已格式化 {} 篇笔记", written.len())
+	};
+	if !skipped.is_empty() {
+		message.push_str(&format!("（{} 篇无法读取，已跳过）", skipped.len()));
+	}
+	if !written.is_empty() {
+		let refs: Vec<&Path> = written.iter().map(PathBuf::as_path).collect();
+		let commit_message = if refs.len() == 1 {
+			note_commit_message("格式化", &root, &refs)
+		} else {
+			format!("docs(knowledge): 格式化 {} 篇笔记", refs.len())
+		};
+		if let Some(err) = auto_commit_notes(&app, &root, &refs, &commit_message).await {
+			message.push_str(&format!("（Git 自动提交失败：{}）", err));
+		}
+		// 分块缓存仅用于检索加速，更新失败不影响格式化结果
+		let _ = update_chunk_cache(&root, &refs);
+	}
+	Ok(KnowledgeFormatResult {
+		scanned,
+		changed,
+		skipped,
+		dry_run,
+		message,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn code_fences_are_left_untouched() {
+		let src = "```md\n*  item\n#Heading ##\n|a|b|\n|-|-|\n\n\n```\n* after\n";
+		assert_eq!(format_markdown(src), "```md\n*  item\n#Heading ##\n|a|b|\n|-|-|\n\n\n```\n- after\n");
+		// 较短的反引号行不会关闭较长的围栏
+		let src = "````\n```\n* inside\n````\n* outside\n";
+		assert_eq!(format_markdown(src), "````\n```\n* inside\n````\n- outside\n");
+		let src = "~~~\n+ tilde\n~~~\n";
+		assert_eq!(format_markdown(src), src);
+	}
+
+	#[test]
+	fn math_blocks_are_left_untouched() {
+		let src = "$$\n* a | b\n|---|---|\n# x\n$$\n+ item\n";
+		assert_eq!(format_markdown(src), "$$\n* a | b\n|---|---|\n# x\n$$\n- item\n");
+	}
+
+	#[test]
+	fn thematic_breaks_are_not_list_items() {
+		assert_eq!(format_markdown("* * *\n***\n*   item\n+ other\n  * nested\n"), "* * *\n***\n-   item\n- other\n  - nested\n");
+		assert_eq!(format_markdown("*emphasis*\n"), "*emphasis*\n");
+	}
+
+	#[test]
+	fn headings_keep_trailing_hash_in_words() {
+		assert_eq!(format_markdown("#  C#\ntext\n"), "# C#\n\ntext\n");
+		assert_eq!(format_markdown("intro\n## Title ##\n"), "intro\n\n## Title\n");
+		assert_eq!(format_markdown("### F# and C#   #\n"), "### F# and C#\n");
+		assert_eq!(format_markdown("#hashtag\n"), "#hashtag\n");
+	}
+
+	#[test]
+	fn tables_align_by_display_width_with_cjk() {
+		let src = "|名称|值|\n|:-|-:|\n|中文|1|\n|ab|12345|\n";
+		assert_eq!(
+			format_markdown(src),
+			"| 名称 |    值 |\n| :--- | ----: |\n| 中文 |     1 |\n| ab   | 12345 |\n"
+		);
+	}
+
+	#[test]
+	fn blank_lines_collapse_and_line_endings_are_kept() {
+		assert_eq!(format_markdown("\n\na\n\n\n\nb\n\n"), "a\n\nb\n");
+		assert_eq!(format_markdown("* a\r\n\r\n\r\n* b\r\n"), "- a\r\n\r\n- b\r\n");
+		assert_eq!(format_markdown("---\ntitle: x\n---\n\n\n* a\n"), "---\ntitle: x\n---\n\n- a\n");
+	}
+}
@@ -0,0 +1,726 @@
+use std::cell::RefCell;
+use std::path::Path;
+
+use git2::{
+	BranchType, Cred, CredentialType, FetchOptions, IndexAddOption, ObjectType, Oid, PushOptions,
+	RemoteCallbacks, Repository, Signature, Status, StatusOptions, build::CheckoutBuilder,
+};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::command::knowledge::resolve_knowledge_root;
+use crate::command::knowledge_chunk::INDEX_DIR_NAME;
+use crate::command::knowledge_history::HISTORY_DIR_NAME;
+use crate::utils::common::get_store_value;
+
+// —— 知识库 Git 集成（libgit2）：保存 / 删除时自动提交，查看状态与单文件历史，拉取 / 推送到远程 ——
+
+const DEFAULT_REMOTE: &str = "origin";
+const DEFAULT_AUTHOR_NAME: &str = "dnhyxc-ai";
+const DEFAULT_AUTHOR_EMAIL: &str = "knowledge@dnhyxc-ai.local";
+const DEFAULT_LOG_LIMIT: usize = 50;
+
+/// store 中的 Git 配置：`knowledgeGitEnabled` / `knowledgeGitRemote` / `knowledgeGitBranch` /
+/// `knowledgeGitUsername` / `knowledgeGitToken`（HTTPS 访问令牌，SSH 走 ssh-agent）
+#[derive(Debug, Clone, Default)]
+pub(crate) struct KnowledgeGitSettings {
+	pub enabled: bool,
+	pub remote: String,
+	pub branch: Option<String>,
+	pub username: Option<String>,
+	pub token: Option<String>,
+}
+
+async fn store_string(app: &AppHandle, key: &str) -> Option<String> {
+	get_store_value(app, key)
+		.await
+		.ok()
+		.map(|v| v.trim().to_string())
+		.filter(|v| !v.is_empty() && v != "null")
+}
+
+pub(crate) async fn load_git_settings(app: &AppHandle) -> KnowledgeGitSettings {
+	KnowledgeGitSettings {
+		enabled: store_string(app, "knowledgeGitEnabled").await.as_deref() == Some("true"),
+		remote: store_string(app, "knowledgeGitRemote")
+			.await
+			.unwrap_or_else(|| DEFAULT_REMOTE.to_string()),
+		branch: store_string(app, "knowledgeGitBranch").await,
+		username: store_string(app, "knowledgeGitUsername").await,
+		token: store_string(app, "knowledgeGitToken").await,
+	}
+}
+
+/// 知识库目录所在的仓库及知识库相对仓库工作区的前缀（`/` 分隔，根目录即仓库时为空）
+fn open_repo(root: &Path) -> Result<(Repository, String), String> {
+	let repo = Repository::discover(root).map_err(|_| format!("{} 不在 Git 仓库中", root.display()))?;
+	let workdir = repo
+		.workdir()
+		.ok_or("不支持裸仓库作为知识库目录")?
+		.canonicalize()
+		.map_err(|e| e.to_string())?;
+	let root = root.canonicalize().map_err(|e| e.to_string())?;
+	let prefix = root
+		.strip_prefix(&workdir)
+		.map(|p| p.to_string_lossy().replace('\\', "/"))
+		.unwrap_or_default();
+	Ok((repo, prefix))
+}
+
+fn repo_path(repo: &Repository, file: &Path) -> Option<String> {
+	let workdir = repo.workdir()?.canonicalize().ok()?;
+	// 已删除的文件无法 canonicalize，退化为规范化父目录后拼接文件名
+	let file = file.canonicalize().ok().or_else(|| {
+		let parent = file.parent()?.canonicalize().ok()?;
+		Some(parent.join(file.file_name()?))
+	})?;
+	file.strip_prefix(&workdir)
+		.ok()
+		.map(|p| p.to_string_lossy().replace('\\', "/"))
+}
+
+fn strip_prefix<'a>(prefix: &str, path: &'a str) -> &'a str {
+	if prefix.is_empty() {
+		path
+	} else {
+		path.strip_prefix(prefix)
+			.and_then(|p| p.strip_prefix('/'))
+			.unwrap_or(path)
+	}
+}
+
+fn signature(repo: &Repository) -> Result<Signature<'static>, String> {
+	repo.signature()
+		.map(|s| s.to_owned())
+		.or_else(|_| Signature::now(DEFAULT_AUTHOR_NAME, DEFAULT_AUTHOR_EMAIL))
+		.map_err(|e| e.to_string())
+}
+
+/// 暂存区与 HEAD 不同时提交，返回新提交 id
+fn commit_index(repo: &Repository, message: &str) -> Result<Option<Oid>, String> {
+	let mut index = repo.index().map_err(|e| e.to_string())?;
+	let tree_id = index.write_tree().map_err(|e| e.to_string())?;
+	let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+	if parent.as_ref().is_some_and(|p| p.tree_id() == tree_id) {
+		return Ok(None);
+	}
+	let tree = repo.find_tree(tree_id).map_err(|e| e.to_string())?;
+	let sig = signature(repo)?;
+	let parents: Vec<&git2::Commit> = parent.iter().collect();
+	repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+		.map(Some)
+		.map_err(|e| format!("提交失败: {}", e))
+}
+
+/// 暂存指定文件（存在则添加，不存在则从索引移除）并提交
+fn commit_paths(repo: &Repository, paths: &[String], message: &str) -> Result<Option<Oid>, String> {
+	let workdir = repo.workdir().ok_or("不支持裸仓库")?.to_path_buf();
+	let mut index = repo.index().map_err(|e| e.to_string())?;
+	for p in paths {
+		if workdir.join(p).is_file() {
+			index.add_path(Path::new(p)).map_err(|e| e.to_string())?;
+		} else {
+			let _ = index.remove_path(Path::new(p));
+		}
+	}
+	index.write().map_err(|e| e.to_string())?;
+	commit_index(repo, message)
+}
+
+/// 仅属于本机的目录（历史快照、检索缓存），不纳入版本控制
+fn is_local_only_dir(name: &str) -> bool {
+	name == HISTORY_DIR_NAME || name == INDEX_DIR_NAME
+}
+
+/// 暂存知识库目录下的全部改动（忽略 `.history` 快照与 `.index` 检索缓存）并提交
+fn commit_all(repo: &Repository, prefix: &str, message: &str) -> Result<Option<Oid>, String> {
+	let mut index = repo.index().map_err(|e| e.to_string())?;
+	let spec = if prefix.is_empty() { "*".to_string() } else { format!("{}/*", prefix) };
+	let mut skip_local_only = |path: &Path, _: &[u8]| -> i32 {
+		let hidden = path
+			.components()
+			.any(|c| is_local_only_dir(&c.as_os_str().to_string_lossy()));
+		if hidden { 1 } else { 0 }
+	};
+	index
+		.add_all([spec.as_str()], IndexAddOption::DEFAULT, Some(&mut skip_local_only))
+		.map_err(|e| e.to_string())?;
+	index.update_all([spec.as_str()], None).map_err(|e| e.to_string())?;
+	index.write().map_err(|e| e.to_string())?;
+	commit_index(repo, message)
+}
+
+/// 笔记保存 / 删除后的自动提交。未启用或知识库不在仓库中时静默跳过；返回失败原因供调用方附加到提示中
+pub(crate) async fn auto_commit_notes(app: &AppHandle, root: &Path, files: &[&Path], message: &str) -> Option<String> {
+	if !load_git_settings(app).await.enabled {
+		return None;
+	}
+	let (repo, _) = open_repo(root).ok()?;
+	let paths: Vec<String> = files.iter().filter_map(|f| repo_path(&repo, f)).collect();
+	if paths.is_empty() {
+		return None;
+	}
+	commit_paths(&repo, &paths, message).err()
+}
+
+/// 自动提交信息：`docs(knowledge): 更新 笔记.md`
+pub(crate) fn note_commit_message(action: &str, root: &Path, files: &[&Path]) -> String {
+	let names: Vec<String> = files
+		.iter()
+		.map(|f| {
+			f.strip_prefix(root)
+				.unwrap_or(f)
+				.to_string_lossy()
+				.replace('\\', "/")
+		})
+		.collect();
+	format!("docs(knowledge): {} {}", action, names.join(" → "))
+}
+
+fn credentials_callbacks<'a>(settings: &'a KnowledgeGitSettings, repo: &'a Repository) -> RemoteCallbacks<'a> {
+	let mut callbacks = RemoteCallbacks::new();
+	// libgit2 认证失败时会反复回调，限制尝试次数
+	let attempts = RefCell::new(0u8);
+	callbacks.credentials(move |url, username, allowed| {
+		*attempts.borrow_mut() += 1;
+		if *attempts.borrow() > 4 {
+			return Err(git2::Error::from_str("认证失败，请检查 Git 凭据配置"));
+		}
+		if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+			if let Some(token) = settings.token.as_deref() {
+				let user = settings.username.as_deref().or(username).unwrap_or("git");
+				return Cred::userpass_plaintext(user, token);
+			}
+			if let Ok(config) = repo.config()
+				&& let Ok(cred) = Cred::credential_helper(&config, url, username)
+			{
+				return Ok(cred);
+			}
+		}
+		if allowed.contains(CredentialType::SSH_KEY) {
+			return Cred::ssh_key_from_agent(username.unwrap_or("git"));
+		}
+		Cred::default()
+	});
+	callbacks
+}
+
+fn current_branch(repo: &Repository, settings: &KnowledgeGitSettings) -> Result<String, String> {
+	if let Some(b) = settings.branch.clone() {
+		return Ok(b);
+	}
+	let head = repo.head().map_err(|_| "仓库尚无提交".to_string())?;
+	head.shorthand()
+		.filter(|_| head.is_branch())
+		.map(str::to_string)
+		.ok_or_else(|| "当前处于分离 HEAD 状态，请先切换到分支".to_string())
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeGitInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeGitFileStatus {
+	/// 相对知识库根目录
+	pub path: String,
+	/// `new` / `modified` / `deleted` / `renamed` / `conflicted`
+	pub status: String,
+	pub staged: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeGitStatus {
+	/// 知识库是否位于 Git 仓库中
+	pub is_repo: bool,
+	/// 设置中是否开启自动提交
+	pub auto_commit: bool,
+	pub repo_root: Option<String>,
+	pub branch: Option<String>,
+	pub remote: Option<String>,
+	pub remote_url: Option<String>,
+	/// 本地领先 / 落后远程跟踪分支的提交数
+	pub ahead: usize,
+	pub behind: usize,
+	pub files: Vec<KnowledgeGitFileStatus>,
+}
+
+fn status_label(s: Status) -> (&'static str, bool) {
+	if s.is_conflicted() {
+		("conflicted", false)
+	} else if s.contains(Status::INDEX_NEW) {
+		("new", true)
+	} else if s.contains(Status::INDEX_DELETED) {
+		("deleted", true)
+	} else if s.contains(Status::INDEX_RENAMED) {
+		("renamed", true)
+	} else if s.contains(Status::INDEX_MODIFIED) {
+		("modified", true)
+	} else if s.contains(Status::WT_NEW) {
+		("new", false)
+	} else if s.contains(Status::WT_DELETED) {
+		("deleted", false)
+	} else if s.contains(Status::WT_RENAMED) {
+		("renamed", false)
+	} else {
+		("modified", false)
+	}
+}
+
+/// 知识库 Git 状态：分支、与远程的差异、未提交的文件
+#[tauri::command]
+pub async fn get_knowledge_git_status(
+	app: AppHandle,
+	input: Option<KnowledgeGitInput>,
+) -> Result<KnowledgeGitStatus, String> {
+	let input = input.unwrap_or_default();
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let settings = load_git_settings(&app).await;
+	let Ok((repo, prefix)) = open_repo(&root) else {
+		return Ok(KnowledgeGitStatus {
+			is_repo: false,
+			auto_commit: settings.enabled,
+			repo_root: None,
+			branch: None,
+			remote: None,
+			remote_url: None,
+			ahead: 0,
+			behind: 0,
+			files: Vec::new(),
+		});
+	};
+
+	let mut opts = StatusOptions::new();
+	opts.include_untracked(true).recurse_untracked_dirs(true);
+	if !prefix.is_empty() {
+		opts.pathspec(&prefix);
+	}
+	let statuses = repo.statuses(Some(&mut opts)).map_err(|e| e.to_string())?;
+	let files = statuses
+		.iter()
+		.filter_map(|entry| {
+			let path = entry.path()?.to_string();
+			if path.split('/').any(is_local_only_dir) {
+				return None;
+			}
+			let (status, staged) = status_label(entry.status());
+			Some(KnowledgeGitFileStatus {
+				path: strip_prefix(&prefix, &path).to_string(),
+				status: status.to_string(),
+				staged,
+			})
+		})
+		.collect();
+
+	let branch = current_branch(&repo, &settings).ok();
+	let (mut ahead, mut behind) = (0, 0);
+	if let Some(b) = branch.as_deref()
+		&& let Ok(local) = repo.find_branch(b, BranchType::Local)
+		&& let Ok(upstream) = local.upstream()
+		&& let (Some(l), Some(u)) = (local.get().target(), upstream.get().target())
+		&& let Ok((a, b)) = repo.graph_ahead_behind(l, u)
+	{
+		ahead = a;
+		behind = b;
+	}
+	let remote_url = repo
+		.find_remote(&settings.remote)
+		.ok()
+		.and_then(|r| r.url().map(str::to_string));
+	Ok(KnowledgeGitStatus {
+		is_repo: true,
+		auto_commit: settings.enabled,
+		repo_root: repo.workdir().map(|p| p.to_string_lossy().to_string()),
+		branch,
+		remote: remote_url.as_ref().map(|_| settings.remote.clone()),
+		remote_url,
+		ahead,
+		behind,
+		files,
+	})
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeGitLogInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	/// 只看某篇笔记的提交；缺省为整个知识库目录
+	#[serde(default)]
+	pub file_path: Option<String>,
+	#[serde(default)]
+	pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeGitCommit {
+	pub id: String,
+	pub short_id: String,
+	pub summary: String,
+	pub message: String,
+	pub author: String,
+	pub email: String,
+	pub time_ms: i64,
+}
+
+/// 提交中 `path`（文件或目录前缀）对应的对象 id
+fn entry_id(commit: &git2::Commit, path: &str) -> Option<Oid> {
+	if path.is_empty() {
+		return Some(commit.tree_id());
+	}
+	commit.tree().ok()?.get_path(Path::new(path)).ok().map(|e| e.id())
+}
+
+/// 提交历史；传入 `filePath` 时只返回改动过该笔记的提交
+#[tauri::command]
+pub async fn get_knowledge_git_log(
+	app: AppHandle,
+	input: KnowledgeGitLogInput,
+) -> Result<Vec<KnowledgeGitCommit>, String> {
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let (repo, prefix) = open_repo(&root)?;
+	let target = match input.file_path.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+		Some(fp) => repo_path(&repo, Path::new(fp)).ok_or("文件不在知识库仓库中")?,
+		None => prefix,
+	};
+	let limit = input.limit.filter(|n| *n > 0).unwrap_or(DEFAULT_LOG_LIMIT);
+
+	let mut walk = repo.revwalk().map_err(|e| e.to_string())?;
+	if walk.push_head().is_err() {
+		return Ok(Vec::new());
+	}
+	walk.set_sorting(git2::Sort::TIME).map_err(|e| e.to_string())?;
+	let mut out = Vec::new();
+	for oid in walk {
+		let Ok(oid) = oid else { continue };
+		let Ok(commit) = repo.find_commit(oid) else { continue };
+		let current = entry_id(&commit, &target);
+		// 与任一父提交相比该路径有变化（首个提交则看是否存在）
+		let changed = if commit.parent_count() == 0 {
+			current.is_some()
+		} else {
+			commit
+				.parents()
+				.all(|p| entry_id(&p, &target) != current)
+		};
+		if !changed {
+			continue;
+		}
+		let author = commit.author();
+		out.push(KnowledgeGitCommit {
+			id: oid.to_string(),
+			short_id: oid.to_string().chars().take(7).collect(),
+			summary: commit.summary().unwrap_or_default().to_string(),
+			message: commit.message().unwrap_or_default().to_string(),
+			author: author.name().unwrap_or_default().to_string(),
+			email: author.email().unwrap_or_default().to_string(),
+			time_ms: commit.time().seconds() * 1000,
+		});
+		if out.len() >= limit {
+			break;
+		}
+	}
+	Ok(out)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitKnowledgeGitInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	#[serde(default)]
+	pub message: Option<String>,
+}
+
+/// 手动提交知识库目录下的全部改动
+#[tauri::command]
+pub async fn commit_knowledge_git(
+	app: AppHandle,
+	input: CommitKnowledgeGitInput,
+) -> Result<Option<String>, String> {
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let (repo, prefix) = open_repo(&root)?;
+	let message = input
+		.message
+		.as_deref()
+		.map(str::trim)
+		.filter(|s| !s.is_empty())
+		.unwrap_or("docs(knowledge): 更新笔记");
+	Ok(commit_all(&repo, &prefix, message)?.map(|oid| oid.to_string()))
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeGitConflict {
+	/// 相对知识库根目录
+	pub path: String,
+	/// 共同祖先 / 本地 / 远程版本内容；文件在对应一侧不存在或非文本时为 None
+	pub base: Option<String>,
+	pub ours: Option<String>,
+	pub theirs: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeGitSyncResult {
+	/// 拉取：`upToDate` / `fastForward` / `merged` / `conflict`；推送：`pushed` / `upToDate` / `rejected`
+	pub status: String,
+	pub branch: String,
+	/// 同步前自动提交的本地改动
+	pub local_commit: Option<String>,
+	pub conflicts: Vec<KnowledgeGitConflict>,
+	pub message: String,
+}
+
+fn blob_text(repo: &Repository, entry: Option<&git2::IndexEntry>) -> Option<String> {
+	let blob = repo.find_blob(entry?.id).ok()?;
+	std::str::from_utf8(blob.content()).ok().map(str::to_string)
+}
+
+/// 检出到指定提交：只更新有变化的文件，工作区内未提交改动会阻止覆盖
+fn checkout_tree(repo: &Repository, oid: Oid) -> Result<(), String> {
+	let obj = repo.find_object(oid, Some(ObjectType::Commit)).map_err(|e| e.to_string())?;
+	repo.checkout_tree(&obj, Some(CheckoutBuilder::new().safe()))
+		.map_err(|e| format!("检出失败: {}", e))
+}
+
+/// 拉取远程分支并合并：能快进则快进，否则生成合并提交；有冲突时不改动工作区，返回冲突文件及三方内容
+#[tauri::command]
+pub async fn pull_knowledge_git(
+	app: AppHandle,
+	input: Option<KnowledgeGitInput>,
+) -> Result<KnowledgeGitSyncResult, String> {
+	let input = input.unwrap_or_default();
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let settings = load_git_settings(&app).await;
+	tauri::async_runtime::spawn_blocking(move || pull_blocking(&root, &settings))
+		.await
+		.map_err(|e| e.to_string())?
+}
+
+fn pull_blocking(root: &Path, settings: &KnowledgeGitSettings) -> Result<KnowledgeGitSyncResult, String> {
+	let (repo, prefix) = open_repo(root)?;
+	let branch = current_branch(&repo, settings)?;
+	let local_commit = commit_all(&repo, &prefix, "docs(knowledge): 同步前提交本地修改")?.map(|o| o.to_string());
+
+	let mut remote = repo
+		.find_remote(&settings.remote)
+		.map_err(|_| format!("未配置远程仓库：{}", settings.remote))?;
+	let mut fetch = FetchOptions::new();
+	fetch.remote_callbacks(credentials_callbacks(settings, &repo));
+	remote
+		.fetch(&[branch.as_str()], Some(&mut fetch), None)
+		.map_err(|e| format!("拉取失败: {}", e))?;
+	drop(remote);
+
+	let remote_ref = format!("refs/remotes/{}/{}", settings.remote, branch);
+	let result = |status: &str, conflicts: Vec<KnowledgeGitConflict>, message: String| KnowledgeGitSyncResult {
+		status: status.to_string(),
+		branch: branch.clone(),
+		local_commit: local_commit.clone(),
+		conflicts,
+		message,
+	};
+	let Ok(theirs) = repo.refname_to_id(&remote_ref) else {
+		return Ok(result("upToDate", Vec::new(), "远程分支尚不存在".to_string()));
+	};
+	let local_ref = format!("refs/heads/{}", branch);
+	let Ok(ours) = repo.refname_to_id(&local_ref) else {
+		// 本地分支尚无提交：直接以远程为准
+		repo.reference(&local_ref, theirs, true, "knowledge pull: 初始化分支")
+			.map_err(|e| e.to_string())?;
+		repo.set_head(&local_ref).map_err(|e| e.to_string())?;
+		checkout_tree(&repo, theirs)?;
+		return Ok(result("fastForward", Vec::new(), "已拉取远程内容".to_string()));
+	};
+
+	let annotated = repo.find_annotated_commit(theirs).map_err(|e| e.to_string())?;
+	let (analysis, _) = repo.merge_analysis(&[&annotated]).map_err(|e| e.to_string())?;
+	if analysis.is_up_to_date() {
+		return Ok(result("upToDate", Vec::new(), "已是最新".to_string()));
+	}
+	if analysis.is_fast_forward() {
+		checkout_tree(&repo, theirs)?;
+		repo.find_reference(&local_ref)
+			.and_then(|mut r| r.set_target(theirs, "knowledge pull: fast-forward"))
+			.map_err(|e| e.to_string())?;
+		return Ok(result("fastForward", Vec::new(), "已快进到远程最新提交".to_string()));
+	}
+
+	let our_commit = repo.find_commit(ours).map_err(|e| e.to_string())?;
+	let their_commit = repo.find_commit(theirs).map_err(|e| e.to_string())?;
+	let mut merged = repo
+		.merge_commits(&our_commit, &their_commit, None)
+		.map_err(|e| format!("合并失败: {}", e))?;
+	if merged.has_conflicts() {
+		let conflicts = merged
+			.conflicts()
+			.map_err(|e| e.to_string())?
+			.flatten()
+			.map(|c| {
+				let path = [&c.our, &c.their, &c.ancestor]
+					.into_iter()
+					.flatten()
+					.next()
+					.map(|e| String::from_utf8_lossy(&e.path).to_string())
+					.unwrap_or_default();
+				KnowledgeGitConflict {
+					path: strip_prefix(&prefix, &path).to_string(),
+					base: blob_text(&repo, c.ancestor.as_ref()),
+					ours: blob_text(&repo, c.our.as_ref()),
+					theirs: blob_text(&repo, c.their.as_ref()),
+				}
+			})
+			.collect::<Vec<_>>();
+		let message = format!("有 {} 个文件冲突，请解决后再同步", conflicts.len());
+		return Ok(result("conflict", conflicts, message));
+	}
+	let tree_id = merged.write_tree_to(&repo).map_err(|e| e.to_string())?;
+	let tree = repo.find_tree(tree_id).map_err(|e| e.to_string())?;
+	let sig = signature(&repo)?;
+	let message = format!("Merge {}/{} into {}", settings.remote, branch, branch);
+	let merge_oid = repo
+		.commit(None, &sig, &sig, &message, &tree, &[&our_commit, &their_commit])
+		.map_err(|e| format!("提交合并失败: {}", e))?;
+	checkout_tree(&repo, merge_oid)?;
+	repo.find_reference(&local_ref)
+		.and_then(|mut r| r.set_target(merge_oid, "knowledge pull: merge"))
+		.map_err(|e| e.to_string())?;
+	Ok(result("merged", Vec::new(), "已合并远程修改".to_string()))
+}
+
+/// 推送当前分支到远程；远程有新提交时返回 `rejected`，需先拉取
+#[tauri::command]
+pub async fn push_knowledge_git(
+	app: AppHandle,
+	input: Option<KnowledgeGitInput>,
+) -> Result<KnowledgeGitSyncResult, String> {
+	let input = input.unwrap_or_default();
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let settings = load_git_settings(&app).await;
+	tauri::async_runtime::spawn_blocking(move || push_blocking(&root, &settings))
+		.await
+		.map_err(|e| e.to_string())?
+}
+
+fn push_blocking(root: &Path, settings: &KnowledgeGitSettings) -> Result<KnowledgeGitSyncResult, String> {
+	let (repo, prefix) = open_repo(root)?;
+	let branch = current_branch(&repo, settings)?;
+	let local_commit = commit_all(&repo, &prefix, "docs(knowledge): 推送前提交本地修改")?.map(|o| o.to_string());
+	let local_ref = format!("refs/heads/{}", branch);
+	let local = repo.refname_to_id(&local_ref).map_err(|_| "本地分支尚无提交".to_string())?;
+	let remote_ref = format!("refs/remotes/{}/{}", settings.remote, branch);
+	let up_to_date = repo.refname_to_id(&remote_ref).ok() == Some(local);
+
+	let mut remote = repo
+		.find_remote(&settings.remote)
+		.map_err(|_| format!("未配置远程仓库：{}", settings.remote))?;
+	let rejected: RefCell<Option<String>> = RefCell::new(None);
+	let mut callbacks = credentials_callbacks(settings, &repo);
+	callbacks.push_update_reference(|_, status| {
+		if let Some(msg) = status {
+			*rejected.borrow_mut() = Some(msg.to_string());
+		}
+		Ok(())
+	});
+	let mut opts = PushOptions::new();
+	opts.remote_callbacks(callbacks);
+	let refspec = format!("{}:{}", local_ref, local_ref);
+	let pushed = remote.push(&[refspec.as_str()], Some(&mut opts));
+	drop(opts);
+	let result = |status: &str, message: String| KnowledgeGitSyncResult {
+		status: status.to_string(),
+		branch: branch.clone(),
+		local_commit: local_commit.clone(),
+		conflicts: Vec::new(),
+		message,
+	};
+	match pushed {
+		Err(e) if e.code() == git2::ErrorCode::NotFastForward => {
+			return Ok(result("rejected", "远程有新的提交，请先拉取".to_string()));
+		}
+		Err(e) => return Err(format!("推送失败: {}", e)),
+		Ok(()) => {}
+	}
+	if let Some(reason) = rejected.into_inner() {
+		return Ok(result("rejected", format!("远程拒绝更新：{}", reason)));
+	}
+	// 同步远程跟踪分支，便于状态中计算领先 / 落后
+	let _ = repo.reference(&remote_ref, local, true, "knowledge push");
+	if up_to_date {
+		Ok(result("upToDate", "远程已是最新".to_string()))
+	} else {
+		Ok(result("pushed", format!("已推送到 {}/{}", settings.remote, branch)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use super::*;
+
+	fn paths(list: &[&str]) -> Vec<String> {
+		list.iter().map(|s| s.to_string()).collect()
+	}
+
+	#[test]
+	fn commit_paths_stages_only_listed_files() {
+		let tmp = tempfile::tempdir().unwrap();
+		let repo = Repository::init(tmp.path()).unwrap();
+		fs::write(tmp.path().join("a.md"), "# A").unwrap();
+		fs::write(tmp.path().join("draft.md"), "# 草稿").unwrap();
+
+		let first = commit_paths(&repo, &paths(&["a.md"]), "add a").unwrap().unwrap();
+		let tree = repo.find_commit(first).unwrap().tree().unwrap();
+		assert!(tree.get_path(Path::new("a.md")).is_ok());
+		assert!(tree.get_path(Path::new("draft.md")).is_err());
+
+		// 内容未变时不产生空提交
+		assert_eq!(commit_paths(&repo, &paths(&["a.md"]), "noop").unwrap(), None);
+
+		// 已删除的文件从索引移除（重命名）
+		fs::rename(tmp.path().join("a.md"), tmp.path().join("b.md")).unwrap();
+		let second = commit_paths(&repo, &paths(&["a.md", "b.md"]), "rename").unwrap().unwrap();
+		let commit = repo.find_commit(second).unwrap();
+		assert_eq!(commit.parent_id(0).unwrap(), first);
+		let tree = commit.tree().unwrap();
+		assert!(tree.get_path(Path::new("a.md")).is_err());
+		assert!(tree.get_path(Path::new("b.md")).is_ok());
+	}
+
+	#[test]
+	fn commit_paths_then_push_to_bare_remote() {
+		let tmp = tempfile::tempdir().unwrap();
+		let remote_dir = tmp.path().join("remote.git");
+		Repository::init_bare(&remote_dir).unwrap();
+		let work = tmp.path().join("work");
+		let repo = Repository::clone(remote_dir.to_str().unwrap(), &work).unwrap();
+
+		fs::write(work.join("note.md"), "# 笔记").unwrap();
+		let oid = commit_paths(&repo, &paths(&["note.md"]), "add note").unwrap().unwrap();
+
+		let settings = KnowledgeGitSettings {
+			enabled: true,
+			remote: DEFAULT_REMOTE.to_string(),
+			..Default::default()
+		};
+		let pushed = push_blocking(&work, &settings).unwrap();
+		assert_eq!(pushed.status, "pushed");
+		assert_eq!(pushed.local_commit, None);
+		let bare = Repository::open_bare(&remote_dir).unwrap();
+		let remote_head = bare
+			.refname_to_id(&format!("refs/heads/{}", pushed.branch))
+			.unwrap();
+		assert_eq!(remote_head, oid);
+
+		let again = push_blocking(&work, &settings).unwrap();
+		assert_eq!(again.status, "upToDate");
+	}
+}
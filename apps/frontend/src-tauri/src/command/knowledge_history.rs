@@ -0,0 +1,348 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use tauri::AppHandle;
+
+use crate::command::knowledge::{is_md_file_path, write_file_atomic};
+use crate::command::knowledge_vault::{decode_note, encode_note, is_encrypted_note, read_note_text};
+use crate::types::common::SaveFileResult;
+use crate::utils::common::get_store_value;
+
+// —— 知识 Markdown 历史版本：覆盖写入前自动快照到笔记同级的 `.history/<文件名>/<毫秒时间戳>.md` ——
+
+/// 历史快照根目录名；以 `.` 开头，`collect_md_files` 会自动跳过
+pub(crate) const HISTORY_DIR_NAME: &str = ".history";
+/// 与前端约定：版本 id 为 `current` 时表示磁盘上的当前内容
+const CURRENT_VERSION_ID: &str = "current";
+const DEFAULT_MAX_VERSIONS: usize = 50;
+const DEFAULT_MAX_DAYS: u64 = 90;
+
+/// 保留策略：store 中 `knowledgeHistoryMaxVersions` / `knowledgeHistoryMaxDays`，未设置或非法时用默认值
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct HistoryRetention {
+	pub max_versions: usize,
+	/// 0 表示不按时间清理
+	pub max_days: u64,
+}
+
+impl Default for HistoryRetention {
+	fn default() -> Self {
+		Self {
+			max_versions: DEFAULT_MAX_VERSIONS,
+			max_days: DEFAULT_MAX_DAYS,
+		}
+	}
+}
+
+pub(crate) async fn load_history_retention(app: &AppHandle) -> HistoryRetention {
+	let mut retention = HistoryRetention::default();
+	if let Ok(v) = get_store_value(app, "knowledgeHistoryMaxVersions").await
+		&& let Ok(n) = v.trim().parse::<usize>()
+		&& n > 0
+	{
+		retention.max_versions = n;
+	}
+	if let Ok(v) = get_store_value(app, "knowledgeHistoryMaxDays").await
+		&& let Ok(n) = v.trim().parse::<u64>()
+	{
+		retention.max_days = n;
+	}
+	retention
+}
+
+fn now_ms() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_millis() as u64)
+		.unwrap_or(0)
+}
+
+/// 某篇笔记的快照目录：`<笔记目录>/.history/<文件名>/`
+pub(crate) fn history_dir_for(note: &Path) -> Option<PathBuf> {
+	let parent = note.parent()?;
+	let name = note.file_name()?;
+	Some(parent.join(HISTORY_DIR_NAME).join(name))
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeMarkdownVersion {
+	/// 快照 id（毫秒时间戳字符串）
+	pub id: String,
+	pub created_at_ms: u64,
+	pub size: u64,
+}
+
+/// 按时间倒序列出快照
+fn list_versions(note: &Path) -> Result<Vec<KnowledgeMarkdownVersion>, String> {
+	let Some(dir) = history_dir_for(note) else {
+		return Ok(Vec::new());
+	};
+	if !dir.is_dir() {
+		return Ok(Vec::new());
+	}
+	let mut out = Vec::new();
+	for ent in fs::read_dir(&dir).map_err(|e| e.to_string())? {
+		let ent = ent.map_err(|e| e.to_string())?;
+		let p = ent.path();
+		if !is_md_file_path(&p) {
+			continue;
+		}
+		let Some(ms) = p
+			.file_stem()
+			.and_then(|s| s.to_str())
+			.and_then(|s| s.parse::<u64>().ok())
+		else {
+			continue;
+		};
+		let size = ent.metadata().map(|m| m.len()).unwrap_or(0);
+		out.push(KnowledgeMarkdownVersion {
+			id: ms.to_string(),
+			created_at_ms: ms,
+			size,
+		});
+	}
+	out.sort_by_key(|v| Reverse(v.created_at_ms));
+	Ok(out)
+}
+
+/// 超出条数或天数的旧快照删除
+fn prune_versions(note: &Path, retention: HistoryRetention) -> Result<(), String> {
+	let Some(dir) = history_dir_for(note) else {
+		return Ok(());
+	};
+	let versions = list_versions(note)?;
+	let cutoff_ms = if retention.max_days > 0 {
+		now_ms().saturating_sub(Duration::from_secs(retention.max_days * 86_400).as_millis() as u64)
+	} else {
+		0
+	};
+	for (i, v) in versions.iter().enumerate() {
+		if i >= retention.max_versions || v.created_at_ms < cutoff_ms {
+			let _ = fs::remove_file(dir.join(format!("{}.md", v.id)));
+		}
+	}
+	Ok(())
+}
+
+/// 写入 `new_content` 前为磁盘上的旧内容生成快照；文件不存在或内容未变时不生成。返回快照 id
+pub(crate) fn snapshot_before_write(
+	note: &Path,
+	new_content: &str,
+	retention: HistoryRetention,
+) -> Result<Option<String>, String> {
+	if !note.is_file() {
+		return Ok(None);
+	}
+	let old = fs::read(note).map_err(|e| e.to_string())?;
+	if old == new_content.as_bytes() || old.is_empty() {
+		return Ok(None);
+	}
+	// 加密笔记磁盘上是密文，按明文比对；快照仍保存原始密文
+	if is_encrypted_note(&old)
+		&& decode_note(note, old.clone()).is_ok_and(|plain| plain == new_content.as_bytes())
+	{
+		return Ok(None);
+	}
+	let dir = history_dir_for(note).ok_or("无法解析历史版本目录")?;
+	fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+	let mut ms = now_ms();
+	// 同一毫秒内多次保存时顺延，避免覆盖已有快照
+	while dir.join(format!("{}.md", ms)).exists() {
+		ms += 1;
+	}
+	fs::write(dir.join(format!("{}.md", ms)), &old).map_err(|e| e.to_string())?;
+	prune_versions(note, retention)?;
+	Ok(Some(ms.to_string()))
+}
+
+/// 笔记重命名后，历史快照目录随之迁移
+pub(crate) fn move_history(old_note: &Path, new_note: &Path) -> Result<(), String> {
+	let (Some(from), Some(to)) = (history_dir_for(old_note), history_dir_for(new_note)) else {
+		return Ok(());
+	};
+	if !from.is_dir() || to.exists() {
+		return Ok(());
+	}
+	if let Some(parent) = to.parent() {
+		fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+	}
+	fs::rename(&from, &to).map_err(|e| e.to_string())
+}
+
+fn checked_note_path(raw: &str) -> Result<PathBuf, String> {
+	let trimmed = raw.trim();
+	if trimmed.is_empty() {
+		return Err("filePath 不能为空".to_string());
+	}
+	let p = PathBuf::from(trimmed);
+	if !is_md_file_path(&p) {
+		return Err("仅支持 .md 文件".to_string());
+	}
+	Ok(p)
+}
+
+/// 读取某个版本内容；`current` 为磁盘当前内容
+fn read_version(note: &Path, id: &str) -> Result<String, String> {
+	let id = id.trim();
+	if id == CURRENT_VERSION_ID {
+		return read_note_text(note);
+	}
+	if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit()) {
+		return Err(format!("无效的版本 id：{}", id));
+	}
+	let dir = history_dir_for(note).ok_or("无法解析历史版本目录")?;
+	let p = dir.join(format!("{}.md", id));
+	if !p.is_file() {
+		return Err(format!("版本不存在：{}", id));
+	}
+	read_note_text(&p)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListKnowledgeMarkdownVersionsInput {
+	pub file_path: String,
+}
+
+/// 列出某篇笔记的历史版本（新 → 旧）
+#[tauri::command]
+pub fn list_knowledge_markdown_versions(
+	input: ListKnowledgeMarkdownVersionsInput,
+) -> Result<Vec<KnowledgeMarkdownVersion>, String> {
+	let note = checked_note_path(&input.file_path)?;
+	list_versions(&note)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffKnowledgeMarkdownVersionsInput {
+	pub file_path: String,
+	/// 旧版本 id；`current` 表示当前文件
+	pub from_id: String,
+	/// 新版本 id；缺省为 `current`
+	#[serde(default)]
+	pub to_id: Option<String>,
+	/// 每个差异块前后保留的上下文行数，缺省 3
+	#[serde(default)]
+	pub context_lines: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeDiffLine {
+	/// `equal` / `insert` / `delete`
+	pub tag: String,
+	/// 在旧版本中的行号（1 起始），新增行为 None
+	pub old_line: Option<usize>,
+	/// 在新版本中的行号（1 起始），删除行为 None
+	pub new_line: Option<usize>,
+	pub content: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeDiffHunk {
+	pub lines: Vec<KnowledgeDiffLine>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeMarkdownDiff {
+	pub hunks: Vec<KnowledgeDiffHunk>,
+	pub added: usize,
+	pub removed: usize,
+	/// unified diff 文本，便于直接展示或复制
+	pub unified: String,
+}
+
+pub(crate) fn line_diff(old: &str, new: &str, context: usize) -> KnowledgeMarkdownDiff {
+	let diff = TextDiff::from_lines(old, new);
+	let mut added = 0usize;
+	let mut removed = 0usize;
+	let mut hunks = Vec::new();
+	for group in diff.grouped_ops(context) {
+		let mut lines = Vec::new();
+		for op in group {
+			for change in diff.iter_changes(&op) {
+				let tag = match change.tag() {
+					ChangeTag::Equal => "equal",
+					ChangeTag::Insert => {
+						added += 1;
+						"insert"
+					}
+					ChangeTag::Delete => {
+						removed += 1;
+						"delete"
+					}
+				};
+				lines.push(KnowledgeDiffLine {
+					tag: tag.to_string(),
+					old_line: change.old_index().map(|i| i + 1),
+					new_line: change.new_index().map(|i| i + 1),
+					content: change.value().trim_end_matches(['\n', '\r']).to_string(),
+				});
+			}
+		}
+		hunks.push(KnowledgeDiffHunk { lines });
+	}
+	let unified = diff
+		.unified_diff()
+		.context_radius(context)
+		.header("a", "b")
+		.to_string();
+	KnowledgeMarkdownDiff {
+		hunks,
+		added,
+		removed,
+		unified,
+	}
+}
+
+/// 比较任意两个版本（含 `current`）的行级差异
+#[tauri::command]
+pub fn diff_knowledge_markdown_versions(
+	input: DiffKnowledgeMarkdownVersionsInput,
+) -> Result<KnowledgeMarkdownDiff, String> {
+	let note = checked_note_path(&input.file_path)?;
+	let old = read_version(&note, &input.from_id)?;
+	let to_id = input.to_id.as_deref().unwrap_or(CURRENT_VERSION_ID);
+	let new = read_version(&note, to_id)?;
+	Ok(line_diff(&old, &new, input.context_lines.unwrap_or(3)))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreKnowledgeMarkdownVersionInput {
+	pub file_path: String,
+	pub id: String,
+}
+
+/// 将笔记恢复为指定版本；恢复前当前内容会先生成一个快照，因此恢复本身也可撤销
+#[tauri::command]
+pub async fn restore_knowledge_markdown_version(
+	app: AppHandle,
+	input: RestoreKnowledgeMarkdownVersionInput,
+) -> Result<SaveFileResult, String> {
+	let note = checked_note_path(&input.file_path)?;
+	if input.id.trim() == CURRENT_VERSION_ID {
+		return Err("无需恢复当前版本".to_string());
+	}
+	let content = read_version(&note, &input.id)?;
+	let bytes = encode_note(&note, &content)?;
+	let retention = load_history_retention(&app).await;
+	snapshot_before_write(&note, &content, retention)?;
+	if let Some(parent) = note.parent() {
+		fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+	}
+	write_file_atomic(&note, &bytes)?;
+	Ok(SaveFileResult {
+		success: "success".to_string(),
+		file_path: Some(note.to_string_lossy().to_string()),
+		message: format!("已恢复至版本 {}", input.id.trim()),
+	})
+}
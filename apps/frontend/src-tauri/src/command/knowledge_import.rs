@@ -0,0 +1,953 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Cursor, Read, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::Engine as _;
+use md5::{Digest, Md5};
+use quick_xml::events::Event as XmlEvent;
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::command::knowledge::{
+	SaveKnowledgeMarkdownInput, compute_save_target_path, is_md_file_path, knowledge_root_for,
+	resolve_knowledge_dir, sanitize_filename, write_file_atomic,
+};
+use crate::command::knowledge_assets::{decode_data_url, ext_of_mime, relative_link, store_asset};
+use crate::command::knowledge_export::resolve_local_image;
+use crate::command::knowledge_history::{load_history_retention, snapshot_before_write};
+use crate::command::knowledge_import_docx::{docx_title, docx_to_markdown};
+use crate::command::knowledge_vault::encode_note;
+use crate::utils::html_to_md::{html_title, html_to_markdown};
+
+// —— 知识库批量导入：Obsidian 库、Notion 导出 zip、Evernote `.enex`，以后台任务执行并推送进度 ——
+
+const PROGRESS_EVENT: &str = "knowledge://import-progress";
+/// 单个附件大小上限，超过的跳过并记入报告
+const MAX_ATTACHMENT_SIZE: u64 = 200 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KnowledgeImportSource {
+	/// Obsidian 库目录（保留 wiki 链接与附件目录结构）
+	Obsidian,
+	/// Notion「Markdown & CSV」导出的 zip
+	Notion,
+	/// Evernote 导出的 `.enex`
+	Evernote,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeImportSkipped {
+	pub path: String,
+	pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeImportReport {
+	/// 导入到的目录
+	pub target_dir: String,
+	pub notes: Vec<String>,
+	pub attachments: usize,
+	pub skipped: Vec<KnowledgeImportSkipped>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeImportJob {
+	pub id: String,
+	pub source: KnowledgeImportSource,
+	/// `running` / `completed` / `failed` / `cancelled`
+	pub status: String,
+	pub processed: usize,
+	pub total: usize,
+	/// 正在处理的条目
+	pub current: Option<String>,
+	pub report: Option<KnowledgeImportReport>,
+	pub error: Option<String>,
+	pub started_at_ms: u64,
+	pub finished_at_ms: Option<u64>,
+	#[serde(skip)]
+	cancel: Arc<AtomicBool>,
+}
+
+static JOBS: LazyLock<Mutex<HashMap<String, KnowledgeImportJob>>> =
+	LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn now_ms() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_millis() as u64)
+		.unwrap_or(0)
+}
+
+fn update_job(id: &str, f: impl FnOnce(&mut KnowledgeImportJob)) -> Option<KnowledgeImportJob> {
+	let mut jobs = JOBS.lock().ok()?;
+	let job = jobs.get_mut(id)?;
+	f(job);
+	Some(job.clone())
+}
+
+/// 单次导入的上下文：写文件、记报告、推进度
+struct ImportCtx {
+	app: AppHandle,
+	job_id: String,
+	cancel: Arc<AtomicBool>,
+	/// 知识库根目录（Evernote 资源写入其 `assets/`）
+	root: PathBuf,
+	/// 本次导入的目标目录
+	target: PathBuf,
+	overwrite: bool,
+	report: KnowledgeImportReport,
+	processed: usize,
+	/// 本次已写出的相对路径，避免来源中同名条目互相覆盖
+	written: HashSet<PathBuf>,
+}
+
+impl ImportCtx {
+	fn cancelled(&self) -> bool {
+		self.cancel.load(Ordering::Relaxed)
+	}
+
+	fn set_total(&self, total: usize) {
+		if let Some(job) = update_job(&self.job_id, |j| j.total = total) {
+			let _ = self.app.emit(PROGRESS_EVENT, &job);
+		}
+	}
+
+	fn tick(&mut self, current: &str) {
+		self.processed += 1;
+		let processed = self.processed;
+		if let Some(job) = update_job(&self.job_id, |j| {
+			j.processed = processed;
+			j.current = Some(current.to_string());
+		}) {
+			let _ = self.app.emit(PROGRESS_EVENT, &job);
+		}
+	}
+
+	fn skip(&mut self, path: &str, reason: impl Into<String>) {
+		self.report.skipped.push(KnowledgeImportSkipped {
+			path: path.to_string(),
+			reason: reason.into(),
+		});
+	}
+
+	/// 同一次导入中重名时追加 `_1`、`_2`…
+	fn unique_rel(&mut self, rel: &Path) -> PathBuf {
+		if self.written.insert(rel.to_path_buf()) {
+			return rel.to_path_buf();
+		}
+		let stem = rel
+			.file_stem()
+			.map(|s| s.to_string_lossy().to_string())
+			.unwrap_or_default();
+		let ext = rel
+			.extension()
+			.map(|e| format!(".{}", e.to_string_lossy()))
+			.unwrap_or_default();
+		let mut n = 1;
+		loop {
+			let candidate = rel.with_file_name(format!("{}_{}{}", stem, n, ext));
+			if self.written.insert(candidate.clone()) {
+				return candidate;
+			}
+			n += 1;
+		}
+	}
+
+	/// 写入目标目录下的相对路径；已存在且不覆盖时跳过。返回写出的完整路径
+	fn write(&mut self, source: &str, rel: &Path, bytes: &[u8], is_note: bool) -> Option<PathBuf> {
+		let rel = self.unique_rel(rel);
+		let path = self.target.join(&rel);
+		if path.exists() && !self.overwrite {
+			self.skip(source, format!("目标已存在：{}", path.display()));
+			return None;
+		}
+		// 导入到已解锁的加密知识库时笔记落盘为密文
+		let data = if is_note {
+			encode_note(&path, &String::from_utf8_lossy(bytes))
+		} else {
+			Ok(bytes.to_vec())
+		};
+		let result = data.and_then(|data| {
+			path.parent()
+				.map(|p| fs::create_dir_all(p).map_err(|e| e.to_string()))
+				.unwrap_or(Ok(()))
+				.and_then(|_| write_file_atomic(&path, &data))
+		});
+		match result {
+			Ok(()) => {
+				if is_note {
+					self.report.notes.push(path.to_string_lossy().to_string());
+				} else {
+					self.report.attachments += 1;
+				}
+				Some(path)
+			}
+			Err(e) => {
+				self.skip(source, format!("写入失败: {}", e));
+				None
+			}
+		}
+	}
+}
+
+// —— Obsidian ——
+
+fn collect_vault_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<(), String> {
+	for ent in fs::read_dir(dir).map_err(|e| e.to_string())? {
+		let ent = ent.map_err(|e| e.to_string())?;
+		// `.obsidian` 配置、`.trash` 回收站等隐藏项不导入
+		if ent.file_name().to_string_lossy().starts_with('.') {
+			continue;
+		}
+		let p = ent.path();
+		let ft = ent.file_type().map_err(|e| e.to_string())?;
+		if ft.is_dir() {
+			collect_vault_files(&p, out)?;
+		} else if ft.is_file() {
+			out.push(p);
+		}
+	}
+	Ok(())
+}
+
+fn import_obsidian(ctx: &mut ImportCtx, vault: &Path) -> Result<(), String> {
+	if !vault.is_dir() {
+		return Err(format!("目录不存在：{}", vault.display()));
+	}
+	let mut files = Vec::new();
+	collect_vault_files(vault, &mut files)?;
+	ctx.set_total(files.len());
+	for file in files {
+		if ctx.cancelled() {
+			break;
+		}
+		let rel = file.strip_prefix(vault).unwrap_or(&file).to_path_buf();
+		let label = rel.to_string_lossy().to_string();
+		ctx.tick(&label);
+		let size = fs::metadata(&file).map(|m| m.len()).unwrap_or(0);
+		if size > MAX_ATTACHMENT_SIZE {
+			ctx.skip(&label, "文件过大");
+			continue;
+		}
+		let bytes = match fs::read(&file) {
+			Ok(b) => b,
+			Err(e) => {
+				ctx.skip(&label, format!("读取失败: {}", e));
+				continue;
+			}
+		};
+		let is_note = is_md_file_path(&file);
+		if is_note && std::str::from_utf8(&bytes).is_err() {
+			ctx.skip(&label, "不是 UTF-8 文本");
+			continue;
+		}
+		ctx.write(&label, &rel, &bytes, is_note);
+	}
+	Ok(())
+}
+
+// —— Notion ——
+
+fn is_hex32(s: &[u8]) -> bool {
+	s.len() == 32 && s.iter().all(u8::is_ascii_hexdigit)
+}
+
+/// 去掉 Notion 文件 / 目录名末尾的 ` <32 位 id>`
+fn strip_notion_id(component: &str) -> String {
+	let (stem, ext) = match component.rfind('.') {
+		Some(i) if i > 0 => (&component[..i], &component[i..]),
+		_ => (component, ""),
+	};
+	if stem.len() > 33 && stem.is_char_boundary(stem.len() - 33) {
+		let (name, id) = stem.split_at(stem.len() - 33);
+		if id.starts_with(' ') && is_hex32(&id.as_bytes()[1..]) {
+			return format!("{}{}", name, ext);
+		}
+	}
+	component.to_string()
+}
+
+/// 正文链接中的 ` <id>` / `%20<id>` 同样去掉，并把数据库 `.csv` 链接指向转换后的 `.md`
+fn strip_notion_ids_in_text(text: &str) -> String {
+	let bytes = text.as_bytes();
+	let mut out = String::with_capacity(text.len());
+	let mut cursor = 0usize;
+	let mut i = 0usize;
+	while i < bytes.len() {
+		let sep = if bytes[i] == b' ' {
+			1
+		} else if bytes[i..].starts_with(b"%20") {
+			3
+		} else {
+			0
+		};
+		if sep > 0 {
+			let id_start = i + sep;
+			let id_end = id_start + 32;
+			if id_end <= bytes.len()
+				&& is_hex32(&bytes[id_start..id_end])
+				&& matches!(bytes.get(id_end), None | Some(b'.' | b'/' | b')' | b'%' | b'#'))
+			{
+				out.push_str(&text[cursor..i]);
+				cursor = id_end;
+				i = id_end;
+				continue;
+			}
+		}
+		i += 1;
+	}
+	out.push_str(&text[cursor..]);
+	out.replace(".csv)", ".md)")
+}
+
+/// 简单的 RFC 4180 CSV 解析（支持引号内逗号、换行与 `""` 转义）
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+	let mut rows = Vec::new();
+	let mut row = Vec::new();
+	let mut field = String::new();
+	let mut in_quotes = false;
+	let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+	while let Some(c) = chars.next() {
+		if in_quotes {
+			match c {
+				'"' if chars.peek() == Some(&'"') => {
+					field.push('"');
+					chars.next();
+				}
+				'"' => in_quotes = false,
+				_ => field.push(c),
+			}
+			continue;
+		}
+		match c {
+			'"' => in_quotes = true,
+			',' => row.push(std::mem::take(&mut field)),
+			'\r' => {}
+			'\n' => {
+				row.push(std::mem::take(&mut field));
+				rows.push(std::mem::take(&mut row));
+			}
+			_ => field.push(c),
+		}
+	}
+	if !field.is_empty() || !row.is_empty() {
+		row.push(field);
+		rows.push(row);
+	}
+	rows
+}
+
+/// Notion 数据库 CSV 转为 Markdown 表格笔记
+fn csv_to_markdown(title: &str, text: &str) -> String {
+	let rows = parse_csv(text);
+	let mut out = format!("# {}\n\n", title);
+	let Some(cols) = rows.iter().map(Vec::len).max().filter(|n| *n > 0) else {
+		return out;
+	};
+	let cell = |s: &str| s.trim().replace('|', "\\|").replace('\n', "<br>");
+	for (i, row) in rows.iter().enumerate() {
+		let mut cells: Vec<String> = row.iter().map(|s| cell(s)).collect();
+		cells.resize(cols, String::new());
+		out.push_str(&format!("| {} |\n", cells.join(" | ")));
+		if i == 0 {
+			out.push_str(&format!("|{}\n", " --- |".repeat(cols)));
+		}
+	}
+	out
+}
+
+fn notion_rel_path(name: &str) -> PathBuf {
+	name.split('/')
+		.filter(|c| !c.is_empty() && *c != "." && *c != "..")
+		.map(strip_notion_id)
+		.collect()
+}
+
+fn import_notion_archive<R: Read + Seek>(
+	ctx: &mut ImportCtx,
+	archive: &mut zip::ZipArchive<R>,
+	prefix: &str,
+) -> Result<(), String> {
+	for i in 0..archive.len() {
+		if ctx.cancelled() {
+			break;
+		}
+		let mut entry = archive.by_index(i).map_err(|e| e.to_string())?;
+		if entry.is_dir() {
+			continue;
+		}
+		let name = entry.name().replace('\\', "/");
+		let label = format!("{}{}", prefix, name);
+		if entry.size() > MAX_ATTACHMENT_SIZE {
+			ctx.tick(&label);
+			ctx.skip(&label, "文件过大");
+			continue;
+		}
+		let mut bytes = Vec::with_capacity(entry.size() as usize);
+		if let Err(e) = entry.read_to_end(&mut bytes) {
+			ctx.tick(&label);
+			ctx.skip(&label, format!("解压失败: {}", e));
+			continue;
+		}
+		drop(entry);
+		let lower = name.to_ascii_lowercase();
+		// 新版 Notion 导出为外层 zip 套分卷 zip
+		if lower.ends_with(".zip") {
+			match zip::ZipArchive::new(Cursor::new(bytes)) {
+				Ok(mut inner) => {
+					ctx.set_total(inner.len());
+					import_notion_archive(ctx, &mut inner, &format!("{}/", label))?;
+				}
+				Err(e) => ctx.skip(&label, format!("无法读取内层 zip: {}", e)),
+			}
+			continue;
+		}
+		ctx.tick(&label);
+		let rel = notion_rel_path(&name);
+		if rel.as_os_str().is_empty() {
+			ctx.skip(&label, "无效路径");
+			continue;
+		}
+		if lower.ends_with(".md") {
+			let Ok(text) = String::from_utf8(bytes) else {
+				ctx.skip(&label, "不是 UTF-8 文本");
+				continue;
+			};
+			ctx.write(&label, &rel, strip_notion_ids_in_text(&text).as_bytes(), true);
+		} else if lower.ends_with("_all.csv") {
+			ctx.skip(&label, "数据库全量视图与同名 CSV 重复，已忽略");
+		} else if lower.ends_with(".csv") {
+			let text = String::from_utf8_lossy(&bytes);
+			let title = rel
+				.file_stem()
+				.map(|s| s.to_string_lossy().to_string())
+				.unwrap_or_default();
+			let md = csv_to_markdown(&title, &strip_notion_ids_in_text(&text));
+			ctx.write(&label, &rel.with_extension("md"), md.as_bytes(), true);
+		} else {
+			ctx.write(&label, &rel, &bytes, false);
+		}
+	}
+	Ok(())
+}
+
+fn import_notion(ctx: &mut ImportCtx, zip_path: &Path) -> Result<(), String> {
+	let file = fs::File::open(zip_path).map_err(|e| format!("打开 zip 失败: {}", e))?;
+	let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("不是有效的 zip: {}", e))?;
+	ctx.set_total(archive.len());
+	import_notion_archive(ctx, &mut archive, "")
+}
+
+// —— Evernote ——
+
+#[derive(Default)]
+struct EnexResource {
+	data: String,
+	mime: String,
+	file_name: Option<String>,
+}
+
+#[derive(Default)]
+struct EnexNote {
+	title: String,
+	content: String,
+	created: Option<String>,
+	updated: Option<String>,
+	tags: Vec<String>,
+	source_url: Option<String>,
+	resources: Vec<EnexResource>,
+}
+
+/// `20200102T030405Z` → `2020-01-02T03:04:05Z`
+fn enex_time(s: &str) -> String {
+	let s = s.trim();
+	if s.len() == 16 && s.as_bytes()[8] == b'T' {
+		format!(
+			"{}-{}-{}T{}:{}:{}Z",
+			&s[0..4],
+			&s[4..6],
+			&s[6..8],
+			&s[9..11],
+			&s[11..13],
+			&s[13..15]
+		)
+	} else {
+		s.to_string()
+	}
+}
+
+fn parse_enex(xml: &str) -> Result<Vec<EnexNote>, String> {
+	let mut reader = Reader::from_str(xml);
+	let mut notes = Vec::new();
+	let mut note: Option<EnexNote> = None;
+	let mut resource: Option<EnexResource> = None;
+	let mut path: Vec<String> = Vec::new();
+	loop {
+		let event = reader
+			.read_event()
+			.map_err(|e| format!("解析 ENEX 失败（位置 {}）: {}", reader.buffer_position(), e))?;
+		let text = match &event {
+			XmlEvent::Start(e) => {
+				let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+				match name.as_str() {
+					"note" => note = Some(EnexNote::default()),
+					"resource" => resource = Some(EnexResource::default()),
+					_ => {}
+				}
+				path.push(name);
+				continue;
+			}
+			XmlEvent::End(e) => {
+				match e.name().as_ref() {
+					b"note" => notes.extend(note.take()),
+					b"resource" => {
+						if let (Some(n), Some(r)) = (note.as_mut(), resource.take()) {
+							n.resources.push(r);
+						}
+					}
+					_ => {}
+				}
+				path.pop();
+				continue;
+			}
+			XmlEvent::Text(t) => t.unescape().map_err(|e| e.to_string())?.to_string(),
+			XmlEvent::CData(c) => String::from_utf8_lossy(c.as_ref()).to_string(),
+			XmlEvent::Eof => break,
+			_ => continue,
+		};
+		let Some(n) = note.as_mut() else {
+			continue;
+		};
+		let field = path.last().map(String::as_str).unwrap_or_default();
+		if let Some(r) = resource.as_mut() {
+			match field {
+				"data" => r.data.push_str(&text),
+				"mime" => r.mime.push_str(text.trim()),
+				"file-name" => r.file_name = Some(text.trim().to_string()),
+				_ => {}
+			}
+			continue;
+		}
+		match field {
+			"title" => n.title.push_str(&text),
+			"content" => n.content.push_str(&text),
+			"created" => n.created = Some(enex_time(&text)),
+			"updated" => n.updated = Some(enex_time(&text)),
+			"tag" => n.tags.push(text.trim().to_string()),
+			"source-url" => n.source_url = Some(text.trim().to_string()),
+			_ => {}
+		}
+	}
+	Ok(notes)
+}
+
+/// 生成 YAML front matter；字符串用 JSON 转义写法（同时是合法的 YAML）
+pub(crate) fn front_matter(fields: &[(&str, Option<String>)], tags: &[String]) -> String {
+	let quote = |s: &str| serde_json::to_string(s).unwrap_or_default();
+	let mut out = String::from("---\n");
+	for (k, v) in fields {
+		if let Some(v) = v.as_ref().filter(|v| !v.is_empty()) {
+			out.push_str(&format!("{}: {}\n", k, quote(v)));
+		}
+	}
+	if !tags.is_empty() {
+		out.push_str(&format!(
+			"tags: [{}]\n",
+			tags.iter().map(|t| quote(t)).collect::<Vec<_>>().join(", ")
+		));
+	}
+	out.push_str("---\n\n");
+	out
+}
+
+fn import_evernote(ctx: &mut ImportCtx, enex: &Path) -> Result<(), String> {
+	let xml = fs::read_to_string(enex).map_err(|e| format!("读取 ENEX 失败: {}", e))?;
+	let notes = parse_enex(&xml)?;
+	drop(xml);
+	ctx.set_total(notes.len());
+	for note in notes {
+		if ctx.cancelled() {
+			break;
+		}
+		let title = if note.title.trim().is_empty() {
+			"未命名".to_string()
+		} else {
+			note.title.trim().to_string()
+		};
+		ctx.tick(&title);
+		let rel = PathBuf::from(sanitize_filename(&title));
+		let note_dir = ctx.target.join(&rel).parent().map(Path::to_path_buf).unwrap_or_default();
+
+		// 资源按内容写入 assets，正文中以 MD5 引用
+		let mut media: HashMap<String, String> = HashMap::new();
+		for res in &note.resources {
+			let cleaned: String = res.data.chars().filter(|c| !c.is_whitespace()).collect();
+			let bytes = match base64::engine::general_purpose::STANDARD.decode(cleaned) {
+				Ok(b) => b,
+				Err(e) => {
+					ctx.skip(&format!("{} / 资源", title), format!("base64 解码失败: {}", e));
+					continue;
+				}
+			};
+			let hash: String = Md5::digest(&bytes).iter().map(|b| format!("{:02x}", b)).collect();
+			let ext = res
+				.file_name
+				.as_deref()
+				.and_then(|n| Path::new(n).extension())
+				.map(|e| e.to_string_lossy().to_ascii_lowercase())
+				.unwrap_or_else(|| ext_of_mime(&res.mime).to_string());
+			match store_asset(&ctx.root, &bytes, &ext) {
+				Ok((path, _)) => {
+					ctx.report.attachments += 1;
+					media.insert(hash, relative_link(&note_dir, &path));
+				}
+				Err(e) => ctx.skip(
+					&format!("{} / {}", title, res.file_name.as_deref().unwrap_or("资源")),
+					e,
+				),
+			}
+		}
+
+		let body = html_to_markdown(&note.content, &mut |src: &str| {
+			src.strip_prefix("en-media:")
+				.and_then(|h| media.get(&h.to_ascii_lowercase()).cloned())
+		});
+		let md = format!(
+			"{}{}\n",
+			front_matter(
+				&[
+					("title", Some(title.clone())),
+					("created", note.created.clone()),
+					("updated", note.updated.clone()),
+					("source", Some("evernote".to_string())),
+					("sourceUrl", note.source_url.clone()),
+				],
+				&note.tags,
+			),
+			body
+		);
+		ctx.write(&title, &rel, md.as_bytes(), true);
+	}
+	Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartKnowledgeImportInput {
+	pub source: KnowledgeImportSource,
+	/// Obsidian 为库目录；Notion 为导出的 zip；Evernote 为 `.enex` 文件
+	pub source_path: String,
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	/// 导入到知识库下的子目录名，缺省取来源名称
+	#[serde(default)]
+	pub target_folder: Option<String>,
+	/// 目标已存在时是否覆盖；默认跳过并记入报告
+	#[serde(default)]
+	pub overwrite: bool,
+}
+
+/// 启动导入任务，立即返回任务 id；进度通过 `knowledge://import-progress` 事件推送
+#[tauri::command]
+pub async fn start_knowledge_import(
+	app: AppHandle,
+	input: StartKnowledgeImportInput,
+) -> Result<KnowledgeImportJob, String> {
+	let source_path = PathBuf::from(input.source_path.trim());
+	if !source_path.exists() {
+		return Err(format!("路径不存在：{}", source_path.display()));
+	}
+	let root = match input.dir_path.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+		Some(d) => PathBuf::from(d),
+		None => resolve_knowledge_dir(&app).await?,
+	};
+	let folder = input
+		.target_folder
+		.as_deref()
+		.map(str::trim)
+		.filter(|s| !s.is_empty())
+		.map(str::to_string)
+		.unwrap_or_else(|| {
+			let stem = source_path
+				.file_stem()
+				.map(|s| s.to_string_lossy().to_string())
+				.unwrap_or_default();
+			match input.source {
+				KnowledgeImportSource::Notion => strip_notion_id(&stem),
+				_ => stem,
+			}
+		});
+	let folder = sanitize_filename(&folder).trim_end_matches(".md").to_string();
+	let target = root.join(folder);
+	fs::create_dir_all(&target).map_err(|e| e.to_string())?;
+
+	let cancel = Arc::new(AtomicBool::new(false));
+	let started_at_ms = now_ms();
+	let id = format!("import-{}", started_at_ms);
+	let job = KnowledgeImportJob {
+		id: id.clone(),
+		source: input.source,
+		status: "running".to_string(),
+		processed: 0,
+		total: 0,
+		current: None,
+		report: None,
+		error: None,
+		started_at_ms,
+		finished_at_ms: None,
+		cancel: cancel.clone(),
+	};
+	JOBS.lock()
+		.map_err(|e| e.to_string())?
+		.insert(id.clone(), job.clone());
+
+	let source = input.source;
+	let mut ctx = ImportCtx {
+		app: app.clone(),
+		job_id: id.clone(),
+		cancel,
+		root,
+		report: KnowledgeImportReport {
+			target_dir: target.to_string_lossy().to_string(),
+			..Default::default()
+		},
+		target,
+		overwrite: input.overwrite,
+		processed: 0,
+		written: HashSet::new(),
+	};
+	tauri::async_runtime::spawn_blocking(move || {
+		let result = match source {
+			KnowledgeImportSource::Obsidian => import_obsidian(&mut ctx, &source_path),
+			KnowledgeImportSource::Notion => import_notion(&mut ctx, &source_path),
+			KnowledgeImportSource::Evernote => import_evernote(&mut ctx, &source_path),
+		};
+		let cancelled = ctx.cancelled();
+		let report = std::mem::take(&mut ctx.report);
+		if let Some(job) = update_job(&id, |j| {
+			j.status = match (&result, cancelled) {
+				(Err(_), _) => "failed",
+				(Ok(()), true) => "cancelled",
+				(Ok(()), false) => "completed",
+			}
+			.to_string();
+			j.error = result.err();
+			j.current = None;
+			j.report = Some(report);
+			j.finished_at_ms = Some(now_ms());
+		}) {
+			let _ = app.emit(PROGRESS_EVENT, &job);
+		}
+	});
+	Ok(job)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeImportJobInput {
+	pub id: String,
+}
+
+/// 查询导入任务状态与报告
+#[tauri::command]
+pub fn get_knowledge_import_job(input: KnowledgeImportJobInput) -> Result<KnowledgeImportJob, String> {
+	JOBS.lock()
+		.map_err(|e| e.to_string())?
+		.get(input.id.trim())
+		.cloned()
+		.ok_or_else(|| format!("导入任务不存在：{}", input.id))
+}
+
+/// 取消进行中的导入（已写入的文件保留）
+#[tauri::command]
+pub fn cancel_knowledge_import(input: KnowledgeImportJobInput) -> Result<(), String> {
+	let jobs = JOBS.lock().map_err(|e| e.to_string())?;
+	let job = jobs
+		.get(input.id.trim())
+		.ok_or_else(|| format!("导入任务不存在：{}", input.id))?;
+	job.cancel.store(true, Ordering::Relaxed);
+	Ok(())
+}
+
+/// 列出本次运行期间的导入任务（按开始时间倒序）
+#[tauri::command]
+pub fn list_knowledge_import_jobs() -> Result<Vec<KnowledgeImportJob>, String> {
+	let mut jobs: Vec<KnowledgeImportJob> = JOBS
+		.lock()
+		.map_err(|e| e.to_string())?
+		.values()
+		.cloned()
+		.collect();
+	jobs.sort_by_key(|j| std::cmp::Reverse(j.started_at_ms));
+	Ok(jobs)
+}
+
+// —— 单篇文档导入：Word（.docx）/ HTML 转 Markdown，按 `save_knowledge_markdown` 的路径规则保存 ——
+
+/// 选择要导入的 Word / HTML 文档
+#[tauri::command]
+pub fn select_knowledge_import_document_file() -> Result<String, String> {
+	match rfd::FileDialog::new()
+		.add_filter("文档", &["docx", "html", "htm"])
+		.pick_file()
+	{
+		Some(path) => Ok(path.to_string_lossy().to_string()),
+		None => Err("canceled".to_string()),
+	}
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportKnowledgeDocumentInput {
+	/// `.docx` / `.html` / `.htm` 文件路径
+	pub source_path: String,
+	/// 笔记标题；缺省取文档属性 / `<title>`，再退化为文件名
+	#[serde(default)]
+	pub title: Option<String>,
+	#[serde(default)]
+	pub file_path: Option<String>,
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	#[serde(default)]
+	pub overwrite: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeDocumentImportResult {
+	pub file_path: String,
+	pub title: String,
+	/// 提取到 assets 的图片数
+	pub images: usize,
+	/// 未能提取的图片（保留原地址或替代文本）
+	pub failed_images: Vec<String>,
+	pub message: String,
+}
+
+/// 导入 Word / HTML 文档为 Markdown 笔记；图片写入知识库 `assets/` 并改为相对链接
+#[tauri::command]
+pub async fn import_knowledge_document(
+	app: AppHandle,
+	input: ImportKnowledgeDocumentInput,
+) -> Result<KnowledgeDocumentImportResult, String> {
+	let source = PathBuf::from(input.source_path.trim());
+	if !source.is_file() {
+		return Err(format!("文件不存在：{}", source.display()));
+	}
+	let ext = source
+		.extension()
+		.map(|e| e.to_string_lossy().to_ascii_lowercase())
+		.unwrap_or_default();
+	let is_docx = match ext.as_str() {
+		"docx" => true,
+		"html" | "htm" => false,
+		_ => return Err("仅支持导入 .docx / .html 文件".to_string()),
+	};
+	let bytes = fs::read(&source).map_err(|e| format!("读取文件失败: {}", e))?;
+	let html = (!is_docx).then(|| String::from_utf8_lossy(&bytes).to_string());
+
+	let title = input
+		.title
+		.as_deref()
+		.map(str::trim)
+		.filter(|s| !s.is_empty())
+		.map(str::to_string)
+		.or_else(|| match html.as_deref() {
+			Some(h) => html_title(h),
+			None => docx_title(&bytes),
+		})
+		.unwrap_or_else(|| {
+			source
+				.file_stem()
+				.map(|s| s.to_string_lossy().to_string())
+				.unwrap_or_default()
+		});
+
+	let save_input = SaveKnowledgeMarkdownInput {
+		title: title.clone(),
+		content: String::new(),
+		file_path: input.file_path.clone(),
+		dir_path: input.dir_path.clone(),
+		overwrite: input.overwrite,
+		previous_title: None,
+		update_links: false,
+		expected_mtime_ms: None,
+		expected_hash: None,
+		base_content: None,
+	};
+	let path = compute_save_target_path(&app, &save_input).await?;
+	if path.is_dir() {
+		return Err("目标路径已存在且非文件".to_string());
+	}
+	if path.exists() && !input.overwrite {
+		return Err(format!("文件已存在：{}", path.to_string_lossy()));
+	}
+	let root = knowledge_root_for(&app, input.dir_path.as_ref(), &path).await?;
+	let note_dir = path.parent().map(Path::to_path_buf).unwrap_or_else(|| root.clone());
+
+	let mut images = 0usize;
+	let mut failed_images = Vec::new();
+	let mut store = |bytes: &[u8], ext: &str| {
+		let (asset, _) = store_asset(&root, bytes, ext)?;
+		images += 1;
+		Ok::<_, String>(relative_link(&note_dir, &asset))
+	};
+	let content = match html.as_deref() {
+		None => docx_to_markdown(&bytes, &mut |data: &[u8], ext: &str| {
+			store(data, ext)
+				.map_err(|e| failed_images.push(format!("内嵌图片：{}", e)))
+				.ok()
+		})?,
+		Some(h) => {
+			let html_dir = source.parent().map(Path::to_path_buf).unwrap_or_default();
+			html_to_markdown(h, &mut |src: &str| {
+				let label: String = src.chars().take(80).collect();
+				let result = if src.trim_start().starts_with("data:") {
+					decode_data_url(src).and_then(|(data, ext)| store(&data, ext))
+				} else {
+					// 远程图片保留原地址，可再用「远程图片本地化」下载
+					let local = resolve_local_image(&html_dir, src)?;
+					let ext = local
+						.extension()
+						.map(|e| e.to_string_lossy().to_string())
+						.unwrap_or_default();
+					fs::read(&local)
+						.map_err(|e| e.to_string())
+						.and_then(|data| store(&data, &ext))
+				};
+				result
+					.map_err(|e| failed_images.push(format!("{}：{}", label, e)))
+					.ok()
+			})
+		}
+	};
+
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+	}
+	let bytes = encode_note(&path, &content)?;
+	let retention = load_history_retention(&app).await;
+	snapshot_before_write(&path, &content, retention)?;
+	write_file_atomic(&path, &bytes)?;
+	let message = if failed_images.is_empty() {
+		format!("已导入至 {}", path.display())
+	} else {
+		format!("已导入至 {}，{} 张图片未能提取", path.display(), failed_images.len())
+	};
+	Ok(KnowledgeDocumentImportResult {
+		file_path: path.to_string_lossy().to_string(),
+		title,
+		images,
+		failed_images,
+		message,
+	})
+}
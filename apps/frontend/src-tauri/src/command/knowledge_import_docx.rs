@@ -0,0 +1,648 @@
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event as XmlEvent};
+
+// —— 知识导入 DOCX：解析 WordprocessingML，输出 Markdown（标题、列表、表格、图片、链接） ——
+
+/// 图片回调：入参为（图片字节, 扩展名），返回写入 Markdown 的链接；None 时输出替代文本
+pub(crate) type DocxImageSink<'a> = dyn FnMut(&[u8], &str) -> Option<String> + 'a;
+
+fn read_entry<R: Read + std::io::Seek>(archive: &mut zip::ZipArchive<R>, name: &str) -> Option<Vec<u8>> {
+	let mut entry = archive.by_name(name).ok()?;
+	let mut buf = Vec::with_capacity(entry.size() as usize);
+	entry.read_to_end(&mut buf).ok()?;
+	Some(buf)
+}
+
+/// 取属性值，按本地名匹配（忽略 `w:` / `r:` 等前缀）
+fn attr(e: &BytesStart, local: &str) -> Option<String> {
+	e.attributes().flatten().find_map(|a| {
+		let key = a.key.as_ref();
+		let name = key.rsplit(|b| *b == b':').next().unwrap_or(key);
+		(name == local.as_bytes()).then(|| String::from_utf8_lossy(&a.value).to_string())
+	})
+}
+
+/// `<w:b/>`、`<w:b w:val="0"/>` 等开关属性
+fn toggle_on(e: &BytesStart) -> bool {
+	!matches!(attr(e, "val").as_deref(), Some("0" | "false" | "off" | "none"))
+}
+
+/// 关系表：rId → (目标, 是否外部链接)
+fn parse_rels(xml: &[u8]) -> HashMap<String, (String, bool)> {
+	let mut rels = HashMap::new();
+	let mut reader = Reader::from_reader(xml);
+	let mut buf = Vec::new();
+	loop {
+		match reader.read_event_into(&mut buf) {
+			Ok(XmlEvent::Start(e) | XmlEvent::Empty(e)) if e.local_name().as_ref() == b"Relationship" => {
+				if let (Some(id), Some(target)) = (attr(&e, "Id"), attr(&e, "Target")) {
+					let external = attr(&e, "TargetMode").as_deref() == Some("External");
+					rels.insert(id, (target, external));
+				}
+			}
+			Ok(XmlEvent::Eof) | Err(_) => break,
+			_ => {}
+		}
+		buf.clear();
+	}
+	rels
+}
+
+/// 样式表：styleId → 标题级别 / 是否代码样式
+#[derive(Default)]
+struct StyleInfo {
+	heading: Option<usize>,
+	code: bool,
+}
+
+fn heading_of_name(name: &str) -> Option<usize> {
+	let lower = name.to_ascii_lowercase();
+	if lower == "title" {
+		return Some(1);
+	}
+	lower
+		.strip_prefix("heading")
+		.and_then(|n| n.trim().parse::<usize>().ok())
+		.filter(|n| (1..=6).contains(n))
+}
+
+fn parse_styles(xml: &[u8]) -> HashMap<String, StyleInfo> {
+	let mut styles = HashMap::new();
+	let mut reader = Reader::from_reader(xml);
+	let mut buf = Vec::new();
+	let mut current: Option<(String, StyleInfo)> = None;
+	loop {
+		match reader.read_event_into(&mut buf) {
+			Ok(XmlEvent::Start(e)) if e.name().as_ref() == b"w:style" => {
+				current = attr(&e, "styleId").map(|id| (id, StyleInfo::default()));
+			}
+			Ok(XmlEvent::Start(e) | XmlEvent::Empty(e)) => {
+				let Some((_, info)) = current.as_mut() else {
+					buf.clear();
+					continue;
+				};
+				match e.name().as_ref() {
+					b"w:name" => {
+						let name = attr(&e, "val").unwrap_or_default();
+						info.heading = info.heading.or(heading_of_name(&name));
+						let lower = name.to_ascii_lowercase();
+						info.code = lower.contains("code") || lower.contains("preformatted");
+					}
+					b"w:outlineLvl" => {
+						if let Some(lvl) = attr(&e, "val").and_then(|v| v.parse::<usize>().ok())
+							&& lvl < 6
+						{
+							info.heading = info.heading.or(Some(lvl + 1));
+						}
+					}
+					_ => {}
+				}
+			}
+			Ok(XmlEvent::End(e)) if e.name().as_ref() == b"w:style" => {
+				if let Some((id, info)) = current.take() {
+					styles.insert(id, info);
+				}
+			}
+			Ok(XmlEvent::Eof) | Err(_) => break,
+			_ => {}
+		}
+		buf.clear();
+	}
+	styles
+}
+
+/// 编号定义：(numId, ilvl) → 是否有序
+fn parse_numbering(xml: &[u8]) -> HashMap<(String, u8), bool> {
+	let mut abstract_fmt: HashMap<(String, u8), bool> = HashMap::new();
+	let mut num_to_abstract: HashMap<String, String> = HashMap::new();
+	let mut reader = Reader::from_reader(xml);
+	let mut buf = Vec::new();
+	let mut abstract_id: Option<String> = None;
+	let mut lvl: u8 = 0;
+	let mut num_id: Option<String> = None;
+	loop {
+		match reader.read_event_into(&mut buf) {
+			Ok(XmlEvent::Start(e) | XmlEvent::Empty(e)) => match e.name().as_ref() {
+				b"w:abstractNum" => abstract_id = attr(&e, "abstractNumId"),
+				b"w:lvl" => lvl = attr(&e, "ilvl").and_then(|v| v.parse().ok()).unwrap_or(0),
+				b"w:numFmt" => {
+					if let Some(id) = abstract_id.as_ref() {
+						let ordered = !matches!(attr(&e, "val").as_deref(), Some("bullet" | "none"));
+						abstract_fmt.insert((id.clone(), lvl), ordered);
+					}
+				}
+				b"w:num" => num_id = attr(&e, "numId"),
+				b"w:abstractNumId" => {
+					if let (Some(n), Some(a)) = (num_id.as_ref(), attr(&e, "val")) {
+						num_to_abstract.insert(n.clone(), a);
+					}
+				}
+				_ => {}
+			},
+			Ok(XmlEvent::End(e)) => match e.name().as_ref() {
+				b"w:abstractNum" => abstract_id = None,
+				b"w:num" => num_id = None,
+				_ => {}
+			},
+			Ok(XmlEvent::Eof) | Err(_) => break,
+			_ => {}
+		}
+		buf.clear();
+	}
+	let mut out = HashMap::new();
+	for (num, abs) in num_to_abstract {
+		for ((a, lvl), ordered) in &abstract_fmt {
+			if *a == abs {
+				out.insert((num.clone(), *lvl), *ordered);
+			}
+		}
+	}
+	out
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+struct RunFmt {
+	bold: bool,
+	italic: bool,
+	strike: bool,
+	code: bool,
+}
+
+enum Piece {
+	Text(RunFmt, String),
+	/// 已是 Markdown 的片段（图片、链接），不再转义
+	Raw(String),
+}
+
+fn escape_md(s: &str) -> String {
+	let mut out = String::with_capacity(s.len());
+	for c in s.chars() {
+		if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']') {
+			out.push('\\');
+		}
+		out.push(c);
+	}
+	out
+}
+
+fn wrap(text: &str, mark: &str) -> String {
+	let t = text.trim();
+	if t.is_empty() {
+		return text.to_string();
+	}
+	// 标记须紧贴文字，前后空白留在外侧
+	let lead = &text[..text.len() - text.trim_start().len()];
+	let trail = &text[text.trim_end().len()..];
+	format!("{}{}{}{}{}", lead, mark, t, mark, trail)
+}
+
+/// 相邻同格式的 run 合并后再加强调标记，避免 `**a****b**`
+fn render_inline(pieces: &[Piece]) -> String {
+	let mut out = String::new();
+	let mut i = 0;
+	while i < pieces.len() {
+		match &pieces[i] {
+			Piece::Raw(s) => {
+				out.push_str(s);
+				i += 1;
+			}
+			Piece::Text(fmt, _) => {
+				let fmt = *fmt;
+				let mut text = String::new();
+				while let Some(Piece::Text(f, t)) = pieces.get(i) {
+					if *f != fmt {
+						break;
+					}
+					text.push_str(t);
+					i += 1;
+				}
+				if fmt.code && !text.trim().is_empty() {
+					let tick = if text.contains('`') { "``" } else { "`" };
+					out.push_str(&format!("{}{}{}", tick, text.trim(), tick));
+					continue;
+				}
+				let mut s = escape_md(&text);
+				if fmt.strike {
+					s = wrap(&s, "~~");
+				}
+				if fmt.italic {
+					s = wrap(&s, "*");
+				}
+				if fmt.bold {
+					s = wrap(&s, "**");
+				}
+				out.push_str(&s);
+			}
+		}
+	}
+	out
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum BlockKind {
+	None,
+	Para,
+	List,
+}
+
+struct DocxConverter<'a, 'b> {
+	rels: HashMap<String, (String, bool)>,
+	styles: HashMap<String, StyleInfo>,
+	numbering: HashMap<(String, u8), bool>,
+	/// 读取 `word/` 下媒体文件
+	media: &'a mut dyn FnMut(&str) -> Option<Vec<u8>>,
+	image: &'a mut DocxImageSink<'b>,
+
+	out: String,
+	last: BlockKind,
+	code_lines: Vec<String>,
+	/// (numId, ilvl) → 当前序号
+	counters: HashMap<(String, u8), u64>,
+
+	// 段落状态
+	pieces: Vec<Piece>,
+	p_style: Option<String>,
+	num_id: Option<String>,
+	ilvl: u8,
+	in_run: bool,
+	in_rpr: bool,
+	in_text: bool,
+	fmt: RunFmt,
+	link: Option<(Option<String>, Vec<Piece>)>,
+	image_alt: String,
+
+	// 表格状态：只展开最外层表格，嵌套表格内容并入单元格
+	table_depth: usize,
+	rows: Vec<Vec<String>>,
+	row: Vec<String>,
+	cell: Vec<String>,
+
+	/// `mc:Fallback` 等需要整体跳过的深度
+	skip_depth: usize,
+	/// 文本框内的段落并入外层段落
+	textbox_depth: usize,
+}
+
+impl DocxConverter<'_, '_> {
+	fn push_piece(&mut self, piece: Piece) {
+		match self.link.as_mut() {
+			Some((_, buf)) => buf.push(piece),
+			None => self.pieces.push(piece),
+		}
+	}
+
+	fn flush_code(&mut self) {
+		if self.code_lines.is_empty() {
+			return;
+		}
+		let body = std::mem::take(&mut self.code_lines).join("\n");
+		let fence = if body.contains("```") { "~~~" } else { "```" };
+		self.emit(BlockKind::Para, format!("{}\n{}\n{}", fence, body, fence));
+	}
+
+	fn emit(&mut self, kind: BlockKind, text: String) {
+		if !self.out.is_empty() {
+			self.out.push_str(if kind == BlockKind::List && self.last == BlockKind::List {
+				"\n"
+			} else {
+				"\n\n"
+			});
+		}
+		self.out.push_str(&text);
+		self.last = kind;
+	}
+
+	fn start_paragraph(&mut self) {
+		self.pieces.clear();
+		self.p_style = None;
+		self.num_id = None;
+		self.ilvl = 0;
+	}
+
+	fn end_paragraph(&mut self) {
+		let pieces = std::mem::take(&mut self.pieces);
+		if self.table_depth > 0 {
+			let text = render_inline(&pieces).trim().to_string();
+			if !text.is_empty() {
+				self.cell.push(text);
+			}
+			return;
+		}
+		let (is_code, heading) = self
+			.p_style
+			.as_ref()
+			.and_then(|s| self.styles.get(s))
+			.map(|s| (s.code, s.heading))
+			.unwrap_or((false, None));
+		if is_code {
+			// 代码段落保留原文，不做 Markdown 转义
+			let raw: String = pieces
+				.iter()
+				.map(|p| match p {
+					Piece::Text(_, t) => t.as_str(),
+					Piece::Raw(r) => r.as_str(),
+				})
+				.collect();
+			self.code_lines.push(raw);
+			return;
+		}
+		self.flush_code();
+		let text = render_inline(&pieces).trim().to_string();
+		let numbering = self
+			.num_id
+			.clone()
+			.filter(|n| n != "0")
+			.map(|n| (n.clone(), self.numbering.get(&(n, self.ilvl)).copied().unwrap_or(false)));
+		if let Some(level) = heading {
+			if !text.is_empty() {
+				self.emit(BlockKind::Para, format!("{} {}", "#".repeat(level), text.replace('\n', " ")));
+			}
+		} else if let Some((num, ordered)) = numbering {
+			let ilvl = self.ilvl;
+			// 上级列表出现新条目时，下级编号重新开始
+			self.counters.retain(|(n, l), _| *n != num || *l <= ilvl);
+			let counter = self.counters.entry((num, ilvl)).or_insert(0);
+			*counter += 1;
+			let marker = if ordered { format!("{}. ", counter) } else { "- ".to_string() };
+			let indent = "    ".repeat(ilvl as usize);
+			let pad = format!("{}{}", indent, " ".repeat(marker.len()));
+			let body = text.replace('\n', &format!("\n{}", pad));
+			self.emit(BlockKind::List, format!("{}{}{}", indent, marker, body));
+		} else if !text.is_empty() {
+			self.emit(BlockKind::Para, text);
+		}
+	}
+
+	fn end_table(&mut self) {
+		let rows = std::mem::take(&mut self.rows);
+		let cols = rows.iter().map(Vec::len).max().unwrap_or(0);
+		if cols == 0 {
+			return;
+		}
+		self.flush_code();
+		let mut table = String::new();
+		for (i, row) in rows.into_iter().enumerate() {
+			let mut cells = row;
+			cells.resize(cols, String::new());
+			if i > 0 {
+				table.push('\n');
+			}
+			table.push_str(&format!("| {} |", cells.join(" | ")));
+			if i == 0 {
+				table.push_str(&format!("\n|{}", " --- |".repeat(cols)));
+			}
+		}
+		self.emit(BlockKind::Para, table);
+	}
+
+	fn image(&mut self, rid: &str) {
+		let Some((target, external)) = self.rels.get(rid).cloned() else {
+			return;
+		};
+		let alt = std::mem::take(&mut self.image_alt).replace(['[', ']'], "");
+		if external {
+			self.push_piece(Piece::Raw(format!("![{}]({})", alt, target)));
+			return;
+		}
+		let name = target.trim_start_matches('/');
+		let name = name.strip_prefix("word/").unwrap_or(name).to_string();
+		let ext = name.rsplit('.').next().unwrap_or("bin").to_ascii_lowercase();
+		let link = (self.media)(&name).and_then(|bytes| (self.image)(&bytes, &ext));
+		match link {
+			Some(link) => self.push_piece(Piece::Raw(format!("![{}]({})", alt, link))),
+			None => self.push_piece(Piece::Raw(format!("[图片：{}]", if alt.is_empty() { &name } else { &alt }))),
+		}
+	}
+
+	fn open(&mut self, e: &BytesStart, empty: bool) {
+		let name = e.name();
+		let name = name.as_ref();
+		if self.skip_depth > 0 {
+			if !empty {
+				self.skip_depth += 1;
+			}
+			return;
+		}
+		match name {
+			b"mc:Fallback" | b"w:del" | b"w:instrText" if !empty => self.skip_depth = 1,
+			b"w:txbxContent" if !empty => self.textbox_depth += 1,
+			b"w:p" if self.textbox_depth == 0 => {
+				self.start_paragraph();
+				if empty {
+					self.end_paragraph();
+				}
+			}
+			b"w:pStyle" => self.p_style = attr(e, "val"),
+			b"w:numId" => self.num_id = attr(e, "val"),
+			b"w:ilvl" => self.ilvl = attr(e, "val").and_then(|v| v.parse().ok()).unwrap_or(0),
+			b"w:r" if !empty => {
+				self.in_run = true;
+				self.fmt = RunFmt::default();
+			}
+			b"w:rPr" if self.in_run && !empty => self.in_rpr = true,
+			b"w:b" if self.in_rpr => self.fmt.bold = toggle_on(e),
+			b"w:i" if self.in_rpr => self.fmt.italic = toggle_on(e),
+			b"w:strike" | b"w:dstrike" if self.in_rpr => self.fmt.strike = toggle_on(e),
+			b"w:rFonts" if self.in_rpr => {
+				let font = attr(e, "ascii").unwrap_or_default().to_ascii_lowercase();
+				self.fmt.code = ["courier", "consolas", "menlo", "mono", "monaco"]
+					.iter()
+					.any(|f| font.contains(f));
+			}
+			b"w:rStyle" if self.in_rpr => {
+				let style = attr(e, "val").unwrap_or_default().to_ascii_lowercase();
+				if style.contains("code") {
+					self.fmt.code = true;
+				}
+			}
+			b"w:t" if !empty => self.in_text = true,
+			b"w:tab" if self.in_run => self.push_piece(Piece::Text(self.fmt, "\t".to_string())),
+			// 分页符不输出
+			b"w:br" | b"w:cr" if self.in_run && attr(e, "type").as_deref() != Some("page") => {
+				self.push_piece(Piece::Raw("  \n".to_string()));
+			}
+			b"w:hyperlink" if !empty => {
+				let url = attr(e, "id")
+					.and_then(|id| self.rels.get(&id).cloned())
+					.filter(|(_, external)| *external)
+					.map(|(t, _)| t);
+				self.link = Some((url, Vec::new()));
+			}
+			b"wp:docPr" => {
+				self.image_alt = attr(e, "descr")
+					.filter(|s| !s.trim().is_empty())
+					.unwrap_or_default();
+			}
+			b"a:blip" => {
+				if let Some(rid) = attr(e, "embed").or_else(|| attr(e, "link")) {
+					self.image(&rid);
+				}
+			}
+			b"v:imagedata" => {
+				if let Some(rid) = attr(e, "id") {
+					self.image(&rid);
+				}
+			}
+			b"w:tbl" if !empty => {
+				self.table_depth += 1;
+				if self.table_depth == 1 {
+					self.rows.clear();
+				}
+			}
+			b"w:tr" if self.table_depth == 1 && !empty => self.row.clear(),
+			b"w:tc" if self.table_depth == 1 && !empty => self.cell.clear(),
+			_ => {}
+		}
+	}
+
+	fn close(&mut self, name: &[u8]) {
+		if self.skip_depth > 0 {
+			self.skip_depth -= 1;
+			return;
+		}
+		match name {
+			b"w:txbxContent" => self.textbox_depth = self.textbox_depth.saturating_sub(1),
+			b"w:p" if self.textbox_depth == 0 => self.end_paragraph(),
+			b"w:r" => {
+				self.in_run = false;
+				self.in_rpr = false;
+			}
+			b"w:rPr" => self.in_rpr = false,
+			b"w:t" => self.in_text = false,
+			b"w:hyperlink" => {
+				if let Some((url, pieces)) = self.link.take() {
+					let text = render_inline(&pieces);
+					match url {
+						Some(url) if !text.trim().is_empty() => {
+							let url = if url.contains(' ') || url.contains(')') {
+								format!("<{}>", url)
+							} else {
+								url
+							};
+							self.pieces.push(Piece::Raw(format!("[{}]({})", text.trim(), url)));
+						}
+						_ => self.pieces.push(Piece::Raw(text)),
+					}
+				}
+			}
+			b"w:tc" if self.table_depth == 1 => {
+				let cell = std::mem::take(&mut self.cell).join("<br>").replace('|', "\\|");
+				self.row.push(cell);
+			}
+			b"w:tr" if self.table_depth == 1 => {
+				let row = std::mem::take(&mut self.row);
+				if !row.is_empty() {
+					self.rows.push(row);
+				}
+			}
+			b"w:tbl" => {
+				self.table_depth = self.table_depth.saturating_sub(1);
+				if self.table_depth == 0 {
+					self.end_table();
+				}
+			}
+			_ => {}
+		}
+	}
+
+	fn text(&mut self, text: String) {
+		if self.skip_depth == 0 && self.in_text && !text.is_empty() {
+			self.push_piece(Piece::Text(self.fmt, text));
+		}
+	}
+}
+
+/// 从 `docProps/core.xml` 读取文档标题
+fn parse_title(xml: &[u8]) -> Option<String> {
+	let mut reader = Reader::from_reader(xml);
+	let mut buf = Vec::new();
+	let mut in_title = false;
+	loop {
+		match reader.read_event_into(&mut buf) {
+			Ok(XmlEvent::Start(e)) if e.name().as_ref() == b"dc:title" => in_title = true,
+			Ok(XmlEvent::Text(t)) if in_title => {
+				let title = t.unescape().ok()?.trim().to_string();
+				return (!title.is_empty()).then_some(title);
+			}
+			Ok(XmlEvent::End(_)) if in_title => return None,
+			Ok(XmlEvent::Eof) | Err(_) => return None,
+			_ => {}
+		}
+		buf.clear();
+	}
+}
+
+/// 文档属性中的标题（`docProps/core.xml` 的 `dc:title`）
+pub(crate) fn docx_title(bytes: &[u8]) -> Option<String> {
+	let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).ok()?;
+	read_entry(&mut archive, "docProps/core.xml").and_then(|x| parse_title(&x))
+}
+
+/// DOCX 转 Markdown
+pub(crate) fn docx_to_markdown(bytes: &[u8], image: &mut DocxImageSink) -> Result<String, String> {
+	let mut archive =
+		zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("不是有效的 DOCX 文件: {}", e))?;
+	let document = read_entry(&mut archive, "word/document.xml").ok_or("DOCX 缺少 word/document.xml")?;
+	let rels = read_entry(&mut archive, "word/_rels/document.xml.rels")
+		.map(|x| parse_rels(&x))
+		.unwrap_or_default();
+	let styles = read_entry(&mut archive, "word/styles.xml")
+		.map(|x| parse_styles(&x))
+		.unwrap_or_default();
+	let numbering = read_entry(&mut archive, "word/numbering.xml")
+		.map(|x| parse_numbering(&x))
+		.unwrap_or_default();
+
+	let mut media = |name: &str| read_entry(&mut archive, &format!("word/{}", name));
+	let mut conv = DocxConverter {
+		rels,
+		styles,
+		numbering,
+		media: &mut media,
+		image,
+		out: String::new(),
+		last: BlockKind::None,
+		code_lines: Vec::new(),
+		counters: HashMap::new(),
+		pieces: Vec::new(),
+		p_style: None,
+		num_id: None,
+		ilvl: 0,
+		in_run: false,
+		in_rpr: false,
+		in_text: false,
+		fmt: RunFmt::default(),
+		link: None,
+		image_alt: String::new(),
+		table_depth: 0,
+		rows: Vec::new(),
+		row: Vec::new(),
+		cell: Vec::new(),
+		skip_depth: 0,
+		textbox_depth: 0,
+	};
+
+	let mut reader = Reader::from_reader(document.as_slice());
+	let mut buf = Vec::new();
+	loop {
+		let event = reader
+			.read_event_into(&mut buf)
+			.map_err(|e| format!("解析 document.xml 失败（位置 {}）: {}", reader.buffer_position(), e))?;
+		match event {
+			XmlEvent::Start(e) => conv.open(&e, false),
+			XmlEvent::Empty(e) => conv.open(&e, true),
+			XmlEvent::End(e) => conv.close(e.name().as_ref()),
+			XmlEvent::Text(t) => conv.text(t.unescape().map_err(|e| e.to_string())?.to_string()),
+			XmlEvent::Eof => break,
+			_ => {}
+		}
+		buf.clear();
+	}
+	conv.flush_code();
+	let mut out = std::mem::take(&mut conv.out);
+	out.push('\n');
+	Ok(out)
+}
@@ -0,0 +1,719 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::command::knowledge::{collect_md_files, resolve_knowledge_root, write_file_atomic};
+use crate::command::knowledge_assets::relative_link;
+use crate::command::knowledge_vault::{encode_note, read_note_text};
+use crate::utils::percent::{encode_link_path, percent_decode};
+
+// —— 知识库 `[[wiki 链接]]` / 相对 Markdown 链接 / `#标签` 索引 ——
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KnowledgeLinkKind {
+	/// `[[note]]`、`[[note#标题|别名]]`、`![[note]]`
+	Wiki,
+	/// `[文本](./note.md)`、`![图](../a.md)`
+	Markdown,
+}
+
+/// 单条链接在原文中的位置；`target_start..target_end` 为可被重写的「目标名」字节区间
+#[derive(Debug, Clone)]
+pub(crate) struct RawLink {
+	pub kind: KnowledgeLinkKind,
+	/// wiki：`|`、`#` 之前的目标名；markdown：去掉 `#锚点`、`?查询` 且已百分号解码的 href
+	pub target: String,
+	/// `#` 之后的锚点（wiki：`|` 之前的标题；markdown：已百分号解码）
+	pub anchor: Option<String>,
+	/// 1 起始行号
+	pub line: usize,
+	pub target_start: usize,
+	pub target_end: usize,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct ParsedNote {
+	pub links: Vec<RawLink>,
+	pub tags: Vec<String>,
+	/// 指向本篇的锚点 `[文本](#标题)`、`[[#标题]]`：(行号, 锚点)
+	pub local_anchors: Vec<(usize, String)>,
+}
+
+fn is_tag_char(c: char) -> bool {
+	c.is_alphanumeric() || c == '_' || c == '-' || c == '/'
+}
+
+/// 外链（http:、mailto: 等）或纯锚点不参与笔记间链接
+pub(crate) fn is_external_href(href: &str) -> bool {
+	if href.starts_with('#') || href.starts_with("//") {
+		return true;
+	}
+	match href.find(':') {
+		Some(i) => {
+			let scheme = &href[..i];
+			// Windows 盘符 `C:\` 视为本地路径
+			scheme.len() > 1
+				&& scheme
+					.chars()
+					.all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+		}
+		None => false,
+	}
+}
+
+/// 标签规范化：去掉 `#`、统一小写，便于计数与检索
+pub(crate) fn normalize_tag(raw: &str) -> String {
+	raw.trim().trim_start_matches('#').trim_end_matches('/').to_lowercase()
+}
+
+/// 解析 front matter 中的 `tags: [a, b]` / `tags: a, b` / 多行 `- a`
+fn parse_front_matter_tags(block: &str, out: &mut Vec<String>) {
+	let mut in_tags = false;
+	for line in block.lines() {
+		let trimmed = line.trim();
+		if in_tags {
+			if let Some(item) = trimmed.strip_prefix("- ") {
+				let t = normalize_tag(item.trim_matches(|c| c == '"' || c == '\''));
+				if !t.is_empty() {
+					out.push(t);
+				}
+				continue;
+			}
+			in_tags = false;
+		}
+		let Some(rest) = trimmed.strip_prefix("tags:").or_else(|| trimmed.strip_prefix("tag:")) else {
+			continue;
+		};
+		let rest = rest.trim();
+		if rest.is_empty() {
+			in_tags = true;
+			continue;
+		}
+		for item in rest.trim_start_matches('[').trim_end_matches(']').split(',') {
+			let t = normalize_tag(item.trim().trim_matches(|c| c == '"' || c == '\''));
+			if !t.is_empty() {
+				out.push(t);
+			}
+		}
+	}
+}
+
+/// 扫描单行（非代码块）中的 wiki 链接、Markdown 链接与 `#标签`；`offset` 为行首在全文中的字节偏移
+fn scan_line(line: &str, offset: usize, line_no: usize, note: &mut ParsedNote) {
+	let bytes = line.as_bytes();
+	let mut i = 0;
+	while i < bytes.len() {
+		match bytes[i] {
+			b'`' => {
+				// 行内代码：跳到同长度的反引号串
+				let run = bytes[i..].iter().take_while(|b| **b == b'`').count();
+				let fence = &line[i..i + run];
+				match line[i + run..].find(fence) {
+					Some(end) => i += run + end + run,
+					None => i += run,
+				}
+				continue;
+			}
+			b'[' if bytes.get(i + 1) == Some(&b'[') => {
+				if let Some(close) = line[i + 2..].find("]]") {
+					let inner_start = i + 2;
+					let inner = &line[inner_start..inner_start + close];
+					let name_len = inner.find(['|', '#']).unwrap_or(inner.len());
+					let name = inner[..name_len].trim();
+					let anchor = inner[name_len..]
+						.strip_prefix('#')
+						.map(|a| a.split('|').next().unwrap_or("").trim().to_string())
+						.filter(|a| !a.is_empty());
+					if !name.is_empty() {
+						note.links.push(RawLink {
+							kind: KnowledgeLinkKind::Wiki,
+							target: name.to_string(),
+							anchor,
+							line: line_no,
+							target_start: offset + inner_start,
+							target_end: offset + inner_start + name_len,
+						});
+					} else if let Some(a) = anchor {
+						note.local_anchors.push((line_no, a));
+					}
+					i = inner_start + close + 2;
+					continue;
+				}
+			}
+			b'[' => {
+				if let Some(close) = line[i + 1..].find(']') {
+					let paren = i + 1 + close + 1;
+					if bytes.get(paren) == Some(&b'(')
+						&& let Some(end) = line[paren + 1..].find(')')
+					{
+						let raw = &line[paren + 1..paren + 1 + end];
+						let lead = raw.len() - raw.trim_start().len();
+						let mut href_start = paren + 1 + lead;
+						let mut href = raw.trim_start();
+						if let Some(stripped) = href.strip_prefix('<') {
+							href_start += 1;
+							href = stripped.split('>').next().unwrap_or("");
+						} else {
+							href = href.split_whitespace().next().unwrap_or("");
+						}
+						if let Some(a) = href.strip_prefix('#').filter(|a| !a.is_empty()) {
+							note.local_anchors.push((line_no, percent_decode(a)));
+						} else if !href.is_empty() && !is_external_href(href) {
+							let path_len = href.find(['#', '?']).unwrap_or(href.len());
+							let path_part = &href[..path_len];
+							if !path_part.is_empty() {
+								note.links.push(RawLink {
+									kind: KnowledgeLinkKind::Markdown,
+									target: percent_decode(path_part),
+									anchor: href
+										.find('#')
+										.map(|i| percent_decode(&href[i + 1..]))
+										.filter(|a| !a.is_empty()),
+									line: line_no,
+									target_start: offset + href_start,
+									target_end: offset + href_start + path_len,
+								});
+							}
+						}
+						i = paren + 1 + end + 1;
+						continue;
+					}
+				}
+			}
+			b'#' => {
+				let prev_ok = i == 0 || line[..i].chars().next_back().is_some_and(char::is_whitespace);
+				if prev_ok {
+					let tag: String = line[i + 1..].chars().take_while(|c| is_tag_char(*c)).collect();
+					if !tag.is_empty() && !tag.chars().all(|c| c.is_ascii_digit()) {
+						note.tags.push(normalize_tag(&tag));
+						i += 1 + tag.len();
+						continue;
+					}
+				}
+			}
+			_ => {}
+		}
+		i += line[i..].chars().next().map(char::len_utf8).unwrap_or(1);
+	}
+}
+
+/// front matter（首行 `---` 至下一个 `---` / `...`）的字节长度与正文；无 front matter 时为 None
+pub(crate) fn split_front_matter(content: &str) -> Option<(usize, &str)> {
+	if !content.starts_with("---") {
+		return None;
+	}
+	let mut consumed = 0usize;
+	for (idx, raw) in content.split_inclusive('\n').enumerate() {
+		consumed += raw.len();
+		if idx == 0 {
+			if raw.trim_end() != "---" {
+				return None;
+			}
+			continue;
+		}
+		let t = raw.trim_end();
+		if t == "---" || t == "..." {
+			let first = content.find('\n').map(|i| i + 1).unwrap_or(content.len());
+			let block_end = consumed - raw.len();
+			return Some((consumed, &content[first..block_end.max(first)]));
+		}
+	}
+	None
+}
+
+/// 解析整篇 Markdown：front matter 只取 `tags`，并跳过 ``` / ~~~ 代码块
+pub(crate) fn parse_note(content: &str) -> ParsedNote {
+	let mut note = ParsedNote::default();
+	let body_start = match split_front_matter(content) {
+		Some((len, block)) => {
+			parse_front_matter_tags(block, &mut note.tags);
+			len
+		}
+		None => 0,
+	};
+	let mut offset = 0usize;
+	let mut fence: Option<String> = None;
+	for (idx, raw) in content.split_inclusive('\n').enumerate() {
+		let line_start = offset;
+		offset += raw.len();
+		if line_start < body_start {
+			continue;
+		}
+		let line = raw.trim_end_matches(['\n', '\r']);
+		let trimmed = line.trim_start();
+		if let Some(ref f) = fence {
+			if trimmed.starts_with(f.as_str()) {
+				fence = None;
+			}
+		} else if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+			fence = Some(trimmed[..3].to_string());
+		} else {
+			// 标题行 `# xxx` 的 `#` 后是空格，不会被识别为标签
+			scan_line(line, line_start, idx + 1, &mut note);
+		}
+	}
+	note.tags.sort();
+	note.tags.dedup();
+	note
+}
+
+/// 按字面消去 `.` 与 `..`，不访问文件系统
+pub(crate) fn normalize_components(p: &Path) -> PathBuf {
+	let mut out = PathBuf::new();
+	for c in p.components() {
+		match c {
+			Component::CurDir => {}
+			Component::ParentDir => {
+				out.pop();
+			}
+			other => out.push(other.as_os_str()),
+		}
+	}
+	out
+}
+
+fn note_title(p: &Path) -> String {
+	p.file_stem()
+		.and_then(|s| s.to_str())
+		.unwrap_or("未命名")
+		.to_string()
+}
+
+/// 相对根目录、去掉 `.md` 后缀、统一 `/` 分隔的路径，即路径式 wiki 链接的写法
+fn wiki_key_path(root: &Path, p: &Path) -> String {
+	let rel = p.strip_prefix(root).unwrap_or(p).with_extension("");
+	rel.to_string_lossy().replace('\\', "/")
+}
+
+/// [`wiki_key_path`] 的小写形式，用于 wiki 链接的路径式匹配
+fn wiki_key(root: &Path, p: &Path) -> String {
+	wiki_key_path(root, p).to_lowercase()
+}
+
+struct IndexedNote {
+	path: PathBuf,
+	title: String,
+	parsed: ParsedNote,
+}
+
+/// 扫描后的知识库链接索引
+pub(crate) struct KnowledgeLinkIndex {
+	root: PathBuf,
+	notes: Vec<IndexedNote>,
+	by_key: HashMap<String, usize>,
+	by_stem: HashMap<String, Vec<usize>>,
+}
+
+impl KnowledgeLinkIndex {
+	pub(crate) fn build(root: &Path) -> Result<Self, String> {
+		let mut paths = Vec::new();
+		collect_md_files(root, &mut paths)?;
+		paths.sort();
+		// 单个文件读取失败（编码等）时跳过，不影响整体索引
+		let parsed = paths
+			.into_iter()
+			.filter_map(|path| {
+				let content = read_note_text(&path).ok()?;
+				Some((path, parse_note(&content)))
+			})
+			.collect();
+		Ok(Self::from_parsed(root, parsed))
+	}
+
+	/// 由已解析的笔记构建索引（调用方已自行读取内容时使用）
+	pub(crate) fn from_parsed(root: &Path, parsed: Vec<(PathBuf, ParsedNote)>) -> Self {
+		let notes: Vec<IndexedNote> = parsed
+			.into_iter()
+			.map(|(path, parsed)| IndexedNote {
+				title: note_title(&path),
+				parsed,
+				path,
+			})
+			.collect();
+		let mut by_key = HashMap::new();
+		let mut by_stem: HashMap<String, Vec<usize>> = HashMap::new();
+		for (i, n) in notes.iter().enumerate() {
+			by_key.insert(wiki_key(root, &n.path), i);
+			by_stem.entry(n.title.to_lowercase()).or_default().push(i);
+		}
+		Self {
+			root: root.to_path_buf(),
+			notes,
+			by_key,
+			by_stem,
+		}
+	}
+
+	/// 将链接解析为索引内笔记下标；未命中返回 None
+	pub(crate) fn resolve(&self, source: &Path, link: &RawLink) -> Option<usize> {
+		match link.kind {
+			KnowledgeLinkKind::Wiki => {
+				let name = link.target.trim().trim_end_matches(".md").replace('\\', "/");
+				let lower = name.to_lowercase();
+				if lower.contains('/') {
+					return self.by_key.get(lower.trim_start_matches('/')).copied();
+				}
+				let candidates = self.by_stem.get(&lower)?;
+				// 同名笔记多于一个时优先与来源同目录的
+				candidates
+					.iter()
+					.copied()
+					.find(|i| self.notes[*i].path.parent() == source.parent())
+					.or_else(|| candidates.first().copied())
+			}
+			KnowledgeLinkKind::Markdown => {
+				let base = source.parent().unwrap_or(&self.root);
+				let joined = if link.target.starts_with('/') {
+					self.root.join(link.target.trim_start_matches('/'))
+				} else {
+					base.join(&link.target)
+				};
+				let target = normalize_components(&joined);
+				self.notes
+					.iter()
+					.position(|n| normalize_components(&n.path) == target)
+			}
+		}
+	}
+
+	/// 所有已解析的边：(来源下标, 目标下标, 原始链接)
+	fn edges(&self) -> Vec<(usize, usize, &RawLink)> {
+		let mut out = Vec::new();
+		for (i, n) in self.notes.iter().enumerate() {
+			for link in &n.parsed.links {
+				if let Some(j) = self.resolve(&n.path, link) {
+					out.push((i, j, link));
+				}
+			}
+		}
+		out
+	}
+
+	/// 按下标遍历笔记路径与解析结果
+	pub(crate) fn notes(&self) -> impl Iterator<Item = (&Path, &ParsedNote)> {
+		self.notes.iter().map(|n| (n.path.as_path(), &n.parsed))
+	}
+
+	pub(crate) fn path_of(&self, idx: usize) -> &Path {
+		&self.notes[idx].path
+	}
+
+	fn position_of(&self, path: &Path) -> Option<usize> {
+		let target = normalize_components(path);
+		self.notes
+			.iter()
+			.position(|n| normalize_components(&n.path) == target)
+	}
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeLinkIndexInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeBacklinksInput {
+	pub file_path: String,
+	#[serde(default)]
+	pub dir_path: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeBacklink {
+	pub source_path: String,
+	pub source_title: String,
+	pub kind: KnowledgeLinkKind,
+	/// 链接所在行（1 起始）
+	pub line: usize,
+	/// 链接所在行原文，便于前端展示上下文
+	pub context: String,
+}
+
+/// 查询指向某篇笔记的所有反向链接
+#[tauri::command]
+pub async fn get_knowledge_backlinks(
+	app: AppHandle,
+	input: KnowledgeBacklinksInput,
+) -> Result<Vec<KnowledgeBacklink>, String> {
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let target = PathBuf::from(input.file_path.trim());
+	tauri::async_runtime::spawn_blocking(move || {
+		let index = KnowledgeLinkIndex::build(&root)?;
+		let Some(target_idx) = index.position_of(&target) else {
+			return Err(format!("笔记不在知识库目录中：{}", target.display()));
+		};
+		let mut sources: HashMap<usize, String> = HashMap::new();
+		let mut out = Vec::new();
+		for (src, dst, link) in index.edges() {
+			if dst != target_idx || src == target_idx {
+				continue;
+			}
+			let note = &index.notes[src];
+			let content = sources
+				.entry(src)
+				.or_insert_with(|| read_note_text(&note.path).unwrap_or_default());
+			let context = content
+				.lines()
+				.nth(link.line.saturating_sub(1))
+				.unwrap_or("")
+				.trim()
+				.to_string();
+			out.push(KnowledgeBacklink {
+				source_path: note.path.to_string_lossy().to_string(),
+				source_title: note.title.clone(),
+				kind: link.kind,
+				line: link.line,
+				context,
+			});
+		}
+		Ok(out)
+	})
+	.await
+	.map_err(|e| e.to_string())?
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeTagEntry {
+	pub tag: String,
+	/// 含该标签的笔记数
+	pub count: usize,
+	pub paths: Vec<String>,
+}
+
+/// 标签列表（按笔记数降序，再按标签名）
+#[tauri::command]
+pub async fn list_knowledge_tags(
+	app: AppHandle,
+	input: KnowledgeLinkIndexInput,
+) -> Result<Vec<KnowledgeTagEntry>, String> {
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	tauri::async_runtime::spawn_blocking(move || {
+		let index = KnowledgeLinkIndex::build(&root)?;
+		let mut tags: BTreeMap<String, Vec<String>> = BTreeMap::new();
+		for n in &index.notes {
+			for t in &n.parsed.tags {
+				tags.entry(t.clone())
+					.or_default()
+					.push(n.path.to_string_lossy().to_string());
+			}
+		}
+		let mut out: Vec<KnowledgeTagEntry> = tags
+			.into_iter()
+			.map(|(tag, paths)| KnowledgeTagEntry {
+				tag,
+				count: paths.len(),
+				paths,
+			})
+			.collect();
+		out.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+		Ok(out)
+	})
+	.await
+	.map_err(|e| e.to_string())?
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeGraphNode {
+	/// 笔记绝对路径，同时作为边的端点 id
+	pub id: String,
+	pub title: String,
+	pub tags: Vec<String>,
+	pub inbound: usize,
+	pub outbound: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeGraphEdge {
+	pub source: String,
+	pub target: String,
+	pub kind: KnowledgeLinkKind,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeLinkGraph {
+	pub nodes: Vec<KnowledgeGraphNode>,
+	pub edges: Vec<KnowledgeGraphEdge>,
+}
+
+fn build_graph(index: &KnowledgeLinkIndex) -> KnowledgeLinkGraph {
+	let mut inbound = vec![0usize; index.notes.len()];
+	let mut outbound = vec![0usize; index.notes.len()];
+	let mut seen: HashSet<(usize, usize)> = HashSet::new();
+	let mut edges = Vec::new();
+	for (src, dst, link) in index.edges() {
+		// 自链接与重复边不计入图
+		if src == dst || !seen.insert((src, dst)) {
+			continue;
+		}
+		outbound[src] += 1;
+		inbound[dst] += 1;
+		edges.push(KnowledgeGraphEdge {
+			source: index.notes[src].path.to_string_lossy().to_string(),
+			target: index.notes[dst].path.to_string_lossy().to_string(),
+			kind: link.kind,
+		});
+	}
+	let nodes = index
+		.notes
+		.iter()
+		.enumerate()
+		.map(|(i, n)| KnowledgeGraphNode {
+			id: n.path.to_string_lossy().to_string(),
+			title: n.title.clone(),
+			tags: n.parsed.tags.clone(),
+			inbound: inbound[i],
+			outbound: outbound[i],
+		})
+		.collect();
+	KnowledgeLinkGraph { nodes, edges }
+}
+
+/// 知识库关系图（节点 + 去重后的有向边），供前端可视化
+#[tauri::command]
+pub async fn get_knowledge_link_graph(
+	app: AppHandle,
+	input: KnowledgeLinkIndexInput,
+) -> Result<KnowledgeLinkGraph, String> {
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	tauri::async_runtime::spawn_blocking(move || {
+		let index = KnowledgeLinkIndex::build(&root)?;
+		Ok(build_graph(&index))
+	})
+	.await
+	.map_err(|e| e.to_string())?
+}
+
+/// 孤立笔记：既没有被其他笔记链接，也没有链接到其他笔记
+#[tauri::command]
+pub async fn list_knowledge_orphan_notes(
+	app: AppHandle,
+	input: KnowledgeLinkIndexInput,
+) -> Result<Vec<KnowledgeGraphNode>, String> {
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	tauri::async_runtime::spawn_blocking(move || {
+		let index = KnowledgeLinkIndex::build(&root)?;
+		Ok(build_graph(&index)
+			.nodes
+			.into_iter()
+			.filter(|n| n.inbound == 0 && n.outbound == 0)
+			.collect())
+	})
+	.await
+	.map_err(|e| e.to_string())?
+}
+
+/// 入链改写计划：各来源笔记改写后的完整内容，[`InboundLinkRewrite::apply`] 时才写回磁盘
+pub(crate) struct InboundLinkRewrite {
+	/// （来源笔记, 改写后内容, 改写的链接数）
+	files: Vec<(PathBuf, String, usize)>,
+}
+
+impl InboundLinkRewrite {
+	/// 放弃对某篇笔记的改写（如该笔记将被覆盖、已移入回收站）
+	pub(crate) fn skip(&mut self, path: &Path) {
+		self.files.retain(|(p, _, _)| p != path);
+	}
+
+	/// 写回全部来源笔记，返回改写的链接数
+	pub(crate) fn apply(self) -> Result<usize, String> {
+		let mut links = 0usize;
+		for (path, content, n) in &self.files {
+			write_file_atomic(path, &encode_note(path, content)?)?;
+			links += n;
+		}
+		Ok(links)
+	}
+}
+
+/// 笔记由 `old_path` 重命名 / 合并为 `new_path` 后，改写 `root` 下其他笔记中指向旧文件的链接；返回改写的链接数
+///
+/// 须在磁盘重命名**之前**调用，以便旧文件仍能参与链接解析
+pub(crate) fn rewrite_inbound_links(
+	root: &Path,
+	old_path: &Path,
+	new_path: &Path,
+) -> Result<usize, String> {
+	plan_inbound_links(root, old_path, new_path)?.apply()
+}
+
+/// 只计算入链改写、不写盘：重命名前解析链接，重命名成功后再 `apply`，失败时其他笔记保持原样
+pub(crate) fn plan_inbound_links(
+	root: &Path,
+	old_path: &Path,
+	new_path: &Path,
+) -> Result<InboundLinkRewrite, String> {
+	let index = KnowledgeLinkIndex::build(root)?;
+	let Some(old_idx) = index.position_of(old_path) else {
+		return Ok(InboundLinkRewrite { files: Vec::new() });
+	};
+	let new_stem = note_title(new_path);
+	let old_name = old_path
+		.file_name()
+		.map(|s| s.to_string_lossy().to_string())
+		.unwrap_or_default();
+	let new_name = new_path
+		.file_name()
+		.map(|s| s.to_string_lossy().to_string())
+		.unwrap_or_default();
+	let same_dir = old_path.parent() == new_path.parent();
+
+	let mut per_note: HashMap<usize, Vec<(usize, usize, KnowledgeLinkKind)>> = HashMap::new();
+	for (src, dst, link) in index.edges() {
+		if dst != old_idx || src == old_idx {
+			continue;
+		}
+		per_note
+			.entry(src)
+			.or_default()
+			.push((link.target_start, link.target_end, link.kind));
+	}
+
+	let mut files = Vec::with_capacity(per_note.len());
+	for (src, mut edits) in per_note {
+		let mut changed = 0usize;
+		let path = &index.notes[src].path;
+		let mut content = read_note_text(path)?;
+		edits.sort_by_key(|e| Reverse(e.0));
+		for (start, end, kind) in edits {
+			let Some(raw) = content.get(start..end) else {
+				continue;
+			};
+			let replacement = match kind {
+				// 同目录时保留 `folder/` 前缀，只替换末段笔记名；跨目录时改为新的根目录相对路径
+				KnowledgeLinkKind::Wiki => match raw.rfind('/') {
+					Some(_) if !same_dir => wiki_key_path(root, new_path),
+					Some(i) => format!("{}{}", &raw[..=i], new_stem),
+					None => new_stem.clone(),
+				},
+				// 同目录时只替换文件名部分（href 中文件名可能经过百分号编码）；跨目录时按来源笔记重算相对路径
+				KnowledgeLinkKind::Markdown => {
+					let dir_len = raw.rfind('/').map(|i| i + 1).unwrap_or(0);
+					if percent_decode(&raw[dir_len..]) != old_name {
+						continue;
+					}
+					if same_dir {
+						format!("{}{}", &raw[..dir_len], encode_link_path(&new_name))
+					} else {
+						encode_link_path(&relative_link(path.parent().unwrap_or(root), new_path))
+					}
+				}
+			};
+			content.replace_range(start..end, &replacement);
+			changed += 1;
+		}
+		files.push((path.clone(), content, changed));
+	}
+	Ok(InboundLinkRewrite { files })
+}
@@ -0,0 +1,379 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::command::knowledge::{collect_md_files, resolve_knowledge_root};
+use crate::command::knowledge_assets::assets_dir;
+use crate::command::knowledge_chunk::atx_heading;
+use crate::command::knowledge_link::{KnowledgeLinkIndex, KnowledgeLinkKind, RawLink, parse_note, split_front_matter};
+use crate::command::knowledge_vault::read_note_text;
+
+// —— 知识库体检：失效的笔记链接与锚点、缺失的附件、重复标题、空笔记 / 超大笔记、标题跳级 ——
+
+const DEFAULT_HUGE_NOTE_KB: u64 = 512;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KnowledgeLintKind {
+	/// 链接的笔记不存在
+	BrokenLink,
+	/// 目标笔记（或本篇）中没有对应标题 / 块
+	BrokenAnchor,
+	/// 引用的图片等附件不存在
+	MissingAsset,
+	/// 与其他笔记标题相同（首个一级标题，无则文件名）
+	DuplicateTitle,
+	/// 除标题外没有正文
+	EmptyNote,
+	/// 文件大小超过阈值
+	HugeNote,
+	/// 标题级别跳级，如 `#` 之后直接 `###`
+	HeadingSkip,
+	/// 读取失败（编码错误、加密知识库未解锁等）
+	Unreadable,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KnowledgeLintSeverity {
+	Error,
+	Warning,
+}
+
+impl KnowledgeLintKind {
+	fn severity(self) -> KnowledgeLintSeverity {
+		match self {
+			Self::BrokenLink | Self::BrokenAnchor | Self::MissingAsset => KnowledgeLintSeverity::Error,
+			_ => KnowledgeLintSeverity::Warning,
+		}
+	}
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeLintIssue {
+	pub kind: KnowledgeLintKind,
+	pub severity: KnowledgeLintSeverity,
+	pub path: String,
+	/// 1 起始行号；针对整篇笔记的问题为 None
+	pub line: Option<usize>,
+	pub message: String,
+	/// 重复标题时为其余同名笔记的路径
+	pub related: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeLintReport {
+	pub scanned: usize,
+	pub error_count: usize,
+	pub warning_count: usize,
+	/// 按路径、行号排序
+	pub issues: Vec<KnowledgeLintIssue>,
+}
+
+fn issue(kind: KnowledgeLintKind, path: &Path, line: Option<usize>, message: String) -> KnowledgeLintIssue {
+	KnowledgeLintIssue {
+		kind,
+		severity: kind.severity(),
+		path: path.to_string_lossy().to_string(),
+		line,
+		message,
+		related: Vec::new(),
+	}
+}
+
+/// GitHub 风格标题锚点：小写，保留字母数字（含中文）、`-`、`_`，空白转 `-`，其余标点去掉
+pub(crate) fn heading_slug(text: &str) -> String {
+	text.trim()
+		.to_lowercase()
+		.chars()
+		.filter_map(|c| {
+			if c.is_alphanumeric() || c == '-' || c == '_' {
+				Some(c)
+			} else if c.is_whitespace() {
+				Some('-')
+			} else {
+				None
+			}
+		})
+		.collect()
+}
+
+/// 单篇笔记中与体检相关的信息
+struct NoteFacts {
+	title: String,
+	/// 标题锚点；重名标题依次追加 `-1`、`-2`
+	slugs: HashSet<String>,
+	/// 行尾 `^id` 块标识
+	block_ids: HashSet<String>,
+}
+
+struct BodyScan {
+	/// (行号, 级别, 文本)
+	headings: Vec<(usize, usize, String)>,
+	block_ids: HashSet<String>,
+	/// 除标题、空行外是否还有内容（代码块计入）
+	has_text: bool,
+}
+
+/// 扫描正文（跳过 front matter；代码块内不识别标题）
+fn scan_body(content: &str) -> BodyScan {
+	let skip = split_front_matter(content).map(|(len, _)| len).unwrap_or(0);
+	let mut scan = BodyScan {
+		headings: Vec::new(),
+		block_ids: HashSet::new(),
+		has_text: false,
+	};
+	let mut fence: Option<&str> = None;
+	let mut offset = 0usize;
+	for (idx, raw) in content.split_inclusive('\n').enumerate() {
+		let start = offset;
+		offset += raw.len();
+		if start < skip {
+			continue;
+		}
+		let line = raw.trim_end_matches(['\r', '\n']);
+		let trimmed = line.trim();
+		if trimmed.is_empty() {
+			continue;
+		}
+		if let Some(f) = fence {
+			if trimmed.starts_with(f) {
+				fence = None;
+			}
+			scan.has_text = true;
+			continue;
+		}
+		if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+			fence = Some(&trimmed[..3]);
+			scan.has_text = true;
+			continue;
+		}
+		if let Some((level, text)) = atx_heading(line) {
+			scan.headings.push((idx + 1, level, text));
+			continue;
+		}
+		scan.has_text = true;
+		let last = trimmed.rsplit(char::is_whitespace).next().unwrap_or("");
+		if let Some(id) = last.strip_prefix('^')
+			&& !id.is_empty()
+			&& id.chars().all(|c| c.is_alphanumeric() || c == '-')
+		{
+			scan.block_ids.insert(id.to_string());
+		}
+	}
+	scan
+}
+
+fn note_facts(path: &Path, scan: BodyScan) -> NoteFacts {
+	let mut slugs = HashSet::new();
+	let mut seen: HashMap<String, usize> = HashMap::new();
+	for (_, _, text) in &scan.headings {
+		let base = heading_slug(text);
+		let n = seen.entry(base.clone()).or_insert(0);
+		slugs.insert(if *n == 0 { base.clone() } else { format!("{}-{}", base, n) });
+		*n += 1;
+	}
+	let title = scan
+		.headings
+		.iter()
+		.find(|(_, level, text)| *level == 1 && !text.is_empty())
+		.map(|(_, _, text)| text.clone())
+		.unwrap_or_else(|| {
+			path.file_stem()
+				.map(|s| s.to_string_lossy().to_string())
+				.unwrap_or_default()
+		});
+	NoteFacts {
+		title,
+		slugs,
+		block_ids: scan.block_ids,
+	}
+}
+
+/// 锚点是否存在：`^id` 为块引用，其余按标题 slug 比较；`[[笔记#一级#二级]]` 取最后一级
+fn anchor_exists(facts: &NoteFacts, anchor: &str) -> bool {
+	if let Some(id) = anchor.strip_prefix('^') {
+		return facts.block_ids.contains(id);
+	}
+	let last = anchor.rsplit('#').next().unwrap_or(anchor);
+	facts.slugs.contains(&heading_slug(last))
+}
+
+/// 带非 Markdown 扩展名（图片、PDF 等）的链接目标视为附件
+fn is_asset_target(target: &str) -> bool {
+	Path::new(target)
+		.extension()
+		.and_then(|e| e.to_str())
+		.is_some_and(|e| {
+			!e.eq_ignore_ascii_case("md")
+				&& (1..=5).contains(&e.len())
+				&& e.chars().all(|c| c.is_ascii_alphanumeric())
+		})
+}
+
+/// 链接目标在磁盘上是否存在：markdown 相对笔记目录（`/` 开头相对知识库根目录）；
+/// wiki 依次尝试笔记目录、知识库根目录与附件目录
+fn local_target_exists(root: &Path, note_dir: &Path, link: &RawLink) -> bool {
+	let target = link.target.trim();
+	let candidates: Vec<PathBuf> = match link.kind {
+		KnowledgeLinkKind::Markdown => match target.strip_prefix('/') {
+			Some(rest) => vec![root.join(rest), PathBuf::from(target)],
+			None => vec![note_dir.join(target)],
+		},
+		KnowledgeLinkKind::Wiki => vec![
+			note_dir.join(target),
+			root.join(target),
+			assets_dir(root).join(target),
+		],
+	};
+	candidates.iter().any(|p| p.exists())
+}
+
+fn lint_root(root: &Path, huge_bytes: u64) -> Result<KnowledgeLintReport, String> {
+	let mut paths = Vec::new();
+	collect_md_files(root, &mut paths)?;
+	paths.sort();
+	let scanned = paths.len();
+	let mut issues = Vec::new();
+	let mut parsed = Vec::with_capacity(paths.len());
+	let mut facts = Vec::with_capacity(paths.len());
+	for path in paths {
+		let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+		if size > huge_bytes {
+			issues.push(issue(
+				KnowledgeLintKind::HugeNote,
+				&path,
+				None,
+				format!("笔记过大：{} KB（阈值 {} KB）", size.div_ceil(1024), huge_bytes / 1024),
+			));
+		}
+		let content = match read_note_text(&path) {
+			Ok(c) => c,
+			Err(e) => {
+				issues.push(issue(KnowledgeLintKind::Unreadable, &path, None, format!("无法读取：{}", e)));
+				continue;
+			}
+		};
+		let scan = scan_body(&content);
+		if !scan.has_text {
+			issues.push(issue(KnowledgeLintKind::EmptyNote, &path, None, "笔记没有正文内容".to_string()));
+		}
+		for pair in scan.headings.windows(2) {
+			let ((_, prev, _), (line, level, text)) = (&pair[0], &pair[1]);
+			if *level > prev + 1 {
+				issues.push(issue(
+					KnowledgeLintKind::HeadingSkip,
+					&path,
+					Some(*line),
+					format!("标题跳级：{} 级标题之后直接出现 {} 级标题「{}」", prev, level, text),
+				));
+			}
+		}
+		facts.push(note_facts(&path, scan));
+		parsed.push((path, parse_note(&content)));
+	}
+
+	// `from_parsed` 保持顺序，索引下标与 `facts` 一一对应
+	let index = KnowledgeLinkIndex::from_parsed(root, parsed);
+	for (i, (path, note)) in index.notes().enumerate() {
+		let note_dir = path.parent().unwrap_or(root);
+		for link in &note.links {
+			let is_asset = is_asset_target(&link.target);
+			if !is_asset && let Some(j) = index.resolve(path, link) {
+				if let Some(anchor) = &link.anchor
+					&& !anchor_exists(&facts[j], anchor)
+				{
+					issues.push(issue(
+						KnowledgeLintKind::BrokenAnchor,
+						path,
+						Some(link.line),
+						format!("锚点不存在：{}#{}", link.target, anchor),
+					));
+				}
+				continue;
+			}
+			if local_target_exists(root, note_dir, link) {
+				continue;
+			}
+			let (kind, message) = if is_asset {
+				(KnowledgeLintKind::MissingAsset, format!("附件不存在：{}", link.target))
+			} else {
+				(KnowledgeLintKind::BrokenLink, format!("链接的笔记不存在：{}", link.target))
+			};
+			issues.push(issue(kind, path, Some(link.line), message));
+		}
+		for (line, anchor) in &note.local_anchors {
+			if !anchor_exists(&facts[i], anchor) {
+				issues.push(issue(
+					KnowledgeLintKind::BrokenAnchor,
+					path,
+					Some(*line),
+					format!("本篇中不存在锚点：#{}", anchor),
+				));
+			}
+		}
+	}
+
+	let mut by_title: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+	for (i, f) in facts.iter().enumerate() {
+		by_title.entry(f.title.trim().to_lowercase()).or_default().push(i);
+	}
+	for group in by_title.values().filter(|g| g.len() > 1) {
+		for &i in group {
+			let path = index.path_of(i);
+			let mut dup = issue(
+				KnowledgeLintKind::DuplicateTitle,
+				path,
+				None,
+				format!("标题「{}」与其他 {} 篇笔记重复", facts[i].title, group.len() - 1),
+			);
+			dup.related = group
+				.iter()
+				.filter(|j| **j != i)
+				.map(|j| index.path_of(*j).to_string_lossy().to_string())
+				.collect();
+			issues.push(dup);
+		}
+	}
+
+	issues.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.line.cmp(&b.line)));
+	let error_count = issues
+		.iter()
+		.filter(|i| i.severity == KnowledgeLintSeverity::Error)
+		.count();
+	Ok(KnowledgeLintReport {
+		scanned,
+		error_count,
+		warning_count: issues.len() - error_count,
+		issues,
+	})
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeLintInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	/// 超大笔记阈值（KB），默认 512
+	#[serde(default)]
+	pub huge_note_kb: Option<u64>,
+}
+
+/// 扫描知识库，报告失效链接 / 锚点、缺失附件、重复标题、空笔记与超大笔记等问题
+#[tauri::command]
+pub async fn lint_knowledge_notes(
+	app: AppHandle,
+	input: Option<KnowledgeLintInput>,
+) -> Result<KnowledgeLintReport, String> {
+	let input = input.unwrap_or_default();
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let huge_bytes = input.huge_note_kb.unwrap_or(DEFAULT_HUGE_NOTE_KB).max(1) * 1024;
+	tauri::async_runtime::spawn_blocking(move || lint_root(&root, huge_bytes))
+		.await
+		.map_err(|e| e.to_string())?
+}
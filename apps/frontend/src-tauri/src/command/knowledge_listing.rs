@@ -0,0 +1,514 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use base64::Engine as _;
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::command::knowledge::{
+	KnowledgeMarkdownFileEntry, resolve_knowledge_dir, resolve_knowledge_root, write_file_atomic,
+};
+use crate::command::knowledge_chunk::{INDEX_DIR_NAME, rel_of};
+use crate::command::knowledge_export::{markdown_options, note_body};
+use crate::command::knowledge_stats::system_time_ms;
+use crate::command::knowledge_vault::{is_vault_dir, read_note_text};
+
+// —— 笔记列表：元数据（mtime、大小、创建时间、摘要）缓存在 `<知识库>/.index/listing.json` 并常驻内存，
+// 每次列出只遍历目录取一次元数据，mtime 或大小变化的条目作废（摘要随之重新生成）；
+// 仅默认知识库目录落盘，前端传入的其他目录与加密知识库只缓存在内存中。
+// 分页查询支持游标、按标题 / 创建 / 更新 / 大小排序、文件夹与 glob 过滤及正文摘要 ——
+
+const LISTING_CACHE_FILE: &str = "listing.json";
+const CACHE_VERSION: u32 = 1;
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 500;
+/// 摘要最多保留的字符数
+const EXCERPT_CHARS: usize = 160;
+/// 带游标的翻页请求在此时间内复用上次遍历结果，连续翻页时不重复扫描目录
+const PAGE_REUSE_WINDOW: Duration = Duration::from_secs(5);
+
+/// 知识库根目录 → 列表缓存
+static CACHES: LazyLock<Mutex<HashMap<PathBuf, ListingState>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NoteMeta {
+	mtime_ms: u64,
+	size: u64,
+	/// 文件创建时间；文件系统不支持时为修改时间
+	created_ms: u64,
+	/// 正文摘要；首次按需生成，None 表示尚未生成
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	excerpt: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListingCache {
+	version: u32,
+	/// 相对知识库根目录的路径（`/` 分隔）→ 元数据
+	notes: BTreeMap<String, NoteMeta>,
+}
+
+struct ListingState {
+	cache: ListingCache,
+	/// 上次遍历目录的时间
+	scanned_at: Option<Instant>,
+}
+
+fn cache_path(root: &Path) -> PathBuf {
+	root.join(INDEX_DIR_NAME).join(LISTING_CACHE_FILE)
+}
+
+fn load_cache(root: &Path) -> ListingCache {
+	fs::read_to_string(cache_path(root))
+		.ok()
+		.and_then(|s| serde_json::from_str::<ListingCache>(&s).ok())
+		.filter(|c| c.version == CACHE_VERSION)
+		.unwrap_or(ListingCache {
+			version: CACHE_VERSION,
+			..Default::default()
+		})
+}
+
+fn save_cache(root: &Path, cache: &ListingCache) -> Result<(), String> {
+	fs::create_dir_all(root.join(INDEX_DIR_NAME)).map_err(|e| e.to_string())?;
+	let json = serde_json::to_string(cache).map_err(|e| e.to_string())?;
+	write_file_atomic(&cache_path(root), json.as_bytes())
+}
+
+/// 与 `collect_md_files` 相同的遍历规则（跳过 `.` 开头的文件与目录），同时带回每个文件的元数据
+fn collect_md_meta(dir: &Path, out: &mut Vec<(PathBuf, fs::Metadata)>) -> Result<(), String> {
+	for ent in fs::read_dir(dir).map_err(|e| e.to_string())? {
+		let ent = ent.map_err(|e| e.to_string())?;
+		if ent.file_name().to_string_lossy().starts_with('.') {
+			continue;
+		}
+		let p = ent.path();
+		let meta = ent.metadata().map_err(|e| e.to_string())?;
+		if meta.is_dir() {
+			collect_md_meta(&p, out)?;
+		} else if meta.is_file()
+			&& p.extension()
+				.and_then(|s| s.to_str())
+				.is_some_and(|e| e.eq_ignore_ascii_case("md"))
+		{
+			out.push((p, meta));
+		}
+	}
+	Ok(())
+}
+
+/// 遍历目录同步缓存：新增与 mtime / 大小变化的条目重建，已删除的移除。返回缓存是否有变化
+fn refresh_cache(root: &Path, cache: &mut ListingCache) -> Result<bool, String> {
+	let mut files = Vec::new();
+	collect_md_meta(root, &mut files)?;
+	let mut live: HashSet<String> = HashSet::with_capacity(files.len());
+	let mut dirty = false;
+	for (path, meta) in files {
+		let Some(rel) = rel_of(root, &path) else {
+			continue;
+		};
+		let mtime_ms = system_time_ms(meta.modified()).unwrap_or(0);
+		let size = meta.len();
+		live.insert(rel.clone());
+		if cache
+			.notes
+			.get(&rel)
+			.is_some_and(|n| n.mtime_ms == mtime_ms && n.size == size)
+		{
+			continue;
+		}
+		cache.notes.insert(
+			rel,
+			NoteMeta {
+				mtime_ms,
+				size,
+				created_ms: system_time_ms(meta.created()).unwrap_or(mtime_ms),
+				excerpt: None,
+			},
+		);
+		dirty = true;
+	}
+	let before = cache.notes.len();
+	cache.notes.retain(|rel, _| live.contains(rel));
+	Ok(dirty || cache.notes.len() != before)
+}
+
+/// 丢弃 `root` 及其子目录的内存缓存（启用加密、锁定时清除已生成的明文摘要）
+pub(crate) fn evict_listing_caches(root: &Path) {
+	if let Ok(mut caches) = CACHES.lock() {
+		caches.retain(|dir, _| !fs::canonicalize(dir).unwrap_or_else(|_| dir.clone()).starts_with(root));
+	}
+}
+
+/// 缓存是否可写入 `root/.index`：列出是只读操作，只有默认知识库目录才落盘，
+/// 其他任意目录（如前端传入的 `dirPath`）不写入文件
+pub(crate) async fn listing_persists(app: &AppHandle, root: &Path) -> bool {
+	let Ok(dir) = resolve_knowledge_dir(app).await else {
+		return false;
+	};
+	match (dir.canonicalize(), root.canonicalize()) {
+		(Ok(a), Ok(b)) => a == b,
+		_ => dir == root,
+	}
+}
+
+/// 取得（必要时刷新）知识库的列表缓存后执行 `f`；`f` 返回 true 表示修改了缓存。
+/// `persist` 为 false 时缓存只保存在内存中
+fn with_listing<T>(
+	root: &Path,
+	persist: bool,
+	reuse_recent: bool,
+	f: impl FnOnce(&mut ListingCache) -> (T, bool),
+) -> Result<T, String> {
+	// 加密知识库不落盘：摘要会泄露明文
+	let persist = persist && !is_vault_dir(root);
+	let mut caches = CACHES.lock().map_err(|e| e.to_string())?;
+	let state = caches.entry(root.to_path_buf()).or_insert_with(|| ListingState {
+		cache: if persist {
+			load_cache(root)
+		} else {
+			ListingCache {
+				version: CACHE_VERSION,
+				..Default::default()
+			}
+		},
+		scanned_at: None,
+	});
+	let fresh = reuse_recent && state.scanned_at.is_some_and(|t| t.elapsed() < PAGE_REUSE_WINDOW);
+	let mut dirty = false;
+	if !fresh {
+		dirty = refresh_cache(root, &mut state.cache)?;
+		state.scanned_at = Some(Instant::now());
+	}
+	let (out, changed) = f(&mut state.cache);
+	if persist && (dirty || changed || !cache_path(root).is_file()) {
+		save_cache(root, &state.cache)?;
+	}
+	Ok(out)
+}
+
+fn note_title(rel: &str) -> String {
+	Path::new(rel)
+		.file_stem()
+		.and_then(|s| s.to_str())
+		.unwrap_or("未命名")
+		.to_string()
+}
+
+fn folder_of(rel: &str) -> &str {
+	rel.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+/// 正文摘要：跳过 front matter、标题与代码块，取前 [`EXCERPT_CHARS`] 个字符
+fn excerpt_of(content: &str) -> String {
+	let mut text = String::new();
+	let mut skip = 0usize;
+	for event in Parser::new_ext(note_body(content), markdown_options()) {
+		match event {
+			Event::Start(Tag::Heading { .. } | Tag::CodeBlock(_)) => skip += 1,
+			Event::End(TagEnd::Heading(_) | TagEnd::CodeBlock) => skip = skip.saturating_sub(1),
+			Event::Text(t) | Event::Code(t) if skip == 0 => text.push_str(&t),
+			Event::SoftBreak | Event::HardBreak | Event::End(TagEnd::Paragraph | TagEnd::Item) => {
+				text.push(' ')
+			}
+			_ => {}
+		}
+		if text.chars().count() > EXCERPT_CHARS * 2 {
+			break;
+		}
+	}
+	let mut out: String = text
+		.split_whitespace()
+		.collect::<Vec<_>>()
+		.join(" ");
+	if out.chars().count() > EXCERPT_CHARS {
+		out = out.chars().take(EXCERPT_CHARS).collect();
+		out.push('…');
+	}
+	out
+}
+
+/// 简易 glob：`*` 匹配不含 `/` 的任意字符，`**` 可跨目录（`**/` 也匹配零层目录），`?` 匹配单个非 `/` 字符
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+	match pattern.split_first() {
+		None => text.is_empty(),
+		Some(('*', rest)) if rest.first() == Some(&'*') => {
+			let rest = &rest[1..];
+			let after_slash = rest.strip_prefix(&['/'][..]);
+			(0..=text.len()).any(|i| {
+				glob_match(rest, &text[i..]) || after_slash.is_some_and(|r| glob_match(r, &text[i..]))
+			})
+		}
+		Some(('*', rest)) => (0..=text.len())
+			.take_while(|&i| i == 0 || text[i - 1] != '/')
+			.any(|i| glob_match(rest, &text[i..])),
+		Some(('?', rest)) => text.first().is_some_and(|c| *c != '/') && glob_match(rest, &text[1..]),
+		Some((c, rest)) => text.first() == Some(c) && glob_match(rest, &text[1..]),
+	}
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KnowledgeListSort {
+	Title,
+	Created,
+	#[default]
+	Updated,
+	Size,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KnowledgeSortOrder {
+	Asc,
+	Desc,
+}
+
+/// 排序键：按标题排序时比较 `text`（小写标题），其余比较 `value`；相同时按相对路径
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+struct SortKey {
+	text: String,
+	value: u64,
+	rel: String,
+}
+
+fn sort_key(sort: KnowledgeListSort, rel: &str, meta: &NoteMeta) -> SortKey {
+	let (text, value) = match sort {
+		KnowledgeListSort::Title => (note_title(rel).to_lowercase(), 0),
+		KnowledgeListSort::Created => (String::new(), meta.created_ms),
+		KnowledgeListSort::Updated => (String::new(), meta.mtime_ms),
+		KnowledgeListSort::Size => (String::new(), meta.size),
+	};
+	SortKey {
+		text,
+		value,
+		rel: rel.to_string(),
+	}
+}
+
+/// 游标：上一页最后一条的排序键，连同排序方式一起编码，避免换了排序后误用
+#[derive(Debug, Serialize, Deserialize)]
+struct ListCursor {
+	sort: KnowledgeListSort,
+	desc: bool,
+	key: SortKey,
+}
+
+fn encode_cursor(cursor: &ListCursor) -> Result<String, String> {
+	let json = serde_json::to_vec(cursor).map_err(|e| e.to_string())?;
+	Ok(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(json))
+}
+
+fn decode_cursor(raw: &str) -> Result<ListCursor, String> {
+	base64::engine::general_purpose::URL_SAFE_NO_PAD
+		.decode(raw.trim())
+		.ok()
+		.and_then(|b| serde_json::from_slice(&b).ok())
+		.ok_or_else(|| "无效的分页游标".to_string())
+}
+
+/// 全部笔记按更新时间新→旧排列（`list_knowledge_markdown_files` 使用）
+pub(crate) fn list_all_markdown(root: &Path, persist: bool) -> Result<Vec<KnowledgeMarkdownFileEntry>, String> {
+	with_listing(root, persist, false, |cache| {
+		let mut rows: Vec<(&String, &NoteMeta)> = cache.notes.iter().collect();
+		rows.sort_by(|a, b| b.1.mtime_ms.cmp(&a.1.mtime_ms).then_with(|| a.0.cmp(b.0)));
+		let out = rows
+			.into_iter()
+			.map(|(rel, meta)| KnowledgeMarkdownFileEntry {
+				path: root.join(rel).to_string_lossy().to_string(),
+				title: note_title(rel),
+				updated_at_ms: meta.mtime_ms,
+			})
+			.collect();
+		(out, false)
+	})
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListKnowledgeMarkdownPageInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	/// 只列出该文件夹（相对知识库根目录，或其下的绝对路径）中的笔记
+	#[serde(default)]
+	pub folder: Option<String>,
+	/// 是否包含 `folder` 的子文件夹，默认 true
+	#[serde(default)]
+	pub recursive: Option<bool>,
+	/// 按相对路径过滤，如 `**/*.md`、`日记/2026-*`；不含 `/` 时只匹配文件名；不区分大小写
+	#[serde(default)]
+	pub glob: Option<String>,
+	#[serde(default)]
+	pub sort: KnowledgeListSort,
+	/// 默认标题升序，其余降序
+	#[serde(default)]
+	pub order: Option<KnowledgeSortOrder>,
+	/// 上一页返回的 `nextCursor`；不传为第一页
+	#[serde(default)]
+	pub cursor: Option<String>,
+	/// 每页条数，默认 50，最多 500
+	#[serde(default)]
+	pub limit: Option<usize>,
+	/// 是否返回正文摘要
+	#[serde(default)]
+	pub preview: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeMarkdownListItem {
+	pub path: String,
+	pub title: String,
+	/// 所在文件夹（相对知识库根目录，根目录为空串）
+	pub folder: String,
+	pub created_at_ms: u64,
+	pub updated_at_ms: u64,
+	pub size: u64,
+	/// 正文摘要；未请求 `preview` 或读取失败（加密知识库未解锁等）时为 None
+	pub excerpt: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeMarkdownPage {
+	pub items: Vec<KnowledgeMarkdownListItem>,
+	/// 过滤后的总条数
+	pub total: usize,
+	/// 下一页游标；没有更多时为 None
+	pub next_cursor: Option<String>,
+}
+
+fn list_page(
+	root: &Path,
+	persist: bool,
+	input: ListKnowledgeMarkdownPageInput,
+) -> Result<KnowledgeMarkdownPage, String> {
+	let sort = input.sort;
+	let desc = match input.order {
+		Some(order) => order == KnowledgeSortOrder::Desc,
+		None => sort != KnowledgeListSort::Title,
+	};
+	let cursor = match input.cursor.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+		Some(raw) => {
+			let cursor = decode_cursor(raw)?;
+			if cursor.sort != sort || cursor.desc != desc {
+				return Err("分页游标与当前排序方式不一致，请从第一页重新查询".to_string());
+			}
+			Some(cursor.key)
+		}
+		None => None,
+	};
+	let limit = input.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+	let folder = input
+		.folder
+		.as_deref()
+		.map(|f| {
+			let f = f.trim();
+			let f = Path::new(f)
+				.strip_prefix(root)
+				.map(|p| p.to_string_lossy().to_string())
+				.unwrap_or_else(|_| f.to_string());
+			f.replace('\\', "/").trim_matches('/').to_string()
+		})
+		.unwrap_or_default();
+	let recursive = input.recursive.unwrap_or(true);
+	let glob: Option<Vec<char>> = input
+		.glob
+		.as_deref()
+		.map(str::trim)
+		.filter(|g| !g.is_empty())
+		.map(|g| g.replace('\\', "/").to_lowercase().chars().collect());
+
+	with_listing(root, persist, cursor.is_some(), |cache| {
+		let mut rows: Vec<(SortKey, &String)> = cache
+			.notes
+			.iter()
+			.filter(|(rel, _)| {
+				let dir = folder_of(rel);
+				(folder.is_empty() && recursive)
+					|| dir == folder
+					|| (recursive && dir.starts_with(&folder) && dir[folder.len()..].starts_with('/'))
+			})
+			.filter(|(rel, _)| {
+				let Some(glob) = &glob else {
+					return true;
+				};
+				let lower = rel.to_lowercase();
+				let target = if glob.contains(&'/') {
+					lower.as_str()
+				} else {
+					lower.rsplit('/').next().unwrap_or(&lower)
+				};
+				glob_match(glob, &target.chars().collect::<Vec<_>>())
+			})
+			.map(|(rel, meta)| (sort_key(sort, rel, meta), rel))
+			.collect();
+		let directed = |a: &SortKey, b: &SortKey| if desc { b.cmp(a) } else { a.cmp(b) };
+		rows.sort_by(|a, b| directed(&a.0, &b.0));
+		let total = rows.len();
+		let start = match &cursor {
+			Some(after) => rows.partition_point(|(key, _)| directed(key, after).is_le()),
+			None => 0,
+		};
+		let end = (start + limit).min(total);
+		let next_cursor = (end < total).then(|| ListCursor {
+			sort,
+			desc,
+			key: rows[end - 1].0.clone(),
+		});
+		let page: Vec<String> = rows[start..end].iter().map(|(_, rel)| (*rel).clone()).collect();
+
+		let mut changed = false;
+		let mut items = Vec::with_capacity(page.len());
+		for rel in page {
+			let Some(meta) = cache.notes.get_mut(&rel) else {
+				continue;
+			};
+			let path = root.join(&rel);
+			if input.preview
+				&& meta.excerpt.is_none()
+				&& let Ok(content) = read_note_text(&path)
+			{
+				meta.excerpt = Some(excerpt_of(&content));
+				changed = true;
+			}
+			items.push(KnowledgeMarkdownListItem {
+				path: path.to_string_lossy().to_string(),
+				title: note_title(&rel),
+				folder: folder_of(&rel).to_string(),
+				created_at_ms: meta.created_ms,
+				updated_at_ms: meta.mtime_ms,
+				size: meta.size,
+				excerpt: if input.preview { meta.excerpt.clone() } else { None },
+			});
+		}
+		(
+			next_cursor.map(|c| encode_cursor(&c)).transpose().map(|next_cursor| KnowledgeMarkdownPage {
+				items,
+				total,
+				next_cursor,
+			}),
+			changed,
+		)
+	})?
+}
+
+/// 分页列出笔记（元数据缓存 + 游标分页、排序、文件夹 / glob 过滤、可选摘要）
+#[tauri::command]
+pub async fn list_knowledge_markdown_page(
+	app: AppHandle,
+	input: Option<ListKnowledgeMarkdownPageInput>,
+) -> Result<KnowledgeMarkdownPage, String> {
+	let input = input.unwrap_or_default();
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let persist = listing_persists(&app, &root).await;
+	tauri::async_runtime::spawn_blocking(move || list_page(&root, persist, input))
+		.await
+		.map_err(|e| e.to_string())?
+}
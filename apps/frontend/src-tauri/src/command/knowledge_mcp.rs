@@ -0,0 +1,1014 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, UNIX_EPOCH};
+
+use chacha20poly1305::aead::OsRng;
+use chacha20poly1305::aead::rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::command::knowledge::{
+	SaveKnowledgeMarkdownInput, collect_md_files, is_md_file_path, resolve_knowledge_root,
+	save_knowledge_markdown, write_file_atomic,
+};
+use crate::command::knowledge_chunk::update_chunk_cache;
+use crate::command::knowledge_conflict::{KnowledgeSaveError, content_hash};
+use crate::command::knowledge_history::{HistoryRetention, snapshot_before_write};
+use crate::command::knowledge_retrieve::retrieve_context;
+use crate::command::knowledge_vault::{encode_note, read_note_text};
+use crate::utils::http_server::{HttpRequest, is_loopback_host, read_request, reason, write_response};
+
+// —— MCP 服务：以 Model Context Protocol 向 Cursor / Trae 暴露知识库（检索、列出、读取、新建、更新笔记）——
+// stdio：`dnhyxc-ai --mcp-stdio --knowledge-dir <目录>`，由编辑器拉起子进程，逐行收发 JSON-RPC，不启动窗口
+// HTTP：应用内启动，仅监听 127.0.0.1 并校验令牌；`POST /mcp`（Streamable HTTP）或 `GET /sse` + `POST /messages`（旧版 SSE）
+// 写入授权：知识库根目录 `.knowledge-mcp.json` 的 `allowWrite` 命中则放行，否则应用内弹窗确认；stdio 模式无法弹窗，未命中即拒绝
+
+const SERVER_NAME: &str = "dnhyxc-ai-knowledge";
+/// 客户端请求的版本在此列表内时原样回应，否则回应第一项
+const PROTOCOL_VERSIONS: [&str; 3] = ["2025-06-18", "2025-03-26", "2024-11-05"];
+const CONFIG_FILE: &str = ".knowledge-mcp.json";
+const DEFAULT_PORT: u16 = 17317;
+const PERMISSION_EVENT: &str = "knowledge://mcp-permission-request";
+const NOTE_WRITTEN_EVENT: &str = "knowledge://mcp-note-written";
+/// 弹窗无响应视为拒绝
+const PERMISSION_TIMEOUT: Duration = Duration::from_secs(120);
+const SSE_KEEPALIVE: Duration = Duration::from_secs(15);
+
+struct McpServer {
+	port: u16,
+	token: String,
+	root: PathBuf,
+	task: tauri::async_runtime::JoinHandle<()>,
+}
+
+static SERVER: LazyLock<Mutex<Option<McpServer>>> = LazyLock::new(|| Mutex::new(None));
+/// 旧版 SSE 会话：sessionId → 推送 JSON-RPC 响应的通道
+static SSE_SESSIONS: LazyLock<Mutex<HashMap<String, mpsc::UnboundedSender<String>>>> =
+	LazyLock::new(|| Mutex::new(HashMap::new()));
+/// 等待前端确认的写入请求
+static PENDING_PERMISSIONS: LazyLock<Mutex<HashMap<String, oneshot::Sender<PermissionDecision>>>> =
+	LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn random_hex(bytes: usize) -> String {
+	let mut buf = vec![0u8; bytes];
+	OsRng.fill_bytes(&mut buf);
+	buf.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// —— 授权配置 ——
+
+/// `.knowledge-mcp.json`：`allowWrite` 中每项为 `*`（全部）、以 `/` 结尾的目录前缀或笔记相对路径
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct McpConfig {
+	#[serde(default)]
+	allow_write: Vec<String>,
+}
+
+fn load_config(root: &Path) -> McpConfig {
+	fs::read(root.join(CONFIG_FILE))
+		.ok()
+		.and_then(|b| serde_json::from_slice(&b).ok())
+		.unwrap_or_default()
+}
+
+fn save_config(root: &Path, config: &McpConfig) -> Result<(), String> {
+	let bytes = serde_json::to_vec_pretty(config).map_err(|e| e.to_string())?;
+	write_file_atomic(&root.join(CONFIG_FILE), &bytes)
+}
+
+fn normalize_allow_entry(entry: &str) -> String {
+	entry.trim().replace('\\', "/").trim_start_matches("./").to_string()
+}
+
+fn allow_write_matches(patterns: &[String], rel: &str) -> bool {
+	patterns.iter().any(|p| {
+		let p = normalize_allow_entry(p);
+		p == "*" || (p.ends_with('/') && rel.starts_with(&p)) || p == rel
+	})
+}
+
+// —— 路径 ——
+
+/// 校验工具参数中的相对路径：拒绝绝对路径、`..` 与隐藏目录（`.history` / `.index` 等），返回规范化的相对路径
+fn safe_relative(raw: &str) -> Result<PathBuf, String> {
+	let trimmed = raw.trim().replace('\\', "/");
+	let mut rel = PathBuf::new();
+	for comp in Path::new(&trimmed).components() {
+		match comp {
+			Component::Normal(s) if !s.to_string_lossy().starts_with('.') => rel.push(s),
+			Component::CurDir => {}
+			_ => return Err(format!("非法路径：{}（须为知识库内的相对路径）", raw.trim())),
+		}
+	}
+	Ok(rel)
+}
+
+/// 已存在的最近上级解析符号链接后仍须位于知识库内
+fn ensure_inside(root: &Path, path: &Path) -> Result<(), String> {
+	if let Ok(canon_root) = root.canonicalize()
+		&& let Some(existing) = path.ancestors().find(|p| p.exists())
+		&& let Ok(canon) = existing.canonicalize()
+		&& !canon.starts_with(&canon_root)
+	{
+		return Err(format!("非法路径：{}（指向知识库之外）", path.display()));
+	}
+	Ok(())
+}
+
+/// 相对路径 → 知识库内的 `.md` 绝对路径，缺省扩展名时补 `.md`；返回（绝对路径, 相对路径）
+fn resolve_note_path(root: &Path, raw: &str) -> Result<(PathBuf, String), String> {
+	let mut rel = safe_relative(raw)?;
+	if rel.as_os_str().is_empty() {
+		return Err("path 不能为空".to_string());
+	}
+	if !is_md_file_path(&rel) {
+		let mut name = rel.file_name().unwrap_or_default().to_os_string();
+		name.push(".md");
+		rel.set_file_name(name);
+	}
+	let path = root.join(&rel);
+	ensure_inside(root, &path)?;
+	Ok((path, rel.to_string_lossy().replace('\\', "/")))
+}
+
+fn relative_to(root: &Path, path: &Path) -> String {
+	path.strip_prefix(root)
+		.unwrap_or(path)
+		.to_string_lossy()
+		.replace('\\', "/")
+}
+
+// —— 工具 ——
+
+#[derive(Clone)]
+struct McpContext {
+	root: PathBuf,
+	/// stdio 模式为 None：无法弹窗授权，也不做 Git 自动提交
+	app: Option<AppHandle>,
+}
+
+struct PermissionDecision {
+	allow: bool,
+	remember: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeMcpPermissionRequest {
+	pub request_id: String,
+	/// `create_note` / `update_note`
+	pub tool: String,
+	/// 相对知识库根目录的路径
+	pub path: String,
+	pub absolute_path: String,
+	/// 待写入内容的前 500 字
+	pub preview: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct KnowledgeMcpNoteWritten {
+	path: String,
+	action: &'static str,
+}
+
+fn tool_definitions() -> Value {
+	json!([
+		{
+			"name": "search_notes",
+			"description": "在本地知识库中按问题检索相关段落（BM25），返回带出处（笔记路径与行号）的上下文。",
+			"inputSchema": {
+				"type": "object",
+				"properties": {
+					"query": { "type": "string", "description": "检索问题或关键词" },
+					"limit": { "type": "integer", "minimum": 1, "description": "最多返回的段落数，默认 8" },
+					"budget": { "type": "integer", "minimum": 1, "description": "上下文 token 预算，默认 1500" }
+				},
+				"required": ["query"]
+			}
+		},
+		{
+			"name": "list_notes",
+			"description": "列出知识库中的笔记（相对路径），按修改时间倒序。",
+			"inputSchema": {
+				"type": "object",
+				"properties": {
+					"folder": { "type": "string", "description": "只列出该子目录下的笔记" }
+				}
+			}
+		},
+		{
+			"name": "read_note",
+			"description": "读取一篇笔记的 Markdown 全文；首行注释含内容哈希，可作为 update_note 的 expectedHash。",
+			"inputSchema": {
+				"type": "object",
+				"properties": {
+					"path": { "type": "string", "description": "相对知识库根目录的路径，如 `项目/部署.md`" }
+				},
+				"required": ["path"]
+			}
+		},
+		{
+			"name": "create_note",
+			"description": "新建笔记；同名文件已存在时失败。写入需用户授权。",
+			"inputSchema": {
+				"type": "object",
+				"properties": {
+					"path": { "type": "string", "description": "相对知识库根目录的路径，缺省扩展名时补 `.md`" },
+					"content": { "type": "string", "description": "Markdown 正文" }
+				},
+				"required": ["path", "content"]
+			}
+		},
+		{
+			"name": "update_note",
+			"description": "以新内容整体覆盖已有笔记，旧内容保留在历史版本中。写入需用户授权。",
+			"inputSchema": {
+				"type": "object",
+				"properties": {
+					"path": { "type": "string", "description": "相对知识库根目录的路径" },
+					"content": { "type": "string", "description": "完整的新 Markdown 正文" },
+					"expectedHash": { "type": "string", "description": "read_note 返回的内容哈希；磁盘内容已变化时拒绝覆盖" }
+				},
+				"required": ["path", "content"]
+			}
+		}
+	])
+}
+
+fn arg_str<'a>(args: &'a Value, key: &str) -> Result<&'a str, String> {
+	args.get(key)
+		.and_then(Value::as_str)
+		.ok_or_else(|| format!("缺少参数 {}", key))
+}
+
+fn arg_usize(args: &Value, key: &str) -> Option<usize> {
+	args.get(key).and_then(Value::as_u64).map(|n| n as usize)
+}
+
+async fn call_tool(ctx: &McpContext, name: &str, args: &Value) -> Result<String, String> {
+	let root = ctx.root.clone();
+	match name {
+		"search_notes" => {
+			let query = arg_str(args, "query")?.to_string();
+			let (limit, budget) = (arg_usize(args, "limit"), arg_usize(args, "budget"));
+			let result = tauri::async_runtime::spawn_blocking(move || {
+				retrieve_context(&root, &query, budget, limit)
+			})
+			.await
+			.map_err(|e| e.to_string())??;
+			if result.passages.is_empty() {
+				return Ok("未找到相关内容".to_string());
+			}
+			Ok(result.context)
+		}
+		"list_notes" => {
+			let folder = args.get("folder").and_then(Value::as_str).unwrap_or_default();
+			let dir = root.join(safe_relative(folder)?);
+			ensure_inside(&root, &dir)?;
+			if !dir.is_dir() {
+				return Err(format!("目录不存在：{}", relative_to(&root, &dir)));
+			}
+			let mut files = Vec::new();
+			collect_md_files(&dir, &mut files)?;
+			let mut entries: Vec<(u64, String)> = files
+				.iter()
+				.map(|p| {
+					let ms = fs::metadata(p)
+						.and_then(|m| m.modified())
+						.ok()
+						.and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+						.map(|d| d.as_millis() as u64)
+						.unwrap_or(0);
+					(ms, relative_to(&root, p))
+				})
+				.collect();
+			entries.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+			if entries.is_empty() {
+				return Ok("（没有笔记）".to_string());
+			}
+			Ok(entries.into_iter().map(|(_, p)| p).collect::<Vec<_>>().join("\n"))
+		}
+		"read_note" => {
+			let (path, rel) = resolve_note_path(&root, arg_str(args, "path")?)?;
+			if !path.is_file() {
+				return Err(format!("笔记不存在：{}", rel));
+			}
+			let text = read_note_text(&path)?;
+			Ok(format!(
+				"<!-- path: {} | hash: {} -->\n{}",
+				rel,
+				content_hash(text.as_bytes()),
+				text
+			))
+		}
+		"create_note" | "update_note" => {
+			let (path, rel) = resolve_note_path(&root, arg_str(args, "path")?)?;
+			let content = arg_str(args, "content")?;
+			let create = name == "create_note";
+			if create && path.exists() {
+				return Err(format!("笔记已存在：{}（更新请用 update_note）", rel));
+			}
+			if !create && !path.is_file() {
+				return Err(format!("笔记不存在：{}（新建请用 create_note）", rel));
+			}
+			authorize(ctx, name, &path, &rel, content).await?;
+			let expected_hash = args.get("expectedHash").and_then(Value::as_str);
+			write_note(ctx, &path, content, create, expected_hash).await?;
+			if let Some(app) = &ctx.app {
+				let _ = app.emit(
+					NOTE_WRITTEN_EVENT,
+					KnowledgeMcpNoteWritten {
+						path: path.to_string_lossy().to_string(),
+						action: if create { "create" } else { "update" },
+					},
+				);
+			}
+			Ok(format!("已{}：{}", if create { "新建" } else { "更新" }, rel))
+		}
+		_ => Err(format!("未知工具：{}", name)),
+	}
+}
+
+/// 写入前授权：命中 allowWrite 直接放行；应用内弹窗等待用户确认（可记住该路径）；stdio 模式未命中即拒绝
+async fn authorize(
+	ctx: &McpContext,
+	tool: &str,
+	path: &Path,
+	rel: &str,
+	content: &str,
+) -> Result<(), String> {
+	if allow_write_matches(&load_config(&ctx.root).allow_write, rel) {
+		return Ok(());
+	}
+	let Some(app) = &ctx.app else {
+		return Err(format!(
+			"未授权写入 {}：请在 {} 的 allowWrite 中加入该路径、所在目录（以 / 结尾）或 \"*\"",
+			rel,
+			ctx.root.join(CONFIG_FILE).display()
+		));
+	};
+	let request_id = random_hex(8);
+	let (tx, rx) = oneshot::channel();
+	PENDING_PERMISSIONS
+		.lock()
+		.map_err(|e| e.to_string())?
+		.insert(request_id.clone(), tx);
+	let request = KnowledgeMcpPermissionRequest {
+		request_id: request_id.clone(),
+		tool: tool.to_string(),
+		path: rel.to_string(),
+		absolute_path: path.to_string_lossy().to_string(),
+		preview: content.chars().take(500).collect(),
+	};
+	let decision = match app.emit(PERMISSION_EVENT, request) {
+		Ok(()) => tokio::time::timeout(PERMISSION_TIMEOUT, rx).await.ok().and_then(Result::ok),
+		Err(_) => None,
+	};
+	if let Ok(mut pending) = PENDING_PERMISSIONS.lock() {
+		pending.remove(&request_id);
+	}
+	match decision {
+		Some(d) if d.allow => {
+			if d.remember {
+				let mut config = load_config(&ctx.root);
+				if !config.allow_write.iter().any(|p| normalize_allow_entry(p) == rel) {
+					config.allow_write.push(rel.to_string());
+					save_config(&ctx.root, &config)?;
+				}
+			}
+			Ok(())
+		}
+		Some(_) => Err(format!("用户拒绝写入 {}", rel)),
+		None => Err(format!("写入 {} 未获确认（已超时）", rel)),
+	}
+}
+
+/// 应用内走与编辑器保存相同的流程（历史快照、加密、Git 自动提交、分块缓存）；stdio 模式无 AppHandle，历史保留用默认策略且不做 Git 提交
+async fn write_note(
+	ctx: &McpContext,
+	path: &Path,
+	content: &str,
+	create: bool,
+	expected_hash: Option<&str>,
+) -> Result<(), String> {
+	if let Some(app) = &ctx.app {
+		let input = SaveKnowledgeMarkdownInput {
+			title: path
+				.file_stem()
+				.map(|s| s.to_string_lossy().to_string())
+				.unwrap_or_default(),
+			content: content.to_string(),
+			file_path: Some(path.to_string_lossy().to_string()),
+			dir_path: Some(ctx.root.to_string_lossy().to_string()),
+			overwrite: !create,
+			previous_title: None,
+			update_links: false,
+			expected_mtime_ms: None,
+			expected_hash: expected_hash.map(str::to_string),
+			base_content: None,
+		};
+		return save_knowledge_markdown(app.clone(), input)
+			.await
+			.map(|_| ())
+			.map_err(|e| match e {
+				KnowledgeSaveError::Message(m) => m,
+				KnowledgeSaveError::Conflict(c) => format!("{}，请重新 read_note 后再更新", c.message),
+			});
+	}
+	if let Some(h) = expected_hash.map(str::trim).filter(|h| !h.is_empty())
+		&& path.is_file()
+		&& !h.eq_ignore_ascii_case(&content_hash(read_note_text(path)?.as_bytes()))
+	{
+		return Err(format!("文件已被外部修改：{}，请重新 read_note 后再更新", path.display()));
+	}
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+	}
+	let bytes = encode_note(path, content)?;
+	snapshot_before_write(path, content, HistoryRetention::default())?;
+	write_file_atomic(path, &bytes)?;
+	let _ = update_chunk_cache(&ctx.root, &[path]);
+	Ok(())
+}
+
+// —— JSON-RPC ——
+
+fn rpc_result(id: &Value, result: Value) -> Value {
+	json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn rpc_error(id: &Value, code: i64, message: &str) -> Value {
+	json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// 处理一条（或一批）JSON-RPC 消息；通知与全部为通知的批量请求无需响应，返回 None
+async fn handle_message(ctx: &McpContext, msg: Value) -> Option<Value> {
+	if let Value::Array(items) = msg {
+		let mut out = Vec::new();
+		for item in items {
+			if let Some(resp) = Box::pin(handle_message(ctx, item)).await {
+				out.push(resp);
+			}
+		}
+		return (!out.is_empty()).then_some(Value::Array(out));
+	}
+	let id = msg.get("id").cloned()?;
+	let Some(method) = msg.get("method").and_then(Value::as_str) else {
+		// 客户端对服务端请求的响应：本服务不发起请求，忽略
+		return None;
+	};
+	let params = msg.get("params").cloned().unwrap_or(Value::Null);
+	let resp = match method {
+		"initialize" => {
+			let requested = params.get("protocolVersion").and_then(Value::as_str);
+			let version = requested
+				.filter(|v| PROTOCOL_VERSIONS.contains(v))
+				.unwrap_or(PROTOCOL_VERSIONS[0]);
+			rpc_result(
+				&id,
+				json!({
+					"protocolVersion": version,
+					"capabilities": { "tools": { "listChanged": false } },
+					"serverInfo": { "name": SERVER_NAME, "version": env!("CARGO_PKG_VERSION") },
+					"instructions": "本地 Markdown 知识库。先用 search_notes 检索，再用 read_note 读取全文；create_note / update_note 需用户授权。"
+				}),
+			)
+		}
+		"ping" => rpc_result(&id, json!({})),
+		"tools/list" => rpc_result(&id, json!({ "tools": tool_definitions() })),
+		"tools/call" => {
+			let Some(name) = params.get("name").and_then(Value::as_str) else {
+				return Some(rpc_error(&id, -32602, "缺少工具名 name"));
+			};
+			let args = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+			// 工具执行失败按规范放入结果（isError），由模型自行调整参数重试
+			let (text, is_error) = match call_tool(ctx, name, &args).await {
+				Ok(text) => (text, false),
+				Err(e) => (e, true),
+			};
+			rpc_result(
+				&id,
+				json!({ "content": [{ "type": "text", "text": text }], "isError": is_error }),
+			)
+		}
+		_ => rpc_error(&id, -32601, &format!("不支持的方法：{}", method)),
+	};
+	Some(resp)
+}
+
+async fn handle_raw(ctx: &McpContext, raw: &[u8]) -> Option<Value> {
+	match serde_json::from_slice::<Value>(raw) {
+		Ok(msg) => handle_message(ctx, msg).await,
+		Err(e) => Some(rpc_error(&Value::Null, -32700, &format!("JSON 解析失败: {}", e))),
+	}
+}
+
+// —— stdio 模式 ——
+
+/// 命令行含 `--mcp-stdio` 时以 stdio MCP 服务运行（由 `run` 在创建窗口前检查）
+pub fn stdio_requested() -> bool {
+	std::env::args().any(|a| a == "--mcp-stdio")
+}
+
+/// 知识库目录：`--knowledge-dir <目录>` / `--knowledge-dir=<目录>`，否则 `KNOWLEDGE_DIR` 环境变量
+fn stdio_knowledge_dir() -> Option<PathBuf> {
+	let mut args = std::env::args().skip(1);
+	while let Some(a) = args.next() {
+		if a == "--knowledge-dir" {
+			return args.next().map(PathBuf::from);
+		}
+		if let Some(v) = a.strip_prefix("--knowledge-dir=") {
+			return Some(PathBuf::from(v));
+		}
+	}
+	std::env::var("KNOWLEDGE_DIR")
+		.ok()
+		.map(|s| s.trim().to_string())
+		.filter(|s| !s.is_empty())
+		.map(PathBuf::from)
+}
+
+/// 逐行读取 stdin 的 JSON-RPC 消息并把响应写到 stdout；日志只写 stderr。返回进程退出码
+pub fn run_stdio() -> i32 {
+	use std::io::{BufRead, Write};
+
+	let Some(root) = stdio_knowledge_dir().filter(|d| d.is_dir()) else {
+		eprintln!("[{}] 知识库目录不存在：请通过 --knowledge-dir <目录> 或 KNOWLEDGE_DIR 指定", SERVER_NAME);
+		return 2;
+	};
+	eprintln!("[{}] stdio 模式，知识库：{}", SERVER_NAME, root.display());
+	let ctx = McpContext { root, app: None };
+	let stdin = std::io::stdin();
+	let mut stdout = std::io::stdout().lock();
+	for line in stdin.lock().lines() {
+		let Ok(line) = line else {
+			break;
+		};
+		if line.trim().is_empty() {
+			continue;
+		}
+		if let Some(resp) = tauri::async_runtime::block_on(handle_raw(&ctx, line.as_bytes()))
+			&& writeln!(stdout, "{}", resp).and_then(|_| stdout.flush()).is_err()
+		{
+			break;
+		}
+	}
+	0
+}
+
+// —— HTTP / SSE 模式 ——
+
+/// 防 DNS 重绑定与跨站调用：Host 必须是回环地址，带 Origin 时也须来自回环地址；再校验令牌（Bearer 头或 `token` 查询参数）
+fn check_access(req: &HttpRequest, token: &str) -> Result<(), u16> {
+	if !req.headers.get("host").is_some_and(|h| is_loopback_host(h)) {
+		return Err(403);
+	}
+	if let Some(origin) = req.headers.get("origin") {
+		let host = origin.split("://").nth(1).unwrap_or_default();
+		if !is_loopback_host(host) {
+			return Err(403);
+		}
+	}
+	let bearer = req
+		.headers
+		.get("authorization")
+		.and_then(|v| v.strip_prefix("Bearer "))
+		.map(str::trim);
+	if bearer == Some(token) || req.query.get("token").map(String::as_str) == Some(token) {
+		Ok(())
+	} else {
+		Err(401)
+	}
+}
+
+async fn handle_connection(stream: TcpStream, ctx: McpContext, token: String) {
+	let mut reader = BufReader::new(stream);
+	let req = read_request(&mut reader).await;
+	let mut stream = reader.into_inner();
+	let req = match req {
+		Ok(r) => r,
+		Err(status) => {
+			write_response(&mut stream, status, "text/plain; charset=utf-8", reason(status).as_bytes()).await;
+			return;
+		}
+	};
+	if let Err(status) = check_access(&req, &token) {
+		write_response(&mut stream, status, "text/plain; charset=utf-8", reason(status).as_bytes()).await;
+		return;
+	}
+	match (req.method.as_str(), req.path.as_str()) {
+		("POST", "/mcp") => match handle_raw(&ctx, &req.body).await {
+			Some(resp) => {
+				write_response(&mut stream, 200, "application/json", resp.to_string().as_bytes()).await;
+			}
+			None => write_response(&mut stream, 202, "text/plain; charset=utf-8", b"").await,
+		},
+		// 本服务不主动推送，按 Streamable HTTP 规范对 GET /mcp 返回 405
+		(_, "/mcp") => write_response(&mut stream, 405, "text/plain; charset=utf-8", b"").await,
+		("GET", "/sse") => serve_sse(stream, &token).await,
+		("POST", "/messages") => {
+			let sender = req
+				.query
+				.get("sessionId")
+				.and_then(|id| SSE_SESSIONS.lock().ok()?.get(id).cloned());
+			let Some(sender) = sender else {
+				write_response(&mut stream, 404, "text/plain; charset=utf-8", "会话不存在".as_bytes()).await;
+				return;
+			};
+			write_response(&mut stream, 202, "text/plain; charset=utf-8", b"Accepted").await;
+			if let Some(resp) = handle_raw(&ctx, &req.body).await {
+				let _ = sender.send(resp.to_string());
+			}
+		}
+		_ => write_response(&mut stream, 404, "text/plain; charset=utf-8", reason(404).as_bytes()).await,
+	}
+}
+
+/// 旧版 SSE 传输：先推送 `endpoint` 事件告知 POST 地址，之后把该会话的响应作为 `message` 事件推送
+async fn serve_sse(mut stream: TcpStream, token: &str) {
+	let session_id = random_hex(16);
+	let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+	if let Ok(mut sessions) = SSE_SESSIONS.lock() {
+		sessions.insert(session_id.clone(), tx);
+	}
+	let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-store\r\nConnection: keep-alive\r\n\r\n";
+	let endpoint = format!(
+		"event: endpoint\ndata: /messages?sessionId={}&token={}\n\n",
+		session_id, token
+	);
+	let mut ok = stream.write_all(head.as_bytes()).await.is_ok()
+		&& stream.write_all(endpoint.as_bytes()).await.is_ok()
+		&& stream.flush().await.is_ok();
+	while ok {
+		let chunk = match tokio::time::timeout(SSE_KEEPALIVE, rx.recv()).await {
+			Ok(Some(msg)) => format!("event: message\ndata: {}\n\n", msg),
+			Ok(None) => break,
+			Err(_) => ": ping\n\n".to_string(),
+		};
+		ok = stream.write_all(chunk.as_bytes()).await.is_ok() && stream.flush().await.is_ok();
+	}
+	if let Ok(mut sessions) = SSE_SESSIONS.lock() {
+		sessions.remove(&session_id);
+	}
+}
+
+// —— 命令 ——
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartKnowledgeMcpServerInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	/// 监听端口，默认 17317
+	#[serde(default)]
+	pub port: Option<u16>,
+	/// 访问令牌；不传时随机生成（前端可存入 store 以保持编辑器配置不变）
+	#[serde(default)]
+	pub token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeMcpDirInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeMcpServerStatus {
+	pub running: bool,
+	pub port: Option<u16>,
+	/// Streamable HTTP 地址：`http://127.0.0.1:<port>/mcp`
+	pub url: Option<String>,
+	/// 旧版 SSE 地址：`http://127.0.0.1:<port>/sse`
+	pub sse_url: Option<String>,
+	pub token: Option<String>,
+	/// 服务中的知识库（未运行时为查询的目录）
+	pub root: String,
+	pub allow_write: Vec<String>,
+	/// 可粘贴到 Cursor / Trae `mcp.json` 的配置：运行中为 HTTP 方式，否则为 stdio 方式
+	pub editor_config: Value,
+}
+
+fn build_status(root: &Path) -> KnowledgeMcpServerStatus {
+	let allow_write = load_config(root).allow_write;
+	let guard = SERVER.lock().ok();
+	if let Some(server) = guard.as_ref().and_then(|g| g.as_ref())
+		&& server.root == root
+	{
+		let url = format!("http://127.0.0.1:{}/mcp", server.port);
+		return KnowledgeMcpServerStatus {
+			running: true,
+			port: Some(server.port),
+			sse_url: Some(format!("http://127.0.0.1:{}/sse", server.port)),
+			token: Some(server.token.clone()),
+			root: root.to_string_lossy().to_string(),
+			allow_write,
+			editor_config: json!({
+				"mcpServers": {
+					SERVER_NAME: {
+						"url": url,
+						"headers": { "Authorization": format!("Bearer {}", server.token) }
+					}
+				}
+			}),
+			url: Some(url),
+		};
+	}
+	let exe = std::env::current_exe()
+		.map(|p| p.to_string_lossy().to_string())
+		.unwrap_or_else(|_| "dnhyxc-ai".to_string());
+	KnowledgeMcpServerStatus {
+		running: false,
+		port: None,
+		url: None,
+		sse_url: None,
+		token: None,
+		root: root.to_string_lossy().to_string(),
+		allow_write,
+		editor_config: json!({
+			"mcpServers": {
+				SERVER_NAME: {
+					"command": exe,
+					"args": ["--mcp-stdio", "--knowledge-dir", root.to_string_lossy()]
+				}
+			}
+		}),
+	}
+}
+
+/// 在 127.0.0.1 启动 MCP HTTP 服务；已在运行时先停止再以新参数启动
+#[tauri::command]
+pub async fn start_knowledge_mcp_server(
+	app: AppHandle,
+	input: Option<StartKnowledgeMcpServerInput>,
+) -> Result<KnowledgeMcpServerStatus, String> {
+	let input = input.unwrap_or(StartKnowledgeMcpServerInput {
+		dir_path: None,
+		port: None,
+		token: None,
+	});
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let port = input.port.unwrap_or(DEFAULT_PORT);
+	let token = input
+		.token
+		.map(|t| t.trim().to_string())
+		.filter(|t| !t.is_empty())
+		.unwrap_or_else(|| random_hex(24));
+	if let Some(old) = SERVER.lock().map_err(|e| e.to_string())?.take() {
+		old.task.abort();
+	}
+	let listener = TcpListener::bind(("127.0.0.1", port))
+		.await
+		.map_err(|e| format!("MCP 服务启动失败（端口 {}）：{}", port, e))?;
+	let ctx = McpContext {
+		root: root.clone(),
+		app: Some(app),
+	};
+	let task_token = token.clone();
+	let task = tauri::async_runtime::spawn(async move {
+		while let Ok((stream, _)) = listener.accept().await {
+			tauri::async_runtime::spawn(handle_connection(stream, ctx.clone(), task_token.clone()));
+		}
+	});
+	*SERVER.lock().map_err(|e| e.to_string())? = Some(McpServer {
+		port,
+		token,
+		root: root.clone(),
+		task,
+	});
+	Ok(build_status(&root))
+}
+
+/// 停止 MCP HTTP 服务；返回之前是否在运行
+#[tauri::command]
+pub fn stop_knowledge_mcp_server() -> Result<bool, String> {
+	let old = SERVER.lock().map_err(|e| e.to_string())?.take();
+	if let Ok(mut sessions) = SSE_SESSIONS.lock() {
+		sessions.clear();
+	}
+	Ok(match old {
+		Some(server) => {
+			server.task.abort();
+			true
+		}
+		None => false,
+	})
+}
+
+/// MCP 服务状态、写入白名单及编辑器配置；未传 `dirPath` 时查询运行中的知识库或默认知识库
+#[tauri::command]
+pub async fn get_knowledge_mcp_server_status(
+	app: AppHandle,
+	input: Option<KnowledgeMcpDirInput>,
+) -> Result<KnowledgeMcpServerStatus, String> {
+	let dir_path = input.and_then(|i| i.dir_path);
+	let running_root = SERVER
+		.lock()
+		.map_err(|e| e.to_string())?
+		.as_ref()
+		.map(|s| s.root.clone());
+	let root = match (dir_path, running_root) {
+		(None, Some(r)) => r,
+		(dir_path, _) => resolve_knowledge_root(&app, dir_path.as_ref()).await?,
+	};
+	Ok(build_status(&root))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RespondKnowledgeMcpPermissionInput {
+	pub request_id: String,
+	pub allow: bool,
+	/// 允许并记住：把该路径加入 allowWrite，之后不再询问
+	#[serde(default)]
+	pub remember: bool,
+}
+
+/// 回应 `knowledge://mcp-permission-request` 事件
+#[tauri::command]
+pub fn respond_knowledge_mcp_permission(input: RespondKnowledgeMcpPermissionInput) -> Result<(), String> {
+	let sender = PENDING_PERMISSIONS
+		.lock()
+		.map_err(|e| e.to_string())?
+		.remove(&input.request_id)
+		.ok_or("授权请求不存在或已超时")?;
+	sender
+		.send(PermissionDecision {
+			allow: input.allow,
+			remember: input.remember,
+		})
+		.map_err(|_| "授权请求已失效".to_string())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetKnowledgeMcpAllowListInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	pub allow_write: Vec<String>,
+}
+
+/// 覆盖写入白名单（`.knowledge-mcp.json`），返回规范化后的列表
+#[tauri::command]
+pub async fn set_knowledge_mcp_allow_list(
+	app: AppHandle,
+	input: SetKnowledgeMcpAllowListInput,
+) -> Result<Vec<String>, String> {
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let mut allow_write: Vec<String> = input
+		.allow_write
+		.iter()
+		.map(|p| normalize_allow_entry(p))
+		.filter(|p| !p.is_empty())
+		.collect();
+	allow_write.dedup();
+	let config = McpConfig { allow_write };
+	save_config(&root, &config)?;
+	Ok(config.allow_write)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn request(headers: &[(&str, &str)], query: &[(&str, &str)]) -> HttpRequest {
+		HttpRequest {
+			method: "POST".to_string(),
+			path: "/mcp".to_string(),
+			query: query.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+			headers: headers.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+			body: Vec::new(),
+		}
+	}
+
+	fn context() -> (tempfile::TempDir, McpContext) {
+		let dir = tempfile::tempdir().unwrap();
+		let ctx = McpContext { root: dir.path().to_path_buf(), app: None };
+		(dir, ctx)
+	}
+
+	fn call(name: &str, arguments: Value) -> Value {
+		json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": { "name": name, "arguments": arguments } })
+	}
+
+	#[test]
+	fn safe_relative_rejects_escapes_and_hidden_paths() {
+		assert_eq!(safe_relative(" ./a/b.md ").unwrap(), Path::new("a/b.md"));
+		assert_eq!(safe_relative("a\\b.md").unwrap(), Path::new("a/b.md"));
+		assert_eq!(safe_relative("").unwrap(), Path::new(""));
+		for raw in ["../x.md", "a/../../x.md", "/etc/passwd", ".history/a.md", "a/.index/chunks.json", "..\\x.md"] {
+			assert!(safe_relative(raw).is_err(), "{}", raw);
+		}
+	}
+
+	#[test]
+	fn allow_write_matches_prefixes_and_exact_paths() {
+		let patterns = vec!["inbox/".to_string(), "./notes\\todo.md".to_string()];
+		assert!(allow_write_matches(&patterns, "inbox/a.md"));
+		assert!(allow_write_matches(&patterns, "inbox/sub/b.md"));
+		assert!(allow_write_matches(&patterns, "notes/todo.md"));
+		assert!(!allow_write_matches(&patterns, "inboxes/a.md"));
+		assert!(!allow_write_matches(&patterns, "notes/todo.md.bak"));
+		assert!(!allow_write_matches(&["inbox".to_string()], "inbox/a.md"));
+		assert!(allow_write_matches(&["*".to_string()], "any/where.md"));
+		assert!(!allow_write_matches(&[], "a.md"));
+	}
+
+	#[test]
+	fn check_access_rejects_foreign_hosts_and_origins_then_checks_the_token() {
+		let ok = request(&[("host", "127.0.0.1:17317"), ("authorization", "Bearer secret")], &[]);
+		assert_eq!(check_access(&ok, "secret"), Ok(()));
+		let by_query = request(&[("host", "localhost:17317")], &[("token", "secret")]);
+		assert_eq!(check_access(&by_query, "secret"), Ok(()));
+		let rebound = request(&[("host", "evil.com"), ("authorization", "Bearer secret")], &[]);
+		assert_eq!(check_access(&rebound, "secret"), Err(403));
+		let no_host = request(&[("authorization", "Bearer secret")], &[]);
+		assert_eq!(check_access(&no_host, "secret"), Err(403));
+		let cross_site = request(
+			&[("host", "127.0.0.1:17317"), ("origin", "https://evil.com"), ("authorization", "Bearer secret")],
+			&[],
+		);
+		assert_eq!(check_access(&cross_site, "secret"), Err(403));
+		let local_origin = request(
+			&[("host", "127.0.0.1:17317"), ("origin", "http://localhost:1420"), ("authorization", "Bearer secret")],
+			&[],
+		);
+		assert_eq!(check_access(&local_origin, "secret"), Ok(()));
+		let wrong = request(&[("host", "127.0.0.1:17317"), ("authorization", "Bearer other")], &[]);
+		assert_eq!(check_access(&wrong, "secret"), Err(401));
+		let missing = request(&[("host", "127.0.0.1:17317")], &[]);
+		assert_eq!(check_access(&missing, "secret"), Err(401));
+	}
+
+	#[tokio::test]
+	async fn handle_message_dispatches_protocol_methods() {
+		let (_dir, ctx) = context();
+		let init = json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "protocolVersion": "2025-03-26" } });
+		let resp = handle_message(&ctx, init).await.unwrap();
+		assert_eq!(resp["result"]["protocolVersion"], "2025-03-26");
+		let init = json!({ "jsonrpc": "2.0", "id": 2, "method": "initialize", "params": { "protocolVersion": "1999-01-01" } });
+		assert_eq!(handle_message(&ctx, init).await.unwrap()["result"]["protocolVersion"], PROTOCOL_VERSIONS[0]);
+
+		let resp = handle_message(&ctx, json!({ "jsonrpc": "2.0", "id": "p", "method": "ping" })).await.unwrap();
+		assert_eq!(resp, json!({ "jsonrpc": "2.0", "id": "p", "result": {} }));
+
+		let resp = handle_message(&ctx, json!({ "jsonrpc": "2.0", "id": 3, "method": "tools/list" })).await.unwrap();
+		let names: Vec<&str> = resp["result"]["tools"].as_array().unwrap().iter().filter_map(|t| t["name"].as_str()).collect();
+		assert!(["search_notes", "list_notes", "read_note", "create_note", "update_note"].iter().all(|n| names.contains(n)));
+
+		let resp = handle_message(&ctx, json!({ "jsonrpc": "2.0", "id": 4, "method": "nope" })).await.unwrap();
+		assert_eq!(resp["error"]["code"], -32601);
+		// 通知与客户端响应无需回应
+		assert!(handle_message(&ctx, json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })).await.is_none());
+		assert!(handle_message(&ctx, json!({ "jsonrpc": "2.0", "id": 5, "result": {} })).await.is_none());
+		let batch = json!([
+			{ "jsonrpc": "2.0", "id": 6, "method": "ping" },
+			{ "jsonrpc": "2.0", "method": "notifications/initialized" }
+		]);
+		let resp = handle_message(&ctx, batch).await.unwrap();
+		assert_eq!(resp.as_array().map(Vec::len), Some(1));
+		assert_eq!(handle_raw(&ctx, b"{not json").await.unwrap()["error"]["code"], -32700);
+	}
+
+	#[tokio::test]
+	async fn handle_message_runs_tools_and_reports_tool_errors() {
+		let (dir, ctx) = context();
+		fs::create_dir_all(dir.path().join("inbox")).unwrap();
+		fs::write(dir.path().join("inbox").join("a.md"), "# A\n正文").unwrap();
+
+		let resp = handle_message(&ctx, call("read_note", json!({ "path": "inbox/a" }))).await.unwrap();
+		assert_eq!(resp["result"]["isError"], false);
+		let text = resp["result"]["content"][0]["text"].as_str().unwrap();
+		assert!(text.starts_with("<!-- path: inbox/a.md | hash: ") && text.ends_with("# A\n正文"));
+
+		let resp = handle_message(&ctx, call("list_notes", json!({}))).await.unwrap();
+		assert_eq!(resp["result"]["content"][0]["text"], "inbox/a.md");
+
+		let resp = handle_message(&ctx, call("read_note", json!({ "path": "../secret" }))).await.unwrap();
+		assert_eq!(resp["result"]["isError"], true);
+		let resp = handle_message(&ctx, call("unknown_tool", json!({}))).await.unwrap();
+		assert_eq!(resp["result"]["isError"], true);
+		let resp = handle_message(&ctx, json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": {} })).await.unwrap();
+		assert_eq!(resp["error"]["code"], -32602);
+
+		// stdio 模式无法弹窗：未列入 allowWrite 的写入被拒绝，列入后放行
+		let create = call("create_note", json!({ "path": "inbox/b.md", "content": "new" }));
+		let resp = handle_message(&ctx, create.clone()).await.unwrap();
+		assert_eq!(resp["result"]["isError"], true);
+		assert!(!dir.path().join("inbox").join("b.md").exists());
+		save_config(dir.path(), &McpConfig { allow_write: vec!["inbox/".to_string()] }).unwrap();
+		let resp = handle_message(&ctx, create).await.unwrap();
+		assert_eq!(resp["result"]["isError"], false);
+		assert_eq!(fs::read_to_string(dir.path().join("inbox").join("b.md")).unwrap(), "new");
+	}
+}
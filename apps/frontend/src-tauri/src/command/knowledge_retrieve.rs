@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::command::knowledge::resolve_knowledge_root;
+use crate::command::knowledge_chunk::{KnowledgeNoteChunk, estimate_tokens, is_cjk, load_all_chunks};
+
+// —— 本地检索：对分块做 BM25 排序（中日韩文本按二元组为主切词），按 token 预算截取上下文并附带出处 ——
+
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+const DEFAULT_BUDGET: usize = 1500;
+const DEFAULT_LIMIT: usize = 8;
+/// 剩余预算不足该值时不再截断塞入下一段
+const MIN_PASSAGE_TOKENS: usize = 48;
+
+/// 切词：拉丁字母 / 数字按词（小写），CJK 连续片段输出相邻二元组；
+/// 文档侧同时输出单字，查询侧仅在片段只有一个字时用单字，避免「的」「在」等常用字带来噪声
+pub(crate) fn tokenize(text: &str, for_query: bool) -> Vec<String> {
+	let mut out = Vec::new();
+	let mut word = String::new();
+	let mut cjk_run: Vec<char> = Vec::new();
+	let flush_word = |word: &mut String, out: &mut Vec<String>| {
+		if !word.is_empty() {
+			out.push(std::mem::take(word));
+		}
+	};
+	let flush_cjk = |run: &mut Vec<char>, out: &mut Vec<String>| {
+		for (i, c) in run.iter().enumerate() {
+			if !for_query || run.len() == 1 {
+				out.push(c.to_string());
+			}
+			if let Some(next) = run.get(i + 1) {
+				out.push(format!("{}{}", c, next));
+			}
+		}
+		run.clear();
+	};
+	for c in text.chars() {
+		if is_cjk(c) {
+			flush_word(&mut word, &mut out);
+			cjk_run.push(c);
+		} else if c.is_alphanumeric() || c == '_' {
+			flush_cjk(&mut cjk_run, &mut out);
+			word.extend(c.to_lowercase());
+		} else {
+			flush_word(&mut word, &mut out);
+			flush_cjk(&mut cjk_run, &mut out);
+		}
+	}
+	flush_word(&mut word, &mut out);
+	flush_cjk(&mut cjk_run, &mut out);
+	out
+}
+
+fn note_title(path: &str) -> String {
+	Path::new(path)
+		.file_stem()
+		.map(|s| s.to_string_lossy().to_string())
+		.unwrap_or_default()
+}
+
+/// 参与打分的文本：笔记标题与标题路径计入，使标题命中也能召回
+fn indexed_text(chunk: &KnowledgeNoteChunk) -> String {
+	format!(
+		"{}\n{}\n{}",
+		note_title(&chunk.path),
+		chunk.chunk.heading_path.join("\n"),
+		chunk.chunk.text
+	)
+}
+
+/// BM25 打分，返回 (块下标, 得分)，按得分降序，只含得分 > 0 的块
+fn bm25_rank(chunks: &[KnowledgeNoteChunk], query: &str) -> Vec<(usize, f64)> {
+	let mut query_terms = tokenize(query, true);
+	query_terms.sort();
+	query_terms.dedup();
+	if query_terms.is_empty() || chunks.is_empty() {
+		return Vec::new();
+	}
+	let docs: Vec<HashMap<String, usize>> = chunks
+		.iter()
+		.map(|c| {
+			let mut tf = HashMap::new();
+			for t in tokenize(&indexed_text(c), false) {
+				*tf.entry(t).or_insert(0) += 1;
+			}
+			tf
+		})
+		.collect();
+	let lens: Vec<usize> = docs.iter().map(|d| d.values().sum()).collect();
+	let avg_len = lens.iter().sum::<usize>() as f64 / docs.len() as f64;
+	let n = docs.len() as f64;
+	let idf: Vec<f64> = query_terms
+		.iter()
+		.map(|t| {
+			let df = docs.iter().filter(|d| d.contains_key(t)).count() as f64;
+			((n - df + 0.5) / (df + 0.5) + 1.0).ln()
+		})
+		.collect();
+
+	let mut scored: Vec<(usize, f64)> = docs
+		.iter()
+		.enumerate()
+		.map(|(i, tf)| {
+			let norm = K1 * (1.0 - B + B * lens[i] as f64 / avg_len.max(1.0));
+			let score = query_terms
+				.iter()
+				.zip(&idf)
+				.filter_map(|(t, idf)| {
+					let f = *tf.get(t)? as f64;
+					Some(idf * f * (K1 + 1.0) / (f + norm))
+				})
+				.sum::<f64>();
+			(i, score)
+		})
+		.filter(|(_, s)| *s > 0.0)
+		.collect();
+	scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+	scored
+}
+
+/// 按行截断到 token 上限；返回（文本, 实际结束行）
+fn trim_to_tokens(text: &str, start_line: usize, max_tokens: usize) -> (String, usize) {
+	let mut used = 0usize;
+	let mut kept = Vec::new();
+	for line in text.lines() {
+		let t = estimate_tokens(line);
+		if used + t > max_tokens && !kept.is_empty() {
+			break;
+		}
+		used += t;
+		kept.push(line);
+	}
+	let end = start_line + kept.len().saturating_sub(1);
+	(kept.join("\n"), end)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetrieveKnowledgeContextInput {
+	pub query: String,
+	/// 上下文 token 预算，默认 1500
+	#[serde(default)]
+	pub budget: Option<usize>,
+	/// 最多返回的段落数，默认 8
+	#[serde(default)]
+	pub limit: Option<usize>,
+	#[serde(default)]
+	pub dir_path: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeContextPassage {
+	/// 引用序号（从 1 开始），与 `context` 中的 `[n]` 对应
+	pub index: usize,
+	pub chunk_id: String,
+	pub path: String,
+	pub title: String,
+	pub heading_path: Vec<String>,
+	pub start_line: usize,
+	pub end_line: usize,
+	pub score: f64,
+	pub token_estimate: usize,
+	pub text: String,
+	/// 因预算不足被截断
+	pub truncated: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeContextResult {
+	pub passages: Vec<KnowledgeContextPassage>,
+	pub total_tokens: usize,
+	/// 可直接附加到对话中的上下文文本，每段以 `[n] 标题 › 小节（路径:L起-止）` 开头
+	pub context: String,
+}
+
+/// 检索核心：供命令与 MCP 工具共用；`budget` / `limit` 为 None 时用默认值
+pub(crate) fn retrieve_context(
+	root: &Path,
+	query: &str,
+	budget: Option<usize>,
+	limit: Option<usize>,
+) -> Result<KnowledgeContextResult, String> {
+	let query = query.trim();
+	if query.is_empty() {
+		return Err("query 不能为空".to_string());
+	}
+	let budget = budget.unwrap_or(DEFAULT_BUDGET);
+	let limit = limit.unwrap_or(DEFAULT_LIMIT).max(1);
+	let chunks = load_all_chunks(root, None)?;
+
+	let mut passages = Vec::new();
+	let mut used = 0usize;
+	for (i, score) in bm25_rank(&chunks, query) {
+		if passages.len() >= limit || used >= budget {
+			break;
+		}
+		let c = &chunks[i];
+		let remaining = budget - used;
+		let (text, end_line, truncated) = if c.chunk.token_estimate <= remaining {
+			(c.chunk.text.clone(), c.chunk.end_line, false)
+		} else if remaining >= MIN_PASSAGE_TOKENS {
+			let (text, end) = trim_to_tokens(&c.chunk.text, c.chunk.start_line, remaining);
+			(text, end, true)
+		} else {
+			break;
+		};
+		let token_estimate = estimate_tokens(&text);
+		used += token_estimate;
+		passages.push(KnowledgeContextPassage {
+			index: passages.len() + 1,
+			chunk_id: c.chunk.id.clone(),
+			path: c.path.clone(),
+			title: note_title(&c.path),
+			heading_path: c.chunk.heading_path.clone(),
+			start_line: c.chunk.start_line,
+			end_line,
+			score,
+			token_estimate,
+			text,
+			truncated,
+		});
+	}
+
+	let context = passages
+		.iter()
+		.map(|p| {
+			let mut label = p.title.clone();
+			for h in &p.heading_path {
+				label.push_str(" › ");
+				label.push_str(h);
+			}
+			format!("[{}] {}（{}:L{}-{}）\n{}", p.index, label, p.path, p.start_line, p.end_line, p.text)
+		})
+		.collect::<Vec<_>>()
+		.join("\n\n");
+	Ok(KnowledgeContextResult {
+		passages,
+		total_tokens: used,
+		context,
+	})
+}
+
+/// 按问题检索本地知识库，返回预算内的相关段落及出处；全部离线完成
+#[tauri::command]
+pub async fn retrieve_knowledge_context(
+	app: AppHandle,
+	input: RetrieveKnowledgeContextInput,
+) -> Result<KnowledgeContextResult, String> {
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	// 刷新分块缓存与打分都要读遍整个知识库，放到阻塞线程执行
+	tauri::async_runtime::spawn_blocking(move || {
+		retrieve_context(&root, &input.query, input.budget, input.limit)
+	})
+	.await
+	.map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::command::knowledge_chunk::KnowledgeChunk;
+
+	fn chunk(path: &str, text: &str) -> KnowledgeNoteChunk {
+		KnowledgeNoteChunk {
+			path: path.to_string(),
+			chunk: KnowledgeChunk {
+				id: path.to_string(),
+				heading_path: Vec::new(),
+				start_line: 1,
+				end_line: 1,
+				token_estimate: estimate_tokens(text),
+				text: text.to_string(),
+			},
+		}
+	}
+
+	#[test]
+	fn tokenize_splits_latin_words_and_cjk_bigrams() {
+		assert_eq!(tokenize("Hello, Rust_lang 2024", false), vec!["hello", "rust_lang", "2024"]);
+		assert_eq!(tokenize("知识库", false), vec!["知", "知识", "识", "识库", "库"]);
+		// 查询侧只保留二元组，单字片段除外
+		assert_eq!(tokenize("知识库", true), vec!["知识", "识库"]);
+		assert_eq!(tokenize("用 Rust 写", true), vec!["用", "rust", "写"]);
+		assert_eq!(tokenize("检索API设计", true), vec!["检索", "api", "设计"]);
+	}
+
+	#[test]
+	fn bm25_prefers_the_chunk_matching_more_query_terms() {
+		let chunks = vec![
+			chunk("/kb/a.md", "今天天气很好，适合出门散步。"),
+			chunk("/kb/b.md", "向量检索与全文检索的区别：全文检索基于倒排索引。"),
+			chunk("/kb/c.md", "倒排索引是搜索引擎的基础结构。"),
+		];
+		let ranked = bm25_rank(&chunks, "全文检索");
+		assert_eq!(ranked.first().map(|r| r.0), Some(1));
+		assert!(ranked.iter().all(|(i, s)| *i != 0 && *s > 0.0));
+		let ranked = bm25_rank(&chunks, "倒排索引");
+		assert_eq!(ranked.len(), 2);
+		assert!(ranked.windows(2).all(|w| w[0].1 >= w[1].1));
+	}
+
+	#[test]
+	fn bm25_counts_the_note_title() {
+		let chunks = vec![chunk("/kb/部署手册.md", "步骤一：安装依赖。"), chunk("/kb/other.md", "其他内容。")];
+		assert_eq!(bm25_rank(&chunks, "部署").first().map(|r| r.0), Some(0));
+	}
+
+	#[test]
+	fn bm25_returns_nothing_for_empty_queries_or_misses() {
+		let chunks = vec![chunk("/kb/a.md", "hello world")];
+		assert!(bm25_rank(&chunks, "  ,, ").is_empty());
+		assert!(bm25_rank(&chunks, "missing").is_empty());
+		assert!(bm25_rank(&[], "hello").is_empty());
+	}
+}
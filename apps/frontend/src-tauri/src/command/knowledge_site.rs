@@ -0,0 +1,939 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::io::BufReader;
+use tokio::net::{TcpListener, TcpStream};
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+use crate::command::clipboard::mime_of;
+use crate::command::knowledge::{collect_md_files, resolve_knowledge_root};
+use crate::command::knowledge_assets::{assets_dir, relative_link};
+use crate::command::knowledge_export::{
+	BASE_CSS, KATEX_JS, KATEX_RENDER, KnowledgeExportFailure, KnowledgeExportTheme, RenderHooks,
+	escape_html, free_path, markdown_options, note_body, render_note_html_with, resolve_local_image,
+};
+use crate::command::knowledge_link::{
+	KnowledgeLinkIndex, KnowledgeLinkKind, RawLink, is_external_href, parse_note,
+};
+use crate::command::knowledge_lint::heading_slug;
+use crate::command::knowledge_vault::{is_vault_dir, read_note_text};
+use crate::utils::common::default_save_base_dir;
+use crate::utils::http_server::{is_loopback_host, read_request, reason, write_response};
+use crate::utils::percent::percent_decode;
+
+// —— 静态站点：把知识库文件夹渲染为可直接托管的 HTML 站点（wiki 链接、反向链接、标签页、搜索索引、本地附件），可另存为 zip，并可在本机预览 ——
+// 输出结构：每篇笔记对应 `<相对路径>.html`；`index.html` 为目录页，`tags.html` 为标签页；
+// `search-index.json` 供外部使用，`search-index.js` + `search.js` 供页内搜索（`file://` 下也可用）；`katex.min.js` 供含公式的页面渲染；引用的附件复制到 `files/`
+// 预览服务仅监听 127.0.0.1，只读提供站点目录中的文件
+
+/// 站点目录中的标记文件；重新生成时只清空带此标记的目录，避免误删用户文件
+const SITE_MARKER: &str = ".knowledge-site";
+/// 未指定输出目录时，站点生成在应用数据目录下的此目录中
+const SITES_DIR_NAME: &str = "knowledge-sites";
+const FILES_DIR: &str = "files";
+/// 站点根目录的保留页面；与之同名的笔记页改名为 `<名称>-note.html`
+const RESERVED_PAGES: [&str; 2] = ["index.html", "tags.html"];
+/// 搜索索引中每篇笔记保留的正文字符数
+const SEARCH_TEXT_CHARS: usize = 5000;
+const DEFAULT_PORT: u16 = 17318;
+/// 含公式的页面共用的 KaTeX 脚本，写在站点根目录
+const KATEX_FILE: &str = "katex.min.js";
+
+const SITE_CSS: &str = r#"
+.site-header{position:sticky;top:0;z-index:10;display:flex;gap:16px;align-items:center;padding:10px 32px;background:var(--bg);border-bottom:1px solid var(--border)}
+.site-header .site-title{font-weight:600;color:var(--fg)}
+.site-search{position:relative;margin-left:auto}
+.site-search input{width:240px;padding:5px 10px;border:1px solid var(--border);border-radius:6px;background:var(--code-bg);color:var(--fg);font:inherit}
+.site-search ul{position:absolute;right:0;width:360px;max-height:60vh;overflow:auto;margin:4px 0 0;padding:6px 0;list-style:none;background:var(--bg);border:1px solid var(--border);border-radius:6px}
+.site-search ul:empty{display:none}
+.site-search li a{display:block;padding:4px 12px}
+.site-search li small{display:block;color:var(--muted)}
+.site-meta{color:var(--muted)}
+.site-tags{margin-top:2em}
+.site-tags a{display:inline-block;margin:0 6px 6px 0;padding:1px 8px;border-radius:10px;background:var(--code-bg);font-size:.85em}
+.site-backlinks{margin-top:3em;padding-top:1em;border-top:1px solid var(--border);font-size:.95em}
+.site-backlinks h2{border:0;font-size:1.1em}
+@media print{.site-header{display:none}}
+"#;
+
+const SEARCH_JS: &str = r#"(function(){
+var input=document.getElementById('site-search-input'),list=document.getElementById('site-search-results');
+if(!input||!list)return;
+var root=document.body.getAttribute('data-root')||'';
+var docs=(window.KNOWLEDGE_SITE_INDEX||[]).map(function(d){return{d:d,title:d.title.toLowerCase(),tags:d.tags.join(' '),headings:d.headings.join(' ').toLowerCase(),text:d.text.toLowerCase()}});
+function esc(s){return String(s).replace(/[&<>"']/g,function(c){return{'&':'&amp;','<':'&lt;','>':'&gt;','"':'&quot;',"'":'&#39;'}[c]})}
+function score(doc,terms){var total=0;for(var i=0;i<terms.length;i++){var t=terms[i],s=0;if(doc.title.indexOf(t)>=0)s+=10;if(doc.tags.indexOf(t)>=0)s+=5;if(doc.headings.indexOf(t)>=0)s+=3;if(doc.text.indexOf(t)>=0)s+=1;if(!s)return 0;total+=s}return total}
+function snippet(doc,t){var i=doc.text.indexOf(t),s=Math.max(0,i-30);return i<0?doc.d.text.slice(0,80):(s>0?'…':'')+doc.d.text.slice(s,s+90)}
+input.addEventListener('input',function(){
+var terms=input.value.toLowerCase().split(/\s+/).filter(Boolean);
+if(!terms.length){list.innerHTML='';return}
+var hits=[];for(var i=0;i<docs.length;i++){var s=score(docs[i],terms);if(s)hits.push([s,docs[i]])}
+hits.sort(function(a,b){return b[0]-a[0]});
+list.innerHTML=hits.slice(0,20).map(function(h){return '<li><a href="'+esc(root+h[1].d.url)+'">'+esc(h[1].d.title)+'<small>'+esc(snippet(h[1],terms[0]))+'</small></a></li>'}).join('')||'<li><a><small>无匹配结果</small></a></li>';
+});
+input.addEventListener('keydown',function(e){if(e.key==='Escape'){input.value='';list.innerHTML=''}});
+})();
+"#;
+
+fn note_title(p: &Path) -> String {
+	p.file_stem()
+		.and_then(|s| s.to_str())
+		.unwrap_or("未命名")
+		.to_string()
+}
+
+/// 站点内相对路径写入 href 时转义 `%`、空格、`#`、`?`
+fn encode_href(path: &str) -> String {
+	let mut out = String::with_capacity(path.len());
+	for c in path.chars() {
+		match c {
+			'%' => out.push_str("%25"),
+			' ' => out.push_str("%20"),
+			'#' => out.push_str("%23"),
+			'?' => out.push_str("%3F"),
+			_ => out.push(c),
+		}
+	}
+	out
+}
+
+fn site_path_string(p: &Path) -> String {
+	p.to_string_lossy().replace('\\', "/")
+}
+
+/// 笔记相对路径 → 页面相对路径
+fn page_rel(rel: &Path) -> PathBuf {
+	let page = rel.with_extension("html");
+	let at_root = page.parent().is_none_or(|p| p.as_os_str().is_empty());
+	if at_root && RESERVED_PAGES.contains(&site_path_string(&page).to_lowercase().as_str()) {
+		return PathBuf::from(format!("{}-note.html", note_title(rel)));
+	}
+	page
+}
+
+/// 从页面所在目录回到站点根目录的前缀，如 `../../`
+fn root_prefix(page: &Path) -> String {
+	let depth = page.components().count().saturating_sub(1);
+	"../".repeat(depth)
+}
+
+/// wiki 锚点 `标题` / `一级#二级` 取最后一级转为 slug；块引用 `^id` 在页面中无对应元素，忽略
+fn wiki_anchor(anchor: &str) -> Option<String> {
+	if anchor.starts_with('^') {
+		return None;
+	}
+	let last = anchor.rsplit('#').next().unwrap_or(anchor);
+	Some(heading_slug(last))
+}
+
+fn tag_id(tag: &str) -> String {
+	format!("tag-{}", tag.replace(char::is_whitespace, "-"))
+}
+
+// —— 附件 ——
+
+/// 被引用的本地文件复制到站点 `files/` 下（同一文件只复制一次）
+struct SiteFiles {
+	/// 规范化后的知识库根目录
+	root: PathBuf,
+	out_dir: PathBuf,
+	copied: HashMap<PathBuf, PathBuf>,
+	failed: Vec<KnowledgeExportFailure>,
+}
+
+impl SiteFiles {
+	/// 返回附件在站点内的相对路径：知识库内保持原有层级；知识库外仅允许图片（与导出 HTML 一致），放在 `files/_external/<哈希>/`；
+	/// 隐藏目录（`.history` 等）中的文件不发布
+	fn copy(&mut self, src: &Path, allow_external: bool) -> Option<PathBuf> {
+		let canon = src.canonicalize().ok().filter(|p| p.is_file())?;
+		if let Some(rel) = self.copied.get(&canon) {
+			return Some(rel.clone());
+		}
+		let rel = match canon.strip_prefix(&self.root) {
+			Ok(r) => {
+				if r.components().any(|c| c.as_os_str().to_string_lossy().starts_with('.')) {
+					return None;
+				}
+				Path::new(FILES_DIR).join(r)
+			}
+			Err(_) if allow_external => {
+				let mut hasher = DefaultHasher::new();
+				canon.hash(&mut hasher);
+				Path::new(FILES_DIR)
+					.join("_external")
+					.join(format!("{:016x}", hasher.finish()))
+					.join(canon.file_name()?)
+			}
+			Err(_) => return None,
+		};
+		let dest = self.out_dir.join(&rel);
+		let copied = fs::create_dir_all(dest.parent().unwrap_or(&self.out_dir))
+			.and_then(|_| fs::copy(&canon, &dest));
+		if let Err(e) = copied {
+			self.failed.push(KnowledgeExportFailure {
+				path: canon.to_string_lossy().to_string(),
+				error: format!("复制附件失败：{}", e),
+			});
+			return None;
+		}
+		self.copied.insert(canon, rel.clone());
+		Some(rel)
+	}
+}
+
+// —— 渲染 ——
+
+fn site_options() -> Options {
+	markdown_options() | Options::ENABLE_WIKILINKS
+}
+
+/// 单个页面渲染时的链接改写：笔记链接指向对应页面，附件复制后指向 `files/` 中的副本
+struct PageHooks<'a> {
+	root: &'a Path,
+	index: &'a KnowledgeLinkIndex,
+	pages: &'a [PathBuf],
+	source: &'a Path,
+	page_dir: &'a Path,
+	files: &'a mut SiteFiles,
+}
+
+impl PageHooks<'_> {
+	fn href_to(&self, site_rel: &Path) -> String {
+		encode_href(&relative_link(self.page_dir, site_rel))
+	}
+
+	fn note_href(&self, idx: usize, anchor: Option<String>) -> String {
+		let href = self.href_to(&self.pages[idx]);
+		match anchor.filter(|a| !a.is_empty()) {
+			Some(a) => format!("{}#{}", href, a),
+			None => href,
+		}
+	}
+
+	fn file_href(&mut self, file: &Path, allow_external: bool) -> Option<String> {
+		let rel = self.files.copy(file, allow_external)?;
+		Some(self.href_to(&rel))
+	}
+
+	/// wiki 附件依次尝试笔记目录、知识库根目录与附件目录
+	fn wiki_file(&self, target: &str) -> Option<PathBuf> {
+		let note_dir = self.source.parent().unwrap_or(self.root);
+		[
+			note_dir.join(target),
+			self.root.join(target),
+			assets_dir(self.root).join(target),
+		]
+		.into_iter()
+		.find(|p| p.is_file())
+	}
+}
+
+impl RenderHooks for PageHooks<'_> {
+	fn image(&mut self, src: &str, wiki: bool) -> Option<String> {
+		let file = if wiki {
+			self.wiki_file(src.trim())?
+		} else {
+			resolve_local_image(self.source.parent().unwrap_or(self.root), src)?
+		};
+		self.file_href(&file, true)
+	}
+
+	fn link(&mut self, href: &str, wiki: bool) -> Option<String> {
+		let href = href.trim();
+		if wiki {
+			let (target, anchor) = match href.split_once('#') {
+				Some((t, a)) => (t.trim(), Some(a.trim())),
+				None => (href, None),
+			};
+			if target.is_empty() {
+				return anchor.and_then(wiki_anchor).map(|a| format!("#{}", a));
+			}
+			let link = RawLink {
+				kind: KnowledgeLinkKind::Wiki,
+				target: target.to_string(),
+				anchor: None,
+				line: 0,
+				target_start: 0,
+				target_end: 0,
+			};
+			if let Some(idx) = self.index.resolve(self.source, &link) {
+				return Some(self.note_href(idx, anchor.and_then(wiki_anchor)));
+			}
+			let file = self.wiki_file(target)?;
+			return self.file_href(&file, false);
+		}
+		if is_external_href(href) {
+			return None;
+		}
+		let (path, anchor) = match href.split_once('#') {
+			Some((p, a)) => (p, Some(a.to_string())),
+			None => (href, None),
+		};
+		let target = percent_decode(path.split('?').next().unwrap_or(path));
+		if target.is_empty() {
+			return None;
+		}
+		let link = RawLink {
+			kind: KnowledgeLinkKind::Markdown,
+			target: target.clone(),
+			anchor: None,
+			line: 0,
+			target_start: 0,
+			target_end: 0,
+		};
+		if let Some(idx) = self.index.resolve(self.source, &link) {
+			return Some(self.note_href(idx, anchor));
+		}
+		let file = match target.strip_prefix('/') {
+			Some(rest) => self.root.join(rest),
+			None => self.source.parent().unwrap_or(self.root).join(&target),
+		};
+		self.file_href(&file, false)
+	}
+}
+
+/// 搜索索引中的一篇笔记
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchEntry {
+	title: String,
+	/// 页面相对站点根目录的地址
+	url: String,
+	/// 笔记相对知识库根目录的路径
+	path: String,
+	tags: Vec<String>,
+	headings: Vec<String>,
+	/// 纯文本正文（截断）
+	text: String,
+}
+
+/// 提取标题列表与纯文本正文
+fn plain_text(content: &str) -> (Vec<String>, String) {
+	let mut headings = Vec::new();
+	let mut heading: Option<String> = None;
+	let mut text = String::new();
+	for event in Parser::new_ext(note_body(content), site_options()) {
+		match event {
+			Event::Start(Tag::Heading { .. }) => heading = Some(String::new()),
+			Event::End(TagEnd::Heading(_)) => {
+				headings.extend(heading.take().map(|h| h.trim().to_string()));
+				text.push(' ');
+			}
+			Event::Text(t) | Event::Code(t) => {
+				if let Some(h) = heading.as_mut() {
+					h.push_str(&t);
+				}
+				// 按字节粗略限长，最终再按字符截断
+				if text.len() < SEARCH_TEXT_CHARS * 4 {
+					text.push_str(&t);
+				}
+			}
+			Event::SoftBreak
+			| Event::HardBreak
+			| Event::End(TagEnd::Paragraph | TagEnd::Item | TagEnd::TableCell | TagEnd::CodeBlock) => {
+				text.push(' ')
+			}
+			_ => {}
+		}
+	}
+	let text = text
+		.split_whitespace()
+		.collect::<Vec<_>>()
+		.join(" ")
+		.chars()
+		.take(SEARCH_TEXT_CHARS)
+		.collect();
+	(headings, text)
+}
+
+/// 完整页面：顶栏（站点标题、标签页、搜索）+ 正文
+fn site_document(
+	site_title: &str,
+	page_title: Option<&str>,
+	page: &Path,
+	body: &str,
+	has_math: bool,
+	theme: KnowledgeExportTheme,
+) -> String {
+	let prefix = root_prefix(page);
+	let title = match page_title {
+		Some(t) => format!("{} · {}", t, site_title),
+		None => site_title.to_string(),
+	};
+	format!(
+		"<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width,initial-scale=1\">\n<title>{title}</title>\n<style>:root{{{vars}}}{base}{site}</style>\n{katex}\n</head>\n<body data-root=\"{prefix}\">\n<header class=\"site-header\">\n<a class=\"site-title\" href=\"{prefix}index.html\">{site_title}</a>\n<a href=\"{prefix}tags.html\">标签</a>\n<div class=\"site-search\"><input id=\"site-search-input\" type=\"search\" placeholder=\"搜索笔记…\" autocomplete=\"off\"><ul id=\"site-search-results\"></ul></div>\n</header>\n<article class=\"markdown-body\">\n{body}</article>\n<script src=\"{prefix}search-index.js\"></script>\n<script src=\"{prefix}search.js\"></script>\n</body>\n</html>\n",
+		title = escape_html(&title),
+		vars = theme.css_vars(),
+		base = BASE_CSS,
+		site = SITE_CSS,
+		katex = if has_math {
+			format!("<script src=\"{}{}\"></script>\n{}", prefix, KATEX_FILE, KATEX_RENDER)
+		} else {
+			String::new()
+		},
+		prefix = prefix,
+		site_title = escape_html(site_title),
+		body = body,
+	)
+}
+
+fn write_site_file(out_dir: &Path, rel: &Path, bytes: &[u8]) -> Result<(), String> {
+	let path = out_dir.join(rel);
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+	}
+	fs::write(&path, bytes).map_err(|e| format!("写入 {} 失败：{}", path.display(), e))
+}
+
+/// 准备输出目录：已存在时仅清空此前生成的站点，其余非空目录拒绝写入
+fn prepare_site_dir(dir: &Path) -> Result<(), String> {
+	if dir.join(SITE_MARKER).is_file() {
+		fs::remove_dir_all(dir).map_err(|e| format!("清空旧站点失败：{}", e))?;
+	} else if fs::read_dir(dir).is_ok_and(|mut rd| rd.next().is_some()) {
+		return Err(format!("输出目录已存在且不是生成的站点，请更换目录：{}", dir.display()));
+	}
+	fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+	fs::write(dir.join(SITE_MARKER), b"").map_err(|e| e.to_string())
+}
+
+struct SiteBuild {
+	pages: usize,
+	assets: usize,
+	tags: usize,
+	failed: Vec<KnowledgeExportFailure>,
+}
+
+/// 渲染 `root` 下的全部笔记到 `out_dir`（须已由 [`prepare_site_dir`] 准备好）
+fn build_site(root: &Path, out_dir: &Path, site_title: &str, theme: KnowledgeExportTheme) -> Result<SiteBuild, String> {
+	let mut paths = Vec::new();
+	collect_md_files(root, &mut paths)?;
+	paths.sort();
+	let mut failed = Vec::new();
+	let mut contents = Vec::new();
+	let mut parsed = Vec::new();
+	for path in paths {
+		match read_note_text(&path) {
+			Ok(content) => {
+				parsed.push((path, parse_note(&content)));
+				contents.push(content);
+			}
+			Err(error) => failed.push(KnowledgeExportFailure {
+				path: path.to_string_lossy().to_string(),
+				error,
+			}),
+		}
+	}
+	// 索引保持传入顺序，下标与 `contents` 一一对应
+	let index = KnowledgeLinkIndex::from_parsed(root, parsed);
+	let notes: Vec<(&Path, _)> = index.notes().collect();
+	let pages: Vec<PathBuf> = notes
+		.iter()
+		.map(|(p, _)| page_rel(p.strip_prefix(root).unwrap_or(p)))
+		.collect();
+
+	let mut backlinks: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); notes.len()];
+	let mut tags: BTreeMap<String, BTreeSet<usize>> = BTreeMap::new();
+	for (i, (path, note)) in notes.iter().enumerate() {
+		for link in &note.links {
+			if let Some(j) = index.resolve(path, link)
+				&& j != i
+			{
+				backlinks[j].insert(i);
+			}
+		}
+		for tag in &note.tags {
+			tags.entry(tag.clone()).or_default().insert(i);
+		}
+	}
+
+	let mut files = SiteFiles {
+		root: root.canonicalize().unwrap_or_else(|_| root.to_path_buf()),
+		out_dir: out_dir.to_path_buf(),
+		copied: HashMap::new(),
+		failed: Vec::new(),
+	};
+	let mut search = Vec::with_capacity(notes.len());
+	for (i, (path, note)) in notes.iter().enumerate() {
+		let page = &pages[i];
+		let page_dir = page.parent().unwrap_or(Path::new(""));
+		let mut hooks = PageHooks {
+			root,
+			index: &index,
+			pages: &pages,
+			source: path,
+			page_dir,
+			files: &mut files,
+		};
+		let (mut body, has_math) = render_note_html_with(&contents[i], theme, site_options(), &mut hooks);
+		let href = |j: usize| encode_href(&relative_link(page_dir, &pages[j]));
+		let prefix = root_prefix(page);
+		if !note.tags.is_empty() {
+			body.push_str("<div class=\"site-tags\">");
+			for tag in &note.tags {
+				body.push_str(&format!(
+					"<a href=\"{}tags.html#{}\">#{}</a>",
+					prefix,
+					escape_html(&encode_href(&tag_id(tag))),
+					escape_html(tag)
+				));
+			}
+			body.push_str("</div>\n");
+		}
+		if !backlinks[i].is_empty() {
+			body.push_str("<section class=\"site-backlinks\">\n<h2>反向链接</h2>\n<ul>\n");
+			for &j in &backlinks[i] {
+				body.push_str(&format!(
+					"<li><a href=\"{}\">{}</a></li>\n",
+					escape_html(&href(j)),
+					escape_html(&note_title(notes[j].0))
+				));
+			}
+			body.push_str("</ul>\n</section>\n");
+		}
+		let title = note_title(path);
+		let html = site_document(site_title, Some(&title), page, &body, has_math, theme);
+		if let Err(error) = write_site_file(out_dir, page, html.as_bytes()) {
+			failed.push(KnowledgeExportFailure {
+				path: path.to_string_lossy().to_string(),
+				error,
+			});
+			continue;
+		}
+		let (headings, text) = plain_text(&contents[i]);
+		search.push(SearchEntry {
+			title,
+			url: encode_href(&site_path_string(page)),
+			path: site_path_string(path.strip_prefix(root).unwrap_or(path)),
+			tags: note.tags.clone(),
+			headings,
+			text,
+		});
+	}
+
+	// 目录页：按文件夹分组
+	let mut folders: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+	for (i, page) in pages.iter().enumerate() {
+		let folder = page.parent().map(site_path_string).unwrap_or_default();
+		folders.entry(folder).or_default().push(i);
+	}
+	let mut index_body = format!(
+		"<h1>{}</h1>\n<p class=\"site-meta\">共 {} 篇笔记 · {} 个标签</p>\n",
+		escape_html(site_title),
+		notes.len(),
+		tags.len()
+	);
+	for (folder, items) in &folders {
+		if !folder.is_empty() {
+			index_body.push_str(&format!("<h2>{}</h2>\n", escape_html(&folder.replace('/', " / "))));
+		}
+		index_body.push_str("<ul class=\"export-index\">\n");
+		for &i in items {
+			index_body.push_str(&format!(
+				"<li><a href=\"{}\">{}</a></li>\n",
+				escape_html(&encode_href(&site_path_string(&pages[i]))),
+				escape_html(&note_title(notes[i].0))
+			));
+		}
+		index_body.push_str("</ul>\n");
+	}
+	let index_page = Path::new("index.html");
+	write_site_file(
+		out_dir,
+		index_page,
+		site_document(site_title, None, index_page, &index_body, false, theme).as_bytes(),
+	)?;
+
+	// 标签页：每个标签一节，按标签名排序
+	let mut tags_body = String::from("<h1>标签</h1>\n");
+	if tags.is_empty() {
+		tags_body.push_str("<p class=\"site-meta\">暂无标签</p>\n");
+	}
+	for (tag, items) in &tags {
+		tags_body.push_str(&format!(
+			"<h2 id=\"{}\">#{} <small class=\"site-meta\">{}</small></h2>\n<ul class=\"export-index\">\n",
+			escape_html(&tag_id(tag)),
+			escape_html(tag),
+			items.len()
+		));
+		for &i in items {
+			tags_body.push_str(&format!(
+				"<li><a href=\"{}\">{}</a></li>\n",
+				escape_html(&encode_href(&site_path_string(&pages[i]))),
+				escape_html(&note_title(notes[i].0))
+			));
+		}
+		tags_body.push_str("</ul>\n");
+	}
+	let tags_page = Path::new("tags.html");
+	write_site_file(
+		out_dir,
+		tags_page,
+		site_document(site_title, Some("标签"), tags_page, &tags_body, false, theme).as_bytes(),
+	)?;
+
+	let json = serde_json::to_string(&search).map_err(|e| e.to_string())?;
+	write_site_file(out_dir, Path::new("search-index.json"), json.as_bytes())?;
+	// 内联到脚本中时避免正文里的 `</script>` 提前结束标签
+	let script = format!("window.KNOWLEDGE_SITE_INDEX={};\n", json.replace("</", "<\\/"));
+	write_site_file(out_dir, Path::new("search-index.js"), script.as_bytes())?;
+	write_site_file(out_dir, Path::new("search.js"), SEARCH_JS.as_bytes())?;
+	write_site_file(out_dir, Path::new(KATEX_FILE), KATEX_JS.as_bytes())?;
+
+	failed.append(&mut files.failed);
+	Ok(SiteBuild {
+		pages: search.len(),
+		assets: files.copied.len(),
+		tags: tags.len(),
+		failed,
+	})
+}
+
+fn collect_site_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<(), String> {
+	for ent in fs::read_dir(dir).map_err(|e| e.to_string())? {
+		let ent = ent.map_err(|e| e.to_string())?;
+		let p = ent.path();
+		if ent.file_type().map_err(|e| e.to_string())?.is_dir() {
+			collect_site_files(&p, out)?;
+		} else if ent.file_name() != SITE_MARKER {
+			out.push(p);
+		}
+	}
+	Ok(())
+}
+
+/// 站点目录打包为 zip，条目以站点名为顶层目录
+fn zip_site(site_dir: &Path, zip_path: &Path) -> Result<(), String> {
+	let mut files = Vec::new();
+	collect_site_files(site_dir, &mut files)?;
+	files.sort();
+	let top = site_dir
+		.file_name()
+		.map(|s| s.to_string_lossy().to_string())
+		.unwrap_or_else(|| "site".to_string());
+	let file = fs::File::create(zip_path).map_err(|e| format!("创建 zip 失败：{}", e))?;
+	let mut zip = ZipWriter::new(file);
+	let opts = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+	for p in files {
+		let rel = site_path_string(p.strip_prefix(site_dir).unwrap_or(&p));
+		let bytes = fs::read(&p).map_err(|e| e.to_string())?;
+		zip.start_file(format!("{}/{}", top, rel), opts)
+			.map_err(|e| e.to_string())?;
+		zip.write_all(&bytes).map_err(|e| e.to_string())?;
+	}
+	zip.finish().map_err(|e| e.to_string())?;
+	Ok(())
+}
+
+// —— 本机预览 ——
+
+struct SitePreview {
+	port: u16,
+	site_dir: PathBuf,
+	task: tauri::async_runtime::JoinHandle<()>,
+}
+
+static PREVIEW: LazyLock<Mutex<Option<SitePreview>>> = LazyLock::new(|| Mutex::new(None));
+
+fn content_type(p: &Path) -> &'static str {
+	let ext = p
+		.extension()
+		.and_then(|e| e.to_str())
+		.map(|e| e.to_ascii_lowercase())
+		.unwrap_or_default();
+	match ext.as_str() {
+		"html" | "htm" => "text/html; charset=utf-8",
+		"css" => "text/css; charset=utf-8",
+		"js" => "text/javascript; charset=utf-8",
+		"json" => "application/json",
+		"md" | "txt" => "text/plain; charset=utf-8",
+		"pdf" => "application/pdf",
+		_ => mime_of(p),
+	}
+}
+
+/// 请求路径映射为站点内文件：拒绝 `..` 与隐藏文件，目录取其 `index.html`
+fn resolve_site_file(site_dir: &Path, url_path: &str) -> Option<PathBuf> {
+	let decoded = percent_decode(url_path);
+	let mut file = site_dir.to_path_buf();
+	for seg in decoded.split('/') {
+		match seg {
+			"" | "." => {}
+			s if s.starts_with('.') || s.contains('\\') => return None,
+			s => file.push(s),
+		}
+	}
+	if file.is_dir() {
+		file.push("index.html");
+	}
+	file.is_file().then_some(file)
+}
+
+async fn serve_preview(stream: TcpStream, site_dir: PathBuf) {
+	let mut reader = BufReader::new(stream);
+	let req = read_request(&mut reader).await;
+	let mut stream = reader.into_inner();
+	let req = match req {
+		Ok(r) => r,
+		Err(status) => {
+			write_response(&mut stream, status, "text/plain; charset=utf-8", reason(status).as_bytes()).await;
+			return;
+		}
+	};
+	// 防 DNS 重绑定：Host 必须是回环地址
+	if !req.headers.get("host").is_some_and(|h| is_loopback_host(h)) {
+		write_response(&mut stream, 403, "text/plain; charset=utf-8", reason(403).as_bytes()).await;
+		return;
+	}
+	if req.method != "GET" {
+		write_response(&mut stream, 405, "text/plain; charset=utf-8", reason(405).as_bytes()).await;
+		return;
+	}
+	let bytes = match resolve_site_file(&site_dir, &req.path) {
+		Some(file) => tokio::fs::read(&file).await.ok().map(|b| (file, b)),
+		None => None,
+	};
+	match bytes {
+		Some((file, body)) => write_response(&mut stream, 200, content_type(&file), &body).await,
+		None => write_response(&mut stream, 404, "text/plain; charset=utf-8", reason(404).as_bytes()).await,
+	}
+}
+
+// —— 命令 ——
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishKnowledgeSiteInput {
+	/// 要发布的文件夹（含子目录）；不传为当前知识库
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	/// 站点标题，默认取文件夹名
+	#[serde(default)]
+	pub title: Option<String>,
+	#[serde(default)]
+	pub theme: KnowledgeExportTheme,
+	/// 输出目录，站点生成在其下的 `<文件夹名>-site/`；不传时生成到应用数据目录（可直接预览）
+	#[serde(default)]
+	pub target_dir: Option<String>,
+	/// 同时打包为 `<文件夹名>-site.zip`，放在 `targetDir`，未传时放在默认保存目录
+	#[serde(default)]
+	pub zip: bool,
+	/// zip 已存在时覆盖，否则顺延为 `name_1.zip`
+	#[serde(default)]
+	pub overwrite: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeSiteResult {
+	pub site_dir: String,
+	pub zip_path: Option<String>,
+	pub pages: usize,
+	/// 复制的附件数
+	pub assets: usize,
+	pub tags: usize,
+	/// 读取失败的笔记、复制失败的附件等
+	pub failed: Vec<KnowledgeExportFailure>,
+	pub message: String,
+}
+
+/// 把知识库文件夹发布为静态网站（可选打包为 zip）
+#[tauri::command]
+pub async fn publish_knowledge_site(
+	app: AppHandle,
+	input: Option<PublishKnowledgeSiteInput>,
+) -> Result<KnowledgeSiteResult, String> {
+	let input = input.unwrap_or_default();
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	if is_vault_dir(&root) {
+		return Err("加密知识库不支持发布为静态网站".to_string());
+	}
+	let folder_name = root
+		.file_name()
+		.map(|s| s.to_string_lossy().to_string())
+		.unwrap_or_else(|| "knowledge".to_string());
+	let site_title = input
+		.title
+		.as_deref()
+		.map(str::trim)
+		.filter(|s| !s.is_empty())
+		.unwrap_or(&folder_name)
+		.to_string();
+	let target_dir = input
+		.target_dir
+		.as_deref()
+		.map(str::trim)
+		.filter(|s| !s.is_empty())
+		.map(PathBuf::from);
+	let site_name = format!("{}-site", folder_name);
+	let site_dir = match &target_dir {
+		Some(dir) => dir.join(&site_name),
+		None => app
+			.path()
+			.app_data_dir()
+			.map_err(|e| format!("获取应用数据目录失败: {}", e))?
+			.join(SITES_DIR_NAME)
+			.join(&site_name),
+	};
+	let zip_path = if input.zip {
+		let base = match &target_dir {
+			Some(dir) => dir.clone(),
+			None => default_save_base_dir(&app).await,
+		};
+		Some(free_path(base.join(format!("{}.zip", site_name)), input.overwrite))
+	} else {
+		None
+	};
+	let theme = input.theme;
+
+	tauri::async_runtime::spawn_blocking(move || {
+		prepare_site_dir(&site_dir)?;
+		let build = build_site(&root, &site_dir, &site_title, theme)?;
+		if let Some(zip_path) = &zip_path {
+			if let Some(parent) = zip_path.parent() {
+				fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+			}
+			zip_site(&site_dir, zip_path)?;
+		}
+		let mut message = format!(
+			"已生成 {} 个页面、{} 个附件、{} 个标签",
+			build.pages, build.assets, build.tags
+		);
+		if !build.failed.is_empty() {
+			message.push_str(&format!("，{} 项失败", build.failed.len()));
+		}
+		Ok(KnowledgeSiteResult {
+			site_dir: site_dir.to_string_lossy().to_string(),
+			zip_path: zip_path.map(|p| p.to_string_lossy().to_string()),
+			pages: build.pages,
+			assets: build.assets,
+			tags: build.tags,
+			failed: build.failed,
+			message,
+		})
+	})
+	.await
+	.map_err(|e| e.to_string())?
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartKnowledgeSitePreviewInput {
+	/// `publish_knowledge_site` 返回的 `siteDir`
+	pub site_dir: String,
+	/// 监听端口，默认 17318；传 0 由系统分配
+	#[serde(default)]
+	pub port: Option<u16>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeSitePreviewStatus {
+	pub running: bool,
+	pub port: Option<u16>,
+	/// `http://127.0.0.1:<port>/`
+	pub url: Option<String>,
+	pub site_dir: Option<String>,
+}
+
+fn preview_status() -> KnowledgeSitePreviewStatus {
+	let guard = PREVIEW.lock().ok();
+	match guard.as_ref().and_then(|g| g.as_ref()) {
+		Some(p) => KnowledgeSitePreviewStatus {
+			running: true,
+			port: Some(p.port),
+			url: Some(format!("http://127.0.0.1:{}/", p.port)),
+			site_dir: Some(p.site_dir.to_string_lossy().to_string()),
+		},
+		None => KnowledgeSitePreviewStatus {
+			running: false,
+			port: None,
+			url: None,
+			site_dir: None,
+		},
+	}
+}
+
+/// 在 127.0.0.1 启动站点预览服务；已在运行时先停止再以新参数启动
+#[tauri::command]
+pub async fn start_knowledge_site_preview(
+	input: StartKnowledgeSitePreviewInput,
+) -> Result<KnowledgeSitePreviewStatus, String> {
+	let site_dir = PathBuf::from(input.site_dir.trim());
+	if !site_dir.join("index.html").is_file() {
+		return Err(format!("站点目录不存在或缺少 index.html：{}", site_dir.display()));
+	}
+	if let Some(old) = PREVIEW.lock().map_err(|e| e.to_string())?.take() {
+		old.task.abort();
+	}
+	let port = input.port.unwrap_or(DEFAULT_PORT);
+	let listener = TcpListener::bind(("127.0.0.1", port))
+		.await
+		.map_err(|e| format!("预览服务启动失败（端口 {}）：{}", port, e))?;
+	let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+	let task_dir = site_dir.clone();
+	let task = tauri::async_runtime::spawn(async move {
+		while let Ok((stream, _)) = listener.accept().await {
+			tauri::async_runtime::spawn(serve_preview(stream, task_dir.clone()));
+		}
+	});
+	*PREVIEW.lock().map_err(|e| e.to_string())? = Some(SitePreview { port, site_dir, task });
+	Ok(preview_status())
+}
+
+/// 停止站点预览服务；返回之前是否在运行
+#[tauri::command]
+pub fn stop_knowledge_site_preview() -> Result<bool, String> {
+	let old = PREVIEW.lock().map_err(|e| e.to_string())?.take();
+	Ok(match old {
+		Some(preview) => {
+			preview.task.abort();
+			true
+		}
+		None => false,
+	})
+}
+
+/// 站点预览服务状态
+#[tauri::command]
+pub fn get_knowledge_site_preview_status() -> Result<KnowledgeSitePreviewStatus, String> {
+	Ok(preview_status())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn page_rel_renames_reserved_root_pages() {
+		assert_eq!(page_rel(Path::new("a/b.md")), Path::new("a/b.html"));
+		assert_eq!(page_rel(Path::new("index.md")), Path::new("index-note.html"));
+		assert_eq!(page_rel(Path::new("Tags.md")), Path::new("Tags-note.html"));
+		assert_eq!(page_rel(Path::new("sub/index.md")), Path::new("sub/index.html"));
+	}
+
+	#[test]
+	fn resolve_site_file_stays_inside_site_dir() {
+		let dir = tempfile::tempdir().unwrap();
+		let site = dir.path().join("site");
+		fs::create_dir_all(site.join("a b")).unwrap();
+		fs::write(site.join("index.html"), "i").unwrap();
+		fs::write(site.join("a b/c.html"), "c").unwrap();
+		fs::write(site.join(SITE_MARKER), "").unwrap();
+		fs::write(dir.path().join("secret.txt"), "s").unwrap();
+
+		assert_eq!(resolve_site_file(&site, "/"), Some(site.join("index.html")));
+		assert_eq!(resolve_site_file(&site, "/a%20b/./c.html"), Some(site.join("a b").join("c.html")));
+		assert_eq!(resolve_site_file(&site, "/missing.html"), None);
+		for hostile in ["/../secret.txt", "/%2e%2e/secret.txt", "/a%20b/..%2f..%2fsecret.txt", "/..%5csecret.txt", "/.knowledge-site"] {
+			assert_eq!(resolve_site_file(&site, hostile), None, "{}", hostile);
+		}
+	}
+}
@@ -0,0 +1,400 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::UNIX_EPOCH;
+
+use chrono::{Days, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::command::knowledge::{collect_md_files, resolve_knowledge_root, write_file_atomic};
+use crate::command::knowledge_chunk::{INDEX_DIR_NAME, is_cjk, rel_of};
+use crate::command::knowledge_export::note_body;
+use crate::command::knowledge_link::parse_note;
+use crate::command::knowledge_listing::listing_persists;
+use crate::command::knowledge_vault::{is_vault_dir, read_note_text};
+
+// —— 知识库统计：按文件夹与总体汇总篇数、字数（中日韩字符逐字计）、阅读时长、标签频次、每日新建 / 更新数与最大笔记。
+// 每篇笔记的统计缓存在 `<知识库>/.index/stats.json`，按 mtime + 大小判断是否需要重读；
+// 仅默认知识库目录落盘，前端传入的其他目录与加密知识库只缓存在内存中 ——
+
+const STATS_CACHE_FILE: &str = "stats.json";
+const CACHE_VERSION: u32 = 1;
+const DEFAULT_DAYS: u32 = 30;
+const MAX_DAYS: u32 = 366;
+const DEFAULT_TOP: usize = 10;
+/// 阅读速度：中日韩字符 / 分钟、其他文字单词 / 分钟
+const CJK_CHARS_PER_MINUTE: f64 = 400.0;
+const WORDS_PER_MINUTE: f64 = 200.0;
+
+/// 知识库根目录 → 统计缓存；进程内常驻，避免每次都读取缓存文件
+static CACHES: LazyLock<Mutex<HashMap<PathBuf, StatsCache>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NoteStats {
+	mtime_ms: u64,
+	size: u64,
+	/// 文件创建时间；文件系统不支持时为修改时间
+	created_ms: u64,
+	/// 单词数：中日韩字符各计 1，其他文字按连续字母数字计 1
+	words: usize,
+	/// 非空白字符数
+	characters: usize,
+	cjk_characters: usize,
+	tags: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StatsCache {
+	version: u32,
+	/// 相对知识库根目录的路径（`/` 分隔）→ 统计
+	notes: BTreeMap<String, NoteStats>,
+}
+
+fn cache_path(root: &Path) -> PathBuf {
+	root.join(INDEX_DIR_NAME).join(STATS_CACHE_FILE)
+}
+
+fn load_cache(root: &Path) -> StatsCache {
+	fs::read_to_string(cache_path(root))
+		.ok()
+		.and_then(|s| serde_json::from_str::<StatsCache>(&s).ok())
+		.filter(|c| c.version == CACHE_VERSION)
+		.unwrap_or(StatsCache {
+			version: CACHE_VERSION,
+			..Default::default()
+		})
+}
+
+fn save_cache(root: &Path, cache: &StatsCache) -> Result<(), String> {
+	fs::create_dir_all(root.join(INDEX_DIR_NAME)).map_err(|e| e.to_string())?;
+	let json = serde_json::to_string(cache).map_err(|e| e.to_string())?;
+	write_file_atomic(&cache_path(root), json.as_bytes())
+}
+
+pub(crate) fn system_time_ms(t: std::io::Result<std::time::SystemTime>) -> Option<u64> {
+	t.ok()
+		.and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+		.map(|d| d.as_millis() as u64)
+}
+
+/// 正文（不含 front matter）的单词数、非空白字符数与中日韩字符数
+fn count_text(body: &str) -> (usize, usize, usize) {
+	let (mut words, mut characters, mut cjk) = (0usize, 0usize, 0usize);
+	let mut in_word = false;
+	for c in body.chars() {
+		if c.is_whitespace() {
+			in_word = false;
+			continue;
+		}
+		characters += 1;
+		if is_cjk(c) {
+			cjk += 1;
+			words += 1;
+			in_word = false;
+		} else if c.is_alphanumeric() {
+			if !in_word {
+				words += 1;
+			}
+			in_word = true;
+		} else {
+			in_word = false;
+		}
+	}
+	(words, characters, cjk)
+}
+
+fn reading_minutes(words: usize, cjk: usize) -> u64 {
+	let minutes = cjk as f64 / CJK_CHARS_PER_MINUTE + words.saturating_sub(cjk) as f64 / WORDS_PER_MINUTE;
+	minutes.ceil() as u64
+}
+
+/// 增量刷新：mtime 或大小变化的笔记重新读取，已删除的移出缓存。返回（重新读取的篇数, 无法读取的笔记, 缓存是否有变化）
+fn refresh_cache(root: &Path, cache: &mut StatsCache) -> Result<(usize, Vec<String>, bool), String> {
+	let mut paths = Vec::new();
+	collect_md_files(root, &mut paths)?;
+	let mut live: HashSet<String> = HashSet::with_capacity(paths.len());
+	let mut refreshed = 0usize;
+	let mut skipped = Vec::new();
+	let mut dirty = false;
+	for path in paths {
+		let Some(rel) = rel_of(root, &path) else {
+			continue;
+		};
+		let Ok(meta) = fs::metadata(&path) else {
+			continue;
+		};
+		let mtime_ms = system_time_ms(meta.modified()).unwrap_or(0);
+		let size = meta.len();
+		live.insert(rel.clone());
+		if cache
+			.notes
+			.get(&rel)
+			.is_some_and(|n| n.mtime_ms == mtime_ms && n.size == size)
+		{
+			continue;
+		}
+		// 读取失败（加密知识库未解锁等）时不保留旧统计，避免展示过期数据
+		let Ok(content) = read_note_text(&path) else {
+			dirty |= cache.notes.remove(&rel).is_some();
+			skipped.push(path.to_string_lossy().to_string());
+			continue;
+		};
+		let (words, characters, cjk_characters) = count_text(note_body(&content));
+		cache.notes.insert(
+			rel,
+			NoteStats {
+				mtime_ms,
+				size,
+				created_ms: system_time_ms(meta.created()).unwrap_or(mtime_ms),
+				words,
+				characters,
+				cjk_characters,
+				tags: parse_note(&content).tags,
+			},
+		);
+		refreshed += 1;
+		dirty = true;
+	}
+	let before = cache.notes.len();
+	cache.notes.retain(|rel, _| live.contains(rel));
+	dirty |= cache.notes.len() != before;
+	Ok((refreshed, skipped, dirty))
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeFolderStats {
+	/// 相对根目录的 `/` 路径，根目录为空串
+	pub folder: String,
+	/// 本目录直接包含的笔记数
+	pub note_count: usize,
+	/// 以下均含所有子目录
+	pub total_note_count: usize,
+	pub words: usize,
+	pub characters: usize,
+	pub bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeTagCount {
+	pub tag: String,
+	pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeDayCount {
+	/// `YYYY-MM-DD`（本地时区）
+	pub date: String,
+	pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeNoteSize {
+	pub path: String,
+	pub title: String,
+	pub bytes: u64,
+	pub words: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeStats {
+	pub note_count: usize,
+	pub folder_count: usize,
+	pub words: usize,
+	pub characters: usize,
+	pub cjk_characters: usize,
+	pub bytes: u64,
+	/// 全部笔记的预计阅读时长（分钟）
+	pub reading_minutes: u64,
+	/// 按路径排序，仅含有笔记的目录及其上级
+	pub folders: Vec<KnowledgeFolderStats>,
+	/// 按笔记数降序，再按标签名
+	pub tags: Vec<KnowledgeTagCount>,
+	/// 最近 `days` 天（含今天）逐日计数，无记录的日期为 0
+	pub created_per_day: Vec<KnowledgeDayCount>,
+	pub updated_per_day: Vec<KnowledgeDayCount>,
+	pub largest_notes: Vec<KnowledgeNoteSize>,
+	/// 本次因内容变化而重新读取的笔记数
+	pub refreshed: usize,
+	/// 无法读取（加密知识库未解锁等）而未计入的笔记
+	pub skipped: Vec<String>,
+}
+
+fn local_date(ms: u64) -> Option<NaiveDate> {
+	Local
+		.timestamp_millis_opt(ms as i64)
+		.single()
+		.map(|t| t.date_naive())
+}
+
+fn per_day(stamps: impl Iterator<Item = u64>, days: u32) -> Vec<KnowledgeDayCount> {
+	let today = Local::now().date_naive();
+	let start = today
+		.checked_sub_days(Days::new(u64::from(days.saturating_sub(1))))
+		.unwrap_or(today);
+	let mut counts: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+	for date in stamps.filter_map(local_date).filter(|d| *d >= start && *d <= today) {
+		*counts.entry(date).or_default() += 1;
+	}
+	start
+		.iter_days()
+		.take_while(|d| *d <= today)
+		.map(|d| KnowledgeDayCount {
+			date: d.format("%Y-%m-%d").to_string(),
+			count: counts.get(&d).copied().unwrap_or(0),
+		})
+		.collect()
+}
+
+fn aggregate(root: &Path, cache: &StatsCache, days: u32, top: usize) -> KnowledgeStats {
+	let notes = &cache.notes;
+	let mut folders: BTreeMap<String, KnowledgeFolderStats> = BTreeMap::new();
+	let mut tags: HashMap<&str, usize> = HashMap::new();
+	for (rel, n) in notes {
+		let parent = rel.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+		folders.entry(parent.to_string()).or_default().note_count += 1;
+		// 逐级累加到所有上级目录（含根目录）
+		let mut dir = Some(parent);
+		while let Some(d) = dir {
+			let f = folders.entry(d.to_string()).or_default();
+			f.total_note_count += 1;
+			f.words += n.words;
+			f.characters += n.characters;
+			f.bytes += n.size;
+			dir = if d.is_empty() {
+				None
+			} else {
+				Some(d.rsplit_once('/').map(|(up, _)| up).unwrap_or(""))
+			};
+		}
+		for t in &n.tags {
+			*tags.entry(t.as_str()).or_default() += 1;
+		}
+	}
+	let folders: Vec<KnowledgeFolderStats> = folders
+		.into_iter()
+		.map(|(folder, mut f)| {
+			f.folder = folder;
+			f
+		})
+		.collect();
+
+	let mut tags: Vec<KnowledgeTagCount> = tags
+		.into_iter()
+		.map(|(tag, count)| KnowledgeTagCount {
+			tag: tag.to_string(),
+			count,
+		})
+		.collect();
+	tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+
+	let mut largest: Vec<(&String, &NoteStats)> = notes.iter().collect();
+	largest.sort_by_key(|(rel, n)| (Reverse(n.size), *rel));
+	let largest_notes = largest
+		.into_iter()
+		.take(top)
+		.map(|(rel, n)| {
+			let path = root.join(rel);
+			KnowledgeNoteSize {
+				title: path
+					.file_stem()
+					.and_then(|s| s.to_str())
+					.unwrap_or("未命名")
+					.to_string(),
+				path: path.to_string_lossy().to_string(),
+				bytes: n.size,
+				words: n.words,
+			}
+		})
+		.collect();
+
+	let words = notes.values().map(|n| n.words).sum();
+	let cjk_characters = notes.values().map(|n| n.cjk_characters).sum();
+	KnowledgeStats {
+		note_count: notes.len(),
+		// 根目录不计入文件夹数
+		folder_count: folders.iter().filter(|f| !f.folder.is_empty()).count(),
+		words,
+		characters: notes.values().map(|n| n.characters).sum(),
+		cjk_characters,
+		bytes: notes.values().map(|n| n.size).sum(),
+		reading_minutes: reading_minutes(words, cjk_characters),
+		folders,
+		tags,
+		created_per_day: per_day(notes.values().map(|n| n.created_ms), days),
+		updated_per_day: per_day(notes.values().map(|n| n.mtime_ms), days),
+		largest_notes,
+		refreshed: 0,
+		skipped: Vec::new(),
+	}
+}
+
+/// 丢弃 `root` 及其子目录的内存缓存（启用加密、锁定时清除其中的标签等明文信息）
+pub(crate) fn evict_stats_caches(root: &Path) {
+	if let Ok(mut caches) = CACHES.lock() {
+		caches.retain(|dir, _| !fs::canonicalize(dir).unwrap_or_else(|_| dir.clone()).starts_with(root));
+	}
+}
+
+/// `persist` 为 false 时缓存只保存在内存中（同列表缓存，仅默认知识库目录落盘）
+fn compute_stats(root: &Path, persist: bool, days: u32, top: usize) -> Result<KnowledgeStats, String> {
+	// 加密知识库不落盘：缓存中的标签等信息会泄露明文
+	let persist = persist && !is_vault_dir(root);
+	let mut caches = CACHES.lock().map_err(|e| e.to_string())?;
+	let cache = caches.entry(root.to_path_buf()).or_insert_with(|| {
+		if persist {
+			load_cache(root)
+		} else {
+			StatsCache {
+				version: CACHE_VERSION,
+				..Default::default()
+			}
+		}
+	});
+	let (refreshed, skipped, dirty) = refresh_cache(root, cache)?;
+	if persist && (dirty || !cache_path(root).is_file()) {
+		save_cache(root, cache)?;
+	}
+	let mut stats = aggregate(root, cache, days, top);
+	stats.refreshed = refreshed;
+	stats.skipped = skipped;
+	Ok(stats)
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeStatsInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	/// 每日新建 / 更新统计覆盖的天数，默认 30，最多 366
+	#[serde(default)]
+	pub days: Option<u32>,
+	/// 最大笔记返回条数，默认 10
+	#[serde(default)]
+	pub top: Option<usize>,
+}
+
+/// 知识库统计（知识库首页仪表盘），基于增量缓存，只重读有变化的笔记
+#[tauri::command]
+pub async fn knowledge_stats(
+	app: AppHandle,
+	input: Option<KnowledgeStatsInput>,
+) -> Result<KnowledgeStats, String> {
+	let input = input.unwrap_or_default();
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	let days = input.days.unwrap_or(DEFAULT_DAYS).clamp(1, MAX_DAYS);
+	let top = input.top.unwrap_or(DEFAULT_TOP);
+	let persist = listing_persists(&app, &root).await;
+	tauri::async_runtime::spawn_blocking(move || compute_stats(&root, persist, days, top))
+		.await
+		.map_err(|e| e.to_string())?
+}
@@ -0,0 +1,115 @@
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use chrono::Local;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{Mutex, MutexGuard};
+
+use crate::command::knowledge::write_file_atomic;
+
+// —— 知识库同步（WebDAV / 服务端）共用：同步清单读写、防并发锁、进度事件与冲突副本命名 ——
+
+/// 防止手动同步、定时同步以及不同同步方式之间并发改写同一批文件
+static SYNC_RUNNING: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+
+/// 占用同步锁，已有同步在进行时返回错误；守卫释放即解锁
+pub(crate) fn try_begin_sync() -> Result<MutexGuard<'static, ()>, String> {
+	SYNC_RUNNING.try_lock().map_err(|_| "同步正在进行中".to_string())
+}
+
+/// 读取知识库根目录下的同步清单；不存在或无法解析时为 None
+pub(crate) fn load_manifest<T: DeserializeOwned>(root: &Path, file_name: &str) -> Option<T> {
+	fs::read_to_string(root.join(file_name))
+		.ok()
+		.and_then(|s| serde_json::from_str(&s).ok())
+}
+
+pub(crate) fn save_manifest<T: Serialize>(root: &Path, file_name: &str, manifest: &T) -> Result<(), String> {
+	let json = serde_json::to_string_pretty(manifest).map_err(|e| e.to_string())?;
+	write_file_atomic(&root.join(file_name), json.as_bytes())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeSyncProgress {
+	/// `scanning` / `syncing` / `done`
+	pub phase: String,
+	pub processed: usize,
+	pub total: usize,
+	pub current: Option<String>,
+}
+
+pub(crate) fn emit_progress(
+	app: &AppHandle,
+	event: &str,
+	phase: &str,
+	processed: usize,
+	total: usize,
+	current: Option<&str>,
+) {
+	let _ = app.emit(
+		event,
+		KnowledgeSyncProgress {
+			phase: phase.to_string(),
+			processed,
+			total,
+			current: current.map(str::to_string),
+		},
+	);
+}
+
+/// `note.md` → `note (conflict 2026-01-02).md`；`taken` 判定已占用时追加序号
+pub(crate) fn conflict_copy_rel(rel: &str, taken: &dyn Fn(&str) -> bool) -> String {
+	let (dir, name) = match rel.rsplit_once('/') {
+		Some((d, n)) => (format!("{}/", d), n),
+		None => (String::new(), rel),
+	};
+	let (stem, ext) = match name.rfind('.') {
+		Some(i) if i > 0 => (&name[..i], &name[i..]),
+		_ => (name, ""),
+	};
+	let date = Local::now().format("%Y-%m-%d");
+	let mut n = 1;
+	loop {
+		let suffix = if n == 1 { String::new() } else { format!(" {}", n) };
+		let candidate = format!("{}{} (conflict {}{}){}", dir, stem, date, suffix, ext);
+		if !taken(&candidate) {
+			return candidate;
+		}
+		n += 1;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn conflict_copy_rel_keeps_dir_and_extension() {
+		let date = Local::now().format("%Y-%m-%d").to_string();
+		let none = |_: &str| false;
+		assert_eq!(conflict_copy_rel("a/b/note.md", &none), format!("a/b/note (conflict {}).md", date));
+		assert_eq!(conflict_copy_rel("README", &none), format!("README (conflict {})", date));
+		assert_eq!(conflict_copy_rel(".env", &none), format!(".env (conflict {})", date));
+	}
+
+	#[test]
+	fn conflict_copy_rel_skips_taken_names() {
+		let date = Local::now().format("%Y-%m-%d").to_string();
+		let first = format!("note (conflict {}).md", date);
+		let second = format!("note (conflict {} 2).md", date);
+		let taken = |c: &str| c == first || c == second;
+		assert_eq!(conflict_copy_rel("note.md", &taken), format!("note (conflict {} 3).md", date));
+	}
+
+	#[test]
+	fn sync_lock_rejects_concurrent_runs() {
+		let guard = try_begin_sync().unwrap();
+		assert!(try_begin_sync().is_err());
+		drop(guard);
+		assert!(try_begin_sync().is_ok());
+	}
+}
@@ -0,0 +1,445 @@
+use std::fmt::{Display, Write as _};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use chrono::{DateTime, Days, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::command::clipboard::read_clipboard_text;
+use crate::command::knowledge::{
+	SaveKnowledgeMarkdownInput, collect_md_files, is_md_file_path, resolve_knowledge_dir,
+	save_knowledge_markdown, write_file_atomic,
+};
+use crate::command::knowledge_conflict::{KnowledgeSaveError, file_mtime_ms};
+use crate::command::knowledge_vault::{encode_note, read_note_text};
+use crate::utils::common::get_store_value;
+
+// —— 笔记模板与日记：模板为知识库根目录 `.templates/` 下的 Markdown（隐藏目录，不出现在笔记列表 / 检索中）——
+// 变量：`{{title}}` `{{date}}` `{{time}}` `{{datetime}}` `{{yesterday}}` `{{tomorrow}}` `{{clipboard}}` `{{selection}}`；
+// 日期类可带 strftime 格式，如 `{{date:%Y年%m月%d日}}`；未知变量原样保留
+// 日记：`journal/YYYY-MM-DD.md`，模板取 store 中 `knowledgeDailyNoteTemplate`，未设置时用 `.templates/daily.md`，再无则用内置模板
+
+pub(crate) const TEMPLATES_DIR_NAME: &str = ".templates";
+const JOURNAL_DIR_NAME: &str = "journal";
+const DAILY_TEMPLATE_NAME: &str = "daily";
+const DEFAULT_DAILY_TEMPLATE: &str = "# {{date}}\n\n";
+
+/// 渲染模板所需的上下文；剪贴板仅在模板用到 `{{clipboard}}` 时读取
+struct TemplateContext<'a> {
+	title: &'a str,
+	/// `{{date}}` 等日期变量对应的日期（日记可指定非今天）
+	date: NaiveDate,
+	now: DateTime<Local>,
+	selection: Option<&'a str>,
+}
+
+/// 格式串含非法占位符，或含该值没有的字段（如日期用 `%H`）时返回 None。
+/// chrono 在 Display 时才报错，`to_string()` 会因此 panic，故写入 String 并检查结果
+fn format_checked<'a, D: Display>(fmt: &'a str, render: impl FnOnce(&'a str) -> D) -> Option<String> {
+	let mut out = String::new();
+	write!(out, "{}", render(fmt)).ok()?;
+	Some(out)
+}
+
+fn render_variable(name: &str, arg: Option<&str>, ctx: &TemplateContext, clipboard: &mut Option<Option<String>>) -> Option<String> {
+	let date_fmt = arg.unwrap_or("%Y-%m-%d");
+	match name {
+		"title" => Some(ctx.title.to_string()),
+		"date" => format_checked(date_fmt, |f| ctx.date.format(f)),
+		"yesterday" => format_checked(date_fmt, |f| (ctx.date - Days::new(1)).format(f)),
+		"tomorrow" => format_checked(date_fmt, |f| (ctx.date + Days::new(1)).format(f)),
+		"time" => format_checked(arg.unwrap_or("%H:%M"), |f| ctx.now.format(f)),
+		"datetime" => format_checked(arg.unwrap_or("%Y-%m-%d %H:%M"), |f| ctx.now.format(f)),
+		"clipboard" => Some(
+			clipboard
+				.get_or_insert_with(read_clipboard_text)
+				.clone()
+				.unwrap_or_default(),
+		),
+		"selection" => Some(ctx.selection.unwrap_or_default().to_string()),
+		_ => None,
+	}
+}
+
+/// 替换 `{{变量}}` / `{{变量:格式}}`；无法识别的占位符原样保留
+fn render_template(template: &str, ctx: &TemplateContext) -> String {
+	let mut out = String::with_capacity(template.len());
+	let mut clipboard: Option<Option<String>> = None;
+	let mut rest = template;
+	while let Some(start) = rest.find("{{") {
+		out.push_str(&rest[..start]);
+		let after = &rest[start + 2..];
+		let Some(end) = after.find("}}") else {
+			out.push_str(&rest[start..]);
+			return out;
+		};
+		let inner = after[..end].trim();
+		let (name, arg) = match inner.split_once(':') {
+			Some((n, a)) => (n.trim(), Some(a.trim())),
+			None => (inner, None),
+		};
+		match render_variable(name, arg, ctx, &mut clipboard) {
+			Some(v) => out.push_str(&v),
+			None => out.push_str(&rest[start..start + 2 + end + 2]),
+		}
+		rest = &after[end + 2..];
+	}
+	out.push_str(rest);
+	out
+}
+
+/// 模板所在知识库：`dir_path` 优先，否则默认知识库目录
+async fn template_root(app: &AppHandle, dir_path: Option<&String>) -> Result<PathBuf, String> {
+	match dir_path.map(|s| s.trim()).filter(|s| !s.is_empty()) {
+		Some(d) => Ok(PathBuf::from(d)),
+		None => resolve_knowledge_dir(app).await,
+	}
+}
+
+/// 模板名 → `.templates/` 下的 `.md` 路径；名称可含子目录，缺省扩展名时补 `.md`，不允许跳出模板目录
+fn template_path(root: &Path, name: &str) -> Result<PathBuf, String> {
+	let trimmed = name.trim().replace('\\', "/");
+	if trimmed.is_empty() {
+		return Err("模板名不能为空".to_string());
+	}
+	let mut rel = PathBuf::new();
+	for comp in Path::new(&trimmed).components() {
+		match comp {
+			Component::Normal(s) => rel.push(s),
+			Component::CurDir => {}
+			_ => return Err(format!("模板名非法：{}", name.trim())),
+		}
+	}
+	if !is_md_file_path(&rel) {
+		let mut file = rel.file_name().unwrap_or_default().to_os_string();
+		file.push(".md");
+		rel.set_file_name(file);
+	}
+	Ok(root.join(TEMPLATES_DIR_NAME).join(rel))
+}
+
+fn read_template(root: &Path, name: &str) -> Result<String, String> {
+	let path = template_path(root, name)?;
+	if !path.is_file() {
+		return Err(format!("模板不存在：{}", name.trim()));
+	}
+	read_note_text(&path)
+}
+
+/// 经 `save_knowledge_markdown` 写入（历史快照、加密、Git 自动提交、分块缓存与普通保存一致）
+async fn save_rendered(app: &AppHandle, input: SaveKnowledgeMarkdownInput) -> Result<String, String> {
+	let result = save_knowledge_markdown(app.clone(), input)
+		.await
+		.map_err(|e| match e {
+			KnowledgeSaveError::Message(m) => m,
+			KnowledgeSaveError::Conflict(c) => c.message,
+		})?;
+	result.file_path.ok_or_else(|| "保存失败".to_string())
+}
+
+// —— 模板管理 ——
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeTemplateDirInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeTemplateEntry {
+	/// 相对 `.templates/` 的名称（不含 `.md`），作为其他命令的 `template` 参数
+	pub name: String,
+	pub path: String,
+	pub updated_at_ms: Option<u64>,
+}
+
+/// 列出知识库 `.templates/` 下的模板，按名称排序；目录不存在时返回空列表
+#[tauri::command]
+pub async fn list_knowledge_templates(
+	app: AppHandle,
+	input: Option<KnowledgeTemplateDirInput>,
+) -> Result<Vec<KnowledgeTemplateEntry>, String> {
+	let root = template_root(&app, input.and_then(|i| i.dir_path).as_ref()).await?;
+	let dir = root.join(TEMPLATES_DIR_NAME);
+	if !dir.is_dir() {
+		return Ok(Vec::new());
+	}
+	let mut files = Vec::new();
+	collect_md_files(&dir, &mut files)?;
+	let mut out: Vec<KnowledgeTemplateEntry> = files
+		.into_iter()
+		.map(|p| {
+			let rel = p.strip_prefix(&dir).unwrap_or(&p).with_extension("");
+			KnowledgeTemplateEntry {
+				name: rel.to_string_lossy().replace('\\', "/"),
+				updated_at_ms: file_mtime_ms(&p),
+				path: p.to_string_lossy().to_string(),
+			}
+		})
+		.collect();
+	out.sort_by(|a, b| a.name.cmp(&b.name));
+	Ok(out)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveKnowledgeTemplateInput {
+	pub name: String,
+	pub content: String,
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	/// 为 true 时允许覆盖同名模板
+	#[serde(default)]
+	pub overwrite: bool,
+}
+
+/// 新建或覆盖模板；位于已解锁的加密知识库中时落盘为密文
+#[tauri::command]
+pub async fn save_knowledge_template(
+	app: AppHandle,
+	input: SaveKnowledgeTemplateInput,
+) -> Result<KnowledgeTemplateEntry, String> {
+	let root = template_root(&app, input.dir_path.as_ref()).await?;
+	let path = template_path(&root, &input.name)?;
+	if path.exists() && !input.overwrite {
+		return Err(format!("模板已存在：{}", input.name.trim()));
+	}
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+	}
+	let bytes = encode_note(&path, &input.content)?;
+	write_file_atomic(&path, &bytes)?;
+	let rel = path
+		.strip_prefix(root.join(TEMPLATES_DIR_NAME))
+		.unwrap_or(&path)
+		.with_extension("");
+	Ok(KnowledgeTemplateEntry {
+		name: rel.to_string_lossy().replace('\\', "/"),
+		updated_at_ms: file_mtime_ms(&path),
+		path: path.to_string_lossy().to_string(),
+	})
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteKnowledgeTemplateInput {
+	pub name: String,
+	#[serde(default)]
+	pub dir_path: Option<String>,
+}
+
+#[tauri::command]
+pub async fn delete_knowledge_template(
+	app: AppHandle,
+	input: DeleteKnowledgeTemplateInput,
+) -> Result<(), String> {
+	let root = template_root(&app, input.dir_path.as_ref()).await?;
+	let path = template_path(&root, &input.name)?;
+	if !path.is_file() {
+		return Err(format!("模板不存在：{}", input.name.trim()));
+	}
+	fs::remove_file(&path).map_err(|e| e.to_string())
+}
+
+// —— 由模板新建笔记 ——
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateNoteFromTemplateInput {
+	/// 模板名（见 `list_knowledge_templates`）
+	pub template: String,
+	pub title: String,
+	/// 与 `save_knowledge_markdown` 相同：完整 .md 路径或目录
+	#[serde(default)]
+	pub file_path: Option<String>,
+	/// 知识库目录：模板从其 `.templates/` 读取，未传 `filePath` 时笔记也保存在此
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	#[serde(default)]
+	pub overwrite: bool,
+	/// 编辑器中的选中文本，填入 `{{selection}}`
+	#[serde(default)]
+	pub selection: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeTemplateNoteResult {
+	pub file_path: String,
+	/// 渲染后的正文，前端可直接载入编辑器
+	pub content: String,
+	pub message: String,
+}
+
+/// 渲染模板并按 `save_knowledge_markdown` 的路径规则保存为新笔记
+#[tauri::command]
+pub async fn create_note_from_template(
+	app: AppHandle,
+	input: CreateNoteFromTemplateInput,
+) -> Result<KnowledgeTemplateNoteResult, String> {
+	let root = template_root(&app, input.dir_path.as_ref()).await?;
+	let template = read_template(&root, &input.template)?;
+	let now = Local::now();
+	let content = render_template(
+		&template,
+		&TemplateContext {
+			title: input.title.trim(),
+			date: now.date_naive(),
+			now,
+			selection: input.selection.as_deref(),
+		},
+	);
+	let file_path = save_rendered(
+		&app,
+		SaveKnowledgeMarkdownInput {
+			title: input.title,
+			content: content.clone(),
+			file_path: input.file_path,
+			dir_path: input.dir_path,
+			overwrite: input.overwrite,
+			previous_title: None,
+			update_links: false,
+			expected_mtime_ms: None,
+			expected_hash: None,
+			base_content: None,
+		},
+	)
+	.await?;
+	Ok(KnowledgeTemplateNoteResult {
+		message: format!("已由模板「{}」创建 {}", input.template.trim(), file_path),
+		file_path,
+		content,
+	})
+}
+
+// —— 日记 ——
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenDailyNoteInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	/// `YYYY-MM-DD`，默认今天
+	#[serde(default)]
+	pub date: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeDailyNoteResult {
+	pub file_path: String,
+	pub date: String,
+	/// 本次是否新建（false 表示日记已存在）
+	pub created: bool,
+	pub content: String,
+}
+
+/// 日记模板正文：store 中指定的模板 → `.templates/daily.md` → 内置模板
+async fn daily_template(app: &AppHandle, root: &Path) -> Result<String, String> {
+	let configured = get_store_value(app, "knowledgeDailyNoteTemplate")
+		.await
+		.ok()
+		.map(|v| v.trim().to_string())
+		.filter(|v| !v.is_empty() && v != "null");
+	if let Some(name) = configured {
+		return read_template(root, &name);
+	}
+	match template_path(root, DAILY_TEMPLATE_NAME) {
+		Ok(p) if p.is_file() => read_note_text(&p),
+		_ => Ok(DEFAULT_DAILY_TEMPLATE.to_string()),
+	}
+}
+
+/// 打开某天的日记 `journal/YYYY-MM-DD.md`，不存在时按日记模板创建
+#[tauri::command]
+pub async fn open_daily_note(
+	app: AppHandle,
+	input: Option<OpenDailyNoteInput>,
+) -> Result<KnowledgeDailyNoteResult, String> {
+	let input = input.unwrap_or(OpenDailyNoteInput {
+		dir_path: None,
+		date: None,
+	});
+	let now = Local::now();
+	let date = match input.date.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+		Some(s) => NaiveDate::parse_from_str(s, "%Y-%m-%d")
+			.map_err(|_| format!("日期格式应为 YYYY-MM-DD：{}", s))?,
+		None => now.date_naive(),
+	};
+	let date_str = date.format("%Y-%m-%d").to_string();
+	let root = template_root(&app, input.dir_path.as_ref()).await?;
+	let path = root.join(JOURNAL_DIR_NAME).join(format!("{}.md", date_str));
+	if path.is_file() {
+		return Ok(KnowledgeDailyNoteResult {
+			file_path: path.to_string_lossy().to_string(),
+			date: date_str,
+			created: false,
+			content: read_note_text(&path)?,
+		});
+	}
+	let template = daily_template(&app, &root).await?;
+	let content = render_template(
+		&template,
+		&TemplateContext {
+			title: &date_str,
+			date,
+			now,
+			selection: None,
+		},
+	);
+	let file_path = save_rendered(
+		&app,
+		SaveKnowledgeMarkdownInput {
+			title: date_str.clone(),
+			content: content.clone(),
+			file_path: Some(path.to_string_lossy().to_string()),
+			dir_path: Some(root.to_string_lossy().to_string()),
+			overwrite: false,
+			previous_title: None,
+			update_links: false,
+			expected_mtime_ms: None,
+			expected_hash: None,
+			base_content: None,
+		},
+	)
+	.await?;
+	Ok(KnowledgeDailyNoteResult {
+		file_path,
+		date: date_str,
+		created: true,
+		content,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use chrono::TimeZone;
+
+	fn render(template: &str) -> String {
+		let ctx = TemplateContext {
+			title: "标题",
+			date: NaiveDate::from_ymd_opt(2026, 3, 1).unwrap(),
+			now: Local.with_ymd_and_hms(2026, 3, 1, 9, 5, 0).unwrap(),
+			selection: Some("选中"),
+		};
+		render_template(template, &ctx)
+	}
+
+	#[test]
+	fn renders_date_variables_with_formats() {
+		assert_eq!(render("{{title}} {{date}} {{yesterday}} {{tomorrow}}"), "标题 2026-03-01 2026-02-28 2026-03-02");
+		assert_eq!(render("{{date:%Y年%m月%d日}} {{time}} {{datetime:%H:%M:%S}}"), "2026年03月01日 09:05 09:05:00");
+		assert_eq!(render("{{selection}} {{unknown}}"), "选中 {{unknown}}");
+	}
+
+	#[test]
+	fn time_fields_in_date_formats_do_not_panic() {
+		assert_eq!(render("{{date:%H:%M}}"), "{{date:%H:%M}}");
+		assert_eq!(render("{{tomorrow:%Y %S}}"), "{{tomorrow:%Y %S}}");
+		assert_eq!(render("{{time:%Q}}"), "{{time:%Q}}");
+	}
+}
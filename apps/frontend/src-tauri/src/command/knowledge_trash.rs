@@ -0,0 +1,329 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::command::knowledge::is_md_file_path;
+use crate::types::common::SaveFileResult;
+use crate::utils::common::get_store_value;
+
+// —— 知识 Markdown 回收站：删除时移入应用数据目录下的 `knowledge-trash/`，并记录原路径 ——
+
+const TRASH_DIR_NAME: &str = "knowledge-trash";
+const TRASH_INDEX_FILE: &str = "index.json";
+const DEFAULT_RETENTION_DAYS: u64 = 30;
+
+/// 回收站索引读改写需串行，避免并发删除 / 恢复互相覆盖
+static TRASH_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeTrashEntry {
+	pub id: String,
+	/// 删除前的完整路径，恢复时默认写回此处
+	pub original_path: String,
+	pub title: String,
+	pub deleted_at_ms: u64,
+	pub size: u64,
+}
+
+fn now_ms() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_millis() as u64)
+		.unwrap_or(0)
+}
+
+fn trash_dir(app: &AppHandle) -> Result<PathBuf, String> {
+	let dir = app
+		.path()
+		.app_data_dir()
+		.map_err(|e| format!("获取应用数据目录失败: {}", e))?
+		.join(TRASH_DIR_NAME);
+	fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+	Ok(dir)
+}
+
+fn read_index(dir: &Path) -> Vec<KnowledgeTrashEntry> {
+	fs::read_to_string(dir.join(TRASH_INDEX_FILE))
+		.ok()
+		.and_then(|s| serde_json::from_str(&s).ok())
+		.unwrap_or_default()
+}
+
+fn write_index(dir: &Path, entries: &[KnowledgeTrashEntry]) -> Result<(), String> {
+	let json = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
+	fs::write(dir.join(TRASH_INDEX_FILE), json).map_err(|e| e.to_string())
+}
+
+/// 回收站中的文件名：`<id>` 加原文件扩展名（附件等非笔记文件恢复后类型不变）
+fn item_path(dir: &Path, id: &str, original: &Path) -> PathBuf {
+	match original.extension() {
+		Some(ext) => dir.join(format!("{}.{}", id, ext.to_string_lossy())),
+		None => dir.join(id),
+	}
+}
+
+/// 条目在回收站中的文件；兼容早期一律存为 `<id>.md` 的条目
+fn entry_path(dir: &Path, entry: &KnowledgeTrashEntry) -> PathBuf {
+	let path = item_path(dir, &entry.id, Path::new(&entry.original_path));
+	let legacy = dir.join(format!("{}.md", entry.id));
+	if !path.exists() && legacy.exists() { legacy } else { path }
+}
+
+/// 跨盘时 `rename` 会失败，退化为复制后删除
+fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+	if fs::rename(from, to).is_ok() {
+		return Ok(());
+	}
+	fs::copy(from, to).map_err(|e| e.to_string())?;
+	fs::remove_file(from).map_err(|e| e.to_string())
+}
+
+/// 将笔记移入回收站，返回新建的回收站条目
+pub(crate) fn move_to_trash(app: &AppHandle, note: &Path) -> Result<KnowledgeTrashEntry, String> {
+	let dir = trash_dir(app)?;
+	let _guard = TRASH_LOCK.lock().map_err(|e| e.to_string())?;
+	let mut entries = read_index(&dir);
+	let entry = trash_into(&dir, &mut entries, note)?;
+	write_index(&dir, &entries)?;
+	Ok(entry)
+}
+
+/// 已持有 [`TRASH_LOCK`] 时移入回收站并追加到 `entries`，由调用方写回索引
+fn trash_into(
+	dir: &Path,
+	entries: &mut Vec<KnowledgeTrashEntry>,
+	note: &Path,
+) -> Result<KnowledgeTrashEntry, String> {
+	let mut ms = now_ms();
+	while item_path(dir, &ms.to_string(), note).exists() {
+		ms += 1;
+	}
+	let id = ms.to_string();
+	let size = fs::metadata(note).map(|m| m.len()).unwrap_or(0);
+	move_file(note, &item_path(dir, &id, note))?;
+	let entry = KnowledgeTrashEntry {
+		id,
+		original_path: note.to_string_lossy().to_string(),
+		// 非笔记文件（随文件夹删除的附件等）保留扩展名以便辨认
+		title: if is_md_file_path(note) { note.file_stem() } else { note.file_name() }
+			.and_then(|s| s.to_str())
+			.unwrap_or("未命名")
+			.to_string(),
+		deleted_at_ms: ms,
+		size,
+	};
+	entries.push(entry.clone());
+	Ok(entry)
+}
+
+/// 回收站中原属于 `root` 目录的 `.md` 笔记：（回收站中的文件, 原路径）。供附件清理判断引用，
+/// 原路径用于定位加密笔记所属的知识库
+pub(crate) fn trashed_note_files(app: &AppHandle, root: &Path) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+	let dir = trash_dir(app)?;
+	let _guard = TRASH_LOCK.lock().map_err(|e| e.to_string())?;
+	Ok(read_index(&dir)
+		.into_iter()
+		.map(|e| (entry_path(&dir, &e), PathBuf::from(e.original_path)))
+		.filter(|(_, original)| original.starts_with(root) && is_md_file_path(original))
+		.collect())
+}
+
+/// 彻底删除满足条件的条目，返回删除数量
+fn purge_where(
+	dir: &Path,
+	mut should_purge: impl FnMut(&KnowledgeTrashEntry) -> bool,
+) -> Result<usize, String> {
+	let _guard = TRASH_LOCK.lock().map_err(|e| e.to_string())?;
+	let entries = read_index(dir);
+	let mut kept = Vec::with_capacity(entries.len());
+	let mut purged = 0usize;
+	for entry in entries {
+		if should_purge(&entry) {
+			let _ = fs::remove_file(entry_path(dir, &entry));
+			purged += 1;
+		} else {
+			kept.push(entry);
+		}
+	}
+	write_index(dir, &kept)?;
+	Ok(purged)
+}
+
+async fn retention_days(app: &AppHandle) -> u64 {
+	get_store_value(app, "knowledgeTrashRetentionDays")
+		.await
+		.ok()
+		.and_then(|v| v.trim().parse::<u64>().ok())
+		.unwrap_or(DEFAULT_RETENTION_DAYS)
+}
+
+/// 清理超过保留天数的条目；`days` 为 None 时读取 store 中 `knowledgeTrashRetentionDays`（默认 30，0 表示不自动清空）
+pub(crate) async fn empty_expired_trash(app: &AppHandle, days: Option<u64>) -> Result<usize, String> {
+	let days = match days {
+		Some(d) => d,
+		None => retention_days(app).await,
+	};
+	if days == 0 {
+		return Ok(0);
+	}
+	let dir = trash_dir(app)?;
+	let cutoff = now_ms().saturating_sub(days * 86_400_000);
+	purge_where(&dir, |e| e.deleted_at_ms < cutoff)
+}
+
+/// 回收站列表（最近删除在前）
+#[tauri::command]
+pub fn list_knowledge_trash(app: AppHandle) -> Result<Vec<KnowledgeTrashEntry>, String> {
+	let dir = trash_dir(&app)?;
+	let _guard = TRASH_LOCK.lock().map_err(|e| e.to_string())?;
+	let mut entries = read_index(&dir);
+	entries.sort_by_key(|e| Reverse(e.deleted_at_ms));
+	Ok(entries)
+}
+
+/// 原路径已被占用时的处理方式
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KnowledgeTrashConflict {
+	/// 报错，由前端询问用户后再选其他策略
+	#[default]
+	Fail,
+	/// 在原目录下生成 `标题_1.md`、`标题_2.md`…
+	Rename,
+	/// 覆盖现有文件（现有文件先移入回收站）
+	Overwrite,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreKnowledgeTrashInput {
+	pub id: String,
+	#[serde(default)]
+	pub on_conflict: KnowledgeTrashConflict,
+}
+
+/// 原路径已被占用时顺延为 `name_1.ext`、`name_2.ext`…（保留原扩展名）
+fn next_free_path(original: &Path) -> PathBuf {
+	let parent = original.parent().unwrap_or(Path::new(""));
+	let stem = original
+		.file_stem()
+		.and_then(|s| s.to_str())
+		.unwrap_or("未命名");
+	let ext = original
+		.extension()
+		.map(|e| format!(".{}", e.to_string_lossy()))
+		.unwrap_or_default();
+	let mut n = 1;
+	loop {
+		let candidate = parent.join(format!("{}_{}{}", stem, n, ext));
+		if !candidate.exists() {
+			return candidate;
+		}
+		n += 1;
+	}
+}
+
+/// 从回收站恢复到原路径（目录不存在时会重建）
+#[tauri::command]
+pub fn restore_knowledge_trash_item(
+	app: AppHandle,
+	input: RestoreKnowledgeTrashInput,
+) -> Result<SaveFileResult, String> {
+	let dir = trash_dir(&app)?;
+	let _guard = TRASH_LOCK.lock().map_err(|e| e.to_string())?;
+	let mut entries = read_index(&dir);
+	let Some(pos) = entries.iter().position(|e| e.id == input.id.trim()) else {
+		return Err(format!("回收站中不存在：{}", input.id));
+	};
+	let entry = entries[pos].clone();
+	let original = PathBuf::from(&entry.original_path);
+	let mut replaced = false;
+	let target = if original.exists() {
+		match input.on_conflict {
+			KnowledgeTrashConflict::Fail => {
+				return Err(format!("文件已存在：{}", original.display()));
+			}
+			KnowledgeTrashConflict::Rename => next_free_path(&original),
+			KnowledgeTrashConflict::Overwrite => {
+				if !original.is_file() {
+					return Err("目标路径已存在且非文件".to_string());
+				}
+				trash_into(&dir, &mut entries, &original)?;
+				replaced = true;
+				original
+			}
+		}
+	} else {
+		original
+	};
+	if let Some(parent) = target.parent() {
+		fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+	}
+	let moved = move_file(&entry_path(&dir, &entry), &target);
+	if moved.is_ok() {
+		entries.remove(pos);
+	}
+	// 被覆盖的文件已移入回收站，无论恢复是否成功都要记入索引
+	write_index(&dir, &entries)?;
+	moved?;
+	Ok(SaveFileResult {
+		success: "success".to_string(),
+		file_path: Some(target.to_string_lossy().to_string()),
+		message: if replaced {
+			format!("已恢复至 {}（原文件已移入回收站）", target.display())
+		} else {
+			format!("已恢复至 {}", target.display())
+		},
+	})
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgeKnowledgeTrashInput {
+	/// 要彻底删除的条目；空列表不删除任何条目
+	#[serde(default)]
+	pub ids: Option<Vec<String>>,
+	/// 清空整个回收站，须显式传入，避免空选择误删全部
+	#[serde(default)]
+	pub all: bool,
+}
+
+/// 彻底删除回收站条目，返回删除数量
+#[tauri::command]
+pub fn purge_knowledge_trash(
+	app: AppHandle,
+	input: PurgeKnowledgeTrashInput,
+) -> Result<usize, String> {
+	let dir = trash_dir(&app)?;
+	if input.all {
+		return purge_where(&dir, |_| true);
+	}
+	match input.ids {
+		Some(ids) if ids.is_empty() => Ok(0),
+		Some(ids) => purge_where(&dir, |e| ids.iter().any(|id| id.trim() == e.id)),
+		None => Err("请传入要删除的 ids，或以 all: true 清空回收站".to_string()),
+	}
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmptyExpiredKnowledgeTrashInput {
+	/// 保留天数；不传时读取设置 `knowledgeTrashRetentionDays`
+	#[serde(default)]
+	pub days: Option<u64>,
+}
+
+/// 清理删除时间超过 N 天的条目，返回删除数量
+#[tauri::command]
+pub async fn empty_expired_knowledge_trash(
+	app: AppHandle,
+	input: EmptyExpiredKnowledgeTrashInput,
+) -> Result<usize, String> {
+	empty_expired_trash(&app, input.days).await
+}
@@ -0,0 +1,492 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::Engine as _;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use zeroize::Zeroizing;
+
+use crate::command::knowledge::{
+	collect_md_files, is_md_file_path, resolve_knowledge_root, write_file_atomic,
+};
+use crate::command::knowledge_chunk::INDEX_DIR_NAME;
+use crate::command::knowledge_history::HISTORY_DIR_NAME;
+use crate::command::knowledge_listing::evict_listing_caches;
+use crate::command::knowledge_stats::evict_stats_caches;
+use crate::command::knowledge_template::TEMPLATES_DIR_NAME;
+
+// —— 加密知识库：笔记以 XChaCha20-Poly1305 认证加密后仍存为 `.md`（首行标记 + base64），
+// 密钥由口令经 Argon2id 派生，仅在解锁期间保存在内存中 ——
+
+/// 知识库根目录下的加密配置；存在即表示该目录已启用加密
+const VAULT_FILE: &str = ".knowledge-vault.json";
+/// 加密笔记首行标记，同时作为 AEAD 附加数据
+const MAGIC: &str = "<!-- dnhyxc-ai vault v1 -->";
+const VERIFIER: &[u8] = b"dnhyxc-ai knowledge vault";
+const NONCE_LEN: usize = 24;
+const SALT_LEN: usize = 16;
+const MIN_PASSWORD_CHARS: usize = 8;
+const KDF_MEMORY_KIB: u32 = 64 * 1024;
+const KDF_ITERATIONS: u32 = 3;
+const KDF_PARALLELISM: u32 = 1;
+
+/// 已解锁知识库根目录（规范化路径）→ 密钥；锁定时移除，Drop 时清零
+static KEYS: LazyLock<Mutex<HashMap<PathBuf, Zeroizing<[u8; 32]>>>> =
+	LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VaultConfig {
+	version: u32,
+	/// 固定 `argon2id`
+	kdf: String,
+	salt: String,
+	memory_kib: u32,
+	iterations: u32,
+	parallelism: u32,
+	/// 固定 `xchacha20poly1305`
+	cipher: String,
+	/// 加密后的固定明文，解锁时用于校验口令
+	verifier: String,
+}
+
+fn b64() -> base64::engine::GeneralPurpose {
+	base64::engine::general_purpose::STANDARD
+}
+
+fn load_config(root: &Path) -> Result<VaultConfig, String> {
+	let raw = fs::read_to_string(root.join(VAULT_FILE)).map_err(|e| format!("读取加密配置失败: {}", e))?;
+	let config: VaultConfig = serde_json::from_str(&raw).map_err(|e| format!("加密配置无效: {}", e))?;
+	if config.version != 1 || config.kdf != "argon2id" || config.cipher != "xchacha20poly1305" {
+		return Err("不支持的加密配置版本".to_string());
+	}
+	Ok(config)
+}
+
+fn derive_key(password: &str, config: &VaultConfig) -> Result<Zeroizing<[u8; 32]>, String> {
+	let salt = b64().decode(&config.salt).map_err(|_| "加密配置中的 salt 无效")?;
+	let params = Params::new(config.memory_kib, config.iterations, config.parallelism, Some(32))
+		.map_err(|e| format!("密钥派生参数无效: {}", e))?;
+	let mut key = Zeroizing::new([0u8; 32]);
+	Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+		.hash_password_into(password.as_bytes(), &salt, key.as_mut())
+		.map_err(|e| format!("密钥派生失败: {}", e))?;
+	Ok(key)
+}
+
+fn seal(key: &[u8; 32], plain: &[u8]) -> Result<Vec<u8>, String> {
+	let cipher = XChaCha20Poly1305::new(key.into());
+	let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+	let sealed = cipher
+		.encrypt(&nonce, Payload { msg: plain, aad: MAGIC.as_bytes() })
+		.map_err(|_| "加密失败".to_string())?;
+	let mut out = nonce.to_vec();
+	out.extend(sealed);
+	Ok(out)
+}
+
+fn open(key: &[u8; 32], sealed: &[u8]) -> Result<Vec<u8>, String> {
+	if sealed.len() < NONCE_LEN {
+		return Err("密文已损坏".to_string());
+	}
+	let (nonce, body) = sealed.split_at(NONCE_LEN);
+	XChaCha20Poly1305::new(key.into())
+		.decrypt(XNonce::from_slice(nonce), Payload { msg: body, aad: MAGIC.as_bytes() })
+		.map_err(|_| "解密失败：密钥错误或文件已被篡改".to_string())
+}
+
+fn canonical(root: &Path) -> PathBuf {
+	fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf())
+}
+
+/// 文件所在的加密知识库根目录（向上查找 `.knowledge-vault.json`）
+fn vault_root_of(path: &Path) -> Option<PathBuf> {
+	path.ancestors()
+		.skip(1)
+		.find(|dir| dir.join(VAULT_FILE).is_file())
+		.map(canonical)
+}
+
+fn key_for(root: &Path) -> Result<Zeroizing<[u8; 32]>, String> {
+	KEYS.lock()
+		.map_err(|e| e.to_string())?
+		.get(root)
+		.cloned()
+		.ok_or_else(|| "知识库已加密，请先解锁".to_string())
+}
+
+pub(crate) fn is_encrypted_note(bytes: &[u8]) -> bool {
+	bytes.starts_with(MAGIC.as_bytes())
+}
+
+/// 目录（或其上级）是否已启用加密
+pub(crate) fn is_vault_dir(dir: &Path) -> bool {
+	dir.join(VAULT_FILE).is_file() || vault_root_of(dir).is_some()
+}
+
+fn encrypt_with(key: &[u8; 32], plain: &[u8]) -> Result<Vec<u8>, String> {
+	let body = b64().encode(seal(key, plain)?);
+	Ok(format!("{}\n{}\n", MAGIC, body).into_bytes())
+}
+
+fn decrypt_with(key: &[u8; 32], bytes: &[u8]) -> Result<Vec<u8>, String> {
+	let body = std::str::from_utf8(&bytes[MAGIC.len()..]).map_err(|_| "密文已损坏")?;
+	let sealed = b64().decode(body.trim()).map_err(|_| "密文已损坏")?;
+	open(key, &sealed)
+}
+
+/// 笔记落盘内容：位于加密知识库中时加密（未解锁则报错），否则原样
+pub(crate) fn encode_note(path: &Path, content: &str) -> Result<Vec<u8>, String> {
+	match vault_root_of(path) {
+		Some(root) => encrypt_with(&*key_for(&root)?, content.as_bytes()),
+		None => Ok(content.as_bytes().to_vec()),
+	}
+}
+
+/// 磁盘内容还原为明文字节；未加密的文件原样返回
+pub(crate) fn decode_note(path: &Path, bytes: Vec<u8>) -> Result<Vec<u8>, String> {
+	if !is_encrypted_note(&bytes) {
+		return Ok(bytes);
+	}
+	let root = vault_root_of(path).ok_or("找不到该加密笔记所属知识库的加密配置")?;
+	decrypt_with(&*key_for(&root)?, &bytes)
+}
+
+/// 读取笔记明文（UTF-8）
+pub(crate) fn read_note_text(path: &Path) -> Result<String, String> {
+	let bytes = fs::read(path).map_err(|e| e.to_string())?;
+	String::from_utf8(decode_note(path, bytes)?).map_err(|e| e.to_string())
+}
+
+/// 丢弃内存中由明文生成的列表摘要与统计缓存
+fn evict_plain_caches(root: &Path) {
+	evict_listing_caches(root);
+	evict_stats_caches(root);
+}
+
+/// 知识库内需要加 / 解密的文件：笔记、各目录 `.history` 下的快照（含已删除笔记的快照）及 `.templates` 下的模板
+fn collect_vault_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<(), String> {
+	for ent in fs::read_dir(dir).map_err(|e| e.to_string())? {
+		let ent = ent.map_err(|e| e.to_string())?;
+		let name = ent.file_name().to_string_lossy().to_string();
+		if name.starts_with('.') && name != HISTORY_DIR_NAME && name != TEMPLATES_DIR_NAME {
+			continue;
+		}
+		let p = ent.path();
+		let ft = ent.file_type().map_err(|e| e.to_string())?;
+		if ft.is_dir() {
+			collect_vault_files(&p, out)?;
+		} else if ft.is_file() && is_md_file_path(&p) {
+			out.push(p);
+		}
+	}
+	Ok(())
+}
+
+/// 对每个文件执行转换，返回实际改写的文件数；单个文件失败不影响其余文件
+fn rewrite_all(
+	files: &[PathBuf],
+	convert: impl Fn(&[u8]) -> Result<Option<Vec<u8>>, String>,
+) -> (usize, Vec<String>) {
+	let mut changed = 0;
+	let mut failed = Vec::new();
+	for p in files {
+		let outcome = fs::read(p)
+			.map_err(|e| e.to_string())
+			.and_then(|bytes| convert(&bytes))
+			.and_then(|out| match out {
+				Some(out) => write_file_atomic(p, &out).map(|_| true),
+				None => Ok(false),
+			});
+		match outcome {
+			Ok(true) => changed += 1,
+			Ok(false) => {}
+			Err(e) => failed.push(format!("{}: {}", p.display(), e)),
+		}
+	}
+	(changed, failed)
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeVaultInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeVaultPasswordInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	pub password: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeVaultStatus {
+	pub enabled: bool,
+	pub unlocked: bool,
+	pub encrypted_notes: usize,
+	/// 加密知识库中仍为明文的笔记（如外部拷入），下次保存时加密
+	pub plain_notes: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeVaultResult {
+	/// 本次加密 / 解密的文件数（含历史快照）
+	pub converted: usize,
+	pub failed: Vec<String>,
+	pub message: String,
+}
+
+#[tauri::command]
+pub async fn get_knowledge_vault_status(
+	app: AppHandle,
+	input: Option<KnowledgeVaultInput>,
+) -> Result<KnowledgeVaultStatus, String> {
+	let input = input.unwrap_or_default();
+	let root = canonical(&resolve_knowledge_root(&app, input.dir_path.as_ref()).await?);
+	let enabled = root.join(VAULT_FILE).is_file();
+	let unlocked = enabled && KEYS.lock().map_err(|e| e.to_string())?.contains_key(&root);
+	let mut notes = Vec::new();
+	collect_md_files(&root, &mut notes)?;
+	let encrypted_notes = notes
+		.iter()
+		.filter(|p| {
+			fs::File::open(p)
+				.and_then(|mut f| {
+					let mut head = [0u8; MAGIC.len()];
+					std::io::Read::read_exact(&mut f, &mut head).map(|_| is_encrypted_note(&head))
+				})
+				.unwrap_or(false)
+		})
+		.count();
+	Ok(KnowledgeVaultStatus {
+		enabled,
+		unlocked,
+		encrypted_notes,
+		plain_notes: if enabled { notes.len() - encrypted_notes } else { 0 },
+	})
+}
+
+/// 为知识库启用加密：生成配置并立即加密现有笔记与历史快照，完成后保持解锁状态
+#[tauri::command]
+pub async fn enable_knowledge_vault(
+	app: AppHandle,
+	input: KnowledgeVaultPasswordInput,
+) -> Result<KnowledgeVaultResult, String> {
+	let root = canonical(&resolve_knowledge_root(&app, input.dir_path.as_ref()).await?);
+	if is_vault_dir(&root) {
+		return Err("该知识库已启用加密".to_string());
+	}
+	if input.password.chars().count() < MIN_PASSWORD_CHARS {
+		return Err(format!("口令至少 {} 个字符", MIN_PASSWORD_CHARS));
+	}
+	let mut salt = [0u8; SALT_LEN];
+	OsRng.fill_bytes(&mut salt);
+	let mut config = VaultConfig {
+		version: 1,
+		kdf: "argon2id".to_string(),
+		salt: b64().encode(salt),
+		memory_kib: KDF_MEMORY_KIB,
+		iterations: KDF_ITERATIONS,
+		parallelism: KDF_PARALLELISM,
+		cipher: "xchacha20poly1305".to_string(),
+		verifier: String::new(),
+	};
+	let password = Zeroizing::new(input.password);
+	let params = config.clone();
+	let key = tauri::async_runtime::spawn_blocking(move || derive_key(&password, &params))
+		.await
+		.map_err(|e| e.to_string())??;
+	config.verifier = b64().encode(seal(&key, VERIFIER)?);
+	let json = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+	write_file_atomic(&root.join(VAULT_FILE), json.as_bytes())?;
+	KEYS.lock().map_err(|e| e.to_string())?.insert(root.clone(), key.clone());
+	// 分块、列表与统计缓存文件都含明文（正文片段、摘要、标签），启用后不再落盘，直接删除
+	match fs::remove_dir_all(root.join(INDEX_DIR_NAME)) {
+		Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+			return Err(format!("删除索引缓存失败: {}", e));
+		}
+		_ => {}
+	}
+	evict_plain_caches(&root);
+
+	let mut files = Vec::new();
+	collect_vault_files(&root, &mut files)?;
+	let (converted, failed) = rewrite_all(&files, |bytes| {
+		if is_encrypted_note(bytes) {
+			return Ok(None);
+		}
+		encrypt_with(&key, bytes).map(Some)
+	});
+	Ok(KnowledgeVaultResult {
+		message: format!("已启用加密，加密 {} 个文件，失败 {}", converted, failed.len()),
+		converted,
+		failed,
+	})
+}
+
+/// 校验口令并返回（规范化根目录, 密钥）
+async fn verify_password(
+	app: &AppHandle,
+	input: KnowledgeVaultPasswordInput,
+) -> Result<(PathBuf, Zeroizing<[u8; 32]>), String> {
+	let root = canonical(&resolve_knowledge_root(app, input.dir_path.as_ref()).await?);
+	if !root.join(VAULT_FILE).is_file() {
+		return Err("该知识库未启用加密".to_string());
+	}
+	let config = load_config(&root)?;
+	let password = Zeroizing::new(input.password);
+	// Argon2 派生耗时较长，放到阻塞线程执行
+	let (key, config) = tauri::async_runtime::spawn_blocking(move || {
+		derive_key(&password, &config).map(|key| (key, config))
+	})
+	.await
+	.map_err(|e| e.to_string())??;
+	let verifier = b64().decode(&config.verifier).map_err(|_| "加密配置中的校验值无效")?;
+	match open(&key, &verifier) {
+		Ok(plain) if plain == VERIFIER => Ok((root, key)),
+		_ => Err("口令错误".to_string()),
+	}
+}
+
+/// 解锁：密钥仅保存在内存中，直至锁定或应用退出
+#[tauri::command]
+pub async fn unlock_knowledge_vault(app: AppHandle, input: KnowledgeVaultPasswordInput) -> Result<(), String> {
+	let (root, key) = verify_password(&app, input).await?;
+	KEYS.lock().map_err(|e| e.to_string())?.insert(root, key);
+	Ok(())
+}
+
+/// 锁定：丢弃内存中的密钥；返回此前是否处于解锁状态
+#[tauri::command]
+pub async fn lock_knowledge_vault(app: AppHandle, input: Option<KnowledgeVaultInput>) -> Result<bool, String> {
+	let input = input.unwrap_or_default();
+	let root = canonical(&resolve_knowledge_root(&app, input.dir_path.as_ref()).await?);
+	evict_plain_caches(&root);
+	Ok(KEYS.lock().map_err(|e| e.to_string())?.remove(&root).is_some())
+}
+
+/// 关闭加密：解密全部笔记与历史快照后删除加密配置；有文件解密失败时保留配置
+#[tauri::command]
+pub async fn disable_knowledge_vault(
+	app: AppHandle,
+	input: KnowledgeVaultPasswordInput,
+) -> Result<KnowledgeVaultResult, String> {
+	let (root, key) = verify_password(&app, input).await?;
+	let mut files = Vec::new();
+	collect_vault_files(&root, &mut files)?;
+	let (converted, failed) = rewrite_all(&files, |bytes| {
+		if !is_encrypted_note(bytes) {
+			return Ok(None);
+		}
+		decrypt_with(&key, bytes).map(Some)
+	});
+	if !failed.is_empty() {
+		return Ok(KnowledgeVaultResult {
+			message: format!("已解密 {} 个文件，{} 个失败，加密仍保持启用", converted, failed.len()),
+			converted,
+			failed,
+		});
+	}
+	fs::remove_file(root.join(VAULT_FILE)).map_err(|e| e.to_string())?;
+	KEYS.lock().map_err(|e| e.to_string())?.remove(&root);
+	Ok(KnowledgeVaultResult {
+		message: format!("已关闭加密，解密 {} 个文件", converted),
+		converted,
+		failed,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// 测试用的低成本派生参数
+	fn test_config() -> VaultConfig {
+		VaultConfig {
+			version: 1,
+			kdf: "argon2id".to_string(),
+			salt: b64().encode([7u8; SALT_LEN]),
+			memory_kib: 64,
+			iterations: 1,
+			parallelism: 1,
+			cipher: "xchacha20poly1305".to_string(),
+			verifier: String::new(),
+		}
+	}
+
+	/// 创建带加密配置的临时知识库；`key` 为 Some 时视为已解锁
+	fn vault(key: Option<[u8; 32]>) -> tempfile::TempDir {
+		let dir = tempfile::tempdir().unwrap();
+		fs::write(dir.path().join(VAULT_FILE), "{}").unwrap();
+		if let Some(key) = key {
+			KEYS.lock().unwrap().insert(canonical(dir.path()), Zeroizing::new(key));
+		}
+		dir
+	}
+
+	#[test]
+	fn encode_decode_roundtrip_in_unlocked_vault() {
+		let dir = vault(Some([1u8; 32]));
+		let note = dir.path().join("sub").join("a.md");
+		let bytes = encode_note(&note, "# 标题\n秘密内容").unwrap();
+		assert!(is_encrypted_note(&bytes));
+		assert!(!String::from_utf8_lossy(&bytes).contains("秘密内容"));
+		assert_eq!(decode_note(&note, bytes.clone()).unwrap(), "# 标题\n秘密内容".as_bytes());
+		fs::create_dir_all(note.parent().unwrap()).unwrap();
+		fs::write(&note, &bytes).unwrap();
+		assert_eq!(read_note_text(&note).unwrap(), "# 标题\n秘密内容");
+	}
+
+	#[test]
+	fn plain_notes_outside_a_vault_pass_through() {
+		let dir = tempfile::tempdir().unwrap();
+		let note = dir.path().join("a.md");
+		assert_eq!(encode_note(&note, "plain").unwrap(), b"plain");
+		assert_eq!(decode_note(&note, b"plain".to_vec()).unwrap(), b"plain");
+	}
+
+	#[test]
+	fn wrong_password_fails_verification() {
+		let config = test_config();
+		let key = derive_key("correct horse battery", &config).unwrap();
+		let verifier = seal(&key, VERIFIER).unwrap();
+		assert_eq!(open(&key, &verifier).unwrap(), VERIFIER);
+		let wrong = derive_key("correct horse battery!", &config).unwrap();
+		assert!(open(&wrong, &verifier).is_err());
+	}
+
+	#[test]
+	fn tampered_ciphertext_is_rejected() {
+		let key = [2u8; 32];
+		let bytes = encrypt_with(&key, b"content").unwrap();
+		let body = std::str::from_utf8(&bytes[MAGIC.len()..]).unwrap().trim();
+		let mut sealed = b64().decode(body).unwrap();
+		let last = sealed.len() - 1;
+		sealed[last] ^= 1;
+		let tampered = format!("{}\n{}\n", MAGIC, b64().encode(&sealed));
+		assert!(decrypt_with(&key, tampered.as_bytes()).is_err());
+		assert!(decrypt_with(&key, format!("{}\nnot base64!\n", MAGIC).as_bytes()).is_err());
+		assert!(open(&key, &sealed[..NONCE_LEN - 1]).is_err());
+	}
+
+	#[test]
+	fn locked_vault_refuses_reads_and_writes() {
+		let key = [3u8; 32];
+		let dir = vault(None);
+		let note = dir.path().join("a.md");
+		let sealed = encrypt_with(&key, b"secret").unwrap();
+		assert!(encode_note(&note, "secret").unwrap_err().contains("请先解锁"));
+		assert!(decode_note(&note, sealed).unwrap_err().contains("请先解锁"));
+	}
+}
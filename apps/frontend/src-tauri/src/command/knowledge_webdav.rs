@@ -0,0 +1,764 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use chrono::Local;
+use quick_xml::Reader;
+use quick_xml::events::Event as XmlEvent;
+use reqwest::{Method, RequestBuilder, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::command::knowledge::{is_md_file_path, resolve_knowledge_root, write_file_atomic};
+use crate::command::knowledge_conflict::content_hash;
+use crate::command::knowledge_history::{HistoryRetention, load_history_retention, snapshot_before_write};
+use crate::command::knowledge_sync::{
+	conflict_copy_rel, emit_progress, load_manifest, save_manifest, try_begin_sync,
+};
+use crate::command::knowledge_trash::move_to_trash;
+use crate::utils::common::{get_store_value, http_client};
+use crate::utils::percent::{encode_segment, percent_decode};
+
+// —— WebDAV 同步：本地清单记录每个文件上次同步时的内容哈希与远程 ETag，据此做双向同步 ——
+
+/// 同步清单文件（位于知识库根目录，以 `.` 开头不参与同步与文件树）
+const MANIFEST_FILE: &str = ".knowledge-webdav.json";
+const PROGRESS_EVENT: &str = "knowledge://webdav-sync-progress";
+const MIN_INTERVAL_MINUTES: u64 = 1;
+
+static AUTO_SYNC: LazyLock<Mutex<Option<tauri::async_runtime::JoinHandle<()>>>> =
+	LazyLock::new(|| Mutex::new(None));
+
+/// store 中的 `knowledgeWebdavUrl` / `knowledgeWebdavUsername` / `knowledgeWebdavPassword`
+struct WebdavConfig {
+	base: Url,
+	username: Option<String>,
+	password: Option<String>,
+}
+
+async fn load_config(app: &AppHandle) -> Result<WebdavConfig, String> {
+	let get = |key: &'static str| async move {
+		get_store_value(app, key)
+			.await
+			.ok()
+			.map(|v| v.trim().to_string())
+			.filter(|v| !v.is_empty() && v != "null")
+	};
+	let raw = get("knowledgeWebdavUrl").await.ok_or("未配置 WebDAV 地址")?;
+	// 目录地址须以 `/` 结尾，否则 `join` 会替换最后一段
+	let raw = if raw.ends_with('/') { raw } else { format!("{}/", raw) };
+	let base = Url::parse(&raw).map_err(|e| format!("WebDAV 地址无效: {}", e))?;
+	if !matches!(base.scheme(), "http" | "https") {
+		return Err("WebDAV 地址须为 http / https".to_string());
+	}
+	Ok(WebdavConfig {
+		base,
+		username: get("knowledgeWebdavUsername").await,
+		password: get("knowledgeWebdavPassword").await,
+	})
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ManifestEntry {
+	hash: String,
+	etag: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+	/// 远程地址变更后清单作废，按首次同步处理
+	base_url: String,
+	files: BTreeMap<String, ManifestEntry>,
+	last_sync_ms: Option<i64>,
+}
+
+fn load_webdav_manifest(root: &Path, base: &Url) -> Manifest {
+	load_manifest::<Manifest>(root, MANIFEST_FILE)
+		.filter(|m| m.base_url == base.as_str())
+		.unwrap_or_else(|| Manifest {
+			base_url: base.to_string(),
+			..Default::default()
+		})
+}
+
+fn is_hidden(name: &str) -> bool {
+	name.starts_with('.')
+}
+
+/// 本地文件：相对路径（`/` 分隔）→ 内容哈希，跳过隐藏文件与目录
+fn scan_local(root: &Path, dir: &Path, out: &mut BTreeMap<String, String>) -> Result<(), String> {
+	for ent in fs::read_dir(dir).map_err(|e| e.to_string())? {
+		let ent = ent.map_err(|e| e.to_string())?;
+		if is_hidden(&ent.file_name().to_string_lossy()) {
+			continue;
+		}
+		let p = ent.path();
+		let ft = ent.file_type().map_err(|e| e.to_string())?;
+		if ft.is_dir() {
+			scan_local(root, &p, out)?;
+		} else if ft.is_file() {
+			let bytes = fs::read(&p).map_err(|e| format!("读取 {} 失败: {}", p.display(), e))?;
+			let rel = p
+				.strip_prefix(root)
+				.map_err(|e| e.to_string())?
+				.to_string_lossy()
+				.replace('\\', "/");
+			out.insert(rel, content_hash(&bytes));
+		}
+	}
+	Ok(())
+}
+
+/// 同步用相对路径：每段都是普通文件名且不以 `.` 开头，拒绝空路径、`..`、绝对路径与反斜杠
+fn is_safe_rel(rel: &str) -> bool {
+	!rel.is_empty()
+		&& !rel.contains('\\')
+		&& Path::new(rel)
+			.components()
+			.all(|c| matches!(c, Component::Normal(s) if !is_hidden(&s.to_string_lossy())))
+}
+
+/// 相对路径对应的本地文件；路径不安全或落到知识库之外时报错
+fn local_file(root: &Path, rel: &str) -> Result<PathBuf, String> {
+	let path = root.join(rel);
+	if !is_safe_rel(rel) || !path.starts_with(root) {
+		return Err(format!("非法路径：{}", rel));
+	}
+	Ok(path)
+}
+
+/// PROPFIND 返回的 href → 相对同步根目录的路径（已解码）；目录自身、根目录之外与不安全的路径返回 None
+fn remote_rel(href: &str, base_path: &str, self_path: &str) -> Option<String> {
+	let path = match Url::parse(href) {
+		Ok(u) => u.path().to_string(),
+		Err(_) => href.to_string(),
+	};
+	let path = percent_decode(&path);
+	if path.trim_end_matches('/') == self_path.trim_end_matches('/') {
+		return None;
+	}
+	let rel = path.strip_prefix(base_path)?.trim_matches('/').to_string();
+	is_safe_rel(&rel).then_some(rel)
+}
+
+struct Dav<'a> {
+	config: &'a WebdavConfig,
+}
+
+/// 远程文件的版本信息；服务器不提供 ETag 时下载后以内容哈希判断是否变化
+#[derive(Debug, Default)]
+struct RemoteFile {
+	etag: Option<String>,
+	hash: Option<String>,
+}
+
+#[derive(Debug)]
+struct RemoteEntry {
+	rel: String,
+	is_dir: bool,
+	etag: Option<String>,
+}
+
+impl Dav<'_> {
+	fn url(&self, rel: &str) -> Result<Url, String> {
+		let encoded: Vec<String> = rel.split('/').filter(|s| !s.is_empty()).map(encode_segment).collect();
+		let mut path = encoded.join("/");
+		if rel.ends_with('/') && !path.is_empty() {
+			path.push('/');
+		}
+		self.config.base.join(&path).map_err(|e| e.to_string())
+	}
+
+	fn request(&self, method: Method, url: Url) -> RequestBuilder {
+		let req = http_client().request(method, url);
+		match self.config.username.as_deref() {
+			Some(user) => req.basic_auth(user, self.config.password.as_deref()),
+			None => req,
+		}
+	}
+
+	/// PROPFIND Depth: 1，返回目录下的直接子项
+	async fn list(&self, rel_dir: &str) -> Result<Option<Vec<RemoteEntry>>, String> {
+		let url = self.url(rel_dir)?;
+		let body = r#"<?xml version="1.0" encoding="utf-8"?><d:propfind xmlns:d="DAV:"><d:prop><d:resourcetype/><d:getetag/></d:prop></d:propfind>"#;
+		let resp = self
+			.request(Method::from_bytes(b"PROPFIND").map_err(|e| e.to_string())?, url.clone())
+			.header("Depth", "1")
+			.header(reqwest::header::CONTENT_TYPE, "application/xml; charset=utf-8")
+			.body(body)
+			.send()
+			.await
+			.map_err(|e| format!("WebDAV 请求失败: {}", e))?;
+		match resp.status() {
+			StatusCode::NOT_FOUND => return Ok(None),
+			StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+				return Err("WebDAV 认证失败，请检查用户名和密码".to_string());
+			}
+			s if !s.is_success() => return Err(format!("列出远程目录失败: HTTP {}", s)),
+			_ => {}
+		}
+		let xml = resp.text().await.map_err(|e| e.to_string())?;
+		let base_path = percent_decode(self.config.base.path());
+		let self_path = percent_decode(url.path());
+		let entries = parse_multistatus(&xml)?
+			.into_iter()
+			.filter_map(|(href, is_dir, etag)| {
+				let rel = remote_rel(&href, &base_path, &self_path)?;
+				Some(RemoteEntry { rel, is_dir, etag })
+			})
+			.collect();
+		Ok(Some(entries))
+	}
+
+	/// 递归列出全部远程文件；远程根目录不存在时返回 `None`
+	async fn scan(&self) -> Result<Option<BTreeMap<String, RemoteFile>>, String> {
+		let mut files = BTreeMap::new();
+		let mut dirs = vec![String::new()];
+		while let Some(dir) = dirs.pop() {
+			let rel_dir = if dir.is_empty() { dir.clone() } else { format!("{}/", dir) };
+			let Some(entries) = self.list(&rel_dir).await? else {
+				if dir.is_empty() {
+					return Ok(None);
+				}
+				continue;
+			};
+			for e in entries {
+				if e.is_dir {
+					dirs.push(e.rel);
+				} else {
+					files.insert(e.rel, RemoteFile { etag: e.etag, hash: None });
+				}
+			}
+		}
+		Ok(Some(files))
+	}
+
+	async fn mkcol(&self, rel_dir: &str) -> Result<(), String> {
+		let rel = format!("{}/", rel_dir.trim_end_matches('/'));
+		let resp = self
+			.request(Method::from_bytes(b"MKCOL").map_err(|e| e.to_string())?, self.url(&rel)?)
+			.send()
+			.await
+			.map_err(|e| format!("创建远程目录失败: {}", e))?;
+		// 405：目录已存在
+		if resp.status().is_success() || resp.status() == StatusCode::METHOD_NOT_ALLOWED {
+			Ok(())
+		} else {
+			Err(format!("创建远程目录失败: HTTP {}", resp.status()))
+		}
+	}
+
+	/// 确保父目录存在
+	async fn ensure_parents(&self, rel: &str, created: &mut BTreeSet<String>) -> Result<(), String> {
+		let parts: Vec<&str> = rel.split('/').collect();
+		for i in 1..parts.len() {
+			let dir = parts[..i].join("/");
+			if created.insert(dir.clone()) {
+				self.mkcol(&dir).await?;
+			}
+		}
+		Ok(())
+	}
+
+	async fn get(&self, rel: &str) -> Result<(Vec<u8>, Option<String>), String> {
+		let resp = self
+			.request(Method::GET, self.url(rel)?)
+			.send()
+			.await
+			.map_err(|e| format!("下载失败: {}", e))?;
+		if !resp.status().is_success() {
+			return Err(format!("下载失败: HTTP {}", resp.status()));
+		}
+		let etag = header_etag(&resp);
+		let bytes = resp.bytes().await.map_err(|e| e.to_string())?;
+		Ok((bytes.to_vec(), etag))
+	}
+
+	async fn put(&self, rel: &str, bytes: Vec<u8>) -> Result<Option<String>, String> {
+		let resp = self
+			.request(Method::PUT, self.url(rel)?)
+			.body(bytes)
+			.send()
+			.await
+			.map_err(|e| format!("上传失败: {}", e))?;
+		if !resp.status().is_success() {
+			return Err(format!("上传失败: HTTP {}", resp.status()));
+		}
+		if let Some(etag) = header_etag(&resp) {
+			return Ok(Some(etag));
+		}
+		// 部分服务器 PUT 不返回 ETag，再查一次
+		let head = self
+			.request(Method::HEAD, self.url(rel)?)
+			.send()
+			.await
+			.map_err(|e| e.to_string())?;
+		Ok(header_etag(&head))
+	}
+
+	async fn delete(&self, rel: &str) -> Result<(), String> {
+		let resp = self
+			.request(Method::DELETE, self.url(rel)?)
+			.send()
+			.await
+			.map_err(|e| format!("删除远程文件失败: {}", e))?;
+		if resp.status().is_success() || resp.status() == StatusCode::NOT_FOUND {
+			Ok(())
+		} else {
+			Err(format!("删除远程文件失败: HTTP {}", resp.status()))
+		}
+	}
+}
+
+fn header_etag(resp: &reqwest::Response) -> Option<String> {
+	resp.headers()
+		.get(reqwest::header::ETAG)
+		.and_then(|v| v.to_str().ok())
+		.map(normalize_etag)
+}
+
+/// 去掉弱校验前缀与引号，PROPFIND 与响应头中的写法保持一致
+fn normalize_etag(raw: &str) -> String {
+	raw.trim().trim_start_matches("W/").trim_matches('"').to_string()
+}
+
+/// 解析 207 Multi-Status：返回（href, 是否目录, ETag）
+fn parse_multistatus(xml: &str) -> Result<Vec<(String, bool, Option<String>)>, String> {
+	let mut reader = Reader::from_str(xml);
+	let mut out = Vec::new();
+	let mut href = String::new();
+	let mut is_dir = false;
+	let mut etag: Option<String> = None;
+	let mut field: Option<&'static str> = None;
+	loop {
+		let event = reader.read_event().map_err(|e| format!("解析 WebDAV 响应失败: {}", e))?;
+		match event {
+			XmlEvent::Start(e) | XmlEvent::Empty(e) => match e.local_name().as_ref() {
+				b"response" => {
+					href.clear();
+					is_dir = false;
+					etag = None;
+				}
+				b"href" => field = Some("href"),
+				b"getetag" => field = Some("etag"),
+				b"collection" => is_dir = true,
+				_ => {}
+			},
+			XmlEvent::Text(t) => {
+				let text = t.unescape().map_err(|e| e.to_string())?;
+				match field {
+					Some("href") => href.push_str(text.trim()),
+					Some("etag") => etag = Some(normalize_etag(&text)),
+					_ => {}
+				}
+			}
+			XmlEvent::End(e) => match e.local_name().as_ref() {
+				b"href" | b"getetag" => field = None,
+				b"response" if !href.is_empty() => out.push((href.clone(), is_dir, etag.take())),
+				_ => {}
+			},
+			XmlEvent::Eof => break,
+			_ => {}
+		}
+	}
+	Ok(out)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeWebdavSyncError {
+	pub path: String,
+	pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeWebdavSyncResult {
+	pub uploaded: Vec<String>,
+	pub downloaded: Vec<String>,
+	pub deleted_local: Vec<String>,
+	pub deleted_remote: Vec<String>,
+	/// 生成的冲突副本（相对路径）
+	pub conflicts: Vec<String>,
+	pub errors: Vec<KnowledgeWebdavSyncError>,
+	pub message: String,
+}
+
+/// 单个文件的同步动作
+enum Action {
+	Upload,
+	Download,
+	DeleteLocal,
+	DeleteRemote,
+	/// 两侧都改过：内容一致时只刷新清单，否则远程版本另存为冲突副本、本地版本上传
+	Conflict,
+	Forget,
+}
+
+fn plan(local: Option<&String>, remote: Option<&RemoteFile>, known: Option<&ManifestEntry>) -> Option<Action> {
+	let local_changed = |hash: &String| known.is_none_or(|m| &m.hash != hash);
+	// 两侧都有 ETag 时比对 ETag，否则比对内容哈希（未能取得哈希时视为已变化）
+	let remote_changed = |r: &RemoteFile| {
+		known.is_none_or(|m| match (&m.etag, &r.etag) {
+			(Some(known_etag), Some(etag)) => known_etag != etag,
+			_ => r.hash.as_ref() != Some(&m.hash),
+		})
+	};
+	match (local, remote, known) {
+		(None, None, Some(_)) => Some(Action::Forget),
+		(None, None, None) => None,
+		(Some(_), None, None) => Some(Action::Upload),
+		(Some(h), None, Some(_)) => Some(if local_changed(h) { Action::Upload } else { Action::DeleteLocal }),
+		(None, Some(_), None) => Some(Action::Download),
+		(None, Some(e), Some(_)) => Some(if remote_changed(e) { Action::Download } else { Action::DeleteRemote }),
+		(Some(h), Some(e), _) => match (local_changed(h), remote_changed(e)) {
+			(false, false) => None,
+			(true, false) => Some(Action::Upload),
+			(false, true) => Some(Action::Download),
+			// 首次同步或两侧都有修改：内容相同则只记录，否则按冲突处理（下载后比对）
+			(true, true) => Some(Action::Conflict),
+		},
+	}
+}
+
+async fn run_sync(app: &AppHandle, root: &Path) -> Result<KnowledgeWebdavSyncResult, String> {
+	let _running = try_begin_sync()?;
+	let config = load_config(app).await?;
+	let dav = Dav { config: &config };
+	let emit = |phase: &str, processed: usize, total: usize, current: Option<&str>| {
+		emit_progress(app, PROGRESS_EVENT, phase, processed, total, current);
+	};
+
+	emit("scanning", 0, 0, None);
+	let mut manifest = load_webdav_manifest(root, &config.base);
+	let mut local = BTreeMap::new();
+	scan_local(root, root, &mut local)?;
+	let mut remote = match dav.scan().await? {
+		Some(files) => files,
+		// 清单非空说明曾经同步过，远程目录消失多半是地址或挂载出错，继续会把本地文件全部删除
+		None if !manifest.files.is_empty() => {
+			return Err(format!("远程目录不存在：{}，已中止同步", config.base));
+		}
+		None => {
+			// 首次同步：创建远程根目录
+			dav.mkcol("").await?;
+			BTreeMap::new()
+		}
+	};
+	// 服务器不提供 ETag 时无法据此判断远程是否变化，下载曾同步过的文件比对内容哈希
+	for (rel, file) in remote.iter_mut() {
+		let Some(known) = manifest.files.get(rel) else {
+			continue;
+		};
+		if known.etag.is_none() || file.etag.is_none() {
+			file.hash = dav.get(rel).await.ok().map(|(bytes, _)| content_hash(&bytes));
+		}
+	}
+
+	let paths: BTreeSet<String> = local
+		.keys()
+		.chain(remote.keys())
+		.chain(manifest.files.keys())
+		.cloned()
+		.collect();
+	let planned: Vec<(String, Action)> = paths
+		.into_iter()
+		.filter_map(|p| {
+			plan(local.get(&p), remote.get(&p), manifest.files.get(&p)).map(|a| (p, a))
+		})
+		.collect();
+
+	let retention = load_history_retention(app).await;
+	let mut result = KnowledgeWebdavSyncResult::default();
+	let mut created_dirs = BTreeSet::new();
+	let total = planned.len();
+	for (i, (rel, action)) in planned.into_iter().enumerate() {
+		emit("syncing", i, total, Some(&rel));
+		let outcome: Result<(), String> = async {
+			let local_path = local_file(root, &rel)?;
+			match action {
+				Action::Forget => {
+					manifest.files.remove(&rel);
+				}
+				Action::Upload => {
+					let bytes = fs::read(&local_path).map_err(|e| e.to_string())?;
+					let hash = content_hash(&bytes);
+					dav.ensure_parents(&rel, &mut created_dirs).await?;
+					let etag = dav.put(&rel, bytes).await?;
+					manifest.files.insert(rel.clone(), ManifestEntry { hash, etag });
+					result.uploaded.push(rel.clone());
+				}
+				Action::Download => {
+					let (bytes, etag) = dav.get(&rel).await?;
+					let etag = etag.or_else(|| remote.get(&rel).and_then(|r| r.etag.clone()));
+					write_local(&local_path, &bytes, retention)?;
+					manifest.files.insert(rel.clone(), ManifestEntry { hash: content_hash(&bytes), etag });
+					result.downloaded.push(rel.clone());
+				}
+				Action::DeleteLocal => {
+					// 附件等非笔记文件同样移入回收站，可恢复
+					move_to_trash(app, &local_path)?;
+					manifest.files.remove(&rel);
+					result.deleted_local.push(rel.clone());
+				}
+				Action::DeleteRemote => {
+					dav.delete(&rel).await?;
+					manifest.files.remove(&rel);
+					result.deleted_remote.push(rel.clone());
+				}
+				Action::Conflict => {
+					let (remote_bytes, etag) = dav.get(&rel).await?;
+					let local_bytes = fs::read(&local_path).map_err(|e| e.to_string())?;
+					let hash = content_hash(&local_bytes);
+					if content_hash(&remote_bytes) == hash {
+						let etag = etag.or_else(|| remote.get(&rel).and_then(|r| r.etag.clone()));
+						manifest.files.insert(rel.clone(), ManifestEntry { hash, etag });
+						return Ok(());
+					}
+					let copy = conflict_copy_rel(&rel, &|c| root.join(c).exists() || remote.contains_key(c));
+					write_local(&local_file(root, &copy)?, &remote_bytes, retention)?;
+					dav.ensure_parents(&copy, &mut created_dirs).await?;
+					let copy_etag = dav.put(&copy, remote_bytes.clone()).await?;
+					manifest.files.insert(
+						copy.clone(),
+						ManifestEntry {
+							hash: content_hash(&remote_bytes),
+							etag: copy_etag,
+						},
+					);
+					let etag = dav.put(&rel, local_bytes).await?;
+					manifest.files.insert(rel.clone(), ManifestEntry { hash, etag });
+					result.conflicts.push(copy);
+					result.uploaded.push(rel.clone());
+				}
+			}
+			Ok(())
+		}
+		.await;
+		if let Err(error) = outcome {
+			result.errors.push(KnowledgeWebdavSyncError { path: rel, error });
+		}
+	}
+
+	manifest.last_sync_ms = Some(Local::now().timestamp_millis());
+	save_manifest(root, MANIFEST_FILE, &manifest)?;
+	emit("done", total, total, None);
+	result.message = format!(
+		"上传 {}，下载 {}，本地删除 {}，远程删除 {}，冲突 {}，失败 {}",
+		result.uploaded.len(),
+		result.downloaded.len(),
+		result.deleted_local.len(),
+		result.deleted_remote.len(),
+		result.conflicts.len(),
+		result.errors.len()
+	);
+	Ok(result)
+}
+
+fn write_local(path: &Path, bytes: &[u8], retention: HistoryRetention) -> Result<(), String> {
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+	}
+	// 被远程覆盖的笔记先留历史快照
+	if is_md_file_path(path)
+		&& let Ok(text) = std::str::from_utf8(bytes)
+	{
+		snapshot_before_write(path, text, retention)?;
+	}
+	write_file_atomic(path, bytes)
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeWebdavSyncInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+}
+
+/// 立即执行一次 WebDAV 双向同步；进度通过 `knowledge://webdav-sync-progress` 事件推送
+#[tauri::command]
+pub async fn sync_knowledge_webdav(
+	app: AppHandle,
+	input: Option<KnowledgeWebdavSyncInput>,
+) -> Result<KnowledgeWebdavSyncResult, String> {
+	let input = input.unwrap_or_default();
+	let root = resolve_knowledge_root(&app, input.dir_path.as_ref()).await?;
+	run_sync(&app, &root).await
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartKnowledgeWebdavAutoSyncInput {
+	#[serde(default)]
+	pub dir_path: Option<String>,
+	/// 同步间隔（分钟）
+	pub interval_minutes: u64,
+}
+
+/// 开启定时同步（替换已有定时器）；每轮结果以 `knowledge://webdav-sync-result` 事件推送
+#[tauri::command]
+pub async fn start_knowledge_webdav_auto_sync(
+	app: AppHandle,
+	input: StartKnowledgeWebdavAutoSyncInput,
+) -> Result<(), String> {
+	load_config(&app).await?;
+	let interval = Duration::from_secs(input.interval_minutes.max(MIN_INTERVAL_MINUTES) * 60);
+	let dir_path = input.dir_path.clone();
+	let handle = tauri::async_runtime::spawn(async move {
+		loop {
+			tokio::time::sleep(interval).await;
+			let outcome = match resolve_knowledge_root(&app, dir_path.as_ref()).await {
+				Ok(root) => run_sync(&app, &root).await,
+				Err(e) => Err(e),
+			};
+			let payload: HashMap<&str, serde_json::Value> = match outcome {
+				Ok(r) => HashMap::from([("result", serde_json::to_value(r).unwrap_or_default())]),
+				Err(e) => HashMap::from([("error", serde_json::Value::String(e))]),
+			};
+			let _ = app.emit("knowledge://webdav-sync-result", payload);
+		}
+	});
+	let mut slot = AUTO_SYNC.lock().map_err(|e| e.to_string())?;
+	if let Some(old) = slot.replace(handle) {
+		old.abort();
+	}
+	Ok(())
+}
+
+/// 关闭定时同步
+#[tauri::command]
+pub fn stop_knowledge_webdav_auto_sync() -> Result<bool, String> {
+	let mut slot = AUTO_SYNC.lock().map_err(|e| e.to_string())?;
+	Ok(match slot.take() {
+		Some(handle) => {
+			handle.abort();
+			true
+		}
+		None => false,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn known(hash: &str, etag: Option<&str>) -> ManifestEntry {
+		ManifestEntry {
+			hash: hash.to_string(),
+			etag: etag.map(str::to_string),
+		}
+	}
+
+	fn remote(etag: Option<&str>, hash: Option<&str>) -> RemoteFile {
+		RemoteFile {
+			etag: etag.map(str::to_string),
+			hash: hash.map(str::to_string),
+		}
+	}
+
+	fn h(s: &str) -> String {
+		s.to_string()
+	}
+
+	#[test]
+	fn plan_new_files_are_copied_to_the_other_side() {
+		assert!(matches!(plan(Some(&h("a")), None, None), Some(Action::Upload)));
+		assert!(matches!(plan(None, Some(&remote(Some("e1"), None)), None), Some(Action::Download)));
+		assert!(plan(None, None, None).is_none());
+		assert!(matches!(plan(None, None, Some(&known("a", Some("e1")))), Some(Action::Forget)));
+	}
+
+	#[test]
+	fn plan_with_etags() {
+		let m = known("a", Some("e1"));
+		assert!(plan(Some(&h("a")), Some(&remote(Some("e1"), None)), Some(&m)).is_none());
+		assert!(matches!(plan(Some(&h("b")), Some(&remote(Some("e1"), None)), Some(&m)), Some(Action::Upload)));
+		assert!(matches!(plan(Some(&h("a")), Some(&remote(Some("e2"), None)), Some(&m)), Some(Action::Download)));
+		assert!(matches!(plan(Some(&h("b")), Some(&remote(Some("e2"), None)), Some(&m)), Some(Action::Conflict)));
+		// 一侧删除、另一侧未改：同步删除；另一侧改过：保留改动
+		assert!(matches!(plan(Some(&h("a")), None, Some(&m)), Some(Action::DeleteLocal)));
+		assert!(matches!(plan(Some(&h("b")), None, Some(&m)), Some(Action::Upload)));
+		assert!(matches!(plan(None, Some(&remote(Some("e1"), None)), Some(&m)), Some(Action::DeleteRemote)));
+		assert!(matches!(plan(None, Some(&remote(Some("e2"), None)), Some(&m)), Some(Action::Download)));
+	}
+
+	#[test]
+	fn plan_without_etags_compares_content_hash() {
+		let m = known("a", None);
+		// 服务器不提供 ETag：内容未变时不应反复下载
+		assert!(plan(Some(&h("a")), Some(&remote(None, Some("a"))), Some(&m)).is_none());
+		assert!(matches!(plan(Some(&h("b")), Some(&remote(None, Some("a"))), Some(&m)), Some(Action::Upload)));
+		assert!(matches!(plan(Some(&h("a")), Some(&remote(None, Some("c"))), Some(&m)), Some(Action::Download)));
+		assert!(matches!(plan(None, Some(&remote(None, Some("a"))), Some(&m)), Some(Action::DeleteRemote)));
+		// 清单有 ETag 而本次列表没有时同样按哈希比对
+		let m = known("a", Some("e1"));
+		assert!(plan(Some(&h("a")), Some(&remote(None, Some("a"))), Some(&m)).is_none());
+		// 未能取得远程哈希时视为已变化，不会删除远程文件
+		assert!(matches!(plan(None, Some(&remote(None, None)), Some(&m)), Some(Action::Download)));
+	}
+
+	#[test]
+	fn parse_multistatus_reads_href_collection_and_etag() {
+		let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:multistatus xmlns:d="DAV:">
+  <d:response>
+    <d:href>/dav/notes/</d:href>
+    <d:propstat><d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop></d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/dav/notes/%E7%AC%94%E8%AE%B0.md</d:href>
+    <d:propstat><d:prop><d:resourcetype/><d:getetag>W/"abc&amp;1"</d:getetag></d:prop></d:propstat>
+  </d:response>
+  <D:response xmlns:D="DAV:">
+    <D:href>http://host/dav/notes/sub/</D:href>
+    <D:propstat><D:prop><D:resourcetype><D:collection/></D:resourcetype></D:prop></D:propstat>
+  </D:response>
+</d:multistatus>"#;
+		let entries = parse_multistatus(xml).unwrap();
+		assert_eq!(
+			entries,
+			vec![
+				("/dav/notes/".to_string(), true, None),
+				("/dav/notes/%E7%AC%94%E8%AE%B0.md".to_string(), false, Some("abc&1".to_string())),
+				("http://host/dav/notes/sub/".to_string(), true, None),
+			]
+		);
+	}
+
+	#[test]
+	fn parse_multistatus_rejects_malformed_xml() {
+		assert!(parse_multistatus("<d:multistatus><d:response></d:multistatus>").is_err());
+	}
+
+	#[test]
+	fn listing_drops_hrefs_that_escape_the_sync_root() {
+		let xml = r#"<d:multistatus xmlns:d="DAV:">
+<d:response><d:href>/dav/kb/</d:href><d:propstat><d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop></d:propstat></d:response>
+<d:response><d:href>/dav/kb/ok%20note.md</d:href><d:propstat><d:prop><d:resourcetype/></d:prop></d:propstat></d:response>
+<d:response><d:href>/dav/kb/..%2F..%2Fetc%2Fpasswd</d:href><d:propstat><d:prop><d:resourcetype/></d:prop></d:propstat></d:response>
+<d:response><d:href>/dav/kb/../outside.md</d:href><d:propstat><d:prop><d:resourcetype/></d:prop></d:propstat></d:response>
+<d:response><d:href>/dav/kb/a%2F..%2F..%2Fx.md</d:href><d:propstat><d:prop><d:resourcetype/></d:prop></d:propstat></d:response>
+<d:response><d:href>/dav/kb/.history</d:href><d:propstat><d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop></d:propstat></d:response>
+<d:response><d:href>http://host/dav/kb/sub/../../x.md</d:href><d:propstat><d:prop><d:resourcetype/></d:prop></d:propstat></d:response>
+</d:multistatus>"#;
+		let rels: Vec<String> = parse_multistatus(xml)
+			.unwrap()
+			.into_iter()
+			.filter_map(|(href, _, _)| remote_rel(&href, "/dav/kb/", "/dav/kb/"))
+			.collect();
+		assert_eq!(rels, vec!["ok note.md".to_string()]);
+	}
+
+	#[test]
+	fn local_file_stays_under_the_root() {
+		let root = Path::new("/kb");
+		assert_eq!(local_file(root, "a/b.md").unwrap(), Path::new("/kb/a/b.md"));
+		for rel in ["../x.md", "a/../../x.md", "/etc/passwd", "a\\..\\x.md", ".history/x", ""] {
+			assert!(local_file(root, rel).is_err(), "{}", rel);
+		}
+	}
+}
@@ -4,3 +4,30 @@ pub mod common;
 pub mod download;
 pub mod ebook;
 pub mod knowledge;
+pub mod knowledge_assets;
+pub mod knowledge_backend_sync;
+pub mod knowledge_chunk;
+pub mod knowledge_clip;
+pub mod knowledge_conflict;
+pub mod knowledge_dedup;
+pub mod knowledge_editor;
+pub mod knowledge_export;
+pub mod knowledge_export_docx;
+pub mod knowledge_folder;
+pub mod knowledge_format;
+pub mod knowledge_git;
+pub mod knowledge_history;
+pub mod knowledge_import;
+pub mod knowledge_import_docx;
+pub mod knowledge_link;
+pub mod knowledge_lint;
+pub mod knowledge_listing;
+pub mod knowledge_mcp;
+pub mod knowledge_retrieve;
+pub mod knowledge_site;
+pub mod knowledge_stats;
+pub mod knowledge_sync;
+pub mod knowledge_template;
+pub mod knowledge_trash;
+pub mod knowledge_vault;
+pub mod knowledge_webdav;
@@ -34,9 +34,67 @@ use command::download::{
 };
 use command::ebook::{pick_ebook_file, read_ebook_file};
 use command::knowledge::{
-    delete_knowledge_markdown, list_knowledge_markdown_files, open_knowledge_markdown_in_editor,
-    read_knowledge_markdown_file, resolve_knowledge_markdown_target,
-    select_knowledge_import_md_file, save_knowledge_markdown,
+    delete_knowledge_markdown, list_knowledge_markdown_files, read_knowledge_markdown_file,
+    resolve_knowledge_markdown_target, select_knowledge_import_md_file, save_knowledge_markdown,
+};
+use command::knowledge_assets::{
+    gc_knowledge_assets, list_unreferenced_knowledge_assets, localize_knowledge_remote_images,
+    save_knowledge_asset,
+};
+use command::knowledge_backend_sync::sync_knowledge_backend;
+use command::knowledge_chunk::{list_knowledge_chunks, refresh_knowledge_chunks};
+use command::knowledge_clip::clip_url_to_knowledge;
+use command::knowledge_dedup::{find_knowledge_duplicates, merge_knowledge_duplicates};
+use command::knowledge_editor::{list_knowledge_editors, open_knowledge_markdown_in_editor};
+use command::knowledge_export::export_knowledge_markdown;
+use command::knowledge_folder::{
+    create_knowledge_folder, delete_knowledge_folder, get_knowledge_folder_tree,
+    move_knowledge_folder, move_knowledge_markdown_to_folder, rename_knowledge_folder,
+    set_knowledge_item_order, set_knowledge_item_pinned,
+};
+use command::knowledge_format::format_knowledge_markdown;
+use command::knowledge_git::{
+    commit_knowledge_git, get_knowledge_git_log, get_knowledge_git_status, pull_knowledge_git,
+    push_knowledge_git,
+};
+use command::knowledge_history::{
+    diff_knowledge_markdown_versions, list_knowledge_markdown_versions,
+    restore_knowledge_markdown_version,
+};
+use command::knowledge_import::{
+    cancel_knowledge_import, get_knowledge_import_job, import_knowledge_document,
+    list_knowledge_import_jobs, select_knowledge_import_document_file, start_knowledge_import,
+};
+use command::knowledge_link::{
+    get_knowledge_backlinks, get_knowledge_link_graph, list_knowledge_orphan_notes,
+    list_knowledge_tags,
+};
+use command::knowledge_lint::lint_knowledge_notes;
+use command::knowledge_listing::list_knowledge_markdown_page;
+use command::knowledge_mcp::{
+    get_knowledge_mcp_server_status, respond_knowledge_mcp_permission,
+    set_knowledge_mcp_allow_list, start_knowledge_mcp_server, stop_knowledge_mcp_server,
+};
+use command::knowledge_retrieve::retrieve_knowledge_context;
+use command::knowledge_site::{
+    get_knowledge_site_preview_status, publish_knowledge_site, start_knowledge_site_preview,
+    stop_knowledge_site_preview,
+};
+use command::knowledge_stats::knowledge_stats;
+use command::knowledge_template::{
+    create_note_from_template, delete_knowledge_template, list_knowledge_templates,
+    open_daily_note, save_knowledge_template,
+};
+use command::knowledge_trash::{
+    empty_expired_knowledge_trash, empty_expired_trash, list_knowledge_trash,
+    purge_knowledge_trash, restore_knowledge_trash_item,
+};
+use command::knowledge_vault::{
+    disable_knowledge_vault, enable_knowledge_vault, get_knowledge_vault_status,
+    lock_knowledge_vault, unlock_knowledge_vault,
+};
+use command::knowledge_webdav::{
+    start_knowledge_webdav_auto_sync, stop_knowledge_webdav_auto_sync, sync_knowledge_webdav,
 };
 
 /// 移动端入口属性宏：当编译目标为移动平台时，自动标记该函数为 Tauri 移动端入口
@@ -45,6 +103,10 @@ use command::knowledge::{
 // 应用程序主入口函数
 // 负责初始化并启动 Tauri 应用
 pub fn run() {
+    // `--mcp-stdio`：由 Cursor / Trae 拉起为 MCP 服务，不创建窗口
+    if command::knowledge_mcp::stdio_requested() {
+        std::process::exit(command::knowledge_mcp::run_stdio());
+    }
     // 使用默认配置创建 Tauri 应用构建器
     tauri::Builder::default()
         // .plugin(tauri_plugin_autostart::Builder::new().build())
@@ -64,6 +126,11 @@ pub fn run() {
             setup_window_events(main_window.clone(), app.handle().clone());
             #[cfg(target_os = "macos")]
             system::zoom::install(&main_window);
+            // 启动时按保留天数自动清空知识库回收站
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = empty_expired_trash(&handle, None).await;
+            });
             Ok(())
         })
         .init_plugin()
@@ -83,7 +150,76 @@ pub fn run() {
             list_knowledge_markdown_files, // 列出目录下所有 Markdown
             read_knowledge_markdown_file, // 读取单个 Markdown 文件
             select_knowledge_import_md_file, // 知识库导入：仅 .md 文件选择
-            open_knowledge_markdown_in_editor, // 本地 .md 在 Cursor / Trae 中打开
+            open_knowledge_markdown_in_editor, // 本地 .md 在外部编辑器中打开（可定位到行）
+            list_knowledge_editors, // 外部编辑器列表与检测顺序
+            get_knowledge_backlinks, // 知识库：某篇笔记的反向链接
+            list_knowledge_tags,   // 知识库：标签及笔记数
+            list_knowledge_orphan_notes, // 知识库：孤立笔记
+            get_knowledge_link_graph, // 知识库：链接关系图
+            list_knowledge_markdown_versions, // 知识库：笔记历史版本列表
+            diff_knowledge_markdown_versions, // 知识库：两个版本的行级差异
+            restore_knowledge_markdown_version, // 知识库：恢复到指定版本
+            list_knowledge_trash,  // 知识库回收站：列表
+            restore_knowledge_trash_item, // 知识库回收站：恢复（支持冲突处理）
+            purge_knowledge_trash, // 知识库回收站：彻底删除
+            empty_expired_knowledge_trash, // 知识库回收站：清理过期条目
+            get_knowledge_folder_tree, // 知识库文件夹：树形结构及笔记数
+            create_knowledge_folder, // 知识库文件夹：新建
+            rename_knowledge_folder, // 知识库文件夹：重命名
+            move_knowledge_folder, // 知识库文件夹：移动
+            delete_knowledge_folder, // 知识库文件夹：删除（笔记移入回收站）
+            move_knowledge_markdown_to_folder, // 知识库：笔记移动到文件夹
+            set_knowledge_item_order, // 知识库：手动排序
+            set_knowledge_item_pinned, // 知识库：置顶 / 取消置顶
+            save_knowledge_asset,  // 知识库附件：粘贴 / 拖入图片写入 assets
+            list_unreferenced_knowledge_assets, // 知识库附件：未引用列表
+            gc_knowledge_assets,   // 知识库附件：清理未引用
+            localize_knowledge_remote_images, // 知识库附件：远程图片下载到本地并改写链接
+            export_knowledge_markdown, // 知识导出：HTML / PDF / DOCX
+            start_knowledge_import, // 知识导入：Obsidian / Notion / Evernote 后台任务
+            get_knowledge_import_job, // 知识导入：任务进度与报告
+            list_knowledge_import_jobs, // 知识导入：任务列表
+            cancel_knowledge_import, // 知识导入：取消任务
+            select_knowledge_import_document_file, // 知识导入：选择 Word / HTML 文档
+            import_knowledge_document, // 知识导入：Word / HTML 转 Markdown 笔记
+            clip_url_to_knowledge, // 网页剪藏：提取正文保存为笔记
+            get_knowledge_git_status, // 知识库 Git：状态
+            get_knowledge_git_log, // 知识库 Git：提交历史（可按笔记）
+            commit_knowledge_git,  // 知识库 Git：手动提交
+            pull_knowledge_git,    // 知识库 Git：拉取并合并（冲突结构化返回）
+            push_knowledge_git,    // 知识库 Git：推送
+            sync_knowledge_webdav, // 知识库 WebDAV：立即双向同步
+            start_knowledge_webdav_auto_sync, // 知识库 WebDAV：开启定时同步
+            stop_knowledge_webdav_auto_sync, // 知识库 WebDAV：关闭定时同步
+            sync_knowledge_backend, // 知识库：与服务端双向同步
+            get_knowledge_vault_status, // 知识库加密：状态
+            enable_knowledge_vault, // 知识库加密：启用并加密现有笔记
+            unlock_knowledge_vault, // 知识库加密：解锁（密钥仅存内存）
+            lock_knowledge_vault,   // 知识库加密：锁定
+            disable_knowledge_vault, // 知识库加密：解密全部笔记并关闭
+            refresh_knowledge_chunks, // 知识库 RAG：增量刷新分块缓存
+            list_knowledge_chunks,  // 知识库 RAG：列出分块
+            retrieve_knowledge_context, // 知识库 RAG：BM25 检索对话上下文
+            start_knowledge_mcp_server, // 知识库 MCP：启动本地 HTTP/SSE 服务
+            stop_knowledge_mcp_server, // 知识库 MCP：停止服务
+            get_knowledge_mcp_server_status, // 知识库 MCP：状态与编辑器配置
+            respond_knowledge_mcp_permission, // 知识库 MCP：回应写入授权弹窗
+            set_knowledge_mcp_allow_list, // 知识库 MCP：设置写入白名单
+            list_knowledge_templates, // 笔记模板：列表
+            save_knowledge_template, // 笔记模板：新建 / 覆盖
+            delete_knowledge_template, // 笔记模板：删除
+            create_note_from_template, // 由模板新建笔记
+            open_daily_note,       // 打开 / 创建日记
+            lint_knowledge_notes,  // 知识库体检：失效链接 / 锚点、缺失附件、重复标题等
+            format_knowledge_markdown, // Markdown 格式化（支持 dry-run 差异预览）
+            find_knowledge_duplicates, // 重复笔记：完全 / 近似重复分组
+            merge_knowledge_duplicates, // 重复笔记：保留一篇，其余移入回收站
+            knowledge_stats,       // 知识库统计：字数、标签、每日新建 / 更新等（增量缓存）
+            publish_knowledge_site, // 知识库文件夹发布为静态网站（可打包 zip）
+            start_knowledge_site_preview, // 启动静态站点本机预览服务
+            stop_knowledge_site_preview, // 停止静态站点预览服务
+            get_knowledge_site_preview_status, // 静态站点预览服务状态
+            list_knowledge_markdown_page, // 分页列出笔记：排序、文件夹 / glob 过滤、摘要（元数据缓存）
             download_file,         // 通用下载
             download_files,        // 批量下载
             get_file_info,         // 获取文件信息
@@ -1,6 +1,7 @@
 use reqwest;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::OnceLock;
 use tauri;
 use tauri::Manager;
 use tauri_plugin_store::StoreBuilder;
@@ -22,6 +23,20 @@ pub fn set_screen_center<R: tauri::Runtime>(window: &tauri::WebviewWindow<R>) {
     }
 }
 
+/// 请求时统一携带的 User-Agent（部分图床会拒绝无 UA 的请求）
+pub const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36";
+
+/// 进程内共享的 HTTP 客户端，复用连接池；批量下载（如笔记图片本地化）时避免每次新建
+pub fn http_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .user_agent(DEFAULT_USER_AGENT)
+            .build()
+            .unwrap_or_default()
+    })
+}
+
 // 辅助函数：根据 Content-Type 获取文件扩展名
 pub fn get_extension_from_content_type(content_type: &str) -> String {
     match content_type {
@@ -138,11 +153,11 @@ pub async fn determine_save_path_for_blob(
     Ok(save_path)
 }
 
-/// 从 store 中获取指定 key 的值
-pub async fn get_store_value<R: tauri::Runtime>(
+/// 从 store 中获取指定 key 的原始 JSON 值（数组 / 对象 / 含引号的字符串等需保留结构时使用）
+pub async fn get_store_json<R: tauri::Runtime>(
 	app_handle: &tauri::AppHandle<R>,
 	key: &str,
-) -> Result<String, String> {
+) -> Result<serde_json::Value, String> {
 	// 使用与前端的 @tauri-apps/plugin-store 相同的存储路径
 	let app_data_dir = app_handle
 		.path()
@@ -154,15 +169,18 @@ pub async fn get_store_value<R: tauri::Runtime>(
 		.build()
 		.map_err(|e| format!("创建存储失败: {}", e))?;
 
-	match store.get(key) {
-		Some(value) => {
-			let value_str = value.to_string();
-			// 去除可能存在的引号
-			let cleaned_value = value_str.trim_matches('"');
-			Ok(cleaned_value.to_string())
-		}
-		None => Err(format!("未找到 key: {}", key)),
-	}
+	store.get(key).ok_or_else(|| format!("未找到 key: {}", key))
+}
+
+/// 从 store 中获取指定 key 的值
+pub async fn get_store_value<R: tauri::Runtime>(
+	app_handle: &tauri::AppHandle<R>,
+	key: &str,
+) -> Result<String, String> {
+	let value_str = get_store_json(app_handle, key).await?.to_string();
+	// 去除可能存在的引号
+	let cleaned_value = value_str.trim_matches('"');
+	Ok(cleaned_value.to_string())
 }
 
 /// 未配置 savePath 时的兜底：系统下载目录下 `dnhyxc-download`，否则 `~/Documents/dnhyxc-download`
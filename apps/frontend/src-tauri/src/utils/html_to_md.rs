@@ -0,0 +1,400 @@
+use ego_tree::NodeRef;
+use reqwest::Url;
+use scraper::{ElementRef, Html, Node};
+
+// —— HTML → Markdown：供知识库导入（Evernote / HTML / 网页剪藏）复用 ——
+
+/// 图片地址改写回调：入参为原始 `src`（Evernote 资源为 `en-media:<md5>`），返回 None 时保留原地址
+pub type ImageRewrite<'a> = dyn FnMut(&str) -> Option<String> + 'a;
+
+struct Converter<'a, 'b> {
+	rewrite: &'a mut ImageRewrite<'b>,
+	/// 页面地址：相对链接 / 图片地址据此补全为绝对地址
+	base: Option<&'a Url>,
+}
+
+fn collapse_whitespace(s: &str) -> String {
+	let mut out = String::with_capacity(s.len());
+	let mut last_space = false;
+	for c in s.chars() {
+		if c.is_whitespace() {
+			if !last_space {
+				out.push(' ');
+			}
+			last_space = true;
+		} else {
+			out.push(c);
+			last_space = false;
+		}
+	}
+	out
+}
+
+fn escape_text(s: &str) -> String {
+	let mut out = String::with_capacity(s.len());
+	for c in s.chars() {
+		if matches!(c, '\\' | '*' | '_' | '`') {
+			out.push('\\');
+		}
+		out.push(c);
+	}
+	out
+}
+
+/// 逐行加前缀；首行可用不同前缀（列表符号）
+fn prefix_lines(text: &str, first: &str, rest: &str) -> String {
+	let mut out = String::new();
+	for (i, line) in text.lines().enumerate() {
+		let prefix = if i == 0 { first } else { rest };
+		if line.is_empty() {
+			out.push_str(prefix.trim_end());
+		} else {
+			out.push_str(prefix);
+			out.push_str(line);
+		}
+		out.push('\n');
+	}
+	out
+}
+
+/// 折叠多余空行并去掉首尾空白
+fn tidy(md: &str) -> String {
+	let mut out = String::with_capacity(md.len());
+	let mut blank = 0usize;
+	for line in md.lines() {
+		let is_blank = line.trim().is_empty();
+		if is_blank {
+			blank += 1;
+			if blank > 1 {
+				continue;
+			}
+			out.push('\n');
+		} else {
+			blank = 0;
+			out.push_str(line);
+			out.push('\n');
+		}
+	}
+	out.trim().to_string()
+}
+
+fn wrap_inline(inner: &str, mark: &str) -> String {
+	let t = inner.trim();
+	if t.is_empty() {
+		return inner.to_string();
+	}
+	// 标记须紧贴文字，前后空格留在外侧
+	let lead = if inner.starts_with(' ') { " " } else { "" };
+	let trail = if inner.ends_with(' ') { " " } else { "" };
+	format!("{}{}{}{}{}", lead, mark, t, mark, trail)
+}
+
+fn code_lang(el: &ElementRef) -> Option<String> {
+	let from = |e: &ElementRef| {
+		e.value().classes().find_map(|c| {
+			c.strip_prefix("language-")
+				.or_else(|| c.strip_prefix("lang-"))
+				.map(str::to_string)
+		})
+	};
+	from(el).or_else(|| {
+		el.children()
+			.filter_map(ElementRef::wrap)
+			.find(|c| c.value().name() == "code")
+			.and_then(|c| from(&c))
+	})
+}
+
+impl Converter<'_, '_> {
+	fn children(&mut self, node: NodeRef<Node>) -> String {
+		let mut out = String::new();
+		for c in node.children() {
+			let part = self.node(c);
+			// 行首不保留 HTML 排版产生的空格，否则会被当作缩进
+			if out.is_empty() || out.ends_with('\n') {
+				out.push_str(part.trim_start_matches(' '));
+			} else {
+				out.push_str(&part);
+			}
+		}
+		out
+	}
+
+	fn absolute(&self, href: &str) -> String {
+		match self.base {
+			Some(base) if !href.starts_with('#') => base
+				.join(href)
+				.map(|u| u.to_string())
+				.unwrap_or_else(|_| href.to_string()),
+			_ => href.to_string(),
+		}
+	}
+
+	fn image(&mut self, src: &str, alt: &str) -> String {
+		if src.trim().is_empty() {
+			return String::new();
+		}
+		let src = if src.starts_with("en-media:") || src.starts_with("data:") {
+			src.to_string()
+		} else {
+			self.absolute(src.trim())
+		};
+		let src = (self.rewrite)(&src).unwrap_or(src);
+		let src = if src.contains(' ') || src.contains(')') {
+			format!("<{}>", src)
+		} else {
+			src
+		};
+		format!("![{}]({})", alt.replace(['[', ']'], ""), src)
+	}
+
+	fn list(&mut self, el: ElementRef, ordered: bool) -> String {
+		let start = el
+			.value()
+			.attr("start")
+			.and_then(|s| s.parse::<u64>().ok())
+			.unwrap_or(1);
+		let mut out = String::from("\n\n");
+		let mut n = start;
+		for li in el.children().filter_map(ElementRef::wrap) {
+			if li.value().name() != "li" {
+				continue;
+			}
+			let marker = if ordered {
+				n += 1;
+				format!("{}. ", n - 1)
+			} else {
+				"- ".to_string()
+			};
+			let mut body = tidy(&self.children(*li));
+			// 不含段落等块级内容的列表项按紧凑列表输出（去掉与子列表之间的空行）
+			let loose = li
+				.children()
+				.filter_map(ElementRef::wrap)
+				.any(|c| matches!(c.value().name(), "p" | "pre" | "blockquote" | "table"));
+			if !loose {
+				body = body
+					.lines()
+					.filter(|l| !l.trim().is_empty())
+					.collect::<Vec<_>>()
+					.join("\n");
+			}
+			let pad = " ".repeat(marker.len());
+			out.push_str(&prefix_lines(if body.is_empty() { " " } else { &body }, &marker, &pad));
+		}
+		out.push('\n');
+		out
+	}
+
+	fn table(&mut self, el: ElementRef) -> String {
+		let mut rows: Vec<Vec<String>> = Vec::new();
+		// 只取本表格直属（及 thead / tbody / tfoot 下）的行，嵌套表格按单元格文本处理
+		let mut sections = vec![*el];
+		sections.extend(
+			el.children()
+				.filter(|c| {
+					ElementRef::wrap(*c)
+						.is_some_and(|e| matches!(e.value().name(), "thead" | "tbody" | "tfoot"))
+				}),
+		);
+		for section in sections {
+			for tr in section.children().filter_map(ElementRef::wrap) {
+				if tr.value().name() != "tr" {
+					continue;
+				}
+				let cells = tr
+					.children()
+					.filter_map(ElementRef::wrap)
+					.filter(|c| matches!(c.value().name(), "th" | "td"))
+					.map(|c| {
+						tidy(&self.children(*c))
+							.replace('|', "\\|")
+							.lines()
+							.map(str::trim)
+							.filter(|l| !l.is_empty())
+							.collect::<Vec<_>>()
+							.join("<br>")
+					})
+					.collect::<Vec<_>>();
+				if !cells.is_empty() {
+					rows.push(cells);
+				}
+			}
+		}
+		if rows.is_empty() {
+			return String::new();
+		}
+		let cols = rows.iter().map(Vec::len).max().unwrap_or(1);
+		let mut out = String::from("\n\n");
+		for (i, row) in rows.iter().enumerate() {
+			let mut cells = row.clone();
+			cells.resize(cols, String::new());
+			out.push_str(&format!("| {} |\n", cells.join(" | ")));
+			if i == 0 {
+				out.push_str(&format!("|{}\n", " --- |".repeat(cols)));
+			}
+		}
+		out.push('\n');
+		out
+	}
+
+	fn node(&mut self, node: NodeRef<Node>) -> String {
+		match node.value() {
+			Node::Text(t) => escape_text(&collapse_whitespace(t)),
+			Node::Element(_) => {
+				let Some(el) = ElementRef::wrap(node) else {
+					return String::new();
+				};
+				self.element(el)
+			}
+			_ => String::new(),
+		}
+	}
+
+	fn element(&mut self, el: ElementRef) -> String {
+		let name = el.value().name();
+		match name {
+			"script" | "style" | "head" | "title" | "noscript" | "template" | "svg" | "button"
+			| "form" | "nav" => String::new(),
+			"h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+				let level = name[1..].parse::<usize>().unwrap_or(1);
+				let text = tidy(&self.children(*el)).replace('\n', " ");
+				if text.is_empty() {
+					String::new()
+				} else {
+					format!("\n\n{} {}\n\n", "#".repeat(level), text)
+				}
+			}
+			"p" | "section" | "article" | "header" | "footer" | "main" | "figure" | "aside"
+			| "address" | "details" | "dl" => format!("\n\n{}\n\n", self.children(*el)),
+			"div" | "en-note" | "body" | "html" | "figcaption" | "summary" | "dt" | "dd" => {
+				format!("\n{}\n", self.children(*el))
+			}
+			"br" => "  \n".to_string(),
+			"hr" => "\n\n---\n\n".to_string(),
+			"strong" | "b" => wrap_inline(&self.children(*el), "**"),
+			"em" | "i" | "cite" => wrap_inline(&self.children(*el), "*"),
+			"del" | "s" | "strike" => wrap_inline(&self.children(*el), "~~"),
+			"code" | "kbd" | "samp" => {
+				let text: String = el.text().collect();
+				if text.is_empty() {
+					return String::new();
+				}
+				if text.contains('`') {
+					format!("`` {} ``", text)
+				} else {
+					format!("`{}`", text)
+				}
+			}
+			"pre" => {
+				let text: String = el.text().collect();
+				let fence = if text.contains("```") { "~~~" } else { "```" };
+				format!(
+					"\n\n{}{}\n{}\n{}\n\n",
+					fence,
+					code_lang(&el).unwrap_or_default(),
+					text.trim_end_matches('\n'),
+					fence
+				)
+			}
+			"a" => {
+				let inner = self.children(*el);
+				let href = el.value().attr("href").unwrap_or_default().trim();
+				let text = inner.trim();
+				if href.is_empty() || href.starts_with("javascript:") || href.starts_with('#') {
+					return inner;
+				}
+				if text.is_empty() {
+					return String::new();
+				}
+				let href = self.absolute(href);
+				let href = if href.contains(' ') || href.contains(')') {
+					format!("<{}>", href)
+				} else {
+					href
+				};
+				format!("[{}]({})", text, href)
+			}
+			"img" => {
+				// 懒加载图片的 `src` 多为占位图，真实地址在 `data-src` / `data-original`
+				let src = el
+					.value()
+					.attr("data-src")
+					.or_else(|| el.value().attr("data-original"))
+					.or_else(|| el.value().attr("src"))
+					.unwrap_or_default()
+					.to_string();
+				let alt = el.value().attr("alt").unwrap_or_default().to_string();
+				self.image(&src, &alt)
+			}
+			// Evernote 资源引用：按 MD5 交给回调解析
+			"en-media" => {
+				let hash = el.value().attr("hash").unwrap_or_default();
+				let src = format!("en-media:{}", hash);
+				let mime = el.value().attr("type").unwrap_or_default();
+				let media = if mime.starts_with("image/") {
+					self.image(&src, "")
+				} else {
+					let target = (self.rewrite)(&src).unwrap_or(src);
+					let name = target.rsplit('/').next().unwrap_or("附件").to_string();
+					format!("[{}]({})", name, target)
+				};
+				format!("{}{}", media, self.children(*el))
+			}
+			// 自闭合的自定义标签会被 HTML 解析器当作开始标签，其后的文字成为子节点
+			"en-todo" => {
+				let mark = if el.value().attr("checked") == Some("true") {
+					"[x] "
+				} else {
+					"[ ] "
+				};
+				format!("{}{}", mark, self.children(*el).trim_start())
+			}
+			"input" if el.value().attr("type") == Some("checkbox") => {
+				if el.value().attr("checked").is_some() {
+					"[x] ".to_string()
+				} else {
+					"[ ] ".to_string()
+				}
+			}
+			"ul" => self.list(el, false),
+			"ol" => self.list(el, true),
+			"blockquote" => {
+				let body = tidy(&self.children(*el));
+				format!("\n\n{}\n", prefix_lines(&body, "> ", "> "))
+			}
+			"table" => self.table(el),
+			_ => self.children(*el),
+		}
+	}
+}
+
+/// 文档标题：优先 `<title>`，其次第一个 `<h1>`
+pub fn html_title(html: &str) -> Option<String> {
+	let doc = Html::parse_document(html);
+	["title", "h1"].iter().find_map(|tag| {
+		let selector = scraper::Selector::parse(tag).ok()?;
+		let text = collapse_whitespace(&doc.select(&selector).next()?.text().collect::<String>());
+		let text = text.trim();
+		(!text.is_empty()).then(|| text.to_string())
+	})
+}
+
+/// 将 HTML（完整文档或片段）转换为 Markdown
+pub fn html_to_markdown(html: &str, rewrite: &mut ImageRewrite) -> String {
+	html_to_markdown_with_base(html, None, rewrite)
+}
+
+/// 同 [`html_to_markdown`]，相对链接与图片地址按 `base` 补全（网页剪藏）
+pub fn html_to_markdown_with_base(html: &str, base: Option<&Url>, rewrite: &mut ImageRewrite) -> String {
+	let doc = Html::parse_document(html);
+	let mut converter = Converter { rewrite, base };
+	let body = doc
+		.root_element()
+		.children()
+		.filter_map(ElementRef::wrap)
+		.find(|e| e.value().name() == "body")
+		.map(|b| *b)
+		.unwrap_or(*doc.root_element());
+	tidy(&converter.node(body))
+}
@@ -0,0 +1,164 @@
+// —— 极简 HTTP/1.1 服务端工具：MCP 的 HTTP/SSE 模式与本地站点预览共用，只支持 `Content-Length` 正文与 `Connection: close` ——
+
+use std::collections::HashMap;
+
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+const MAX_BODY_BYTES: usize = 8 * 1024 * 1024;
+
+
+pub struct HttpRequest {
+	pub method: String,
+	pub path: String,
+	pub query: HashMap<String, String>,
+	pub headers: HashMap<String, String>,
+	pub body: Vec<u8>,
+}
+
+fn parse_query(raw: &str) -> HashMap<String, String> {
+	raw.split('&')
+		.filter_map(|kv| kv.split_once('=').or(Some((kv, ""))))
+		.filter(|(k, _)| !k.is_empty())
+		.map(|(k, v)| (k.to_string(), v.to_string()))
+		.collect()
+}
+
+/// 读取请求行、头与 `Content-Length` 指定的正文；出错时返回应答的状态码
+pub async fn read_request(reader: &mut BufReader<TcpStream>) -> Result<HttpRequest, u16> {
+	let mut head = Vec::new();
+	let mut lines = Vec::new();
+	loop {
+		let mut line = String::new();
+		let n = reader.read_line(&mut line).await.map_err(|_| 400u16)?;
+		if n == 0 {
+			return Err(400);
+		}
+		head.extend_from_slice(line.as_bytes());
+		if head.len() > MAX_HEADER_BYTES {
+			return Err(431);
+		}
+		let line = line.trim_end().to_string();
+		if line.is_empty() {
+			break;
+		}
+		lines.push(line);
+	}
+	let mut first = lines.first().ok_or(400u16)?.split_whitespace();
+	let method = first.next().ok_or(400u16)?.to_string();
+	let target = first.next().ok_or(400u16)?;
+	let (path, query) = match target.split_once('?') {
+		Some((p, q)) => (p.to_string(), parse_query(q)),
+		None => (target.to_string(), HashMap::new()),
+	};
+	let headers: HashMap<String, String> = lines[1..]
+		.iter()
+		.filter_map(|l| l.split_once(':'))
+		.map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim().to_string()))
+		.collect();
+	let len = match headers.get("content-length") {
+		Some(v) => v.parse::<usize>().map_err(|_| 400u16)?,
+		None => 0,
+	};
+	if len > MAX_BODY_BYTES {
+		return Err(413);
+	}
+	let mut body = vec![0u8; len];
+	reader.read_exact(&mut body).await.map_err(|_| 400u16)?;
+	Ok(HttpRequest {
+		method,
+		path,
+		query,
+		headers,
+		body,
+	})
+}
+
+pub fn reason(status: u16) -> &'static str {
+	match status {
+		200 => "OK",
+		202 => "Accepted",
+		400 => "Bad Request",
+		401 => "Unauthorized",
+		403 => "Forbidden",
+		404 => "Not Found",
+		405 => "Method Not Allowed",
+		413 => "Payload Too Large",
+		431 => "Request Header Fields Too Large",
+		_ => "Error",
+	}
+}
+
+pub async fn write_response(stream: &mut TcpStream, status: u16, content_type: &str, body: &[u8]) {
+	let head = format!(
+		"HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+		status,
+		reason(status),
+		content_type,
+		body.len()
+	);
+	let _ = stream.write_all(head.as_bytes()).await;
+	let _ = stream.write_all(body).await;
+	let _ = stream.flush().await;
+}
+
+pub fn is_loopback_host(host: &str) -> bool {
+	let host = host.trim();
+	let name = if let Some(rest) = host.strip_prefix('[') {
+		rest.split(']').next().unwrap_or_default()
+	} else {
+		host.rsplit_once(':').map(|(h, _)| h).unwrap_or(host)
+	};
+	matches!(name, "127.0.0.1" | "localhost" | "::1")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tokio::net::TcpListener;
+
+	async fn parse(raw: &'static [u8]) -> Result<HttpRequest, u16> {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let addr = listener.local_addr().unwrap();
+		let client = tokio::spawn(async move {
+			let mut stream = TcpStream::connect(addr).await.unwrap();
+			stream.write_all(raw).await.unwrap();
+		});
+		let (stream, _) = listener.accept().await.unwrap();
+		let mut reader = BufReader::new(stream);
+		client.await.unwrap();
+		read_request(&mut reader).await
+	}
+
+	#[tokio::test]
+	async fn read_request_parses_head_query_and_body() {
+		let req = parse(b"POST /mcp?token=abc&x HTTP/1.1\r\nHost: 127.0.0.1\r\nContent-Length: 5\r\n\r\nhello")
+			.await
+			.unwrap();
+		assert_eq!(req.method, "POST");
+		assert_eq!(req.path, "/mcp");
+		assert_eq!(req.query.get("token").map(String::as_str), Some("abc"));
+		assert_eq!(req.query.get("x").map(String::as_str), Some(""));
+		assert_eq!(req.headers.get("host").map(String::as_str), Some("127.0.0.1"));
+		assert_eq!(req.body, b"hello");
+	}
+
+	#[tokio::test]
+	async fn read_request_rejects_malformed_and_oversized() {
+		assert_eq!(parse(b"\r\n").await.err(), Some(400));
+		assert_eq!(parse(b"GET / HTTP/1.1\r\nContent-Length: x\r\n\r\n").await.err(), Some(400));
+		assert_eq!(parse(b"POST / HTTP/1.1\r\nContent-Length: 99999999999\r\n\r\n").await.err(), Some(413));
+		assert_eq!(parse(b"GET / HTTP/1.1\r\nContent-Length: 4\r\n\r\nab").await.err(), Some(400));
+	}
+
+	#[test]
+	fn loopback_hosts() {
+		for host in ["127.0.0.1", "127.0.0.1:17317", "localhost:80", "[::1]:17317", "[::1]"] {
+			assert!(is_loopback_host(host), "{}", host);
+		}
+		for host in ["example.com", "127.0.0.1.evil.com:80", "localhost.evil.com", "0.0.0.0:17317", ""] {
+			assert!(!is_loopback_host(host), "{}", host);
+		}
+	}
+}
@@ -1 +1,5 @@
 pub mod common;
+pub mod html_to_md;
+pub mod http_server;
+pub mod percent;
+pub mod readability;
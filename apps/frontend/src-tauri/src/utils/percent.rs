@@ -0,0 +1,51 @@
+// —— URL 百分号编码：WebDAV 路径段、`obsidian://` 等 URI 参数与 Markdown 链接中的文件名 ——
+
+/// 编码单个路径段 / 查询参数值：仅保留 RFC 3986 非保留字符
+pub fn encode_segment(seg: &str) -> String {
+	let mut out = String::with_capacity(seg.len());
+	for b in seg.bytes() {
+		if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~') {
+			out.push(b as char);
+		} else {
+			out.push_str(&format!("%{:02X}", b));
+		}
+	}
+	out
+}
+
+/// 编码 Markdown 链接目标中的路径：保留 `/` 与非 ASCII 字符，只转义空白、括号等会截断或改变链接的字符
+pub fn encode_link_path(path: &str) -> String {
+	let mut out = String::with_capacity(path.len());
+	for c in path.chars() {
+		if c.is_ascii_control() || matches!(c, ' ' | '%' | '(' | ')' | '<' | '>' | '[' | ']' | '#' | '?' | '"') {
+			let mut buf = [0u8; 4];
+			for b in c.encode_utf8(&mut buf).bytes() {
+				out.push_str(&format!("%{:02X}", b));
+			}
+		} else {
+			out.push(c);
+		}
+	}
+	out
+}
+
+/// 解码 `%XX`；非法序列原样保留，解码结果不是 UTF-8 时返回原串
+pub fn percent_decode(s: &str) -> String {
+	let bytes = s.as_bytes();
+	let hex = |b: u8| (b as char).to_digit(16).map(|v| v as u8);
+	let mut out: Vec<u8> = Vec::with_capacity(bytes.len());
+	let mut i = 0;
+	while i < bytes.len() {
+		if bytes[i] == b'%'
+			&& i + 2 < bytes.len()
+			&& let (Some(h), Some(l)) = (hex(bytes[i + 1]), hex(bytes[i + 2]))
+		{
+			out.push(h * 16 + l);
+			i += 3;
+			continue;
+		}
+		out.push(bytes[i]);
+		i += 1;
+	}
+	String::from_utf8(out).unwrap_or_else(|_| s.to_string())
+}
@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+
+use ego_tree::NodeId;
+use scraper::{ElementRef, Html, Selector};
+
+// —— 正文提取（Readability 风格）：按段落文本量给祖先节点打分，取得分最高的容器作为正文 ——
+
+/// 提取结果：正文 HTML 片段及页面元信息
+#[derive(Debug, Default, Clone)]
+pub struct Article {
+	pub title: Option<String>,
+	pub author: Option<String>,
+	pub site_name: Option<String>,
+	pub published: Option<String>,
+	pub excerpt: Option<String>,
+	/// 正文容器的 HTML（已去掉导航、评论、分享等噪声节点）
+	pub content_html: String,
+}
+
+const UNLIKELY: &[&str] = &[
+	"comment", "footer", "sidebar", "sidenav", "nav", "menu", "share", "social", "related",
+	"recommend", "popup", "modal", "cookie", "banner", "advert", "ad-", "sponsor",
+	"breadcrumb", "pagination", "subscribe", "newsletter", "toc-", "masthead", "header",
+];
+const POSITIVE: &[&str] = &[
+	"article", "content", "main", "post", "entry", "text", "body", "story", "blog", "markdown",
+	"prose", "doc",
+];
+/// 不可能是正文的标签，整段移除
+const NOISE_TAGS: &[&str] = &[
+	"script", "style", "noscript", "nav", "aside", "footer", "form", "iframe", "button", "select",
+	"textarea", "svg", "canvas", "template", "dialog",
+];
+
+fn text_len(el: &ElementRef) -> usize {
+	el.text().map(|t| t.trim().chars().count()).sum()
+}
+
+fn link_density(el: &ElementRef) -> f64 {
+	let total = text_len(el);
+	if total == 0 {
+		return 0.0;
+	}
+	let links: usize = el
+		.descendants()
+		.filter_map(ElementRef::wrap)
+		.filter(|e| e.value().name() == "a")
+		.map(|a| text_len(&a))
+		.sum();
+	links as f64 / total as f64
+}
+
+/// class / id 的正负倾向
+fn class_weight(el: &ElementRef) -> f64 {
+	let v = el.value();
+	let hint = format!(
+		"{} {}",
+		v.attr("class").unwrap_or_default(),
+		v.attr("id").unwrap_or_default()
+	)
+	.to_ascii_lowercase();
+	if hint.trim().is_empty() {
+		return 0.0;
+	}
+	let mut weight = 0.0;
+	if POSITIVE.iter().any(|p| hint.contains(p)) {
+		weight += 25.0;
+	}
+	if UNLIKELY.iter().any(|p| hint.contains(p)) {
+		weight -= 25.0;
+	}
+	weight
+}
+
+fn is_unlikely(el: &ElementRef) -> bool {
+	if matches!(el.value().name(), "body" | "html" | "article" | "main") {
+		return false;
+	}
+	if el.value().attr("role").is_some_and(|r| {
+		matches!(r, "navigation" | "complementary" | "banner" | "contentinfo" | "dialog")
+	}) {
+		return true;
+	}
+	if el.value().attr("aria-hidden") == Some("true") || el.value().attr("hidden").is_some() {
+		return true;
+	}
+	class_weight(el) < 0.0
+}
+
+fn meta_content(doc: &Html, selectors: &[&str]) -> Option<String> {
+	selectors.iter().find_map(|s| {
+		let sel = Selector::parse(s).ok()?;
+		let el = doc.select(&sel).next()?;
+		let value = el
+			.value()
+			.attr("content")
+			.map(str::to_string)
+			.unwrap_or_else(|| el.text().collect::<String>());
+		let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+		(!value.is_empty()).then_some(value)
+	})
+}
+
+/// 段落打分：文本越长、逗号越多得分越高，父节点全额、祖父节点减半计入
+fn score_candidates(doc: &Html) -> HashMap<NodeId, f64> {
+	let mut scores: HashMap<NodeId, f64> = HashMap::new();
+	let Ok(sel) = Selector::parse("p, pre, td, blockquote, li, h2, h3") else {
+		return scores;
+	};
+	for p in doc.select(&sel) {
+		let text: String = p.text().collect();
+		let len = text.trim().chars().count();
+		if len < 25 {
+			continue;
+		}
+		if p.ancestors().filter_map(ElementRef::wrap).any(|a| {
+			NOISE_TAGS.contains(&a.value().name()) || is_unlikely(&a)
+		}) {
+			continue;
+		}
+		let commas = text.matches([',', '，', '、', '。']).count() as f64;
+		let score = 1.0 + commas + (len as f64 / 100.0).min(3.0);
+		for (depth, ancestor) in p.ancestors().filter_map(ElementRef::wrap).take(3).enumerate() {
+			let entry = scores.entry(ancestor.id()).or_insert_with(|| {
+				let base = match ancestor.value().name() {
+					"article" | "main" => 10.0,
+					"div" | "section" => 5.0,
+					"pre" | "td" | "blockquote" => 3.0,
+					"form" | "ul" | "ol" | "dl" => -3.0,
+					"th" | "h1" | "h2" | "h3" => -5.0,
+					_ => 0.0,
+				};
+				base + class_weight(&ancestor)
+			});
+			*entry += match depth {
+				0 => score,
+				1 => score / 2.0,
+				_ => score / 3.0,
+			};
+		}
+	}
+	scores
+}
+
+/// 提取页面正文；找不到合适容器时退化为 `<body>`
+pub fn extract_article(html: &str) -> Article {
+	let mut doc = Html::parse_document(html);
+
+	let title = meta_content(
+		&doc,
+		&[
+			"meta[property='og:title']",
+			"meta[name='twitter:title']",
+			"title",
+			"h1",
+		],
+	);
+	let author = meta_content(
+		&doc,
+		&[
+			"meta[name='author']",
+			"meta[property='article:author']",
+			"meta[name='twitter:creator']",
+			"[rel='author']",
+			"[itemprop='author']",
+			".byline",
+			".author",
+		],
+	);
+	let site_name = meta_content(&doc, &["meta[property='og:site_name']", "meta[name='application-name']"]);
+	let published = meta_content(
+		&doc,
+		&[
+			"meta[property='article:published_time']",
+			"meta[name='date']",
+			"meta[itemprop='datePublished']",
+		],
+	)
+	.or_else(|| {
+		let sel = Selector::parse("time[datetime]").ok()?;
+		doc.select(&sel)
+			.next()
+			.and_then(|t| t.value().attr("datetime").map(str::to_string))
+	});
+	let excerpt = meta_content(&doc, &["meta[name='description']", "meta[property='og:description']"]);
+
+	let scores = score_candidates(&doc);
+	let body_id = Selector::parse("body")
+		.ok()
+		.and_then(|s| doc.select(&s).next().map(|b| b.id()))
+		.unwrap_or_else(|| doc.root_element().id());
+	let top = scores
+		.iter()
+		.filter_map(|(id, score)| {
+			let el = ElementRef::wrap(doc.tree.get(*id)?)?;
+			Some((*id, score * (1.0 - link_density(&el))))
+		})
+		.max_by(|a, b| a.1.total_cmp(&b.1))
+		.map(|(id, _)| id)
+		.unwrap_or(body_id);
+
+	// 正文容器内的导航、评论、分享按钮等噪声节点
+	let noise: Vec<NodeId> = doc
+		.tree
+		.get(top)
+		.and_then(ElementRef::wrap)
+		.map(|el| {
+			el.descendants()
+				.filter_map(ElementRef::wrap)
+				.filter(|e| {
+					NOISE_TAGS.contains(&e.value().name())
+						|| is_unlikely(e)
+						|| (matches!(e.value().name(), "div" | "ul" | "section")
+							&& link_density(e) > 0.6 && text_len(e) < 300)
+				})
+				.map(|e| e.id())
+				.collect()
+		})
+		.unwrap_or_default();
+	for id in noise {
+		if let Some(mut node) = doc.tree.get_mut(id) {
+			node.detach();
+		}
+	}
+	let content_html = doc
+		.tree
+		.get(top)
+		.and_then(ElementRef::wrap)
+		.map(|el| el.html())
+		.unwrap_or_default();
+
+	Article {
+		title,
+		author,
+		site_name,
+		published,
+		excerpt,
+		content_html,
+	}
+}